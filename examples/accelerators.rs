@@ -0,0 +1,62 @@
+use std::time::Instant;
+
+use rust3d::engine::camera::RayCamera;
+use rust3d::engine::pathtracing::RayTracingScene;
+use rust3d::engine::simplex3d_bvh::bvh::Bvh;
+use rust3d::engine::simplex3d_sphere_tree::poly_tree::PolyTree;
+use rust3d::engine::utils::virtual_canvas::VirtualCanvas;
+use rust3d::geometry::simplex3d::Simplex3D;
+use rust3d::geometry::vector3::Vector3 as V;
+
+const W: usize = 320;
+const H: usize = 240;
+
+/// Renders the same mesh once through the sphere tree and once through the BVH
+/// and prints build and render times for both.
+fn benchmark(name: &str, mesh: Simplex3D, camera: RayCamera) {
+    println!("{} ({} faces)", name, mesh.x.len());
+
+    let t = Instant::now();
+    let mut tree = *PolyTree::new(mesh.clone());
+    tree.goto(V::new(0.0, 0.0, 0.0));
+    let tree_build = t.elapsed();
+
+    let t = Instant::now();
+    let mut bvh = *Bvh::new(mesh);
+    bvh.goto(V::new(0.0, 0.0, 0.0));
+    let bvh_build = t.elapsed();
+
+    let mut scene = RayTracingScene::new();
+    scene.add(tree);
+    let mut frame = VirtualCanvas::new(W, H);
+    let t = Instant::now();
+    camera.render(&scene, &mut frame);
+    let tree_render = t.elapsed();
+
+    let mut scene = RayTracingScene::new();
+    scene.add(bvh);
+    let mut frame = VirtualCanvas::new(W, H);
+    let t = Instant::now();
+    camera.render(&scene, &mut frame);
+    let bvh_render = t.elapsed();
+
+    println!("  sphere tree: build {:>8.2?}  render {:>8.2?}", tree_build, tree_render);
+    println!("  bvh:         build {:>8.2?}  render {:>8.2?}", bvh_build, bvh_render);
+    println!("  speedup:     {:.1}x", tree_render.as_secs_f64() / bvh_render.as_secs_f64());
+}
+
+fn main() {
+    let camera = RayCamera::new(V::new(-5.0, 0.0, 0.0), 0.0, 0.0, 0.0);
+
+    let horse = Simplex3D::parse_wavefront(
+        &String::from("demo_assets/models/horse.obj"),
+        &String::from("demo_assets/models/horse_tex.png"),
+    );
+    benchmark("horse", horse, camera);
+
+    let whale = Simplex3D::parse_wavefront(
+        &String::from("demo_assets/models/whale.obj"),
+        &String::from("demo_assets/models/whale.jpg"),
+    );
+    benchmark("whale", whale, camera);
+}
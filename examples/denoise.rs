@@ -0,0 +1,60 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use rust3d::engine::camera::RayCamera;
+use rust3d::engine::lighting::{Light, Material};
+use rust3d::engine::pathtracing::RayTracingScene;
+use rust3d::engine::utils::aov::AovSelection;
+use rust3d::engine::utils::denoise::Denoiser;
+use rust3d::engine::utils::virtual_canvas::VirtualCanvas;
+use rust3d::geometry::quad::Quad;
+use rust3d::geometry::sphere::Sphere;
+use rust3d::geometry::vector3::Vector3 as V;
+use sdl2::pixels::Color;
+
+fn scene(shading_samples: usize) -> RayTracingScene {
+    let mut scene = RayTracingScene::new();
+    scene.set_shading_samples(shading_samples);
+    scene.set_ambient_light(Color::RGB(25, 25, 30));
+
+    scene.add(Quad::new(V::new(0.0, 1.5, 0.0), V::new(40.0, 0.1, 40.0), Color::RGB(190, 190, 190)));
+    scene.add(Sphere::new(V::new(1.5, 0.8, 1.4), 0.7, Material::new(Color::RGB(200, 60, 50), 0.6)));
+    scene.add(Sphere::new(V::new(2.5, 0.6, -0.3), 0.9, Material::new(Color::RGB(60, 150, 220), 0.8)));
+    scene.add(Sphere::new(V::new(0.8, 1.05, -1.6), 0.45, Material::new(Color::RGB(230, 200, 90), 1.0)));
+
+    // one big soft box, the shadows are noisy with few samples
+    scene.add_light(Light::rect_area(V::new(1.0, -2.5, 0.5), V::new(2.5, 0.0, 0.0), V::new(0.0, 0.0, -2.5), Color::WHITE, 7.0));
+    scene
+}
+
+fn rmse(a: &VirtualCanvas, b: &VirtualCanvas) -> f64 {
+    let sum: f64 = a.pixels.iter().zip(b.pixels.iter()).map(|(&x, &y)| (x as f64 - y as f64).powi(2)).sum();
+    (sum / a.pixels.len() as f64).sqrt()
+}
+
+// A soft shadow scene with 2 shadow samples per pixel, filtered with the guide buffers of the
+// same render, next to a reference with 256 samples.
+fn main() {
+    let width: usize = 640;
+    let height: usize = 360;
+    let camera = RayCamera::new(V::new(-4.0, -1.0, 0.0), 0.25, 0.0, 0.0);
+
+    std::fs::create_dir_all("target/renders").expect("could not create output directory");
+
+    let selection = AovSelection { depth: true, normal: true, albedo: true, object_id: true, position: false };
+    let noisy = camera.render_aovs_multi(Arc::new(scene(2)), width, height, selection, 8);
+    noisy.color.save_png("target/renders/denoise_noisy.png").expect("failed to save image");
+
+    let mut denoiser = Denoiser::new();
+    denoiser.set_threads(8);
+    let start = Instant::now();
+    let denoised = denoiser.denoise_canvas(&noisy.color, &noisy);
+    println!("denoised in {:.2?}", start.elapsed());
+    denoised.save_png("target/renders/denoise_filtered.png").expect("failed to save image");
+
+    let mut reference = VirtualCanvas::new(width, height);
+    camera.render_multi(Arc::new(scene(256)), &mut reference, 8);
+    reference.save_png("target/renders/denoise_reference.png").expect("failed to save image");
+
+    println!("rmse noisy {:.2}, denoised {:.2}", rmse(&noisy.color, &reference), rmse(&denoised, &reference));
+}
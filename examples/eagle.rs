@@ -9,8 +9,7 @@ use sdl2::video::Window;
 
 use std::borrow::Borrow;
 use std::ops::Deref;
-use std::sync::{mpsc, Arc, RwLock, Mutex};
-use std::thread;
+use std::sync::{Arc, RwLock, Mutex};
 use std::time::Duration;
 use std::time::Instant;
 
@@ -24,6 +23,7 @@ use rust3d::geometry::face::Face;
 use rust3d::geometry::quad::Quad;
 use rust3d::geometry::vector3::Vector3 as V;
 use rust3d::engine::camera::RayCamera;
+use rust3d::engine::utils::virtual_canvas::VirtualCanvas;
 use rust3d::engine::pathtracing::PathTracingScene;
 use rust3d::engine::pathtracing::PathtracingObject;
 use rust3d::geometry::simplex3d::Simplex3D;
@@ -128,45 +128,16 @@ pub fn render_mod(canvas : &mut Canvas<Window>, objs : RayRenderScene, camera :
     let w = canvas.window().drawable_size().0 as usize;
     let h = canvas.window().drawable_size().1 as usize;
     let section = camera.render_modulus(&objs, w, h, stage, modulus);
-    camera.draw_modulus(&section, canvas, stage, modulus, *w_, *h_);
+    let mut layer = VirtualCanvas::new(w, h);
+    camera.draw_modulus(&section, &mut layer, stage, modulus);
+    layer.present(canvas);
 }
 
 pub fn render_multi(canvas : &mut Canvas<Window>, objs : RayRenderScene, camera : RayCamera, w_ : &usize, h_ : &usize) {
-    //let w = canvas.window().drawable_size().0 as usize;
-    //let h = canvas.window().drawable_size().1 as usize;
-    //canvas.clear();
-    println!("Setting up threads...");
     let now = Instant::now();
-
-    let (tx, rx) = mpsc::channel::<(usize, Vec<Color>)>();
-    let n = 8;
-    let camera_arc = Arc::new(camera);
-    let objs =  Arc::new(objs);
-
-    for i in 0..n {
-        let camera_arc = Arc::clone(&camera_arc);
-        let objs = Arc::clone(&objs);
-        let tx = tx.clone();
-        let w_ = w_.clone();
-        let h_ = h_.clone();
-
-        thread::spawn(move || {
-            //let obj_mutex_cloned = Arc::clone(&obj_mutex_cloned);
-            let section = camera_arc.render_modulus_multi(objs, w_, h_, i, n);
-            tx.send((i.to_owned(), section));
-        });
-    }
-    println!("Setup took {}ms", now.elapsed().as_millis());
-    println!("Started rendering without issues");
-    let now = Instant::now();
-
-    for i in 0..n {
-        let section = rx.recv().unwrap();
-
-        camera.draw_modulus(&section.1, canvas, section.0, n, *w_, *h_);
-
-        println!("Thread {} finished rendering", section.0);
-    }
+    let mut frame = VirtualCanvas::new(*w_, *h_);
+    camera.render_multi(Arc::new(objs), &mut frame, 8);
+    frame.present(canvas);
 
     println!("Render took {}ms", now.elapsed().as_millis());
 }
\ No newline at end of file
@@ -24,6 +24,7 @@ use rust3d::geometry::face::Face;
 use rust3d::geometry::quad::Quad;
 use rust3d::geometry::vector3::Vector3 as V;
 use rust3d::engine::camera::RayCamera;
+use rust3d::engine::utils::virtual_canvas::VirtualCanvas;
 use rust3d::engine::pathtracing::PathTracingScene;
 use rust3d::engine::pathtracing::PathtracingObject;
 use rust3d::geometry::simplex3d::Simplex3D;
@@ -59,7 +60,7 @@ pub fn main() -> Result<(), String>{
 
     let mut p1 = Quad::new(V{x: 0.0, y: 0.0, z: 0.0}, V{x: 1., y: 1., z: 1.}, Color::RED);
 
-    let mut g1 = Graph3D::new(p1, FunctionR2ToR::new(Box::new(|x, y| - x*x -  y*y)), vec!["x", "y", "z"]);
+    let mut g1 = Graph3D::new(Clone::clone(&p1), FunctionR2ToR::new(Box::new(|x, y| - x*x -  y*y)), vec!["x", "y", "z"]);
     g1.rot(V{x: PI / 2., y: 0.0, z: 0.0});
     g1.rot(V{x: PI, y: 0.0, z: 0.0});
     let root = p1.x[7];
@@ -72,6 +73,8 @@ pub fn main() -> Result<(), String>{
     let mut change_modulus = 0;
     let mut block_size = 20;
     let mut motion = true; //first render without this condition
+    // the preview blocks land here, the window only shows it
+    let mut frame = VirtualCanvas::new(W, H);
     'running: loop {
         for event in event_pump.poll_iter() {
             match event {
@@ -106,7 +109,7 @@ pub fn main() -> Result<(), String>{
         objs.wrap(Box::new(Graph3D::wrapup(&g1)));
 
         if (motion) {
-            camera.render_and_draw_modulus_block(&mut canvas, &objs, block_size, stage, modulus_size / block_size, W, H);
+            camera.render_modulus_block(&mut frame, &objs, block_size, stage, modulus_size / block_size);
 
             let diff = now.elapsed().as_nanos();
             if ((diff as u32) < NANOS) {
@@ -139,9 +142,10 @@ pub fn main() -> Result<(), String>{
                 }
                 else {
                     motion = false;
-                    camera.render_anker_labels(&g1, &mut canvas, W, H);
+                    camera.render_anker_labels_to(&g1, &mut frame);
                 }
             }
+            frame.present(&mut canvas);
             canvas.present();
         }
         else {
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use rust3d::engine::camera::RayCamera;
+use rust3d::engine::lighting::{Attenuation, Light, Material};
+use rust3d::engine::pathtracing::{Integrator, RayTracingScene};
+use rust3d::engine::tile_renderer::TileRenderer;
+use rust3d::engine::utils::accumulator::Accumulator;
+use rust3d::engine::utils::environment_map::EnvironmentMap;
+use rust3d::engine::utils::hdr::{HdrBuffer, ToneMapper, ToneMapping};
+use rust3d::engine::utils::radiance::Radiance;
+use rust3d::engine::utils::virtual_canvas::VirtualCanvas;
+use rust3d::geometry::quad::Quad;
+use rust3d::geometry::sphere::Sphere;
+use rust3d::geometry::vector3::Vector3 as V;
+use sdl2::pixels::Color;
+
+/// Simple sky: blue at the zenith, pale at the horizon and a darker ground below it.
+fn procedural_sky(width: usize, height: usize) -> EnvironmentMap {
+    let mut data = Vec::with_capacity(width * height);
+    for y in 0..height {
+        // 0 at the zenith, 1 at the horizon
+        let t = y as f64 / (height as f64 / 2.0);
+        for _ in 0..width {
+            let c = if t < 1.0 {
+                let zenith = Radiance::new(0.15, 0.3, 0.65);
+                let horizon = Radiance::new(0.7, 0.75, 0.8);
+                zenith * (1.0 - t) + horizon * t
+            } else {
+                Radiance::new(0.45, 0.42, 0.38)
+            };
+            data.push(c);
+        }
+    }
+    EnvironmentMap::new(width, height, data)
+}
+
+// Every light type in one scene. Pass the path of an equirectangular .hdr/.exr image to use
+// it instead of the generated sky.
+fn main() {
+    let width: usize = 640;
+    let height: usize = 360;
+    let frames: u32 = 64;
+
+    let mut scene = RayTracingScene::new();
+    scene.set_integrator(Integrator::PathTracing);
+    scene.set_max_depth(5);
+
+    let mut sky = match std::env::args().nth(1) {
+        Some(path) => EnvironmentMap::load(&path).expect("failed to load environment map"),
+        None => procedural_sky(256, 128),
+    };
+    sky.set_intensity(0.5);
+    scene.set_environment(sky);
+
+    scene.add(Quad::new(V::new(0.0, 1.5, 0.0), V::new(400.0, 0.1, 400.0), Color::RGB(190, 190, 190)));
+    scene.add(Sphere::new(V::new(1.5, 0.8, 1.6), 0.7, Material::new(Color::RGB(200, 60, 50), 0.4)));
+    scene.add(Sphere::new(V::new(2.5, 0.6, 0.0), 0.9, Material::metal(Color::RGB(230, 200, 140), 0.25)));
+    scene.add(Sphere::new(V::new(1.0, 0.95, -1.5), 0.55, Material::glass(1.5)));
+
+    // low evening sun from the left
+    scene.add_light(Light::directional(V::new(0.3, 0.5, -1.0), Color::RGB(255, 220, 180), 0.8));
+    // blue spot on the metal sphere
+    let mut spot = Light::spot(V::new(0.0, -2.0, 2.5), V::new(2.5, 2.6, -2.5), 0.2, 0.35, Color::RGB(90, 140, 255), 8.0);
+    spot.set_attenuation(Attenuation::inverse_square());
+    scene.add_light(spot);
+    // soft box above the scene and a glowing ball behind it
+    scene.add_light(Light::rect_area(V::new(1.5, -2.5, 0.0), V::new(1.5, 0.0, 0.0), V::new(0.0, 0.0, -1.5), Color::WHITE, 9.0));
+    scene.add_light(Light::sphere_area(V::new(5.0, -0.5, -3.0), 0.4, Color::RGB(255, 120, 40), 38.0));
+
+    let camera = RayCamera::new(V::new(-5.0, -1.2, 0.0), 0.21, 0.0, 0.0);
+    let scene = Arc::new(scene);
+    let mut acc = Accumulator::new(width, height);
+    let mut frame = VirtualCanvas::new(width, height);
+
+    std::fs::create_dir_all("target/renders").expect("could not create output directory");
+
+    let renderer = TileRenderer::new(8);
+    let start = Instant::now();
+    for _ in 0..frames {
+        camera.render_progressive_on(&renderer, Arc::clone(&scene), &mut acc);
+    }
+    acc.to_canvas(&mut frame);
+    frame.save_png("target/renders/lights.png").expect("failed to save image");
+    println!("{} spp after {:.2?}: target/renders/lights.png", frames, start.elapsed());
+
+    // the raw buffer keeps the highlights the 8 bit image clips
+    let hdr = HdrBuffer::from_accumulator(&acc);
+    hdr.save_exr("target/renders/lights.exr").expect("failed to save image");
+    let mut tone_mapper = ToneMapper::new(ToneMapping::Aces);
+    tone_mapper.set_exposure(-1.0);
+    hdr.to_canvas(&tone_mapper).save_png("target/renders/lights_aces.png").expect("failed to save image");
+    println!("target/renders/lights.exr, tone mapped: target/renders/lights_aces.png");
+}
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use rust3d::engine::camera::RayCamera;
+use rust3d::engine::lighting::{Light, Material};
+use rust3d::engine::pathtracing::{Integrator, RayTracingScene};
+use rust3d::engine::tile_renderer::TileRenderer;
+use rust3d::engine::utils::accumulator::Accumulator;
+use rust3d::engine::utils::virtual_canvas::VirtualCanvas;
+use rust3d::geometry::quad::Quad;
+use rust3d::geometry::sphere::Sphere;
+use rust3d::geometry::vector3::Vector3 as V;
+use sdl2::pixels::Color;
+
+// Progressive path tracing of a small cornell-style box. Every frame adds one sample per
+// pixel, the intermediate images show how the noise goes away.
+fn main() {
+    let width: usize = 640;
+    let height: usize = 360;
+    let frames: u32 = 32;
+
+    let mut scene = RayTracingScene::new();
+    scene.set_integrator(Integrator::PathTracing);
+    scene.set_max_depth(6);
+    scene.set_seed(7);
+    scene.set_ambient_light(Color::RGB(10, 10, 12));
+
+    let t = 0.1;
+    // floor (+y is down) and ceiling
+    scene.add(Quad::new(V::new(0.0, 2.0, 0.0), V::new(8.0, t, 8.0), Color::RGB(180, 180, 180)));
+    scene.add(Quad::new(V::new(0.0, -2.0, 0.0), V::new(8.0, t, 8.0), Color::RGB(180, 180, 180)));
+    // back wall and the colored side walls
+    scene.add(Quad::new(V::new(3.0, 0.0, 0.0), V::new(t, 4.0, 8.0), Color::RGB(180, 180, 180)));
+    scene.add(Quad::new(V::new(0.0, 0.0, 2.5), V::new(8.0, 4.0, t), Color::RGB(180, 35, 35)));
+    scene.add(Quad::new(V::new(0.0, 0.0, -2.5), V::new(8.0, 4.0, t), Color::RGB(35, 170, 55)));
+
+    scene.add(Sphere::new(V::new(1.6, 1.25, 0.9), 0.7, Material::new(Color::RGB(230, 230, 230), 1.0)));
+    scene.add(Sphere::mirror(V::new(1.2, 1.35, -0.9), 0.6));
+
+    // slightly green glass in front
+    let mut glass = Material::glass(1.5);
+    glass.set_absorption(Color::RGB(170, 235, 200), 1.0);
+    scene.add(Sphere::new(V::new(-0.3, 1.5, 0.1), 0.5, glass));
+
+    scene.add_light(Light::point(V::new(1.0, -1.6, 0.0), Color::RGB(255, 240, 220), 0.35));
+
+    let camera = RayCamera::new(V::new(-8.0, 0.0, 0.0), 0.0, 0.0, 0.0);
+    let scene = Arc::new(scene);
+    let mut acc = Accumulator::new(width, height);
+    let mut frame = VirtualCanvas::new(width, height);
+
+    std::fs::create_dir_all("target/renders").expect("could not create output directory");
+
+    let renderer = TileRenderer::new(8);
+    let start = Instant::now();
+    for f in 1..=frames {
+        camera.render_progressive_on(&renderer, Arc::clone(&scene), &mut acc);
+
+        if f.is_power_of_two() {
+            acc.to_canvas(&mut frame);
+            let out = format!("target/renders/path_traced_{:03}spp.png", f);
+            frame.save_png(&out).expect("failed to save image");
+            println!("{:>3} spp after {:.2?}: {}", f, start.elapsed(), out);
+        }
+    }
+}
@@ -1,11 +1,11 @@
-use std::sync::{mpsc, Arc, RwLock};
-use std::thread;
+use std::sync::{Arc, RwLock};
 use std::time::Instant;
 use sdl2::pixels::Color;
 use sdl2::render::Canvas;
 use sdl2::video::Window;
 use rust3d::geometry::vector3::Vector3 as V;
 use rust3d::engine::camera::{RayCamera};
+use rust3d::engine::utils::virtual_canvas::VirtualCanvas;
 use rust3d::engine::gameplay::movement::{MovementInputMap, PlayerMovementController};
 use rust3d::engine::lighting::{Light, Material};
 use rust3d::engine::raymarching::{RayMarchingObject, RayMarchingScene};
@@ -16,7 +16,7 @@ use rust3d::geometry::sphere::Sphere;
 const W : usize = 600;
 const H : usize = 600;
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 struct MorphSphereCube {
     center: V,
     sphere_radius: f64,
@@ -87,7 +87,7 @@ impl RayMarchingObject for MorphSphereCube {
     }
 
     fn clone(&self) -> Box<dyn RayMarchingObject + Send + Sync> {
-        Box::new(*self)
+        Box::new(Clone::clone(self))
     }
 
     fn get_material(&self) -> &Material {
@@ -106,31 +106,23 @@ pub fn main() -> Result<(), String> {
         .expect("could not make a canvas");
     let mut event_pump = sdl_context.event_pump()?;
     let mut camera: RayCamera = RayCamera::new(V{x: -3.0, y: 0.0, z: 0.0}, 0.0, 0.0, 0.0);
-    let p2 = Sphere::new(V{x: 2.0, y: 1.0, z: 1.0}, 1.2, Material{ color: Color::WHITE, diffuse: 1.0 });
-    let p2_hole = Sphere::new(V{x: 1.0, y: 1.0, z: 1.0}, 0.8, Material{ color: Color::WHITE, diffuse: 1.0 });
-    let morph_material = Material { color: Color::RGB(240, 180, 255), diffuse: 1.0 };
+    let p2 = Sphere::new(V{x: 2.0, y: 1.0, z: 1.0}, 1.2, Material::new(Color::WHITE, 1.0));
+    let p2_hole = Sphere::new(V{x: 1.0, y: 1.0, z: 1.0}, 0.8, Material::new(Color::WHITE, 1.0));
+    let morph_material = Material::new(Color::RGB(240, 180, 255), 1.0);
     let morph_center = V { x: 2.0, y: 1.0, z: -2.0 };
     let morph = MorphSphereCube::new(
         morph_center,
         1.0,
         V { x: 0.9, y: 0.9, z: 0.9 },
         0.0,
-        morph_material,
+        morph_material.clone(),
     );
     let mut rm_objs : RayMarchingScene = RayMarchingScene::new(0.005);
     rm_objs.add(p2);
     rm_objs.add_negative(p2_hole);
     rm_objs.add(morph);
-    rm_objs.add_light(Light {
-        position: V{x: 2.0, y: 2.0, z: 5.0},
-        color: Color::YELLOW,
-        intensity: 1.0,
-    });
-    rm_objs.add_light(Light {
-        position: V{x: -2.0, y: 2.0, z: 3.0},
-        color: Color::BLUE,
-        intensity: 1.0,
-    });
+    rm_objs.add_light(Light::point(V{x: 2.0, y: 2.0, z: 5.0}, Color::YELLOW, 1.0));
+    rm_objs.add_light(Light::point(V{x: -2.0, y: 2.0, z: 3.0}, Color::BLUE, 1.0));
     let rm_objs = Arc::new(RwLock::new(rm_objs));
     let mut movement_handler = PlayerMovementController::new(&mut event_pump, &mut camera, MovementInputMap::get_default());
     let mut line_t = 0.0f64;
@@ -160,7 +152,7 @@ pub fn main() -> Result<(), String> {
             scene.negative_objects[0] = Box::new(Sphere::new(
                 V { x: hole_x, y: 1.0, z: 1.0 },
                 0.8,
-                Material { color: Color::WHITE, diffuse: 1.0 },
+                Material::new(Color::WHITE, 1.0),
             ));
 
             scene.objects[1] = Box::new(MorphSphereCube::new(
@@ -168,7 +160,7 @@ pub fn main() -> Result<(), String> {
                 1.0,
                 V { x: 0.9, y: 0.9, z: 0.9 },
                 blend,
-                morph_material,
+                morph_material.clone(),
             ));
         }
 
@@ -180,30 +172,10 @@ pub fn main() -> Result<(), String> {
 }
 
 pub fn render_multi(canvas : &mut Canvas<Window>, objs : RayRenderScene, camera : RayCamera, w_ : &usize, h_ : &usize) {
-    println!("Setting up threads...");
-    let (tx, rx) = mpsc::channel::<(usize, Vec<Color>)>();
-    let n = 5;
-    let camera_arc = Arc::new(camera);
-    let objs =  Arc::new(objs);
-
-    for i in 0..n {
-        let camera_arc = Arc::clone(&camera_arc);
-        let objs = Arc::clone(&objs);
-        let tx = tx.clone();
-        let w_ = w_.clone();
-        let h_ = h_.clone();
-
-        thread::spawn(move || {
-            let section = camera_arc.render_modulus_multi(objs, w_, h_, i, n);
-            let _ = tx.send((i.to_owned(), section));
-        });
-    }
     let now = Instant::now();
-
-    for _ in 0..n {
-        let section = rx.recv().unwrap();
-        camera.draw_modulus(&section.1, canvas, section.0, n, *w_, *h_);
-    }
+    let mut frame = VirtualCanvas::new(*w_, *h_);
+    camera.render_multi(Arc::new(objs), &mut frame, 5);
+    frame.present(canvas);
 
     println!("Render took {}ms", now.elapsed().as_millis());
 }
\ No newline at end of file
@@ -1,11 +1,11 @@
 use std::sync::Arc;
 
-use image::{Rgb, RgbImage};
 use rust3d::engine::camera::RayCamera;
 use rust3d::engine::lighting::Light;
 use rust3d::engine::pathtracing::RayTracingScene;
 use rust3d::engine::simplex3d_sphere_tree::poly_tree::PolyTree;
 use rust3d::engine::utils::transformation::{PI, Transformable};
+use rust3d::engine::utils::virtual_canvas::VirtualCanvas;
 use rust3d::geometry::quad::Quad;
 use rust3d::geometry::simplex3d::Simplex3D;
 use rust3d::geometry::sphere::Sphere;
@@ -18,10 +18,8 @@ fn main() {
     let width: usize = 1280;
     let height: usize = 720;
 
-    let mut mesh = Simplex3D::parse_wavefront(
-        &String::from("demo_assets/models/horse.obj"),
-        &String::from("demo_assets/models/horse_tex.png"),
-    );
+    // horse.mtl maps horse_tex.png onto the mesh
+    let mut mesh = Simplex3D::load_obj("demo_assets/models/horse.obj").expect("failed to load horse");
 
     mesh.scale(V { x: 0.65, y: 0.65, z: 0.65 });
     let mut horse = *PolyTree::new(mesh);
@@ -82,12 +80,12 @@ fn main() {
     scene.add(Sphere::new(
         V::new(-0.6, -0.2, -1.5),
         0.45,
-        Material::new(Color::RGB(80, 220, 255), 1.0),
+        Material::new(Color::RGB(80, 220, 255), 0.3),
     ));
     scene.add(Sphere::new(
         V::new(1.4, 0.4, 1.35),
         0.35,
-        Material::new(Color::RGB(255, 120, 230), 1.0),
+        Material::metal(Color::RGB(255, 120, 230), 0.35),
     ));
     scene.add(Sphere::new(
         V::new(2.3, -0.1, -0.2),
@@ -102,37 +100,16 @@ fn main() {
 
     scene.add(horse);
 
-    scene.add_light(Light {
-        position: V { x: -1.1, y: 2.8, z: -2.1 },
-        color: Color::RGB(80, 130, 255),
-        intensity: 1.25,
-    });
-    scene.add_light(Light {
-        position: V { x: -0.4, y: 2.6, z: 2.2 },
-        color: Color::RGB(185, 90, 255),
-        intensity: 1.15,
-    });
-    scene.add_light(Light {
-        position: V { x: 1.0, y: 2.2, z: 0.2 },
-        color: Color::RGB(255, 228, 95),
-        intensity: 1.35,
-    });
-
-    let camera = RayCamera::new(V { x: -5.2, y: -0.1, z: 0.0 }, -0.22, -0.40, 0.0);
-    let pixels = camera.render_frame_multi(Arc::new(scene), width, height, 8);
-
-    let mut image = RgbImage::new(width as u32, height as u32);
-    let mut pos = 0usize;
-    for x in 0..width {
-        for y in 0..height {
-            let c = pixels[pos];
-            image.put_pixel(x as u32, y as u32, Rgb([c.r, c.g, c.b]));
-            pos += 1;
-        }
-    }
+    scene.add_light(Light::point(V { x: -1.1, y: 2.8, z: -2.1 }, Color::RGB(80, 130, 255), 1.25));
+    scene.add_light(Light::point(V { x: -0.4, y: 2.6, z: 2.2 }, Color::RGB(185, 90, 255), 1.15));
+    scene.add_light(Light::point(V { x: 1.0, y: 2.2, z: 0.2 }, Color::RGB(255, 228, 95), 1.35));
+
+    let camera = RayCamera::new(V { x: -5.2, y: -0.1, z: 0.0 }, 0.04, -0.01, 0.0);
+    let mut frame = VirtualCanvas::new(width, height);
+    camera.render_multi(Arc::new(scene), &mut frame, 8);
 
     std::fs::create_dir_all("target/renders").expect("could not create output directory");
     let out = "target/renders/raytraced_scene.png";
-    image.save(out).expect("failed to save raytraced image");
+    frame.save_png(out).expect("failed to save raytraced image");
     println!("Saved raytraced image to {out}");
 }
@@ -0,0 +1,85 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use rust3d::engine::camera::RayCamera;
+use rust3d::engine::lighting::{Light, Material};
+use rust3d::engine::raymarching::RayMarchingScene;
+use rust3d::engine::sdf::domain::{Bend, Mirror, Repeat, Twist};
+use rust3d::engine::sdf::operators::{Difference, Intersection, Union};
+use rust3d::engine::sdf::primitives::{Capsule, Cone, Cylinder, Ellipsoid, Plane, RoundedBox, Torus};
+use rust3d::engine::utils::transformation::{PI, Transformable};
+use rust3d::engine::utils::virtual_canvas::VirtualCanvas;
+use rust3d::geometry::sphere::Sphere;
+use rust3d::geometry::vector3::Vector3 as V;
+use sdl2::pixels::Color;
+
+// All SDF primitives, boolean and domain operators in one ray marched scene.
+fn main() {
+    let width: usize = 800;
+    let height: usize = 450;
+
+    let red = Material::new(Color::RGB(210, 70, 60), 0.35);
+    let blue = Material::new(Color::RGB(70, 110, 210), 1.0);
+    let green = Material::new(Color::RGB(90, 180, 100), 1.0);
+    let yellow = Material::new(Color::RGB(230, 200, 80), 0.3);
+    let gray = Material::new(Color::RGB(180, 180, 180), 1.0);
+
+    let mut scene = RayMarchingScene::new(0.002);
+    scene.set_shadows(true);
+    scene.set_ambient_occlusion(true);
+    scene.set_specular(true);
+    scene.set_ambient_light(Color::RGB(60, 65, 75));
+    scene.add(Plane::new(V::new(0.0, 1.0, 0.0), V::new(0.0, -1.0, 0.0), gray.clone()));
+
+    // back row: plain primitives
+    let mut torus = Torus::new(V::new(6.0, 0.2, 3.0), 0.6, 0.2, yellow.clone());
+    torus.rot(V::new(PI / 2.0, 0.0, 0.0));
+    scene.add(torus);
+    scene.add(Capsule::new(V::new(6.0, 0.7, 1.0), V::new(6.0, -0.5, 1.8), 0.25, red.clone()));
+    scene.add(Cylinder::new(V::new(6.0, 0.3, 0.0), 0.4, 0.7, blue.clone()));
+    scene.add(Cone::new(V::new(6.0, -0.6, -1.2), 0.6, 1.6, green.clone()));
+    scene.add(Ellipsoid::new(V::new(6.0, 0.4, -2.6), V::new(0.4, 0.6, 0.7), yellow.clone()));
+
+    // front row: operators
+    scene.add(Difference::smooth(
+        RoundedBox::new(V::new(3.0, 0.4, 2.0), V::new(0.6, 0.6, 0.6), 0.1, blue.clone()),
+        Sphere::new(V::new(3.0, 0.4, 2.0), 0.75, red.clone()),
+        0.05,
+    ));
+    scene.add(Union::smooth(
+        Sphere::new(V::new(3.0, 0.5, 0.6), 0.45, green.clone()),
+        Sphere::new(V::new(3.0, 0.1, 0.0), 0.4, yellow.clone()),
+        0.3,
+    ));
+    scene.add(Intersection::new(
+        Sphere::new(V::new(3.0, 0.3, -1.1), 0.7, red.clone()),
+        RoundedBox::new(V::new(3.0, 0.3, -1.1), V::new(0.5, 0.5, 0.5), 0.0, red.clone()),
+    ));
+    scene.add(Twist::new(RoundedBox::new(V::new(3.0, 0.2, -2.4), V::new(0.35, 0.8, 0.15), 0.03, green.clone()), V::new(3.0, 0.2, -2.4), 1.5));
+
+    // domain repetition and friends, further back
+    scene.add(Repeat::limited(
+        Sphere::new(V::new(10.0, 0.8, 0.0), 0.2, red.clone()),
+        V::new(10.0, 0.8, 0.0),
+        V::new(0.0, 0.0, 0.8),
+        V::new(0.0, 0.0, 4.0),
+    ));
+    // bent along x, then turned to face the camera
+    let mut arch = Bend::new(Capsule::new(V::new(7.5, -1.5, 0.0), V::new(10.5, -1.5, 0.0), 0.1, blue.clone()), V::new(9.0, -1.5, 0.0), 0.4);
+    arch.rot(V::new(0.0, PI / 2.0, 0.0));
+    scene.add(arch);
+    scene.add(Mirror::new(Sphere::new(V::new(8.0, -0.5, 3.2), 0.3, yellow.clone()), V::new(8.0, 0.0, 0.0), [false, false, true]));
+
+    scene.add_light(Light::point(V::new(0.0, -4.0, 3.0), Color::WHITE, 0.8));
+    scene.add_light(Light::directional(V::new(0.4, 1.0, -0.5), Color::RGB(255, 240, 220), 0.5));
+
+    let camera = RayCamera::new(V::new(-3.5, -1.4, 0.0), 0.14, 0.0, 0.0);
+    let mut frame = VirtualCanvas::new(width, height);
+
+    std::fs::create_dir_all("target/renders").expect("could not create output directory");
+
+    let start = Instant::now();
+    camera.render_multi(Arc::new(scene), &mut frame, 8);
+    frame.save_png("target/renders/sdf_shapes.png").expect("failed to save image");
+    println!("rendered in {:.2?}: target/renders/sdf_shapes.png", start.elapsed());
+}
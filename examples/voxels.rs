@@ -0,0 +1,40 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use rust3d::engine::camera::RayCamera;
+use rust3d::engine::lighting::Light;
+use rust3d::engine::pathtracing::RayTracingScene;
+use rust3d::engine::utils::transformation::PI;
+use rust3d::engine::utils::virtual_canvas::VirtualCanvas;
+use rust3d::engine::voxels::terrain::TerrainGenerator;
+use rust3d::engine::voxels::voxel_world::VoxelWorld;
+use rust3d::geometry::vector3::Vector3 as V;
+use sdl2::pixels::Color;
+
+const W: usize = 800;
+const H: usize = 500;
+
+fn main() {
+    let t = Instant::now();
+    let mut world = VoxelWorld::new(0.25);
+    TerrainGenerator::new(42).generate(&mut world, 96, 96);
+    println!("Generated {} chunks in {:.2?}", world.chunks.len(), t.elapsed());
+
+    let mut scene = RayTracingScene::new();
+    scene.set_ambient_light(Color::RGB(90, 90, 100));
+    scene.add(world);
+    scene.add_light(Light::point(V { x: 40.0, y: 30.0, z: -10.0 }, Color::RGB(255, 245, 225), 1.0));
+
+    // looking along -x with the pitch flipped by PI, so +y points up in the image
+    let camera = RayCamera::new(V { x: 34.0, y: 9.0, z: 12.0 }, PI + 0.46, 0.0, 0.0);
+
+    let t = Instant::now();
+    let mut frame = VirtualCanvas::new(W, H);
+    camera.render_multi(Arc::new(scene), &mut frame, 8);
+    println!("Rendered in {:.2?}", t.elapsed());
+
+    std::fs::create_dir_all("target/renders").expect("could not create output directory");
+    let out = "target/renders/voxels.png";
+    frame.save_png(out).expect("failed to save voxel image");
+    println!("Saved {out}");
+}
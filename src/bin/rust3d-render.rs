@@ -0,0 +1,344 @@
+//! Offline renderer: renders a scene file or an OBJ model to PNG without opening a window.
+//!
+//! ```text
+//! rust3d-render demo_assets/scenes/horse_room.scene -o horse.png --samples 16
+//! rust3d-render demo_assets/models/horse.obj --renderer projection --turntable 36
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
+use std::time::Instant;
+
+use sdl2::pixels::Color;
+
+use rust3d::engine::camera::RayCamera;
+use rust3d::engine::lighting::Light;
+use rust3d::engine::pathtracing::RayTracingScene;
+use rust3d::engine::projection::rasterizer::Rasterizer;
+use rust3d::engine::raymarching::RayMarchingScene;
+use rust3d::engine::scene_file::{SceneDescription, SceneObject, Shape};
+use rust3d::engine::tile_renderer::TileRenderer;
+use rust3d::engine::utils::accumulator::Accumulator;
+use rust3d::engine::utils::aov::{AovBuffers, AovSelection};
+use rust3d::engine::utils::virtual_canvas::VirtualCanvas;
+use rust3d::geometry::simplex3d::Simplex3D;
+use rust3d::geometry::vector3::Vector3 as V3;
+
+const USAGE: &str = "usage: rust3d-render [options] <scene.scene | model.obj>
+
+options:
+  -o, --output FILE          PNG to write, defaults to the output of the scene or <input>.png
+  -r, --renderer NAME        raytracing (default), raymarching or projection
+  -W, --width N              image width, defaults to the render settings of the scene
+  -H, --height N             image height
+  -s, --samples N            frames the ray tracer accumulates, 1 renders a single frame
+  -t, --threads N            render threads, defaults to the number of cores
+      --position X,Y,Z       camera position
+      --look-at X,Y,Z        point the camera looks at, also the center of a turntable
+      --rotation R,Y,P       camera roll, yaw and pitch in radians
+      --fov RADIANS          vertical field of view
+      --aov LIST             also write depth, normal, albedo, object_id, position or all,
+                             comma separated, next to the output
+      --turntable N          N frames around the look-at point, written as <output>_0000.png ...
+  -q, --quiet                no progress output
+  -h, --help                 show this help";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Renderer {
+    RayTracing,
+    RayMarching,
+    Projection,
+}
+
+#[derive(Debug, Clone)]
+struct Options {
+    input: PathBuf,
+    output: Option<PathBuf>,
+    renderer: Renderer,
+    width: Option<usize>,
+    height: Option<usize>,
+    samples: Option<u32>,
+    threads: usize,
+    position: Option<V3>,
+    look_at: Option<V3>,
+    rotation: Option<V3>,
+    fov: Option<f64>,
+    aovs: AovSelection,
+    turntable: Option<usize>,
+    quiet: bool,
+}
+
+impl Options {
+    fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Self>, String> {
+        let mut options = Options {
+            input: PathBuf::new(),
+            output: None,
+            renderer: Renderer::RayTracing,
+            width: None,
+            height: None,
+            samples: None,
+            threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            position: None,
+            look_at: None,
+            rotation: None,
+            fov: None,
+            aovs: AovSelection::none(),
+            turntable: None,
+            quiet: false,
+        };
+        let mut input = None;
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "-q" | "--quiet" => options.quiet = true,
+                "-o" | "--output" => options.output = Some(PathBuf::from(value()?)),
+                "-r" | "--renderer" => {
+                    options.renderer = match value()?.as_str() {
+                        "raytracing" => Renderer::RayTracing,
+                        "raymarching" => Renderer::RayMarching,
+                        "projection" => Renderer::Projection,
+                        other => return Err(format!("unknown renderer '{}', expected raytracing, raymarching or projection", other)),
+                    }
+                }
+                "-W" | "--width" => options.width = Some(count(&arg, &value()?)?),
+                "-H" | "--height" => options.height = Some(count(&arg, &value()?)?),
+                "-s" | "--samples" => options.samples = Some(count(&arg, &value()?)? as u32),
+                "-t" | "--threads" => options.threads = count(&arg, &value()?)?,
+                "--position" => options.position = Some(vector(&arg, &value()?)?),
+                "--look-at" => options.look_at = Some(vector(&arg, &value()?)?),
+                "--rotation" => options.rotation = Some(vector(&arg, &value()?)?),
+                "--fov" => options.fov = Some(number(&arg, &value()?)?),
+                "--aov" => options.aovs = aovs(&value()?)?,
+                "--turntable" => options.turntable = Some(count(&arg, &value()?)?),
+                _ if arg.starts_with('-') && arg.len() > 1 => return Err(format!("unknown option '{}'", arg)),
+                _ if input.is_some() => return Err(format!("more than one input: '{}'", arg)),
+                _ => input = Some(PathBuf::from(arg)),
+            }
+        }
+
+        options.input = input.ok_or_else(|| String::from("no scene file or model given"))?;
+        if options.look_at.is_some() && options.rotation.is_some() {
+            return Err(String::from("--look-at and --rotation cannot be used together"));
+        }
+        Ok(Some(options))
+    }
+}
+
+/// Positive whole number.
+fn count(option: &str, s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("{} needs a positive whole number, got '{}'", option, s)),
+    }
+}
+
+fn number(option: &str, s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(x) if x.is_finite() => Ok(x),
+        _ => Err(format!("{} needs a number, got '{}'", option, s)),
+    }
+}
+
+fn vector(option: &str, s: &str) -> Result<V3, String> {
+    let v = s.split(',').map(|c| number(option, c.trim())).collect::<Result<Vec<f64>, String>>()?;
+    match v[..] {
+        [x, y, z] => Ok(V3::new(x, y, z)),
+        _ => Err(format!("{} needs three numbers like 1,-2,0.5, got '{}'", option, s)),
+    }
+}
+
+fn aovs(s: &str) -> Result<AovSelection, String> {
+    let mut selection = AovSelection::none();
+    for name in s.split(',') {
+        match name.trim() {
+            "all" => selection = AovSelection::all(),
+            "depth" => selection.depth = true,
+            "normal" => selection.normal = true,
+            "albedo" => selection.albedo = true,
+            "object_id" => selection.object_id = true,
+            "position" => selection.position = true,
+            other => return Err(format!("unknown AOV '{}', expected depth, normal, albedo, object_id, position or all", other)),
+        }
+    }
+    Ok(selection)
+}
+
+/// Scene around a single model: the model centered at the origin, lit from the upper left
+/// and seen from a little above so that it fills most of the image.
+fn model_scene(path: &Path) -> Result<SceneDescription, String> {
+    let mesh = Simplex3D::load_obj(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let radius = mesh
+        .x
+        .iter()
+        .flat_map(|f| [f.r, f.a, f.b])
+        .map(|p| p.d(mesh.m))
+        .fold(0.0, f64::max)
+        .max(1e-3);
+
+    let mut scene = SceneDescription::new();
+    scene.path = path.to_path_buf();
+    let file = path.file_name().map_or_else(String::new, |f| f.to_string_lossy().into_owned());
+    scene.objects.push(SceneObject {
+        shape: Shape::Mesh { path: file, position: Some(V3::new(0.0, 0.0, 0.0)), rotation: V3::new(0.0, 0.0, 0.0), scale: V3::new(1.0, 1.0, 1.0) },
+        material: None,
+        line: 0,
+    });
+    scene.lights.push(Light::directional(V3::new(1.0, 1.0, 0.6), Color::WHITE, 0.9));
+    scene.lights.push(Light::directional(V3::new(0.3, 0.5, -1.0), Color::WHITE, 0.3));
+    scene.camera = RayCamera::look_at(V3::new(-3.0 * radius, -1.2 * radius, 0.0), V3::new(0.0, 0.0, 0.0), V3::new(0.0, -1.0, 0.0));
+    Ok(scene)
+}
+
+/// The scene in the form the chosen renderer needs.
+enum Prepared {
+    RayTracing(Arc<RayTracingScene>),
+    RayMarching(Arc<RayMarchingScene>),
+    Projection(Rasterizer),
+}
+
+impl Prepared {
+    fn new(scene: &SceneDescription, renderer: Renderer) -> Result<Self, String> {
+        let prepared = match renderer {
+            Renderer::RayTracing => scene.to_raytracing().map(|s| Prepared::RayTracing(Arc::new(s))),
+            Renderer::RayMarching => scene.to_raymarching().map(|s| Prepared::RayMarching(Arc::new(s))),
+            Renderer::Projection => scene.to_projection().map(Prepared::Projection),
+        };
+        prepared.map_err(|e| e.to_string())
+    }
+
+    fn render(&self, camera: &RayCamera, target: &mut VirtualCanvas, samples: u32, pool: &TileRenderer) {
+        let (w, h) = (target.width, target.height);
+        match self {
+            Prepared::RayTracing(scene) if samples > 1 => {
+                let mut acc = Accumulator::new(w, h);
+                for _ in 0..samples {
+                    camera.render_progressive_on(pool, Arc::clone(scene), &mut acc);
+                }
+                acc.to_canvas(target);
+            }
+            Prepared::RayTracing(scene) => *target = pool.render(camera, Arc::clone(scene), w, h).wait().expect("render job was cancelled"),
+            Prepared::RayMarching(scene) => *target = pool.render(camera, Arc::clone(scene), w, h).wait().expect("render job was cancelled"),
+            Prepared::Projection(rasterizer) => rasterizer.render(camera, target),
+        }
+    }
+
+    /// Writes the selected AOVs as `<name>_<pass>` files into `dir`.
+    fn save_aovs(&self, camera: &RayCamera, color: &VirtualCanvas, selection: AovSelection, pool: &TileRenderer, dir: &Path, name: &str) -> Result<Vec<PathBuf>, String> {
+        let mut buffers = AovBuffers::new(color.width, color.height, selection);
+        match self {
+            Prepared::RayTracing(scene) => camera.trace_aovs_on(pool, Arc::clone(scene), &mut buffers),
+            Prepared::RayMarching(scene) => camera.trace_aovs_on(pool, Arc::clone(scene), &mut buffers),
+            Prepared::Projection(_) => return Err(String::from("AOVs need the raytracing or raymarching renderer")),
+        }
+        buffers.color = color.clone();
+        buffers.save(dir, name).map_err(|e| format!("could not write AOVs: {}", e))
+    }
+}
+
+/// Cameras of a turntable: `frames` positions on a horizontal circle around `target`.
+fn turntable(camera: &RayCamera, target: V3, frames: usize) -> Vec<RayCamera> {
+    let mut offset = camera.position;
+    offset.subtract(target);
+    (0..frames)
+        .map(|i| {
+            let (sin, cos) = (std::f64::consts::TAU * i as f64 / frames as f64).sin_cos();
+            let position = V3::new(target.x + offset.x * cos - offset.z * sin, target.y + offset.y, target.z + offset.x * sin + offset.z * cos);
+            let mut c = *camera;
+            c.position = position;
+            c.rotation = RayCamera::look_at(position, target, V3::new(0.0, -1.0, 0.0)).rotation;
+            c
+        })
+        .collect()
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let is_model = options.input.extension().is_some_and(|e| e.eq_ignore_ascii_case("obj"));
+    let mut scene = if is_model {
+        model_scene(&options.input)?
+    } else {
+        SceneDescription::load(&options.input).map_err(|e| e.to_string())?
+    };
+
+    let r = &mut scene.render;
+    r.width = options.width.unwrap_or(r.width);
+    r.height = options.height.unwrap_or(r.height);
+    r.samples = options.samples.unwrap_or(r.samples);
+    let camera = &mut scene.camera;
+    camera.position = options.position.unwrap_or(camera.position);
+    camera.fov = options.fov.unwrap_or(camera.fov);
+    if let Some(rotation) = options.rotation {
+        camera.rotation = rotation;
+    }
+    if let Some(target) = options.look_at {
+        camera.rotation = RayCamera::look_at(camera.position, target, V3::new(0.0, -1.0, 0.0)).rotation;
+    }
+
+    let output = match (&options.output, &scene.render.output) {
+        (Some(output), _) => output.clone(),
+        (None, Some(output)) => PathBuf::from(output),
+        (None, None) => options.input.with_extension("png"),
+    };
+    let dir = output.parent().map_or_else(PathBuf::new, Path::to_path_buf);
+    let stem = output.file_stem().map_or_else(|| String::from("render"), |s| s.to_string_lossy().into_owned());
+    if !dir.as_os_str().is_empty() {
+        std::fs::create_dir_all(&dir).map_err(|e| format!("could not create {}: {}", dir.display(), e))?;
+    }
+
+    let frames: Vec<(RayCamera, String)> = match options.turntable {
+        Some(n) => {
+            // models sit at the origin, scenes need to say what to turn around
+            let target = match options.look_at {
+                Some(target) => target,
+                None if is_model => V3::new(0.0, 0.0, 0.0),
+                None => return Err(String::from("--turntable needs --look-at to know what to turn around")),
+            };
+            turntable(&scene.camera, target, n).into_iter().enumerate().map(|(i, c)| (c, format!("{}_{:04}", stem, i))).collect()
+        }
+        None => vec![(scene.camera, stem)],
+    };
+
+    let start = Instant::now();
+    let prepared = Prepared::new(&scene, options.renderer)?;
+    if !options.quiet {
+        eprintln!("prepared {} in {:.2?}", options.input.display(), start.elapsed());
+    }
+
+    // one pool of render threads for all frames
+    let pool = TileRenderer::new(options.threads);
+    let (w, h) = (scene.render.width, scene.render.height);
+    for (camera, name) in frames {
+        let start = Instant::now();
+        let mut canvas = VirtualCanvas::new(w, h);
+        prepared.render(&camera, &mut canvas, scene.render.samples, &pool);
+        let path = dir.join(format!("{}.png", name));
+        canvas.save_png(&path).map_err(|e| format!("could not write {}: {}", path.display(), e))?;
+        if options.aovs != AovSelection::none() {
+            prepared.save_aovs(&camera, &canvas, options.aovs, &pool, &dir, &name)?;
+        }
+        if !options.quiet {
+            eprintln!("rendered {} ({}x{}) in {:.2?}", path.display(), w, h, start.elapsed());
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(e) = run(options) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
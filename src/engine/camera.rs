@@ -1,22 +1,35 @@
-use std::{borrow::Borrow, sync::{Arc, Mutex, mpsc}, thread};
+use std::cell::Cell;
+use std::sync::Arc;
 
-use sdl2::{pixels::Color, rect::Point, render::Canvas, video::Window};
+use sdl2::pixels::Color;
 
 use crate::{geometry::vector3::Vector3 as V3, math::utils::graph_utils::WithLabels};
 
-use crate::engine::utils::{rendering::{RayRenderScene, RayRenderable}, transformation::Transformable};
+use crate::engine::utils::{rendering::{RayRenderScene, RayRenderable, SurfaceHit}, transformation::Transformable};
 
-use super::utils::{anker_label::AnkerLabel, rendering::RaySphereable, rendering_ui::UiElement};
+use super::pathtracing::RayTracingScene;
+use super::tile_renderer::{TileJob, TileRect, TileRenderer};
+use super::utils::{accumulator::Accumulator, anker_label::AnkerLabel, aov::{AovBuffers, AovSelection}, antialiasing::{edge_pixels, Supersampling}, hdr::HdrBuffer, radiance::Radiance, random::{hash_combine, Rng}, rendering::RaySphereable, rendering_ui::UiElement, virtual_canvas::VirtualCanvas};
 
-#[derive(Copy, Clone)]
+/// Pinhole or thin lens camera. It looks along +x when not rotated, with -y as up and -z to
+/// the right of the image. `rotation` holds roll, yaw and pitch around the x, y and z axes;
+/// a positive yaw turns to the right, a positive pitch looks down.
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct RayCamera {
 	pub rotation: V3,
 	pub zoom: f64,
 	pub position: V3,
 	pub epsilon: f64,
 	pub view_distance: f64,
-	pub fov_pitch: f64,
-	pub fov_yaw: f64,
+	/// Vertical field of view in radians, the horizontal one follows from the aspect ratio
+	/// of the image.
+	pub fov: f64,
+	/// Diameter of the lens, 0 for a pinhole camera.
+	pub aperture: f64,
+	/// Distance along the view direction that is in focus when `aperture` is not 0.
+	pub focal_distance: f64,
+	/// Rays per pixel and how they are filtered, one ray through the pixel center by default.
+	pub supersampling: Supersampling,
 }
 
 impl<'a> RayCamera {
@@ -29,13 +42,33 @@ impl<'a> RayCamera {
             },
             position,
             zoom: 1.0,
-            fov_pitch: 60.0f64.to_radians(),
-            fov_yaw: 90.0f64.to_radians(),
+            fov: 40.0f64.to_radians(),
+            aperture: 0.0,
+            focal_distance: 1.0,
+            supersampling: Supersampling::none(),
             epsilon: 0.8f64,
             view_distance: 100.0,
         }
     }
 
+	/// Camera at `position` looking at `target`, rolled so that `up` points up in the image.
+	pub fn look_at(position: V3, target: V3, up: V3) -> Self {
+		let mut forward = target;
+		forward.subtract(position);
+		forward.normalize();
+		let mut right = forward;
+		right.cross(up);
+		right.normalize();
+		let mut up = right;
+		up.cross(forward);
+
+		// angles of the rotation that maps the unrotated basis onto this one
+		let yaw = f64::asin(f64::clamp(-forward.z, -1.0, 1.0));
+		let pitch = f64::atan2(forward.y, forward.x);
+		let roll = f64::atan2(-up.z, -right.z);
+		RayCamera::new(position, pitch, yaw, roll)
+	}
+
 	pub fn rot(&mut self, delta: V3) {
         self.rotation.add(delta);
     }
@@ -44,187 +77,507 @@ impl<'a> RayCamera {
         self.rotation = new_rot;
     }
 
-    fn get_ray_vec(&self, j: usize, i : usize, w: usize, h : usize) -> V3 {
-        let vxp : f64 = j as f64 / w as f64;
-        let vyp : f64 = i as f64 / h as f64;
-        
-		let ray_pitch = vyp * self.fov_pitch / 2.0;
-        let ray_yaw = vxp * self.fov_yaw / 2.0;
-        
-        let mut ray = V3::new(1.0, 0.0, 0.0);
-        
-        ray.rotate(V3::new(
-            self.rotation.x,
-            ray_yaw + self.rotation.y,
-			ray_pitch + self.rotation.z
-        ));
-
-		//TODO: Implement this mathematically correct. It somehow worked on the other Axis
-		if (ray.y == 0.0) { ray.y = 0.00000001; }
+	/// Sets the vertical field of view in radians.
+	pub fn set_fov(&mut self, fov: f64) {
+		self.fov = fov;
+	}
+
+	/// Turns the camera into a thin lens camera with the given lens diameter, focused at
+	/// `focal_distance`. An aperture of 0 goes back to a pinhole.
+	pub fn set_lens(&mut self, aperture: f64, focal_distance: f64) {
+		self.aperture = aperture;
+		self.focal_distance = focal_distance;
+	}
+
+	/// Sets the rays per pixel of the ray renderers, see [`Supersampling`].
+	pub fn set_supersampling(&mut self, supersampling: Supersampling) {
+		self.supersampling = supersampling;
+	}
 
+	/// View direction, right and up of the camera in world space.
+	pub fn basis(&self) -> (V3, V3, V3) {
+		let mut forward = V3::new(1.0, 0.0, 0.0);
+		let mut right = V3::new(0.0, 0.0, -1.0);
+		let mut up = V3::new(0.0, -1.0, 0.0);
+		forward.rotate(self.rotation);
+		right.rotate(self.rotation);
+		up.rotate(self.rotation);
+		(forward, right, up)
+	}
+
+	/// Normalized direction of the pinhole ray through the point `(x, y)` of a `w` x `h` image,
+	/// in pixels from the top left corner. Pixel `(j, i)` covers `[j, j + 1) x [i, i + 1)`.
+	pub fn ray_direction(&self, x: f64, y: f64, w: usize, h: usize) -> V3 {
+		let (forward, right, up) = self.basis();
+		let half_height = f64::tan(self.fov / 2.0);
+		let half_width = half_height * w as f64 / h as f64;
+
+		let sx = (2.0 * x / w as f64 - 1.0) * half_width;
+		let sy = (1.0 - 2.0 * y / h as f64) * half_height;
+
+		let mut ray = forward;
+		for (axis, t) in [(right, sx), (up, sy)] {
+			let mut a = axis;
+			a.scale(t);
+			ray.add(a);
+		}
+		ray.normalize();
 		ray
+	}
+
+	/// Origin and direction of a ray through `(x, y)` that leaves the lens at the point given
+	/// by `u` and `v` in `[0, 1)`. All rays through the same image point meet on the plane at
+	/// `focal_distance`. Without an aperture this is the pinhole ray.
+	pub fn lens_ray(&self, x: f64, y: f64, w: usize, h: usize, u: f64, v: f64) -> (V3, V3) {
+		let dir = self.ray_direction(x, y, w, h);
+		if self.aperture <= 0.0 {
+			return (self.position, dir);
+		}
+		let (forward, right, up) = self.basis();
+
+		let mut focus = dir;
+		focus.scale(self.focal_distance / dir.dt(forward));
+		focus.add(self.position);
+
+		// uniform point on the lens disk
+		let r = self.aperture / 2.0 * u.sqrt();
+		let phi = 2.0 * std::f64::consts::PI * v;
+		let mut origin = self.position;
+		for (axis, t) in [(right, r * phi.cos()), (up, r * phi.sin())] {
+			let mut a = axis;
+			a.scale(t);
+			origin.add(a);
+		}
+
+		let mut dir = focus;
+		dir.subtract(origin);
+		dir.normalize();
+		(origin, dir)
+	}
+
+    fn get_ray_vec(&self, j: usize, i : usize, w: usize, h : usize) -> V3 {
+		self.ray_direction(j as f64 + 0.5, i as f64 + 0.5, w, h)
     }
 
-	pub fn render_and_draw_modulus_block<R : RayRenderable>(&self, canvas : &mut Canvas<Window>, obj: &R, blocksize : usize, index: usize, n : usize, w: usize, h : usize) {
-		for j in (0..w).step_by(blocksize) {
-			if ((j / blocksize) % n == index) {
-				for i in (0..h).step_by(blocksize) {
-					let mut v = self.get_ray_vec(j, i, w, h);
-					let coll = obj.get_collision(self.position, v, 100.0);
-					let color = coll.c;
-					canvas.set_draw_color(color);
-					canvas.fill_rect(sdl2::rect::Rect::new(j as i32, i as i32, blocksize as u32, blocksize as u32));
+	/// Filtered radiance of pixel (j, i) over all samples of `self.supersampling`. `trace` gives
+	/// the light seen along a ray direction, samples it returns `None` for do not count. `None`
+	/// if no sample counts.
+	fn filtered_pixel<F: Fn(V3) -> Option<Radiance>>(&self, j: usize, i: usize, w: usize, h: usize, trace: F) -> Option<Radiance> {
+		if !self.supersampling.is_enabled() {
+			return trace(self.get_ray_vec(j, i, w, h));
+		}
+
+		// seeded by the pixel, so every split of the frame gives the same image
+		let mut rng = Rng::new(hash_combine(&[self.supersampling.seed, j as u64, i as u64]));
+		let mut sum = Radiance::black();
+		let mut weight = 0.0;
+		for (dx, dy, wt) in self.supersampling.samples(&mut rng) {
+			if wt == 0.0 {
+				continue;
+			}
+			if let Some(c) = trace(self.ray_direction(j as f64 + 0.5 + dx, i as f64 + 0.5 + dy, w, h)) {
+				sum += c * wt;
+				weight += wt;
+			}
+		}
+		if weight <= 0.0 {
+			return None;
+		}
+
+		Some(sum / weight)
+	}
+
+	/// Supersampled color of pixel (j, i), seen up to `view_distance`.
+	fn shade_pixel<R : RayRenderable + ?Sized>(&self, obj: &R, j: usize, i: usize, w: usize, h: usize) -> Color {
+		self.filtered_pixel(j, i, w, h, |v| Some(Radiance::from_color(obj.get_collision(self.position, v, self.view_distance).c)))
+			.map_or(Color::BLACK, Radiance::to_color)
+	}
+
+	/// [`RayCamera::shade_pixel`] together with the surface on the center ray of the pixel. The
+	/// center ray also shades the pixel if it is one of the samples, otherwise it is traced once
+	/// more for the surface.
+	fn shade_pixel_with_surface<R : RayRenderable + ?Sized>(&self, obj: &R, j: usize, i: usize, w: usize, h: usize) -> (Color, Option<SurfaceHit>) {
+		let center = self.get_ray_vec(j, i, w, h);
+		let surface = Cell::new(None);
+		let color = self.filtered_pixel(j, i, w, h, |v| {
+			let (c, hit) = obj.get_collision_with_surface(self.position, v, self.view_distance);
+			if v == center {
+				surface.set(Some(hit));
+			}
+			Some(Radiance::from_color(c.c))
+		}).map_or(Color::BLACK, Radiance::to_color);
+		let hit = surface.get().unwrap_or_else(|| obj.get_surface(self.position, center, self.view_distance));
+		(color, hit)
+	}
+
+	/// Supersampled radiance of pixel (j, i), values above 1 are kept.
+	fn shade_radiance<R : RayRenderable + ?Sized>(&self, obj: &R, j: usize, i: usize, w: usize, h: usize) -> Radiance {
+		self.filtered_pixel(j, i, w, h, |v| Some(obj.get_radiance(self.position, v, self.view_distance)))
+			.unwrap_or(Radiance::black())
+	}
+
+	/// Renders `tile` of a `w` x `h` image, column-major within the tile. Adaptive supersampling
+	/// looks one pixel beyond the tile, so tiles put together give the same image as one tile
+	/// over the whole frame.
+	pub fn render_tile<R : RayRenderable + ?Sized>(&self, obj: &R, tile: TileRect, w: usize, h: usize) -> Vec<Color> {
+		self.render_tile_surfaces(obj, tile, w, h, false).0
+	}
+
+	/// [`RayCamera::render_tile`], with `surfaces` also the surface on the center ray of every
+	/// pixel and its linear depth in the same order. They come from the rays that shade the
+	/// pixels, the scene is traversed only once.
+	fn render_tile_surfaces<R : RayRenderable + ?Sized>(&self, obj: &R, tile: TileRect, w: usize, h: usize, surfaces: bool) -> (Vec<Color>, Vec<(Option<SurfaceHit>, f64)>) {
+		let TileRect { x, y, width: tw, height: th } = tile;
+		let forward = self.basis().0;
+		let mut hits = Vec::new();
+		let threshold = match self.supersampling.adaptive {
+			Some(threshold) if self.supersampling.is_enabled() => threshold,
+			_ => {
+				let mut pixels = Vec::with_capacity(tw * th);
+				for j in x..x + tw {
+					for i in y..y + th {
+						if surfaces {
+							let (c, hit) = self.shade_pixel_with_surface(obj, j, i, w, h);
+							pixels.push(c);
+							hits.push((hit, self.linear_depth(hit, forward)));
+						} else {
+							pixels.push(self.shade_pixel(obj, j, i, w, h));
+						}
+					}
+				}
+				return (pixels, hits);
+			}
+		};
+
+		// first pass: center rays of the tile and its border
+		let (x0, y0) = (x.saturating_sub(1), y.saturating_sub(1));
+		let (x1, y1) = (usize::min(x + tw + 1, w), usize::min(y + th + 1, h));
+		let (aw, ah) = (x1 - x0, y1 - y0);
+		let mut apron = Vec::with_capacity(aw * ah);
+		for j in x0..x1 {
+			for i in y0..y1 {
+				let v = self.get_ray_vec(j, i, w, h);
+				if surfaces && j >= x && j < x + tw && i >= y && i < y + th {
+					let (c, hit) = obj.get_collision_with_surface(self.position, v, self.view_distance);
+					apron.push(c.c);
+					hits.push((hit, self.linear_depth(hit, forward)));
+				} else {
+					apron.push(obj.get_collision(self.position, v, self.view_distance).c);
 				}
 			}
 		}
+
+		// second pass: the full grid where neighbors differ
+		let mut pixels = Vec::with_capacity(tw * th);
+		let edges = edge_pixels(&apron, aw, ah, threshold);
+		for j in x..x + tw {
+			for i in y..y + th {
+				pixels.push(apron[(j - x0) * ah + i - y0]);
+			}
+		}
+		for (a, b) in edges {
+			let (j, i) = (a + x0, b + y0);
+			if j >= x && j < x + tw && i >= y && i < y + th {
+				pixels[(j - x) * th + i - y] = self.shade_pixel(obj, j, i, w, h);
+			}
+		}
+		(pixels, hits)
 	}
 
-	pub fn draw_modulus(&self, p: &Vec<Color>, canvas : &mut Canvas<Window>, index: usize, n : usize, w: usize, h : usize) {
+	/// Copies the columns rendered by [`RayCamera::render_modulus`] into `target`.
+	pub fn draw_modulus(&self, p: &[Color], target : &mut VirtualCanvas, index: usize, n : usize) {
+		let (w, h) = (target.width, target.height);
 		let mut pos : usize = 0;
 
-		for j in 0..w {
-			if (j % n == index) {
-				for i in 0..h {
-					let c = p[pos];
-					pos = pos + 1;
-					canvas.set_draw_color(c);
-			
-					canvas.draw_point(Point::new((j) as i32, (i) as i32));
-				}
+		for j in (index..w).step_by(n) {
+			for i in 0..h {
+				target.draw_sdl2_color(j, i, p[pos]);
+				pos += 1;
 			}
 		}
 	}
 
+	/// Every `n`-th column starting at `index`, column-major. Only sees a part of the frame, so
+	/// adaptive supersampling falls back to the full grid for every pixel.
 	pub fn render_modulus<R : RayRenderable>(&self, obj: &R, w: usize, h : usize, index : usize, n : usize) -> Vec<Color> {
-		//let mut pos : usize = 0;
 		let mut pixels: Vec<Color> = Vec::new();
+		for j in (index..w).step_by(n) {
+			for i in 0..h {
+				pixels.push(self.shade_pixel(obj, j, i, w, h));
+			}
+		}
+		pixels
+	}
 
+	/// Renders the frame in tiles on a temporary [`TileRenderer`] with `threads` workers and
+	/// returns it column-major. Produces the same image as [`RayCamera::render`].
+	pub fn render_frame_multi<R: RayRenderable + 'static>(&self, obj: Arc<R>, w: usize, h: usize, threads: usize) -> Vec<Color> {
+		let frame = TileRenderer::new(threads).render(self, obj, w, h).wait().expect("render job was cancelled");
+		let mut pixels: Vec<Color> = Vec::with_capacity(w * h);
 		for j in 0..w {
-			if (j % n == index) {
-				for i in 0..h {
-					let mut v = self.get_ray_vec(j, i, w, h);
-					let c = obj.get_collision(self.position, v, 100.0);
-					pixels.push(c.c);
-				}
+			for i in 0..h {
+				pixels.push(frame.get_sdl2_color(j, i));
 			}
 		}
-
 		pixels
-	}	
+	}
 
-	pub fn render_modulus_multi<R : RayRenderable>(&self, obj: Arc<R>, w: usize, h : usize, index : usize, n : usize) -> Vec<Color> {
-		let mut pos : usize = 0;
-		let mut pixels: Vec<Color> = Vec::new();
+	/// Renders a full frame into `target` without touching SDL.
+	pub fn render<R : RayRenderable>(&self, obj: &R, target: &mut VirtualCanvas) {
+		let (w, h) = (target.width, target.height);
+		let pixels = self.render_tile(obj, TileRect { x: 0, y: 0, width: w, height: h }, w, h);
+		for j in 0..w {
+			for i in 0..h {
+				target.draw_sdl2_color(j, i, pixels[j * h + i]);
+			}
+		}
+	}
 
+	/// Multithreaded variant of [`RayCamera::render`], see [`RayCamera::render_frame_multi`].
+	pub fn render_multi<R: RayRenderable + 'static>(&self, obj: Arc<R>, target: &mut VirtualCanvas, threads: usize) {
+		*target = TileRenderer::new(threads).render(self, obj, target.width, target.height).wait().expect("render job was cancelled");
+	}
+
+	/// Renders a full frame into an HDR buffer, without clamping the shading. Supersampling
+	/// applies, adaptive refinement does not.
+	pub fn render_hdr<R : RayRenderable>(&self, obj: &R, target: &mut HdrBuffer) {
+		let (w, h) = (target.width, target.height);
 		for j in 0..w {
-			if (j % n == index) {
-				for i in 0..h {
-					let v = self.get_ray_vec(j, i, w, h);
-					let c = obj.get_collision(self.position, v, 100.0);
-					pixels.push(c.c);
-				}
+			for i in 0..h {
+				target.set(j, i, self.shade_radiance(obj, j, i, w, h));
 			}
 		}
+	}
 
-		pixels
-	}	
+	/// Queues the pixels of [`RayCamera::render_hdr`] for a `w` x `h` image on `renderer`.
+	pub fn hdr_job<R: RayRenderable + 'static>(&self, renderer: &TileRenderer, obj: Arc<R>, w: usize, h: usize) -> TileJob<Radiance> {
+		let camera = *self;
+		renderer.render_pixels(w, h, move |j, i| camera.shade_radiance(&*obj, j, i, w, h))
+	}
 
-	pub fn render_frame_multi<R: RayRenderable + 'static>(&self, obj: Arc<R>, w: usize, h: usize, threads: usize) -> Vec<Color> {
-		let n = if threads == 0 { 1 } else { threads };
-		let (tx, rx) = mpsc::channel::<(usize, Vec<Color>)>();
+	/// Multithreaded variant of [`RayCamera::render_hdr`] on a temporary [`TileRenderer`].
+	pub fn render_hdr_multi<R: RayRenderable + 'static>(&self, obj: Arc<R>, target: &mut HdrBuffer, threads: usize) {
+		let (w, h) = (target.width, target.height);
+		let pixels = self.hdr_job(&TileRenderer::new(threads), obj, w, h).wait().expect("render job was cancelled");
+		for i in 0..h {
+			for j in 0..w {
+				target.set(j, i, pixels[i * w + j]);
+			}
+		}
+	}
 
-		for i in 0..n {
-			let camera = *self;
-			let tx = tx.clone();
-			let obj = Arc::clone(&obj);
+	/// Linear depth of `hit`, the distance along the view direction `forward` rather than along
+	/// the ray.
+	fn linear_depth(&self, hit: Option<SurfaceHit>, forward: V3) -> f64 {
+		hit.map_or(f64::INFINITY, |hit| {
+			let mut d = hit.p;
+			d.subtract(self.position);
+			d.dt(forward)
+		})
+	}
+
+	/// First surface on the center ray of pixel (j, i) and its linear depth along `forward`.
+	fn surface_at<R : RayRenderable + ?Sized>(&self, obj: &R, j: usize, i: usize, w: usize, h: usize, forward: V3) -> (Option<SurfaceHit>, f64) {
+		let hit = obj.get_surface(self.position, self.get_ray_vec(j, i, w, h), self.view_distance);
+		(hit, self.linear_depth(hit, forward))
+	}
+
+	/// Renders the color image of `obj` like [`RayCamera::render`] together with the auxiliary
+	/// buffers in `selection`. The buffers come from the center ray of every pixel, so they stay
+	/// sharp with supersampling, and that ray also shades the pixel.
+	pub fn render_aovs<R : RayRenderable>(&self, obj: &R, w: usize, h: usize, selection: AovSelection) -> AovBuffers {
+		let mut buffers = AovBuffers::new(w, h, selection);
+		let tile = TileRect { x: 0, y: 0, width: w, height: h };
+		let (pixels, hits) = self.render_tile_surfaces(obj, tile, w, h, selection != AovSelection::none());
+		self.draw_aov_tile(&mut buffers, tile, &pixels, &hits);
+		buffers
+	}
 
-			thread::spawn(move || {
-				let section = camera.render_modulus_multi(obj, w, h, i, n);
-				let _ = tx.send((i, section));
-			});
+	/// Puts a tile of [`RayCamera::render_tile_surfaces`] into `target`.
+	fn draw_aov_tile(&self, target: &mut AovBuffers, tile: TileRect, pixels: &[Color], hits: &[(Option<SurfaceHit>, f64)]) {
+		for (k, &c) in pixels.iter().enumerate() {
+			let (j, i) = (tile.x + k / tile.height, tile.y + k % tile.height);
+			target.color.draw_sdl2_color(j, i, c);
+			if let Some(&(hit, depth)) = hits.get(k) {
+				target.set(j, i, hit, depth);
+			}
 		}
+	}
 
-		let mut sections: Vec<Vec<Color>> = vec![Vec::new(); n];
-		for _ in 0..n {
-			if let Ok((idx, section)) = rx.recv() {
-				sections[idx] = section;
+	/// Fills only the auxiliary buffers of `target` and leaves its color image alone, for
+	/// guiding a denoiser over a color image that is rendered some other way.
+	pub fn trace_aovs<R : RayRenderable + ?Sized>(&self, obj: &R, target: &mut AovBuffers) {
+		if target.selection() == AovSelection::none() {
+			return;
+		}
+		let (w, h) = (target.width, target.height);
+		let forward = self.basis().0;
+		for j in 0..w {
+			for i in 0..h {
+				let (hit, depth) = self.surface_at(obj, j, i, w, h, forward);
+				target.set(j, i, hit, depth);
 			}
 		}
+	}
 
-		let mut counters: Vec<usize> = vec![0; n];
-		let mut pixels: Vec<Color> = Vec::with_capacity(w * h);
+	/// Multithreaded variant of [`RayCamera::trace_aovs`], in tiles on `renderer`.
+	pub fn trace_aovs_on<R: RayRenderable + 'static>(&self, renderer: &TileRenderer, obj: Arc<R>, target: &mut AovBuffers) {
+		if target.selection() == AovSelection::none() {
+			return;
+		}
+		let (w, h) = (target.width, target.height);
+		let camera = *self;
+		let forward = self.basis().0;
+		let surfaces = renderer.render_pixels(w, h, move |j, i| camera.surface_at(&*obj, j, i, w, h, forward))
+			.wait()
+			.expect("render job was cancelled");
+		for i in 0..h {
+			for j in 0..w {
+				let (hit, depth) = surfaces[i * w + j];
+				target.set(j, i, hit, depth);
+			}
+		}
+	}
+
+	/// Multithreaded variant of [`RayCamera::render_aovs`], in tiles on a temporary
+	/// [`TileRenderer`].
+	pub fn render_aovs_multi<R: RayRenderable + 'static>(&self, obj: Arc<R>, w: usize, h: usize, selection: AovSelection, threads: usize) -> AovBuffers {
+		let mut buffers = AovBuffers::new(w, h, selection);
+		let surfaces = selection != AovSelection::none();
+		let camera = *self;
+		let job = TileRenderer::new(threads).render_tiles(w, h, move |rect| {
+			let (pixels, hits) = camera.render_tile_surfaces(&*obj, rect, w, h, surfaces);
+			pixels.into_iter().enumerate().map(|(k, c)| (c, hits.get(k).copied())).collect()
+		});
+		for tile in job {
+			let pixels: Vec<Color> = tile.pixels.iter().map(|p| p.0).collect();
+			let hits: Vec<(Option<SurfaceHit>, f64)> = tile.pixels.iter().filter_map(|p| p.1).collect();
+			self.draw_aov_tile(&mut buffers, tile.rect, &pixels, &hits);
+		}
+		buffers
+	}
+
+	/// Path traces one more frame of `scene` into `acc`. Every frame uses different
+	/// random numbers, so the average in `acc` converges with each call. With an aperture
+	/// each frame also uses other points on the lens, which gives depth of field.
+	pub fn render_progressive(&self, scene: &RayTracingScene, acc: &mut Accumulator) {
+		let (w, h) = (acc.width, acc.height);
+		let mut frame = vec![Radiance::black(); w * h];
 		for j in 0..w {
-			let idx = j % n;
-			for _i in 0..h {
-				let p = sections[idx][counters[idx]];
-				counters[idx] += 1;
-				pixels.push(p);
+			for i in 0..h {
+				frame[i * w + j] = self.sample_pixel(scene, j, i, w, h, acc.frames);
 			}
 		}
+		acc.add_frame(&frame);
+	}
 
-		pixels
+	/// Queues the next frame of [`RayCamera::render_progressive`] on `renderer`, the pixels of
+	/// the finished job go into `acc` with [`Accumulator::add_frame`].
+	pub fn progressive_job(&self, renderer: &TileRenderer, scene: Arc<RayTracingScene>, acc: &Accumulator) -> TileJob<Radiance> {
+		let camera = *self;
+		let (w, h, frame) = (acc.width, acc.height, acc.frames);
+		renderer.render_pixels(w, h, move |j, i| camera.sample_pixel(&scene, j, i, w, h, frame))
+	}
+
+	/// Multithreaded variant of [`RayCamera::render_progressive`], in tiles on `renderer`.
+	/// Produces the same image as the single threaded one.
+	pub fn render_progressive_on(&self, renderer: &TileRenderer, scene: Arc<RayTracingScene>, acc: &mut Accumulator) {
+		let frame = self.progressive_job(renderer, scene, acc).wait().expect("render job was cancelled");
+		acc.add_frame(&frame);
+	}
+
+	/// [`RayCamera::render_progressive_on`] a temporary [`TileRenderer`] with `threads` workers.
+	pub fn render_progressive_multi(&self, scene: Arc<RayTracingScene>, acc: &mut Accumulator, threads: usize) {
+		self.render_progressive_on(&TileRenderer::new(threads), scene, acc);
+	}
+
+	fn sample_pixel(&self, scene: &RayTracingScene, j: usize, i: usize, w: usize, h: usize, frame: u32) -> Radiance {
+		let mut rng = Rng::with_stream(hash_combine(&[scene.seed, frame as u64]), (i * w + j) as u64);
+		if self.aperture > 0.0 {
+			let (p0, v) = self.lens_ray(j as f64 + 0.5, i as f64 + 0.5, w, h, rng.next_f64(), rng.next_f64());
+			return scene.sample(p0, v, &mut rng);
+		}
+		let v = self.get_ray_vec(j, i, w, h);
+		scene.sample(self.position, v, &mut rng)
 	}
 
-	pub fn draw_section(&self, p: &Vec<Color>, canvas : &mut Canvas<Window>, i1: usize, j1 : usize, i2: usize, j2 : usize) {
+	/// Renders one ray per `blocksize` x `blocksize` block, for every `n`-th column of blocks
+	/// starting at `index`, and fills the blocks with it. A quick preview while the camera moves.
+	pub fn render_modulus_block<R : RayRenderable>(&self, target : &mut VirtualCanvas, obj: &R, blocksize : usize, index: usize, n : usize) {
+		let (w, h) = (target.width, target.height);
+		for j in (0..w).step_by(blocksize) {
+			if (j / blocksize) % n == index {
+				for i in (0..h).step_by(blocksize) {
+					let v = self.get_ray_vec(j, i, w, h);
+					let coll = obj.get_collision(self.position, v, self.view_distance);
+					target.fill_block(j, i, blocksize, coll.c);
+				}
+			}
+		}
+	}
+
+	/// Multithreaded variant of [`RayCamera::render_modulus_block`] for the whole frame, on
+	/// `renderer`. The job has one color per block, `w.div_ceil(blocksize)` by
+	/// `h.div_ceil(blocksize)` of them, from the ray through the top left pixel of the block.
+	pub fn block_job<R: RayRenderable + 'static>(&self, renderer: &TileRenderer, obj: Arc<R>, w: usize, h: usize, blocksize: usize) -> TileJob<Color> {
+		let camera = *self;
+		renderer.render_pixels(w.div_ceil(blocksize), h.div_ceil(blocksize), move |bj, bi| {
+			let v = camera.get_ray_vec(bj * blocksize, bi * blocksize, w, h);
+			obj.get_collision(camera.position, v, camera.view_distance).c
+		})
+	}
+
+	/// Copies a section rendered by [`RayCamera::render_section`] into `target`, transparent
+	/// pixels are left out.
+	pub fn draw_section(&self, p: &[Color], target : &mut VirtualCanvas, i1: usize, j1 : usize, i2: usize, j2 : usize) {
 		let mut pos : usize = 0;
 
 		for i in i1..i2 {
 			for j in j1..j2 {
 				let c = p[pos];
-				pos = pos + 1;
-				if (c.a != 0) {
-				canvas.set_draw_color(c);
-        
-        		canvas.draw_point(Point::new((i) as i32, (j) as i32));
+				pos += 1;
+				if c.a != 0 {
+					target.draw_sdl2_color(i, j, c);
 				}
 			}
-		}	
+		}
 	}
 
 	pub fn render_section(&self, j1: usize, i1 : usize, j2: usize, i2 : usize, obj: &dyn RayRenderable, w: usize, h : usize) -> Vec<Color> {
 		let mut section: Vec<Color> = Vec::new();
 
+		// samples that miss do not count, pixels without any hit stay transparent
 		for i in i1..i2 {
 			for j in j1..j2 {
-				let v = self.get_ray_vec(j, i, w, h);
-				let c = obj.get_collision(self.position, v, 100.0);
-				if (c.hit) {
-					section.push(c.c);
-				}
-				else {
-					section.push(Color::RGBA(0, 0, 0, 0));
-				}
+				let c = self.filtered_pixel(j, i, w, h, |v| {
+					let c = obj.get_collision(self.position, v, self.view_distance);
+					if c.hit { Some(Radiance::from_color(c.c)) } else { None }
+				});
+				section.push(c.map_or(Color::RGBA(0, 0, 0, 0), Radiance::to_color));
 			}
 		}
 
 		return section;
 	}
 
-    pub fn render_pixel_at(&self, j: usize, i : usize, canvas : &mut Canvas<Window>, obj: &dyn RayRenderable, w: usize, h : usize,) {
-        let v = self.get_ray_vec(j, i, w, h);
-        let mut c = obj.get_collision(self.position, v, 100.0);
-    
-        canvas.set_draw_color(c.c);
-        
-        canvas.draw_point(Point::new(j as i32, i as i32));
+	pub fn render_pixel_at(&self, j: usize, i : usize, target : &mut VirtualCanvas, obj: &dyn RayRenderable) {
+		let c = self.shade_pixel(obj, j, i, target.width, target.height);
+		target.draw_sdl2_color(j, i, c);
 	}
 
-	pub fn render_anker_labels<W : WithLabels>(&self, a : &W, canvas : &mut Canvas<Window>, w: usize, h : usize) {
-		let labels = a.get_labels();
-
-		for l in labels.iter() {
-			self.render_anker_label(l, canvas, w, h);
+	pub fn render_anker_labels_to<W : WithLabels>(&self, a : &W, target : &mut VirtualCanvas) {
+		for l in a.get_labels().iter() {
+			self.render_anker_label_to(l, target);
 		}
 	}
 
-	pub fn render_anker_label(&self, a : &AnkerLabel, canvas : &mut Canvas<Window>, w: usize, h : usize) {
-		let mut pos : usize = 0;
-
+	pub fn render_anker_label_to(&self, a : &AnkerLabel, target : &mut VirtualCanvas) {
+		let (w, h) = (target.width, target.height);
 		for j in 0..w {
 			for i in 0..h {
 				let v = self.get_ray_vec(j, i, w, h);
-				if (a.is_colliding(self.position, v)) {
-					a.render(canvas, j as i32, i as i32);
+				if a.is_colliding(self.position, v) {
+					a.render_to(target, j as i32, i as i32);
 					return;
 				}
 			}
@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+use sdl2::pixels::Color;
+
+use crate::engine::lighting::Material;
+use crate::engine::pathtracing::RaytracingObject;
+use crate::engine::utils::rendering::Collision;
+use crate::engine::utils::transformation::{Transform, Transformable};
+use crate::geometry::aabb::Aabb;
+use crate::geometry::vector3::Vector3 as V3;
+
+/// One placement of an object that is shared with other instances, e.g. a mesh with its
+/// `PolyTree` or `Bvh`. Cloning an instance only copies the pointer, the transform and the
+/// material, so a scene can hold thousands of the same model. Rays are intersected with the
+/// shared object in its own coordinates; transforming an instance never touches the object.
+#[derive(Clone)]
+pub struct Instance {
+    pub object: Arc<dyn RaytracingObject + Send + Sync>,
+    pub transform: Transform,
+    /// Replaces the materials of the object everywhere, textures included.
+    pub material: Option<Material>,
+}
+
+impl Instance {
+    pub fn new(object: Arc<dyn RaytracingObject + Send + Sync>, transform: Transform) -> Self {
+        Instance { object, transform, material: None }
+    }
+
+    /// Another instance of the same object at `transform`, with the same material.
+    pub fn place(&self, transform: Transform) -> Self {
+        Instance { transform, ..Clone::clone(self) }
+    }
+
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+    }
+
+    pub fn set_material(&mut self, material: Material) {
+        self.material = Some(material);
+    }
+
+    fn with_material(&self, c: Collision) -> Collision {
+        match &self.material {
+            Some(m) if c.hit => Collision { c: m.albedo, ..c },
+            _ => c,
+        }
+    }
+}
+
+impl Transformable for Instance {
+    fn rot_reverse(&mut self, r: V3) {
+        self.transform.rot_reverse(r);
+    }
+
+    fn rot(&mut self, r: V3) {
+        self.transform.rot(r);
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        self.transform.rot_by(p, r);
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.transform.translate(p);
+    }
+
+    fn scale(&mut self, p: V3) {
+        self.transform.scale(p);
+    }
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
+}
+
+impl RaytracingObject for Instance {
+    fn d(&self, p: V3) -> f64 {
+        self.object.d(self.transform.inverse_point(p)) * self.transform.min_scale()
+    }
+
+    fn color(&self, p: V3) -> Color {
+        match &self.material {
+            Some(m) => m.albedo,
+            None => self.object.color(self.transform.inverse_point(p)),
+        }
+    }
+
+    fn is_colliding(&mut self, p0: V3, p: V3) -> bool {
+        self.get_collision(p0, p).hit
+    }
+
+    fn get_collision(&self, p0: V3, p: V3) -> Collision {
+        self.get_collision_with_normal(p0, p).0
+    }
+
+    fn get_collision_with_normal(&self, p0: V3, p: V3) -> (Collision, Option<V3>) {
+        let (o, d, l) = self.transform.inverse_ray(p0, p);
+        let (c, n) = self.object.get_collision_with_normal(o, d);
+        let (c, n) = self.transform.apply_collision(c, n, p, l);
+        (self.with_material(c), n)
+    }
+
+    fn get_collision_with_material(&self, p0: V3, p: V3) -> (Collision, Option<V3>, Material) {
+        let (o, d, l) = self.transform.inverse_ray(p0, p);
+        let (c, n, m) = self.object.get_collision_with_material(o, d);
+        let (c, n) = self.transform.apply_collision(c, n, p, l);
+        (self.with_material(c), n, self.material.clone().unwrap_or(m))
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.object.bounds().transformed(|p| self.transform.apply_point(p))
+    }
+
+    fn clone(&self) -> Box<dyn RaytracingObject + Send + Sync> {
+        Box::new(Clone::clone(self))
+    }
+}
@@ -8,24 +8,249 @@
  * You should have received a copy of the GNU General Public License along with this program. If not, see <https://www.gnu.org/licenses/>.
  */
 
+use std::sync::Arc;
+
 use sdl2::pixels::Color;
+use crate::engine::utils::radiance::Radiance;
+use crate::engine::utils::sampling::sample_cone;
+use crate::engine::utils::texture::Texture;
 use crate::engine::utils::transformation::Transformable;
 use crate::geometry::vector3::Vector3 as V3;
 
-#[derive(Clone)]
+/// Shape and emission of a light. Positional kinds are placed at `Light::position`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LightKind {
+    /// Emits equally in all directions.
+    Point,
+    /// Parallel light from infinitely far away travelling along `direction`, e.g. the sun.
+    Directional { direction: V3 },
+    /// Point light limited to a cone around `direction`: full intensity up to the half angle
+    /// `inner_angle`, fading out until `outer_angle` (radians).
+    Spot { direction: V3, inner_angle: f64, outer_angle: f64 },
+    /// One-sided rectangle centered at the position with the edges `u` and `v`, emitting
+    /// towards `u × v`. The intensity is the emitted radiance, so a small light of area `A`
+    /// looks like a point light with `A * intensity` and inverse-square falloff.
+    RectArea { u: V3, v: V3 },
+    /// Sphere around the position, with the intensity as emitted radiance like `RectArea`.
+    SphereArea { radius: f64 },
+}
+
+/// Distance falloff `1 / (constant + linear * d + quadratic * d²)`. Lights have none by
+/// default, i.e. the same brightness at any distance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Attenuation {
+    pub constant: f64,
+    pub linear: f64,
+    pub quadratic: f64,
+}
+
+impl Attenuation {
+    pub fn new(constant: f64, linear: f64, quadratic: f64) -> Self {
+        Attenuation { constant, linear, quadratic }
+    }
+
+    pub fn none() -> Self {
+        Attenuation::new(1.0, 0.0, 0.0)
+    }
+
+    /// Physically correct falloff, `intensity` is the brightness at distance 1.
+    pub fn inverse_square() -> Self {
+        Attenuation::new(0.0, 0.0, 1.0)
+    }
+
+    pub fn factor(&self, d: f64) -> f64 {
+        let denom = self.constant + self.linear * d + self.quadratic * d * d;
+        if denom <= 1e-12 {
+            return 0.0;
+        }
+        1.0 / denom
+    }
+}
+
+/// One sample of the light arriving at a point.
+#[derive(Clone, Copy, Debug)]
+pub struct LightSample {
+    /// Unit vector from the point towards the light.
+    pub direction: V3,
+    /// Distance to the sampled point on the light, infinite for directional lights.
+    pub distance: f64,
+    pub radiance: Radiance,
+}
+
+#[derive(Clone, Debug)]
 pub struct Light {
     pub position: V3,
     pub color: Color,
     pub intensity: f64,
+    pub kind: LightKind,
+    pub attenuation: Attenuation,
+}
+
+impl Light {
+    pub fn point(position: V3, color: Color, intensity: f64) -> Self {
+        Light { position, color, intensity, kind: LightKind::Point, attenuation: Attenuation::none() }
+    }
+
+    pub fn directional(direction: V3, color: Color, intensity: f64) -> Self {
+        let mut direction = direction;
+        direction.normalize();
+        Light {
+            kind: LightKind::Directional { direction },
+            ..Light::point(V3::new(0.0, 0.0, 0.0), color, intensity)
+        }
+    }
+
+    pub fn spot(position: V3, direction: V3, inner_angle: f64, outer_angle: f64, color: Color, intensity: f64) -> Self {
+        let mut direction = direction;
+        direction.normalize();
+        Light {
+            kind: LightKind::Spot { direction, inner_angle, outer_angle: f64::max(outer_angle, inner_angle) },
+            ..Light::point(position, color, intensity)
+        }
+    }
+
+    pub fn rect_area(position: V3, u: V3, v: V3, color: Color, intensity: f64) -> Self {
+        Light {
+            kind: LightKind::RectArea { u, v },
+            ..Light::point(position, color, intensity)
+        }
+    }
+
+    pub fn sphere_area(position: V3, radius: f64, color: Color, intensity: f64) -> Self {
+        Light {
+            kind: LightKind::SphereArea { radius },
+            ..Light::point(position, color, intensity)
+        }
+    }
+
+    pub fn set_attenuation(&mut self, attenuation: Attenuation) {
+        self.attenuation = attenuation;
+    }
+
+    /// Lights without extent need only one sample, area lights are averaged over several.
+    pub fn is_delta(&self) -> bool {
+        !matches!(self.kind, LightKind::RectArea { .. } | LightKind::SphereArea { .. })
+    }
+
+    /// Factor for the BRDF of the shading, which leaves out the 1/π of the lambertian and the
+    /// Blinn-Phong lobe. Punctual lights are given by the light that a white matte surface
+    /// facing them reflects, so it is 1 for them. Area lights are given by the radiance they
+    /// emit and reflect like emissive geometry of that radiance, through the full albedo / π.
+    pub fn brdf_scale(&self) -> f64 {
+        if self.is_delta() {
+            1.0
+        } else {
+            1.0 / std::f64::consts::PI
+        }
+    }
+
+    /// Light arriving at `p` from one point on the light, picked by `u` and `v` in [0, 1).
+    /// Averaging samples over uniform `u`, `v` gives the light of the whole area: area lights
+    /// weight each sample by the solid angle it stands for.
+    pub fn sample(&self, p: V3, u: f64, v: f64) -> LightSample {
+        let base = Radiance::from_color(self.color) * self.intensity;
+
+        // solid angle of the cone of directions sampled for sphere lights
+        let mut cone = 1.0;
+        let target = match &self.kind {
+            LightKind::Directional { direction } => {
+                return LightSample {
+                    direction: V3::new(-direction.x, -direction.y, -direction.z),
+                    distance: f64::INFINITY,
+                    radiance: base,
+                };
+            }
+            LightKind::Point | LightKind::Spot { .. } => self.position,
+            LightKind::RectArea { u: eu, v: ev } => {
+                let mut a = *eu;
+                a.scale(u - 0.5);
+                let mut b = *ev;
+                b.scale(v - 0.5);
+                let mut t = self.position;
+                t.add(a);
+                t.add(b);
+                t
+            }
+            LightKind::SphereArea { radius } => {
+                let mut to_center = self.position;
+                to_center.subtract(p);
+                let d = to_center.norm();
+                if d <= *radius {
+                    self.position
+                } else {
+                    // uniform in the cone of directions that see the sphere
+                    to_center.normalize();
+                    let sin_max = radius / d;
+                    let cos_max = f64::sqrt(f64::max(1.0 - sin_max * sin_max, 0.0));
+                    cone = 2.0 * std::f64::consts::PI * (1.0 - cos_max);
+                    let dir = sample_cone(to_center, cos_max, u, v);
+                    let cos_t = dir.dt(to_center);
+                    let t = d * cos_t - f64::sqrt(f64::max(radius * radius - d * d * (1.0 - cos_t * cos_t), 0.0));
+                    let mut hit = dir;
+                    hit.scale(t);
+                    hit.add(p);
+                    hit
+                }
+            }
+        };
+
+        let mut direction = target;
+        direction.subtract(p);
+        let distance = direction.norm();
+        if distance > 0.0 {
+            direction.normalize();
+        }
+
+        let shape = match &self.kind {
+            LightKind::Spot { direction: axis, inner_angle, outer_angle } => {
+                let cos_a = -direction.dt(*axis);
+                let (cos_in, cos_out) = (f64::cos(*inner_angle), f64::cos(*outer_angle));
+                if cos_in - cos_out <= 1e-9 {
+                    if cos_a >= cos_out { 1.0 } else { 0.0 }
+                } else {
+                    let t = f64::clamp((cos_a - cos_out) / (cos_in - cos_out), 0.0, 1.0);
+                    t * t * (3.0 - 2.0 * t)
+                }
+            }
+            LightKind::RectArea { u: eu, v: ev } => {
+                let mut n = *eu;
+                n.cross(*ev);
+                let area = n.norm();
+                if distance <= 0.0 || area <= 0.0 {
+                    return LightSample { direction, distance, radiance: Radiance::black() };
+                }
+                n.normalize();
+                // uniform over the area: the sample stands for area / distance² of solid angle
+                f64::max(-direction.dt(n), 0.0) * area / (distance * distance)
+            }
+            LightKind::SphereArea { .. } => cone,
+            _ => 1.0,
+        };
+
+        LightSample { direction, distance, radiance: base * (shape * self.attenuation.factor(distance)) }
+    }
+
+    fn rotate_kind(&mut self, r: V3) {
+        match &mut self.kind {
+            LightKind::Directional { direction } | LightKind::Spot { direction, .. } => direction.rotate(r),
+            LightKind::RectArea { u, v } => {
+                u.rotate(r);
+                v.rotate(r);
+            }
+            _ => {}
+        }
+    }
 }
 
 impl Transformable for Light {
     fn rot(&mut self, r: V3) {
         self.position.rotate(r);
+        self.rotate_kind(r);
     }
 
     fn rot_by(&mut self, p: V3, r: V3) {
         self.position.rot_by(p, r);
+        self.rotate_kind(r);
     }
 
     fn translate(&mut self, p: V3) {
@@ -36,6 +261,17 @@ impl Transformable for Light {
         self.position.x *= p.x;
         self.position.y *= p.y;
         self.position.z *= p.z;
+        match &mut self.kind {
+            LightKind::RectArea { u, v } => {
+                for e in [u, v] {
+                    e.x *= p.x;
+                    e.y *= p.y;
+                    e.z *= p.z;
+                }
+            }
+            LightKind::SphereArea { radius } => *radius *= p.x,
+            _ => {}
+        }
     }
 
     fn transform(&mut self) -> Box<&mut dyn Transformable> {
@@ -43,14 +279,172 @@ impl Transformable for Light {
     }
 }
 
-#[derive(Clone, Copy)]
+/// Physically based surface description, shared by the ray tracer, the ray marcher and meshes.
+///
+/// Dielectrics reflect according to their index of refraction, metals tint their reflection
+/// with the albedo and have no diffuse part. Roughness goes from a perfect mirror (0.0) to a
+/// fully matte surface (1.0).
+#[derive(Clone, Debug)]
 pub struct Material {
-    pub color: Color,
-    pub diffuse: f64,
+    pub albedo: Color,
+    pub roughness: f64,
+    pub metalness: f64,
+    /// Emitted light, added on top of the reflected light. Can exceed 1.0.
+    pub emission: Radiance,
+    pub ior: f64,
+    /// Fraction of the non-reflected light that passes through the surface.
+    pub transmission: f64,
+    /// Beer-Lambert absorption coefficient per unit of distance travelled inside the object.
+    pub absorption: Radiance,
+    pub albedo_texture: Option<Arc<Texture>>,
+    /// Roughness is read from the red channel, replacing `roughness`.
+    pub roughness_texture: Option<Arc<Texture>>,
 }
 
 impl Material {
-    pub fn new(color: Color, diffuse: f64) -> Self {
-        Material { color, diffuse }
+    /// Dielectric with the given albedo and roughness, `Material::new(color, 1.0)` is fully matte.
+    pub fn new(albedo: Color, roughness: f64) -> Self {
+        Material {
+            albedo,
+            roughness: f64::clamp(roughness, 0.0, 1.0),
+            metalness: 0.0,
+            emission: Radiance::black(),
+            ior: 1.5,
+            transmission: 0.0,
+            absorption: Radiance::black(),
+            albedo_texture: None,
+            roughness_texture: None,
+        }
+    }
+
+    pub fn metal(albedo: Color, roughness: f64) -> Self {
+        Material {
+            metalness: 1.0,
+            ..Material::new(albedo, roughness)
+        }
+    }
+
+    pub fn mirror(albedo: Color) -> Self {
+        Material::metal(albedo, 0.0)
+    }
+
+    /// Clear, perfectly smooth dielectric such as glass (ior 1.5) or water (ior 1.33).
+    pub fn glass(ior: f64) -> Self {
+        Material {
+            ior,
+            transmission: 1.0,
+            ..Material::new(Color::WHITE, 0.0)
+        }
+    }
+
+    pub fn emissive(color: Color, strength: f64) -> Self {
+        Material {
+            emission: Radiance::from_color(color) * strength,
+            ..Material::new(Color::BLACK, 1.0)
+        }
+    }
+
+    pub fn set_emission(&mut self, color: Color, strength: f64) {
+        self.emission = Radiance::from_color(color) * strength;
+    }
+
+    /// Sets the absorption so that white light has the given color after travelling
+    /// `distance` through the object.
+    pub fn set_absorption(&mut self, color: Color, distance: f64) {
+        let c = Radiance::from_color(color);
+        let k = |v: f64| -f64::ln(f64::max(v, 1e-4)) / f64::max(distance, 1e-9);
+        self.absorption = Radiance::new(k(c.r), k(c.g), k(c.b));
+    }
+
+    pub fn set_albedo_texture(&mut self, texture: Arc<Texture>) {
+        self.albedo_texture = Some(texture);
+    }
+
+    pub fn set_roughness_texture(&mut self, texture: Arc<Texture>) {
+        self.roughness_texture = Some(texture);
+    }
+
+    /// The material at texture coordinates `(u, v)`, with textures resolved into plain values.
+    pub fn sample(&self, u: f64, v: f64) -> Material {
+        let mut m = Material {
+            albedo_texture: None,
+            roughness_texture: None,
+            ..self.clone()
+        };
+        if let Some(t) = &self.albedo_texture {
+            m.albedo = t.sample(u, v);
+        }
+        if let Some(t) = &self.roughness_texture {
+            m.roughness = t.sample(u, v).r as f64 / 255.0;
+        }
+        m
     }
-}
\ No newline at end of file
+
+    /// Same material with a different albedo, e.g. the color an object computed for a hit.
+    pub fn with_albedo(&self, albedo: Color) -> Material {
+        Material { albedo, ..self.clone() }
+    }
+
+    /// Reflectance at normal incidence: derived from the ior for dielectrics, the albedo for metals.
+    pub fn f0(&self) -> Radiance {
+        let r = (self.ior - 1.0) / (self.ior + 1.0);
+        let dielectric = Radiance::gray(r * r);
+        let albedo = Radiance::from_color(self.albedo);
+        dielectric * (1.0 - self.metalness) + albedo * self.metalness
+    }
+
+    /// Fresnel-Schlick reflectance for the cosine between view direction and normal.
+    pub fn fresnel(&self, cos_theta: f64) -> Radiance {
+        let f0 = self.f0();
+        let k = f64::powf(1.0 - f64::clamp(cos_theta, 0.0, 1.0), 5.0);
+        f0 + (Radiance::gray(1.0) - f0) * k
+    }
+
+    /// Weight of the lambertian part: metals and transmissive surfaces have none.
+    pub fn diffuse_weight(&self) -> f64 {
+        (1.0 - self.metalness) * (1.0 - self.transmission)
+    }
+
+    /// Lambertian reflectance seen from the given view angle, without the light
+    /// that the specular part already reflects.
+    pub fn diffuse_color(&self, cos_view: f64) -> Radiance {
+        let kd = self.diffuse_weight() * (1.0 - self.fresnel(cos_view).max_component());
+        Radiance::from_color(self.albedo) * kd
+    }
+
+    /// Weight of the refracted part, tinted by the albedo.
+    pub fn transmission_color(&self, fresnel: f64) -> Radiance {
+        let kt = (1.0 - self.metalness) * self.transmission * (1.0 - fresnel);
+        Radiance::from_color(self.albedo) * kt
+    }
+
+    /// Fraction of light left after travelling `distance` inside the object.
+    pub fn transmittance(&self, distance: f64) -> Radiance {
+        Radiance::new(
+            f64::exp(-self.absorption.r * distance),
+            f64::exp(-self.absorption.g * distance),
+            f64::exp(-self.absorption.b * distance),
+        )
+    }
+
+    /// Blinn-Phong exponent equivalent to the roughness.
+    pub fn shininess(&self) -> f64 {
+        let alpha = f64::max(self.roughness * self.roughness, 1e-3);
+        f64::clamp(2.0 / (alpha * alpha) - 2.0, 1.0, 1e5)
+    }
+
+    /// Specular reflection of a light: normalized Blinn-Phong times Fresnel, in the units of
+    /// the lights (a white lambertian surface facing a light reflects `intensity`).
+    pub fn specular(&self, normal: V3, view_dir: V3, light_dir: V3) -> Radiance {
+        let mut half = light_dir;
+        half.add(view_dir);
+        if half.norm() == 0.0 {
+            return Radiance::black();
+        }
+        half.normalize();
+
+        let n = self.shininess();
+        let ndoth = f64::max(normal.dt(half), 0.0);
+        self.fresnel(view_dir.dt(half)) * ((n + 8.0) / 8.0 * f64::powf(ndoth, n))
+    }
+}
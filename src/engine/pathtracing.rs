@@ -1,136 +1,69 @@
+use std::f64::consts::PI;
+use std::sync::{Arc, Mutex, OnceLock};
+
 use sdl2::pixels::Color;
 
-use crate::engine::lighting::Light;
-use crate::engine::utils::{rendering::{RayRenderable, Collision}, transformation::Transformable};
+use crate::engine::lighting::{Light, Material};
+use crate::engine::simplex3d_bvh::tlas::Tlas;
+use crate::engine::utils::{rendering::{RayRenderable, Collision, SurfaceHit}, transformation::Transformable};
+use crate::engine::utils::environment_map::EnvironmentMap;
+use crate::engine::utils::radiance::Radiance;
+use crate::engine::utils::random::{hash_combine, Rng};
+use crate::engine::utils::sampling::{sample_blinn_phong, sample_cosine_hemisphere, sample_glossy, tangent_frame};
+use crate::geometry::aabb::Aabb;
 use crate::geometry::vector3::Vector3 as V3;
 
-fn fract(x: f64) -> f64 {
-    x - x.floor()
-}
+/// Paths shorter than this are never terminated by russian roulette.
+const RUSSIAN_ROULETTE_DEPTH: usize = 3;
 
-fn hash3(seed: f64) -> f64 {
-    fract(f64::sin(seed * 12.9898) * 43758.5453123)
+/// `p` moved by `eps` along `normal`, to start secondary rays off the surface.
+fn offset(p: V3, normal: V3, eps: f64) -> V3 {
+    p + normal * eps
 }
 
-fn shade_point(
-    lights: &Vec<Light>,
-    objects: &Vec<Box<dyn RaytracingObject + Send + Sync>>,
-    base_collision: Collision,
-    mut normal: V3,
-    view_origin: V3,
-    samples: usize,
-    ambient_light: Color,
-) -> Color {
-    normal.normalize();
-
-    let ar = ambient_light.r as f64 / 255.0;
-    let ag = ambient_light.g as f64 / 255.0;
-    let ab = ambient_light.b as f64 / 255.0;
-
-    let mut r = base_collision.c.r as f64 * ar;
-    let mut g = base_collision.c.g as f64 * ag;
-    let mut b = base_collision.c.b as f64 * ab;
-
-    let samples = usize::max(samples, 1);
-
-    for (light_index, light) in lights.iter().enumerate() {
-        let mut to_light = light.position;
-        to_light.subtract(base_collision.p);
-        let light_dist = to_light.norm();
-
-        let mut base_light_dir = to_light;
-        base_light_dir.normalize();
-
-        let mut tangent = if f64::abs(normal.x) < 0.9 {
-            V3::new(1.0, 0.0, 0.0)
-        } else {
-            V3::new(0.0, 1.0, 0.0)
-        };
-        tangent.cross(normal);
-        tangent.normalize();
-
-        let mut bitangent = normal;
-        bitangent.cross(tangent);
-        bitangent.normalize();
-
-        let mut local_r = 0.0;
-        let mut local_g = 0.0;
-        let mut local_b = 0.0;
-
-        for sample in 0..samples {
-            let seed = base_collision.p.x * 13.0
-                + base_collision.p.y * 17.0
-                + base_collision.p.z * 19.0
-                + light_index as f64 * 23.0
-                + sample as f64 * 29.0;
-
-            let ru = hash3(seed * 1.31) * 2.0 - 1.0;
-            let rv = hash3(seed * 1.73) * 2.0 - 1.0;
-
-            let mut jitter_u = tangent;
-            jitter_u.scale(ru * 0.08);
-            let mut jitter_v = bitangent;
-            jitter_v.scale(rv * 0.08);
-
-            let mut light_dir = base_light_dir;
-            light_dir.add(jitter_u);
-            light_dir.add(jitter_v);
-            light_dir.normalize();
-
-            // hard shadow ray
-            let mut normal_offset = normal;
-            normal_offset.scale(1e-4);
-
-            let mut shadow_origin = base_collision.p;
-            shadow_origin.add(normal_offset);
-
-            let mut shadowed = false;
-            for object in objects.iter() {
-                let shadow_coll = object.get_collision(shadow_origin, light_dir);
-                if shadow_coll.hit && shadow_coll.d > 1e-6 && shadow_coll.d < light_dist - 1e-4 {
-                    shadowed = true;
-                    break;
-                }
-            }
-
-            if shadowed {
-                continue;
-            }
-
-            let ndotl = f64::max(normal.dt(light_dir), 0.0);
-            let diffuse = light.intensity * ndotl;
-
-            let mut view_dir = view_origin;
-            view_dir.subtract(base_collision.p);
-            view_dir.normalize();
-
-            let mut half_vec = light_dir;
-            half_vec.add(view_dir);
-            half_vec.normalize();
-
-            let specular = f64::powf(f64::max(normal.dt(half_vec), 0.0), 36.0) * light.intensity * 0.35;
+/// A ray meeting a surface: the normal turned against the ray, the reflectance and for
+/// transmissive materials the refracted direction.
+struct Interface {
+    normal: V3,
+    /// The ray comes from outside, i.e. it hits the front of the geometric normal.
+    entering: bool,
+    fresnel: Radiance,
+    refracted: Option<V3>,
+}
 
-            let lr = light.color.r as f64 / 255.0;
-            let lg = light.color.g as f64 / 255.0;
-            let lb = light.color.b as f64 / 255.0;
+impl Interface {
+    fn new(dir: V3, geometric_normal: V3, material: &Material) -> Self {
+        let mut normal = geometric_normal.normalized();
+        let entering = normal.dt(dir) <= 0.0;
+        if !entering {
+            normal = -normal;
+        }
+        let cos_i = -dir.dt(normal);
 
-            local_r += base_collision.c.r as f64 * diffuse * lr + 255.0 * specular * lr;
-            local_g += base_collision.c.g as f64 * diffuse * lg + 255.0 * specular * lg;
-            local_b += base_collision.c.b as f64 * diffuse * lb + 255.0 * specular * lb;
+        if material.transmission <= 0.0 {
+            return Interface { normal, entering, fresnel: material.fresnel(cos_i), refracted: None };
         }
 
-        r += local_r / samples as f64;
-        g += local_g / samples as f64;
-        b += local_b / samples as f64;
+        let eta = if entering { 1.0 / material.ior } else { material.ior };
+        match dir.refract(normal, eta) {
+            Some(t) => {
+                // Schlick needs the angle on the optically thinner side
+                let cos = if entering { cos_i } else { -t.dt(normal) };
+                Interface { normal, entering, fresnel: material.fresnel(cos), refracted: Some(t) }
+            }
+            None => Interface { normal, entering, fresnel: Radiance::gray(1.0), refracted: None },
+        }
     }
-
-    Color::RGB(
-        f64::clamp(r, 0.0, 255.0) as u8,
-        f64::clamp(g, 0.0, 255.0) as u8,
-        f64::clamp(b, 0.0, 255.0) as u8,
-    )
 }
 
+/// Seed derived from the ray itself, so a render does not depend on the pixel order.
+fn ray_seed(seed: u64, p0: V3, p: V3) -> u64 {
+    hash_combine(&[
+        seed,
+        p0.x.to_bits(), p0.y.to_bits(), p0.z.to_bits(),
+        p.x.to_bits(), p.y.to_bits(), p.z.to_bits(),
+    ])
+}
 
 pub trait RaytracingObject : Transformable {
     fn d(&self, p: V3) -> f64;
@@ -140,19 +73,51 @@ pub trait RaytracingObject : Transformable {
     fn get_collision_with_normal(&self, p0 : V3, p : V3) -> (Collision, Option<V3>) {
         (self.get_collision(p0, p), None)
     }
-    fn reflectivity(&self, _p: V3) -> f64 {
-        0.0
+    /// Like `get_collision_with_normal`, plus the material at the hit with textures already
+    /// resolved. The default is a matte surface in the collision color.
+    fn get_collision_with_material(&self, p0 : V3, p : V3) -> (Collision, Option<V3>, Material) {
+        let (c, n) = self.get_collision_with_normal(p0, p);
+        (c, n, Material::new(c.c, 1.0))
+    }
+    /// World space box around everything the object can be hit at, used to skip objects a ray
+    /// can't reach. The default is infinite, such objects are tested for every ray.
+    fn bounds(&self) -> Aabb {
+        Aabb::infinite()
     }
     fn clone(&self) -> Box<dyn RaytracingObject + Send + Sync>;
 }
 
 pub use RaytracingObject as PathtracingObject;
 
+/// How `RayTracingScene` computes the color of a ray.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Integrator {
+    /// Direct lighting with jittered shadow rays plus one mirror bounce.
+    #[default]
+    Whitted,
+    /// Unbiased Monte Carlo path tracing, see [`RayTracingScene::trace_path`].
+    PathTracing,
+}
+
 pub struct RayTracingScene {
-    pub objects: Vec<Box<dyn RaytracingObject + Send + Sync>>,
+    /// Only changed through `get`, `add`, `remove` and the `Transformable` methods, which keep
+    /// the acceleration structure up to date.
+    objects: Vec<Box<dyn RaytracingObject + Send + Sync>>,
     pub lights: Vec<Light>,
     pub shading_samples: usize,
     pub ambient_light: Color,
+    pub integrator: Integrator,
+    /// Maximum number of bounces of a path.
+    pub max_depth: usize,
+    /// Paths traced per pixel and frame by the path tracer.
+    pub samples_per_pixel: usize,
+    pub seed: u64,
+    /// Light from far away, replaces `ambient_light` when set.
+    pub environment: Option<Arc<EnvironmentMap>>,
+    /// Hierarchy over the object bounds, built on the first ray after a change.
+    tlas: OnceLock<Tlas>,
+    /// The hierarchy before objects were moved, refit instead of built again if it still fits.
+    stale_tlas: Mutex<Option<Tlas>>,
 }
 
 pub type PathTracingScene = RayTracingScene;
@@ -164,6 +129,13 @@ impl RayTracingScene {
             lights: Vec::new(),
             shading_samples: 1,
             ambient_light: Color::RGB(31, 31, 31),
+            integrator: Integrator::Whitted,
+            max_depth: 8,
+            samples_per_pixel: 1,
+            seed: 0,
+            environment: None,
+            tlas: OnceLock::new(),
+            stale_tlas: Mutex::new(None),
         }
     }
 
@@ -177,25 +149,73 @@ impl RayTracingScene {
             lights: old.lights.clone(),
             shading_samples: old.shading_samples,
             ambient_light: old.ambient_light,
+            integrator: old.integrator,
+            max_depth: old.max_depth,
+            samples_per_pixel: old.samples_per_pixel,
+            seed: old.seed,
+            environment: old.environment.clone(),
+            tlas: OnceLock::new(),
+            stale_tlas: Mutex::new(None),
         }
     }
 
+    pub fn objects(&self) -> &[Box<dyn RaytracingObject + Send + Sync>] {
+        &self.objects
+    }
+
+    /// Object `i` for changing it, the acceleration structure is refit on the next ray.
     pub fn get(&mut self, i : usize) -> &mut Box<dyn RaytracingObject + 'static + Send + Sync> {
+        self.refit();
         &mut self.objects[i]
     }
 
     pub fn remove(&mut self, i : usize) {
         self.objects.remove(i);
+        self.rebuild();
     }
 
     pub fn remove_and_clone(&mut self, i : usize) -> Box<dyn RaytracingObject> {
         let obj = self.objects[i].clone();
         self.objects.remove(i);
+        self.rebuild();
         obj
     }
 
     pub fn add(&mut self, obj : impl RaytracingObject + 'static + Send + Sync) {
         self.objects.push(Box::new(obj));
+        self.rebuild();
+    }
+
+    /// Updates the acceleration structure for objects that moved, on the next ray.
+    fn refit(&mut self) {
+        if let Some(tlas) = self.tlas.take() {
+            *self.stale_tlas.get_mut().unwrap() = Some(tlas);
+        }
+    }
+
+    /// Builds the acceleration structure again on the next ray, after objects were added or
+    /// removed.
+    fn rebuild(&mut self) {
+        self.tlas = OnceLock::new();
+        *self.stale_tlas.get_mut().unwrap() = None;
+    }
+
+    fn tlas(&self) -> &Tlas {
+        self.tlas.get_or_init(|| {
+            let bounds: Vec<Aabb> = self.objects.iter().map(|o| o.bounds()).collect();
+            let stale = self.stale_tlas.lock().unwrap().take();
+            match stale {
+                Some(mut tlas) => {
+                    if tlas.refit(&bounds) { tlas } else { Tlas::build(&bounds) }
+                }
+                None => Tlas::build(&bounds),
+            }
+        })
+    }
+
+    /// Bounds of all objects, infinite if one of them is unbounded.
+    pub fn bounds(&self) -> Aabb {
+        self.tlas().bounds()
     }
 
     pub fn add_light(&mut self, light: Light) {
@@ -210,99 +230,397 @@ impl RayTracingScene {
         self.ambient_light = ambient_light;
     }
 
-    fn trace_closest(&self, p0: V3, p: V3) -> (Collision, Option<V3>, f64) {
-        let mut c: Collision = Collision::empty();
-        let mut bd: f64 = f64::MAX;
-        let mut n: Option<V3> = None;
-        let mut reflectivity: f64 = 0.0;
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        self.integrator = integrator;
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = usize::max(max_depth, 1);
+    }
+
+    pub fn set_samples_per_pixel(&mut self, samples: usize) {
+        self.samples_per_pixel = usize::max(samples, 1);
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    pub fn set_environment(&mut self, environment: EnvironmentMap) {
+        self.environment = Some(Arc::new(environment));
+    }
+
+    /// Radiance of rays leaving the scene in direction `dir`.
+    pub fn background(&self, dir: V3) -> Radiance {
+        match &self.environment {
+            Some(env) => env.lookup(dir),
+            None => Radiance::from_color(self.ambient_light),
+        }
+    }
+
+    /// Constant ambient light for the Whitted shading.
+    fn ambient(&self) -> Radiance {
+        match &self.environment {
+            Some(env) => env.average(),
+            None => Radiance::from_color(self.ambient_light),
+        }
+    }
+
+    /// Direct light at a hit for the Whitted integrator: ambient, emission and for every light
+    /// the diffuse and specular reflection. Area lights are sampled `shading_samples` times,
+    /// which gives soft shadows.
+    fn shade_point(&self, hit: &Collision, material: &Material, normal: V3, view_origin: V3, rng: &mut Rng) -> Radiance {
+        let normal = normal.normalized();
+        let view_dir = (view_origin - hit.p).normalized();
+
+        let diffuse = material.diffuse_color(normal.dt(view_dir));
+        let mut result = diffuse * self.ambient() + material.emission;
+
+        let shadow_origin = offset(hit.p, normal, 1e-4);
+
+        for light in self.lights.iter() {
+            let samples = if light.is_delta() { 1 } else { usize::max(self.shading_samples, 1) };
+            let mut local = Radiance::black();
+
+            for _ in 0..samples {
+                let ls = light.sample(hit.p, rng.next_f64(), rng.next_f64());
+                let ndotl = normal.dt(ls.direction);
+                if ndotl <= 0.0 || ls.radiance.is_black() || self.occluded(shadow_origin, ls.direction, ls.distance) {
+                    continue;
+                }
+
+                let brdf = diffuse + material.specular(normal, view_dir, ls.direction);
+                local += ls.radiance * brdf * (ndotl * light.brdf_scale());
+            }
+
+            result += local / samples as f64;
+        }
+
+        result
+    }
+
+    /// Whitted radiance along a ray and the distance to the first hit, `depth` counts the
+    /// bounces so far.
+    fn trace_whitted(&self, p0: V3, p: V3, depth: usize, rng: &mut Rng) -> (Radiance, f64) {
+        let (c, n, material) = self.trace_closest(p0, p);
+        if !c.hit {
+            return (self.background(p), f64::INFINITY);
+        }
+        let radiance = match n {
+            Some(n) => self.shade_whitted(p, &c, &material, n, depth, rng),
+            None => Radiance::from_color(c.c),
+        };
+        (radiance, c.d)
+    }
+
+    /// Whitted shading of a hit: direct light plus a mirror bounce, weighted with the Fresnel
+    /// reflectance of the material and fading out with its roughness, and for transmissive
+    /// materials a refracted ray. Light travelling inside an object is absorbed by its
+    /// material. Secondary rays stop after `max_depth` bounces.
+    fn shade_whitted(&self, p: V3, hit: &Collision, material: &Material, normal: V3, depth: usize, rng: &mut Rng) -> Radiance {
+        let dir = p.normalized();
+        let surface = Interface::new(dir, normal, material);
+
+        let mut result = self.shade_point(hit, material, surface.normal, hit.p - dir, rng);
+        if depth + 1 >= self.max_depth {
+            return result;
+        }
+
+        let smoothness = (1.0 - material.roughness) * (1.0 - material.roughness);
+        let reflectance = surface.fresnel * smoothness;
+        if reflectance.max_component() >= 0.01 {
+            let refl_dir = dir.reflect(surface.normal).normalized();
+            let (reflected, _) = self.trace_whitted(offset(hit.p, surface.normal, 1e-4), refl_dir, depth + 1, rng);
+            result += reflected * reflectance;
+        }
+
+        if let Some(refr_dir) = surface.refracted {
+            let weight = material.transmission_color(surface.fresnel.max_component());
+            if weight.max_component() >= 0.01 {
+                let (transmitted, d) = self.trace_whitted(offset(hit.p, surface.normal, -1e-4), refr_dir, depth + 1, rng);
+                let absorbed = if surface.entering && d.is_finite() {
+                    material.transmittance(d)
+                } else {
+                    Radiance::gray(1.0)
+                };
+                result += transmitted * weight * absorbed;
+            }
+        }
+
+        result
+    }
+
+    /// Average of `samples_per_pixel` paths along the ray.
+    pub fn sample(&self, p0: V3, p: V3, rng: &mut Rng) -> Radiance {
+        self.sample_hit(p0, p, self.trace_closest(p0, p.normalized()), rng)
+    }
+
+    /// [`RayTracingScene::sample`] with the first hit of the ray already traced, all paths
+    /// start with it.
+    fn sample_hit(&self, p0: V3, p: V3, hit: (Collision, Option<V3>, Material), rng: &mut Rng) -> Radiance {
+        let spp = usize::max(self.samples_per_pixel, 1);
+        let mut sum = Radiance::black();
+        for _ in 0..spp {
+            sum += self.trace_path_from(p0, p, Some(hit.clone()), rng);
+        }
+        sum / spp as f64
+    }
+
+    /// Estimates the radiance arriving along the ray with one random path.
+    ///
+    /// At every vertex the path continues either into the specular lobe of the material
+    /// (chosen with the Fresnel reflectance) or into a cosine-weighted diffuse bounce.
+    /// Every light is sampled once at every vertex, a point light delivers `albedo * color *
+    /// intensity * cos θ` to a white matte surface, the same as the Whitted shading. Area lights
+    /// reflect like emissive surfaces of the same radiance, see [`Light::brdf_scale`].
+    /// Emissive surfaces are picked up when a path hits them and the environment map (or
+    /// `ambient_light`) is the radiance of the sky seen by escaping rays.
+    /// After a few bounces paths are terminated by russian roulette.
+    pub fn trace_path(&self, p0: V3, p: V3, rng: &mut Rng) -> Radiance {
+        self.trace_path_from(p0, p, None, rng)
+    }
+
+    /// [`RayTracingScene::trace_path`], starting with `first` as the hit of the ray if it was
+    /// traced before.
+    fn trace_path_from(&self, p0: V3, p: V3, mut first: Option<(Collision, Option<V3>, Material)>, rng: &mut Rng) -> Radiance {
+        let mut radiance = Radiance::black();
+        let mut throughput = Radiance::gray(1.0);
+
+        let mut origin = p0;
+        let mut dir = p.normalized();
+
+        // material of the object the path is currently inside of
+        let mut medium: Option<Material> = None;
+
+        for depth in 0..self.max_depth {
+            let (c, n, material) = first.take().unwrap_or_else(|| self.trace_closest(origin, dir));
+            if let (Some(m), true) = (&medium, c.hit) {
+                throughput = throughput * m.transmittance(c.d);
+            }
+            if !c.hit {
+                radiance += throughput * self.background(dir);
+                break;
+            }
+
+            let geometric_normal = match n {
+                Some(n) => n,
+                None => -dir,
+            };
+            let surface = Interface::new(dir, geometric_normal, &material);
+            let normal = surface.normal;
+
+            let view_dir = -dir;
+            origin = offset(c.p, normal, 1e-4);
+
+            radiance += throughput * (material.emission + self.direct_light(origin, normal, view_dir, &material, rng));
+
+            let fresnel = surface.fresnel;
+            let diffuse = material.diffuse_color(normal.dt(view_dir));
+            let transmitted = match surface.refracted {
+                Some(_) => material.transmission_color(fresnel.max_component()),
+                None => Radiance::black(),
+            };
+            let spec_weight = fresnel.max_component();
+            let diffuse_weight = diffuse.max_component();
+            let trans_weight = transmitted.max_component();
+            let total = spec_weight + diffuse_weight + trans_weight;
+            if total <= 0.0 {
+                break;
+            }
+
+            let p_spec = spec_weight / total;
+            let p_trans = trans_weight / total;
+            let r = rng.next_f64();
+            if r < p_spec {
+                // the specular lobe of `direct_light`, weighted with BRDF · cos / pdf
+                let (next, pdf) = sample_blinn_phong(normal, view_dir, material.shininess(), rng);
+                let cos = normal.dt(next);
+                if cos <= 0.0 || pdf <= 0.0 {
+                    break;
+                }
+                let brdf = material.specular(normal, view_dir, next) / PI;
+                dir = next;
+                throughput = throughput * brdf * (cos / (pdf * p_spec));
+            } else if let Some(refr_dir) = surface.refracted.filter(|_| r < p_spec + p_trans) {
+                dir = sample_glossy(refr_dir, -normal, material.shininess(), rng);
+                throughput = throughput * transmitted / p_trans;
+                origin = offset(c.p, normal, -1e-4);
+                medium = if surface.entering { Some(material.clone()) } else { None };
+            } else {
+                dir = sample_cosine_hemisphere(normal, rng);
+                throughput = throughput * diffuse / (1.0 - p_spec - p_trans);
+            }
+
+            if throughput.is_black() {
+                break;
+            }
+            if depth + 1 >= RUSSIAN_ROULETTE_DEPTH {
+                let survive = f64::min(throughput.max_component(), 0.95);
+                if rng.next_f64() >= survive {
+                    break;
+                }
+                throughput = throughput / survive;
+            }
+        }
+
+        radiance
+    }
+
+    /// Light reflected towards `view_dir` from one sample of every light.
+    fn direct_light(&self, p: V3, normal: V3, view_dir: V3, material: &Material, rng: &mut Rng) -> Radiance {
+        let diffuse = material.diffuse_color(normal.dt(view_dir));
+        let mut sum = Radiance::black();
+        for light in self.lights.iter() {
+            let ls = light.sample(p, rng.next_f64(), rng.next_f64());
+            let ndotl = normal.dt(ls.direction);
+            if ndotl <= 0.0 || ls.radiance.is_black() || self.occluded(p, ls.direction, ls.distance) {
+                continue;
+            }
+            let brdf = diffuse + material.specular(normal, view_dir, ls.direction);
+            sum += ls.radiance * brdf * (ndotl * light.brdf_scale());
+        }
+        sum
+    }
+
+    /// Whether anything lies between `p0` and `max_d` along the unit direction `p`. Stops at
+    /// the first blocker, which need not be the closest one.
+    fn occluded(&self, p0: V3, p: V3, max_d: f64) -> bool {
+        self.tlas().any(p0, p, max_d, |i| {
+            let c = self.objects[i].get_collision(p0, p);
+            c.hit && c.d > 1e-6 && c.d < max_d - 1e-4
+        })
+    }
+
+    /// Closest hit along the ray and the index of the object that was hit.
+    fn closest_object(&self, p0: V3, p: V3) -> Option<(usize, Collision, Option<V3>, Material)> {
+        let mut closest: Option<(usize, Collision, Option<V3>, Material)> = None;
+        self.tlas().closest(p0, p, |i| {
+            let (c, n, m) = self.objects[i].get_collision_with_material(p0, p);
+            if !c.hit {
+                return None;
+            }
+            if closest.as_ref().is_none_or(|(_, b, _, _)| c.d < b.d) {
+                closest = Some((i, c, n, m));
+            }
+            Some((c.p - p0).norm())
+        });
+        closest
+    }
+
+    fn trace_closest(&self, p0: V3, p: V3) -> (Collision, Option<V3>, Material) {
+        Self::hit_or_miss(self.closest_object(p0, p))
+    }
 
-        for po in self.objects.iter() {
-            let (c_, n_) = po.get_collision_with_normal(p0, p);
-            if c_.hit && c_.d < bd {
-                c = c_;
-                bd = c_.d;
-                n = n_;
-                reflectivity = po.reflectivity(c_.p);
+    /// The hit of `closest_object` without the index, an empty collision on a miss.
+    fn hit_or_miss(hit: Option<(usize, Collision, Option<V3>, Material)>) -> (Collision, Option<V3>, Material) {
+        match hit {
+            Some((_, c, n, material)) => (c, n, material),
+            None => {
+                let c = Collision::empty();
+                (c, None, Material::new(c.c, 1.0))
             }
         }
+    }
 
-        (c, n, reflectivity)
+    /// The hit of `closest_object` for the auxiliary outputs, with the normal against the ray
+    /// like in the shading.
+    fn surface_hit(p: V3, (i, c, n, material): &(usize, Collision, Option<V3>, Material)) -> SurfaceHit {
+        let normal = n.map(|n| if n.dt(p) > 0.0 { -n } else { n });
+        SurfaceHit { d: c.d, p: c.p, normal, albedo: material.albedo, object: *i }
     }
 }
 
-impl RayRenderable for RayTracingScene {
-	fn get_collision(&self, p0 : V3, p : V3, radius : f64) -> Collision {
-        let (mut c, n, reflectivity) = self.trace_closest(p0, p);
+impl RayTracingScene {
+    /// Closest hit along the ray and the light that comes back from it, not clamped.
+    fn render_ray(&self, p0: V3, p: V3) -> (Collision, Radiance) {
+        self.render_hit(p0, p, self.closest_object(p0, p))
+    }
+
+    /// Shades `hit`, the closest hit along the ray as found by `closest_object`.
+    fn render_hit(&self, p0: V3, p: V3, hit: Option<(usize, Collision, Option<V3>, Material)>) -> (Collision, Radiance) {
+        let (c, n, material) = Self::hit_or_miss(hit);
+        let mut rng = Rng::new(ray_seed(self.seed, p0, p));
+
+        if c.hit && self.integrator == Integrator::PathTracing {
+            let radiance = self.sample_hit(p0, p, (c, n, material), &mut rng);
+            return (c, radiance);
+        }
+
+        if !c.hit {
+            return match &self.environment {
+                Some(env) => (c, env.lookup(p)),
+                None => (c, Radiance::from_color(c.c)),
+            };
+        }
 
-        if c.hit {
+        if !self.lights.is_empty() || self.environment.is_some() {
             if let Some(normal) = n {
-                if !self.lights.is_empty() {
-                    let direct = shade_point(
-                        &self.lights,
-                        &self.objects,
-                        c,
-                        normal,
-                        p0,
-                        self.shading_samples,
-                        self.ambient_light,
-                    );
-
-                    let refl = f64::clamp(reflectivity, 0.0, 1.0);
-                    if refl > 0.0 {
-                        let mut in_dir = p;
-                        in_dir.normalize();
-
-                        let mut nrm = normal;
-                        nrm.normalize();
-
-                        // Fresnel-Schlick: stronger reflection at grazing angles.
-                        let mut view_dir = in_dir;
-                        view_dir.scale(-1.0);
-                        let cos_theta = f64::clamp(nrm.dt(view_dir), 0.0, 1.0);
-                        let fresnel = refl + (1.0 - refl) * f64::powf(1.0 - cos_theta, 5.0);
-
-                        let proj = 2.0 * in_dir.dt(nrm);
-                        let mut refl_dir = in_dir;
-                        let mut n_scaled = nrm;
-                        n_scaled.scale(proj);
-                        refl_dir.subtract(n_scaled);
-                        refl_dir.normalize();
-
-                        let mut offset = nrm;
-                        offset.scale(1e-4);
-                        let mut refl_origin = c.p;
-                        refl_origin.add(offset);
-
-                        let (mut rc, rn, _rr) = self.trace_closest(refl_origin, refl_dir);
-                        if rc.hit {
-                            if let Some(rn_) = rn {
-                                rc.c = shade_point(
-                                    &self.lights,
-                                    &self.objects,
-                                    rc,
-                                    rn_,
-                                    refl_origin,
-                                    self.shading_samples,
-                                    self.ambient_light,
-                                );
-                            }
-                        }
-
-                        // Keep non-reflected shading visible even at grazing angles.
-                        let min_direct = 0.35;
-                        let reflection_mix = f64::clamp(fresnel * (1.0 - min_direct), 0.0, 1.0 - min_direct);
-
-                        c.c = Color::RGB(
-                            f64::clamp(direct.r as f64 * (1.0 - reflection_mix) + rc.c.r as f64 * reflection_mix, 0.0, 255.0) as u8,
-                            f64::clamp(direct.g as f64 * (1.0 - reflection_mix) + rc.c.g as f64 * reflection_mix, 0.0, 255.0) as u8,
-                            f64::clamp(direct.b as f64 * (1.0 - reflection_mix) + rc.c.b as f64 * reflection_mix, 0.0, 255.0) as u8,
-                        );
-                    } else {
-                        c.c = direct;
-                    }
-                }
+                return (c, self.shade_whitted(p, &c, &material, normal, 0, &mut rng));
             }
         }
+        (c, Radiance::from_color(c.c))
+    }
+}
+
+impl RayRenderable for RayTracingScene {
+	fn get_collision(&self, p0 : V3, p : V3, _radius : f64) -> Collision {
+        let (mut c, radiance) = self.render_ray(p0, p);
+        c.c = radiance.to_color();
 		c
 	}
+
+    fn get_radiance(&self, p0: V3, p: V3, _radius: f64) -> Radiance {
+        self.render_ray(p0, p).1
+    }
+
+    fn get_surface(&self, p0: V3, p: V3, _radius: f64) -> Option<SurfaceHit> {
+        self.closest_object(p0, p).map(|hit| Self::surface_hit(p, &hit))
+    }
+
+    fn get_collision_with_surface(&self, p0: V3, p: V3, _radius: f64) -> (Collision, Option<SurfaceHit>) {
+        let hit = self.closest_object(p0, p);
+        let surface = hit.as_ref().map(|hit| Self::surface_hit(p, hit));
+        let (mut c, radiance) = self.render_hit(p0, p, hit);
+        c.c = radiance.to_color();
+        (c, surface)
+    }
+
+    fn object_count(&self) -> usize {
+        self.objects.len()
+    }
+
+    fn bounds(&self) -> Aabb {
+        RayTracingScene::bounds(self)
+    }
+}
+
+impl Transformable for RayTracingScene {
+    fn rot_reverse(&mut self, r: V3) {
+        self.objects.iter_mut().for_each(|o| o.rot_reverse(r));
+        self.refit();
+    }
+
+    fn rot(&mut self, r: V3) {
+        self.objects.iter_mut().for_each(|o| o.rot(r));
+        self.refit();
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        self.objects.iter_mut().for_each(|o| o.rot_by(p, r));
+        self.refit();
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.objects.iter_mut().for_each(|o| o.translate(p));
+        self.refit();
+    }
+
+    fn scale(&mut self, p: V3) {
+        self.objects.iter_mut().for_each(|o| o.scale(p));
+        self.refit();
+    }
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
 }
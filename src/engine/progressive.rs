@@ -0,0 +1,191 @@
+use std::sync::Arc;
+
+use crate::engine::camera::RayCamera;
+use crate::engine::tile_renderer::{TileJob, TileRenderer};
+use crate::engine::utils::accumulator::Accumulator;
+use crate::engine::utils::antialiasing::Supersampling;
+use crate::engine::utils::aov::{AovBuffers, AovSelection};
+use crate::engine::utils::denoise::Denoiser;
+use crate::engine::utils::hdr::{HdrBuffer, ToneMapper, ToneMapping};
+use crate::engine::utils::radiance::Radiance;
+use crate::engine::utils::rendering::RayRenderable;
+use crate::engine::utils::virtual_canvas::VirtualCanvas;
+
+pub const DEFAULT_START_BLOCK: usize = 16;
+
+/// Interactive preview that gets better the longer the view stays still. After a change it
+/// draws the frame in coarse blocks, halving the block size on every step, then keeps adding
+/// passes with one jittered ray per pixel to an accumulation buffer. Everything is traced on a
+/// [`TileRenderer`]: the passes in the background, while [`ProgressiveRenderer::step`] only
+/// waits for the blocks and the guide of the denoiser, both cheaper than a pass.
+pub struct ProgressiveRenderer {
+    pub width: usize,
+    pub height: usize,
+    pub start_block: usize,
+    /// Stops adding passes after this many, 0 for no limit.
+    pub max_samples: u32,
+    /// Filters the accumulated passes before they are shown, the buffer itself stays noisy.
+    pub denoiser: Option<Denoiser>,
+    /// Depth, normals, albedo and objects of the current view, for the denoiser.
+    guide: Option<AovBuffers>,
+    block: usize,
+    shown_block: usize,
+    acc: Accumulator,
+    preview: VirtualCanvas,
+    renderer: TileRenderer,
+    job: Option<TileJob<Radiance>>,
+    /// Tiles of the running job so far, row-major.
+    pass: Vec<Radiance>,
+    camera: Option<RayCamera>,
+    version: u64,
+}
+
+impl ProgressiveRenderer {
+    pub fn new(width: usize, height: usize, threads: usize) -> Self {
+        ProgressiveRenderer {
+            width,
+            height,
+            start_block: DEFAULT_START_BLOCK,
+            max_samples: 0,
+            denoiser: None,
+            guide: None,
+            block: DEFAULT_START_BLOCK,
+            shown_block: 0,
+            acc: Accumulator::new(width, height),
+            preview: VirtualCanvas::new(width, height),
+            renderer: TileRenderer::new(threads),
+            job: None,
+            pass: vec![Radiance::black(); width * height],
+            camera: None,
+            version: 0,
+        }
+    }
+
+    pub fn set_start_block(&mut self, start_block: usize) {
+        self.start_block = start_block.max(1);
+    }
+
+    pub fn set_max_samples(&mut self, max_samples: u32) {
+        self.max_samples = max_samples;
+    }
+
+    pub fn set_denoiser(&mut self, denoiser: Option<Denoiser>) {
+        self.denoiser = denoiser;
+    }
+
+    /// Throws away everything and starts over with the coarsest blocks.
+    pub fn reset(&mut self) {
+        self.job = None;
+        self.guide = None;
+        self.acc.reset();
+        self.block = self.start_block;
+        self.shown_block = 0;
+    }
+
+    /// Number of passes in the accumulation buffer, 0 while still drawing blocks.
+    pub fn samples(&self) -> u32 {
+        self.acc.frames
+    }
+
+    /// Size of the blocks of the current preview, 1 once at full resolution and 0 before
+    /// anything was drawn.
+    pub fn block_size(&self) -> usize {
+        self.shown_block
+    }
+
+    /// Average of all passes at (x, y).
+    pub fn radiance(&self, x: usize, y: usize) -> Radiance {
+        self.acc.get(x, y)
+    }
+
+    pub fn frame(&self) -> &VirtualCanvas {
+        &self.preview
+    }
+
+    pub fn is_converged(&self) -> bool {
+        self.max_samples > 0 && self.acc.frames >= self.max_samples
+    }
+
+    /// Advances the preview of `obj` as seen by `camera` and returns whether [`frame`] changed.
+    /// `version` identifies the state of the scene, usually [`Watched::version`]: the preview
+    /// starts over whenever it or the camera differ from the previous call.
+    ///
+    /// [`frame`]: ProgressiveRenderer::frame
+    /// [`Watched::version`]: crate::engine::utils::transformation::Watched::version
+    pub fn step<R: RayRenderable + 'static>(&mut self, camera: &RayCamera, obj: Arc<R>, version: u64) -> bool {
+        if self.camera != Some(*camera) || self.version != version {
+            self.camera = Some(*camera);
+            self.version = version;
+            self.reset();
+        }
+
+        if self.block > 1 {
+            self.show_blocks(camera, obj, self.block);
+            self.shown_block = self.block;
+            self.block /= 2;
+            return true;
+        }
+
+        let mut changed = false;
+        if let Some(job) = self.job.as_mut() {
+            while let Some(tile) = job.try_next() {
+                let r = tile.rect;
+                for (k, &value) in tile.pixels.iter().enumerate() {
+                    self.pass[(r.y + k % r.height) * self.width + r.x + k / r.height] = value;
+                }
+            }
+            if !job.is_finished() {
+                return false;
+            }
+            self.job = None;
+            self.acc.add_frame(&self.pass);
+            self.show_passes();
+            self.shown_block = 1;
+            changed = true;
+        }
+
+        if self.job.is_none() && !self.is_converged() {
+            if self.denoiser.is_some() && self.guide.is_none() {
+                let selection = AovSelection { depth: true, normal: true, albedo: true, object_id: true, position: false };
+                let mut guide = AovBuffers::new(self.width, self.height, selection);
+                camera.trace_aovs_on(&self.renderer, Arc::clone(&obj), &mut guide);
+                self.guide = Some(guide);
+            }
+            self.job = Some(self.pass_camera(camera).hdr_job(&self.renderer, obj, self.width, self.height));
+        }
+        changed
+    }
+
+    /// Draws the frame in blocks of `blocksize` pixels, one ray each.
+    fn show_blocks<R: RayRenderable + 'static>(&mut self, camera: &RayCamera, obj: Arc<R>, blocksize: usize) {
+        for tile in camera.block_job(&self.renderer, obj, self.width, self.height, blocksize) {
+            let r = tile.rect;
+            for (k, &c) in tile.pixels.iter().enumerate() {
+                self.preview.fill_block((r.x + k / r.height) * blocksize, (r.y + k % r.height) * blocksize, blocksize, c);
+            }
+        }
+    }
+
+    /// Draws the average of the passes into the preview, through the denoiser if there is one.
+    fn show_passes(&mut self) {
+        match (&self.denoiser, &self.guide) {
+            (Some(denoiser), Some(guide)) => {
+                let filtered = denoiser.denoise(&HdrBuffer::from_accumulator(&self.acc), guide);
+                // the same colors as without denoiser
+                self.preview = filtered.to_canvas(&ToneMapper::new(ToneMapping::Clamp));
+            }
+            _ => self.acc.to_canvas(&mut self.preview),
+        }
+    }
+
+    /// `camera` with other random positions inside the pixels for every pass. Keeps the grid
+    /// and the filter of the camera, adaptive refinement would only skip pixels.
+    fn pass_camera(&self, camera: &RayCamera) -> RayCamera {
+        let mut supersampling = Supersampling::jittered(camera.supersampling.grid);
+        supersampling.set_filter(camera.supersampling.filter);
+        supersampling.set_seed(self.acc.frames as u64);
+        let mut camera = *camera;
+        camera.set_supersampling(supersampling);
+        camera
+    }
+}
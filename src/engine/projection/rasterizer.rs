@@ -0,0 +1,240 @@
+use sdl2::pixels::Color;
+
+use crate::engine::camera::RayCamera;
+use crate::engine::utils::virtual_canvas::VirtualCanvas;
+use crate::geometry::quad::Quad;
+use crate::geometry::simplex3d::Simplex3D;
+use crate::geometry::vector3::Vector3 as V3;
+
+/// Distance in front of the camera where triangles are cut off.
+const NEAR: f64 = 1e-3;
+
+/// Corners of the six sides of a [`Quad`], in the order of `Quad::x`.
+const QUAD_SIDES: [[usize; 4]; 6] = [[0, 1, 2, 3], [4, 5, 6, 7], [0, 1, 5, 4], [1, 2, 6, 5], [2, 3, 7, 6], [3, 0, 4, 7]];
+
+/// Triangle in world space with one color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Triangle {
+    pub a: V3,
+    pub b: V3,
+    pub c: V3,
+    pub color: Color,
+}
+
+impl Triangle {
+    pub fn new(a: V3, b: V3, c: V3, color: Color) -> Self {
+        Triangle { a, b, c, color }
+    }
+
+    fn normal(&self) -> V3 {
+        (self.b - self.a).crossed(self.c - self.a)
+    }
+}
+
+/// Projects triangles through a [`RayCamera`] and fills them with a depth buffer. Much faster
+/// than the ray based renderers and meant for previews: surfaces are only shaded by the
+/// angle it is seen at, there are no lights, shadows or reflections.
+#[derive(Debug, Clone)]
+pub struct Rasterizer {
+    pub triangles: Vec<Triangle>,
+    pub background: Color,
+    /// Brightness of surfaces seen edge on, surfaces facing the camera are at full brightness.
+    pub ambient: f64,
+    /// Number of segments around a sphere, half as many from pole to pole.
+    pub sphere_segments: usize,
+}
+
+impl Rasterizer {
+    pub fn new() -> Self {
+        Rasterizer {
+            triangles: Vec::new(),
+            background: Color::BLACK,
+            ambient: 0.25,
+            sphere_segments: 32,
+        }
+    }
+
+    pub fn set_background(&mut self, color: Color) {
+        self.background = color;
+    }
+
+    pub fn set_ambient(&mut self, ambient: f64) {
+        self.ambient = ambient.clamp(0.0, 1.0);
+    }
+
+    pub fn add_triangle(&mut self, triangle: Triangle) {
+        self.triangles.push(triangle);
+    }
+
+    /// All faces of `mesh`, colored by their material at the center of the face.
+    pub fn add_mesh(&mut self, mesh: &Simplex3D) {
+        for (i, f) in mesh.x.iter().enumerate() {
+            let color = mesh.face_material(i, (1.0 / 3.0, 1.0 / 3.0)).albedo;
+            self.add_triangle(Triangle::new(f.r, f.a, f.b, color));
+        }
+    }
+
+    pub fn add_quad(&mut self, quad: &Quad, color: Color) {
+        for side in QUAD_SIDES.iter() {
+            let [a, b, c, d] = side.map(|i| quad.x[i]);
+            self.add_triangle(Triangle::new(a, b, c, color));
+            self.add_triangle(Triangle::new(a, c, d, color));
+        }
+    }
+
+    pub fn add_sphere(&mut self, center: V3, radius: f64, color: Color) {
+        let n = self.sphere_segments.max(3);
+        let rings = (n / 2).max(2);
+        let point = |ring: usize, segment: usize| {
+            let theta = std::f64::consts::PI * ring as f64 / rings as f64;
+            let phi = std::f64::consts::TAU * segment as f64 / n as f64;
+            V3::new(
+                center.x + radius * theta.sin() * phi.cos(),
+                center.y - radius * theta.cos(),
+                center.z + radius * theta.sin() * phi.sin(),
+            )
+        };
+        for ring in 0..rings {
+            for segment in 0..n {
+                let (a, b) = (point(ring, segment), point(ring, segment + 1));
+                let (c, d) = (point(ring + 1, segment + 1), point(ring + 1, segment));
+                if ring > 0 {
+                    self.add_triangle(Triangle::new(a, b, c, color));
+                }
+                if ring + 1 < rings {
+                    self.add_triangle(Triangle::new(a, c, d, color));
+                }
+            }
+        }
+    }
+
+    /// Square rod from `from` to `to` whose sides are `thickness` away from the axis.
+    pub fn add_line(&mut self, from: V3, to: V3, thickness: f64, color: Color) {
+        let axis = to - from;
+        if axis.norm() == 0.0 {
+            return;
+        }
+        let helper = if axis.x.abs() < 0.9 * axis.norm() { V3::new(1.0, 0.0, 0.0) } else { V3::new(0.0, 1.0, 0.0) };
+        let u = axis.crossed(helper).normalized() * thickness;
+        let v = axis.crossed(u).normalized() * thickness;
+
+        let corner = |p: V3, su: f64, sv: f64| p + u * su + v * sv;
+        let signs = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
+        let mut x = [V3::new(0.0, 0.0, 0.0); 8];
+        for (i, (su, sv)) in signs.iter().enumerate() {
+            x[i] = corner(from, *su, *sv);
+            x[i + 4] = corner(to, *su, *sv);
+        }
+        for side in QUAD_SIDES.iter() {
+            let [a, b, c, d] = side.map(|i| x[i]);
+            self.add_triangle(Triangle::new(a, b, c, color));
+            self.add_triangle(Triangle::new(a, c, d, color));
+        }
+    }
+
+    /// Draws all triangles into `target` as seen by `camera`, everything else gets the
+    /// background color.
+    pub fn render(&self, camera: &RayCamera, target: &mut VirtualCanvas) {
+        let (w, h) = (target.width, target.height);
+        target.clear(self.background.into());
+        if w == 0 || h == 0 {
+            return;
+        }
+
+        let (forward, right, up) = camera.basis();
+        let half_height = f64::tan(camera.fov / 2.0);
+        let half_width = half_height * w as f64 / h as f64;
+        // camera space: x to the right, y up, z along the view direction
+        let to_camera = |p: V3| {
+            let d = p - camera.position;
+            V3::new(d.dt(right), d.dt(up), d.dt(forward))
+        };
+        let to_screen = |p: V3| ((p.x / p.z / half_width + 1.0) * w as f64 / 2.0, (1.0 - p.y / p.z / half_height) * h as f64 / 2.0, 1.0 / p.z);
+
+        // 1 / depth of the closest surface per pixel, 0 is infinitely far away
+        let mut depth = vec![0.0; w * h];
+        for triangle in self.triangles.iter() {
+            let n = triangle.normal();
+            if n.norm() == 0.0 {
+                continue;
+            }
+            let n = n.normalized();
+            let normal = V3::new(n.dt(right), n.dt(up), n.dt(forward));
+            // brightness by the angle between the surface and the ray through the pixel
+            let shade = |x: f64, y: f64| {
+                let ray = V3::new((2.0 * x / w as f64 - 1.0) * half_width, (1.0 - 2.0 * y / h as f64) * half_height, 1.0).normalized();
+                let light = self.ambient + (1.0 - self.ambient) * normal.dt(ray).abs();
+                let c = triangle.color;
+                Color::RGB((c.r as f64 * light).round() as u8, (c.g as f64 * light).round() as u8, (c.b as f64 * light).round() as u8)
+            };
+
+            let polygon = clip_near([to_camera(triangle.a), to_camera(triangle.b), to_camera(triangle.c)], NEAR);
+            if polygon.len() < 3 {
+                continue;
+            }
+            let screen: Vec<(f64, f64, f64)> = polygon.iter().map(|&p| to_screen(p)).collect();
+            for i in 1..screen.len() - 1 {
+                fill([screen[0], screen[i], screen[i + 1]], &shade, target, &mut depth);
+            }
+        }
+    }
+}
+
+impl Default for Rasterizer {
+    fn default() -> Self {
+        Rasterizer::new()
+    }
+}
+
+/// Cuts away the part of a triangle in camera space that lies closer than `near`.
+fn clip_near(triangle: [V3; 3], near: f64) -> Vec<V3> {
+    let mut polygon = Vec::with_capacity(4);
+    for i in 0..3 {
+        let (p, q) = (triangle[i], triangle[(i + 1) % 3]);
+        if p.z >= near {
+            polygon.push(p);
+        }
+        if (p.z >= near) != (q.z >= near) {
+            let t = (near - p.z) / (q.z - p.z);
+            let mut cut = p.lerp(q, t);
+            cut.z = near;
+            polygon.push(cut);
+        }
+    }
+    polygon
+}
+
+/// Fills the pixels whose centers lie inside the screen space triangle `v` of `(x, y, 1 / z)`
+/// where it is closer than what `depth` holds, with the color `shade` gives for the pixel.
+fn fill<F: Fn(f64, f64) -> Color>(v: [(f64, f64, f64); 3], shade: &F, target: &mut VirtualCanvas, depth: &mut [f64]) {
+    let (w, h) = (target.width, target.height);
+    let edge = |a: (f64, f64, f64), b: (f64, f64, f64), x: f64, y: f64| (b.0 - a.0) * (y - a.1) - (b.1 - a.1) * (x - a.0);
+    let area = edge(v[0], v[1], v[2].0, v[2].1);
+    if area == 0.0 || !area.is_finite() {
+        return;
+    }
+
+    let min_x = v.iter().map(|p| p.0).fold(f64::INFINITY, f64::min).floor().max(0.0) as usize;
+    let max_x = v.iter().map(|p| p.0).fold(f64::NEG_INFINITY, f64::max).ceil().min(w as f64) as usize;
+    let min_y = v.iter().map(|p| p.1).fold(f64::INFINITY, f64::min).floor().max(0.0) as usize;
+    let max_y = v.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max).ceil().min(h as f64) as usize;
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+            let b0 = edge(v[1], v[2], px, py) / area;
+            let b1 = edge(v[2], v[0], px, py) / area;
+            let b2 = edge(v[0], v[1], px, py) / area;
+            if b0 < 0.0 || b1 < 0.0 || b2 < 0.0 {
+                continue;
+            }
+            // 1 / z is linear in screen space
+            let z = b0 * v[0].2 + b1 * v[1].2 + b2 * v[2].2;
+            let k = y * w + x;
+            if z > depth[k] {
+                depth[k] = z;
+                target.draw_sdl2_color(x, y, shade(px, py));
+            }
+        }
+    }
+}
@@ -1,19 +1,34 @@
+use std::sync::Arc;
+
 use sdl2::pixels::Color;
 use sdl2::render::Canvas;
 use sdl2::video::Window;
 use sdl2::rect::Point;
 use crate::engine::lighting::{Light, Material};
-use crate::engine::utils::{rendering::{RayRenderScene, RayRenderable, Collision}, transformation::Transformable};
+use crate::engine::utils::environment_map::EnvironmentMap;
+use crate::engine::utils::radiance::Radiance;
+use crate::engine::utils::{rendering::{RayRenderScene, RayRenderable, Collision, SurfaceHit}, transformation::Transformable};
 use crate::geometry::sphere::Sphere;
 use crate::geometry::quad::Quad;
 use crate::geometry::vector3::Vector3 as V3;
 use crate::geometry::face::Face;
 
+/// Area lights are evaluated on a fixed grid of this many points per side.
+const AREA_LIGHT_GRID: usize = 4;
+/// Upper bound for the steps of a shadow ray.
+const SHADOW_STEPS: usize = 128;
+/// Number of distance samples along the normal for ambient occlusion.
+const AO_SAMPLES: usize = 5;
+
 pub trait RayMarchingObject : Transformable {
     fn sdf(&self, p: V3) -> f64;
 	//fn color(&self, p: V3) -> Color;
 	fn clone(&self) -> Box<dyn RayMarchingObject + Send + Sync>;
 	fn get_material(&self) -> &Material;
+	/// Material of the surface closest to `p`, for objects combining several materials.
+	fn material_at(&self, p: V3) -> &Material {
+		self.get_material()
+	}
 }
 
 pub struct RayMarchingScene {
@@ -23,6 +38,17 @@ pub struct RayMarchingScene {
 	//pub materials: Vec<Material>,
 	pub epsilon: f64,
 	pub flat_color: bool,
+	/// Used when there is no environment.
+	pub ambient_light: Color,
+	/// Background and ambient light, if set.
+	pub environment: Option<Arc<EnvironmentMap>>,
+	/// March shadow rays towards the lights.
+	pub shadows: bool,
+	/// `k` of the `k * h / t` penumbra estimate, higher values give harder shadows.
+	pub shadow_softness: f64,
+	pub ambient_occlusion: bool,
+	/// Add Blinn-Phong highlights of the material to the lambertian shading.
+	pub specular: bool,
 }
 
 impl RayMarchingScene {
@@ -33,6 +59,12 @@ impl RayMarchingScene {
 			lights: Vec::new(),
 			epsilon,
 			flat_color: false,
+			ambient_light: Color::RGB(0, 0, 0),
+			environment: None,
+			shadows: false,
+			shadow_softness: 16.0,
+			ambient_occlusion: false,
+			specular: false,
 		}
 	}
 
@@ -51,6 +83,12 @@ impl RayMarchingScene {
 			negative_objects: negative_objects_vec,
 			lights: old.lights.clone(),
 			flat_color: old.flat_color,
+			ambient_light: old.ambient_light,
+			environment: old.environment.clone(),
+			shadows: old.shadows,
+			shadow_softness: old.shadow_softness,
+			ambient_occlusion: old.ambient_occlusion,
+			specular: old.specular,
 		}
     }
 
@@ -58,6 +96,26 @@ impl RayMarchingScene {
 		self.flat_color = flat_color;
 	}
 
+	pub fn set_ambient_light(&mut self, ambient_light: Color) {
+		self.ambient_light = ambient_light;
+	}
+
+	pub fn set_shadows(&mut self, shadows: bool) {
+		self.shadows = shadows;
+	}
+
+	pub fn set_shadow_softness(&mut self, shadow_softness: f64) {
+		self.shadow_softness = shadow_softness;
+	}
+
+	pub fn set_ambient_occlusion(&mut self, ambient_occlusion: bool) {
+		self.ambient_occlusion = ambient_occlusion;
+	}
+
+	pub fn set_specular(&mut self, specular: bool) {
+		self.specular = specular;
+	}
+
 	pub fn get(&mut self, i: usize) -> &mut Box<dyn RayMarchingObject + 'static + Send + Sync>{
 		&mut self.objects[i]
 	}
@@ -74,6 +132,10 @@ impl RayMarchingScene {
 		self.lights.push(light);
 	}
 
+	pub fn set_environment(&mut self, environment: EnvironmentMap) {
+		self.environment = Some(Arc::new(environment));
+	}
+
     pub fn nearest_distance(&self, p : V3) -> f64{
 		let mut result : f64 = f64::MAX;
 
@@ -110,46 +172,129 @@ impl RayMarchingScene {
 		n
 	}
 
-	pub fn diffuse_lighting(&self, p: V3, material: &Material) -> Color {
+	/// Fraction of the light from direction `dir` that reaches `p` on a surface with normal
+	/// `n`, 0 in the umbra and 1 if nothing is in the way. The shadow ray is marched against
+	/// `nearest_distance` up to `max_t`, the closest miss on the way darkens the penumbra.
+	pub fn soft_shadow(&self, p: V3, n: V3, dir: V3, max_t: f64) -> f64 {
+		let bias = 10.0 * self.epsilon;
+		let mut origin = n;
+		origin.scale(bias);
+		origin.add(p);
+
+		let mut result: f64 = 1.0;
+		let mut t = bias;
+		for _ in 0..SHADOW_STEPS {
+			if t >= max_t {
+				break;
+			}
+			let mut q = dir;
+			q.scale(t);
+			q.add(origin);
+			let h = self.nearest_distance(q);
+			if h < self.epsilon {
+				return 0.0;
+			}
+			result = f64::min(result, self.shadow_softness * h / t);
+			t += h;
+		}
+		f64::clamp(result, 0.0, 1.0)
+	}
+
+	/// Ambient occlusion at `p`: compares the distance to the scene at a few points along the
+	/// normal with their distance to `p`, 1 for an open surface, towards 0 in creases.
+	pub fn ambient_occlusion(&self, p: V3, n: V3) -> f64 {
+		let mut occlusion = 0.0;
+		let mut weight = 1.0;
+		for i in 0..AO_SAMPLES {
+			let h = 0.01 + 0.12 * i as f64 / (AO_SAMPLES - 1) as f64;
+			let mut q = n;
+			q.scale(h);
+			q.add(p);
+			occlusion += (h - self.nearest_distance(q)) * weight;
+			weight *= 0.95;
+		}
+		f64::clamp(1.0 - 3.0 * occlusion, 0.0, 1.0)
+	}
+
+	/// Shading of the material at `p` seen along `v`: lambertian and, if enabled, specular
+	/// reflection of the lights plus the emission. Area lights are averaged over a grid of
+	/// points on the light. The environment or `ambient_light` is added as ambient light.
+	/// Shadows and ambient occlusion are applied if they are switched on. Not clamped.
+	pub fn lighting(&self, p: V3, v: V3, material: &Material) -> Radiance {
 		let n = self.get_normal(p);
-		let mut r = 0.0;
-		let mut g = 0.0;
-		let mut b = 0.0;
+		let mut view_dir = v;
+		view_dir.scale(-1.0);
+		view_dir.normalize();
+
+		let mut ambient = match &self.environment {
+			Some(env) => env.average(),
+			None => Radiance::from_color(self.ambient_light),
+		};
+		if self.ambient_occlusion {
+			ambient = ambient * self.ambient_occlusion(p, n);
+		}
+
+		let diffuse = Radiance::from_color(material.albedo) * material.diffuse_weight();
+		let mut result = diffuse * ambient + material.emission;
 
 		for light in &self.lights {
-			let mut light_dir = light.position.clone();
-			light_dir.subtract(p);
-			light_dir.normalize();
-			let intensity = f64::max(0.0, n.dt(light_dir)) * light.intensity;
-			r += light.color.r as f64 * intensity;
-			g += light.color.g as f64 * intensity;
-			b += light.color.b as f64 * intensity;
-		}
-		Color::RGB(
-			(r * material.diffuse) as u8,
-			(g * material.diffuse) as u8,
-			(b * material.diffuse) as u8
-		)
-	}
-
-	pub fn current_color(&self, p: V3) -> Color {
-		let mut result_color = Color::RGB(10, 0, 0);
+			let grid = if light.is_delta() { 1 } else { AREA_LIGHT_GRID };
+			let mut sum = Radiance::black();
+			for i in 0..grid {
+				for j in 0..grid {
+					let u = (i as f64 + 0.5) / grid as f64;
+					let v = (j as f64 + 0.5) / grid as f64;
+					let ls = light.sample(p, u, v);
+					let ndotl = n.dt(ls.direction);
+					if ndotl <= 0.0 || ls.radiance.is_black() {
+						continue;
+					}
+
+					let visibility = if self.shadows { self.soft_shadow(p, n, ls.direction, ls.distance) } else { 1.0 };
+					let mut brdf = diffuse;
+					if self.specular {
+						brdf += material.specular(n, view_dir, ls.direction);
+					}
+					sum += ls.radiance * brdf * (ndotl * visibility * light.brdf_scale());
+				}
+			}
+			result += sum / (grid * grid) as f64;
+		}
+
+		result
+	}
+
+	/// Index of the object closest to `p`.
+	fn closest_object(&self, p: V3) -> Option<usize> {
+		let mut closest = None;
 		let mut bd = f64::MAX;
 
 		for (i, component) in self.objects.iter().enumerate() {
 			let cd = component.sdf(p);
 			if cd < bd {
-				let material = component.get_material();
-				if self.flat_color || self.lights.is_empty() {
-					result_color = material.color;
-				} else {
-					result_color = self.diffuse_lighting(p, material);
-				}
+				closest = Some(i);
 				bd = cd;
 			}
 		}
+		closest
+	}
 
-		result_color
+	/// Color of the object closest to the hit point `p` of a ray along `v`.
+	pub fn current_color(&self, p: V3, v: V3) -> Color {
+		self.current_radiance(p, v).to_color()
+	}
+
+	/// Unclamped variant of [`RayMarchingScene::current_color`].
+	pub fn current_radiance(&self, p: V3, v: V3) -> Radiance {
+		let material = match self.closest_object(p) {
+			Some(i) => self.objects[i].material_at(p),
+			None => return Radiance::from_color(Color::RGB(10, 0, 0)),
+		};
+		if self.flat_color || (self.lights.is_empty() && self.environment.is_none()) {
+			Radiance::from_color(material.albedo)
+		} else {
+			self.lighting(p, v, material)
+		}
 	}
 }
 
@@ -200,25 +345,77 @@ impl Transformable for RayMarchingScene {
 
 }
 
-impl RayRenderable for RayMarchingScene {
-	fn get_collision(&self, p0 : V3, v : V3, radius : f64) -> Collision {
+impl RayMarchingScene {
+	/// Marches along `v` until the first surface, without shading it. Returns the hit point, or
+	/// as error where the ray left the sphere of `radius` around `p0`.
+	fn march_to_surface(&self, p0 : V3, v : V3, radius : f64) -> Result<V3, V3> {
 		let mut p : V3 = p0;
-		let mut d : f64 = 0.0;
-		let mut c = Color::RGB(51, 51, 51);
 		loop {
-			d = self.nearest_distance(p);
-			if (d < self.epsilon) {
-				c = self.current_color(p);
-				let d = p.d(p0);
-				return Collision{d, p, hit: true, c};
+			let d = self.nearest_distance(p);
+			if d < self.epsilon {
+				return Ok(p);
 			}
-			else if (p.d(p0) > radius) {
-				c = Color::RGB(51, 51, 51);
-				return Collision{d: 0.0, p, hit: false, c};
+			else if p.d(p0) > radius {
+				return Err(p);
 			}
 			else {
 				p.translate(v.x * d / 2.0, v.y * d / 2.0, v.z * d / 2.0);
 			}
 		}
 	}
+
+	/// The surface at `p`, the end of a march from `p0`.
+	fn surface_hit(&self, p0 : V3, p : V3) -> Option<SurfaceHit> {
+		let object = self.closest_object(p)?;
+		let albedo = self.objects[object].material_at(p).albedo;
+		Some(SurfaceHit { d: p.d(p0), p, normal: Some(self.get_normal(p)), albedo, object })
+	}
+
+	/// Marches along `v` and returns the hit together with its unclamped shading.
+	fn march(&self, p0 : V3, v : V3, radius : f64) -> (Collision, Radiance) {
+		self.shade_march(p0, v, self.march_to_surface(p0, v, radius))
+	}
+
+	/// Shades the end of a march from `p0` along `v`, see `march_to_surface`.
+	fn shade_march(&self, p0 : V3, v : V3, end : Result<V3, V3>) -> (Collision, Radiance) {
+		match end {
+			Ok(p) => {
+				let radiance = self.current_radiance(p, v);
+				(Collision{d: p.d(p0), p, hit: true, c: radiance.to_color()}, radiance)
+			}
+			Err(p) => {
+				let radiance = match &self.environment {
+					Some(env) => env.lookup(v),
+					None => Radiance::from_color(Color::RGB(51, 51, 51)),
+				};
+				(Collision{d: 0.0, p, hit: false, c: radiance.to_color()}, radiance)
+			}
+		}
+	}
+}
+
+impl RayRenderable for RayMarchingScene {
+	fn get_collision(&self, p0 : V3, v : V3, radius : f64) -> Collision {
+		self.march(p0, v, radius).0
+	}
+
+	fn get_radiance(&self, p0 : V3, v : V3, radius : f64) -> Radiance {
+		self.march(p0, v, radius).1
+	}
+
+	fn get_surface(&self, p0 : V3, v : V3, radius : f64) -> Option<SurfaceHit> {
+		// the buffers only need the surface, not its shading
+		let p = self.march_to_surface(p0, v, radius).ok()?;
+		self.surface_hit(p0, p)
+	}
+
+	fn get_collision_with_surface(&self, p0 : V3, v : V3, radius : f64) -> (Collision, Option<SurfaceHit>) {
+		let end = self.march_to_surface(p0, v, radius);
+		let surface = end.ok().and_then(|p| self.surface_hit(p0, p));
+		(self.shade_march(p0, v, end).0, surface)
+	}
+
+	fn object_count(&self) -> usize {
+		self.objects.len()
+	}
 }
\ No newline at end of file
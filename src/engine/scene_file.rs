@@ -0,0 +1,997 @@
+//! Text format for whole scenes, so a scene can be changed without recompiling.
+//!
+//! Every line is one statement: a keyword, for some statements a kind or a name, then
+//! `key value...` pairs in any order. `#` starts a comment. Values with spaces or `#` in them,
+//! like some file names, are put in double quotes, a value cannot contain a quote. Colors are 0 to 255 per channel,
+//! radiance values (emission, absorption, environment) are linear floats, angles are radians
+//! and relative paths are resolved against the directory of the scene file.
+//!
+//! ```text
+//! render width 640 height 360 samples 16 output target/renders/room.png
+//! tracer integrator whitted max_depth 8 shading_samples 4 ambient 20 20 20
+//! camera position -5 -1 0 rotation 0 0 0.2 fov 0.7
+//! material red albedo 200 60 50 roughness 0.4
+//! sphere center 1.5 0.8 1.6 radius 0.7 material red
+//! quad center 0 1.5 0 size 40 0.1 40
+//! mesh path models/horse.obj position 0.8 0.35 0 rotation 0 0 3.14159 scale 0.65 0.65 0.65
+//! light rect position 1.5 -2.5 0 u 1.5 0 0 v 0 0 -1.5 color 255 255 255 intensity 9
+//! ```
+//!
+//! [`SceneDescription`] keeps what was read, builds a [`RayTracingScene`], a
+//! [`RayMarchingScene`] or a [`Rasterizer`] from it and writes it back out with
+//! [`SceneDescription::save`].
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use image::ImageError;
+use sdl2::pixels::Color;
+
+use crate::engine::camera::RayCamera;
+use crate::engine::instance::Instance;
+use crate::engine::lighting::{Attenuation, Light, LightKind, Material};
+use crate::engine::pathtracing::{Integrator, RayTracingScene, RaytracingObject};
+use crate::engine::projection::rasterizer::Rasterizer;
+use crate::engine::raymarching::RayMarchingScene;
+use crate::engine::sdf::primitives::{Capsule, Cone, Cylinder, Ellipsoid, Plane, RoundedBox, Torus};
+use crate::engine::simplex3d_bvh::bvh::Bvh;
+use crate::engine::utils::antialiasing::{Filter, Supersampling};
+use crate::engine::utils::environment_map::EnvironmentMap;
+use crate::engine::utils::radiance::Radiance;
+use crate::engine::utils::texture::Texture;
+use crate::engine::utils::transformation::{Transform, Transformable};
+use crate::geometry::face::Face;
+use crate::geometry::line::Line;
+use crate::geometry::quaternion::Quaternion;
+use crate::geometry::quad::Quad;
+use crate::geometry::simplex3d::{MeshMaterial, Simplex3D};
+use crate::geometry::sphere::Sphere;
+use crate::geometry::vector3::Vector3 as V3;
+use crate::geometry::wavefront::ObjError;
+
+/// Model with its acceleration structure, shared by all statements placing it.
+type SharedMesh = Arc<dyn RaytracingObject + Send + Sync>;
+
+/// Error while reading a scene file or building a scene from it. Everything that belongs to a
+/// statement carries the 1-based line number of the statement.
+#[derive(Debug)]
+pub enum SceneError {
+    Io { path: PathBuf, source: io::Error },
+    Parse { path: PathBuf, line: usize, message: String },
+    Mesh { path: PathBuf, line: usize, source: ObjError },
+    Image { path: PathBuf, line: usize, source: ImageError },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            SceneError::Parse { path, line, message } => write!(f, "{}:{}: {}", path.display(), line, message),
+            SceneError::Mesh { path, line, source } => write!(f, "{}:{}: {}", path.display(), line, source),
+            SceneError::Image { path, line, source } => write!(f, "{}:{}: {}", path.display(), line, source),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SceneError::Io { source, .. } => Some(source),
+            SceneError::Mesh { source, .. } => Some(source),
+            SceneError::Image { source, .. } => Some(source),
+            SceneError::Parse { .. } => None,
+        }
+    }
+}
+
+/// `render` statement: how the scene is meant to be rendered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderSettings {
+    pub width: usize,
+    pub height: usize,
+    /// Frames accumulated by progressive renderers.
+    pub samples: u32,
+    pub output: Option<String>,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        RenderSettings { width: 640, height: 360, samples: 1, output: None }
+    }
+}
+
+/// `tracer` statement: settings of the [`RayTracingScene`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TracerSettings {
+    pub integrator: Integrator,
+    pub max_depth: usize,
+    pub shading_samples: usize,
+    pub samples_per_pixel: usize,
+    pub seed: u64,
+    pub ambient: Color,
+}
+
+impl Default for TracerSettings {
+    fn default() -> Self {
+        let scene = RayTracingScene::new();
+        TracerSettings {
+            integrator: scene.integrator,
+            max_depth: scene.max_depth,
+            shading_samples: scene.shading_samples,
+            samples_per_pixel: scene.samples_per_pixel,
+            seed: scene.seed,
+            ambient: scene.ambient_light,
+        }
+    }
+}
+
+/// `marcher` statement: settings of the [`RayMarchingScene`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarcherSettings {
+    pub epsilon: f64,
+    pub shadows: bool,
+    pub shadow_softness: f64,
+    pub ambient_occlusion: bool,
+    pub specular: bool,
+    pub flat_color: bool,
+    pub ambient: Color,
+}
+
+impl Default for MarcherSettings {
+    fn default() -> Self {
+        let scene = RayMarchingScene::new(0.001);
+        MarcherSettings {
+            epsilon: scene.epsilon,
+            shadows: scene.shadows,
+            shadow_softness: scene.shadow_softness,
+            ambient_occlusion: scene.ambient_occlusion,
+            specular: scene.specular,
+            flat_color: scene.flat_color,
+            ambient: scene.ambient_light,
+        }
+    }
+}
+
+/// `environment` statement: an equirectangular image from `path`, or the same `radiance`
+/// from every direction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneEnvironment {
+    pub path: Option<String>,
+    pub radiance: Radiance,
+    pub intensity: f64,
+    pub rotation: f64,
+    pub line: usize,
+}
+
+/// `material` statement, textures are kept as paths so they can be written back.
+#[derive(Debug, Clone)]
+pub struct SceneMaterial {
+    pub name: String,
+    pub material: Material,
+    pub albedo_texture: Option<String>,
+    pub roughness_texture: Option<String>,
+    pub line: usize,
+}
+
+/// Geometry of an object statement.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Sphere { center: V3, radius: f64 },
+    Quad { center: V3, size: V3 },
+    Line { from: V3, to: V3, thickness: f64 },
+    /// Wavefront `.obj` file, scaled around its center, rotated and then moved so its center
+    /// is at `position`.
+    Mesh { path: String, position: Option<V3>, rotation: V3, scale: V3 },
+    Plane { point: V3, normal: V3 },
+    Torus { center: V3, major: f64, minor: f64 },
+    Capsule { from: V3, to: V3, radius: f64 },
+    Cylinder { center: V3, radius: f64, half_height: f64 },
+    Cone { tip: V3, radius: f64, height: f64 },
+    Box { center: V3, half_extent: V3, radius: f64 },
+    Ellipsoid { center: V3, radii: V3 },
+}
+
+/// An object statement. Without a material objects are white and matte, meshes keep the
+/// materials of their `.mtl` files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneObject {
+    pub shape: Shape,
+    pub material: Option<String>,
+    pub line: usize,
+}
+
+/// Everything a scene file says, in the order it was written.
+#[derive(Debug, Clone)]
+pub struct SceneDescription {
+    /// File the description was read from, for error messages and relative paths.
+    pub path: PathBuf,
+    pub render: RenderSettings,
+    pub tracer: TracerSettings,
+    pub marcher: MarcherSettings,
+    pub environment: Option<SceneEnvironment>,
+    pub camera: RayCamera,
+    pub materials: Vec<SceneMaterial>,
+    pub objects: Vec<SceneObject>,
+    pub lights: Vec<Light>,
+}
+
+impl SceneDescription {
+    /// Empty scene with default settings, seen by a camera at the origin looking along +x.
+    pub fn new() -> Self {
+        SceneDescription {
+            path: PathBuf::new(),
+            render: RenderSettings::default(),
+            tracer: TracerSettings::default(),
+            marcher: MarcherSettings::default(),
+            environment: None,
+            camera: RayCamera::new(V3::new(0.0, 0.0, 0.0), 0.0, 0.0, 0.0),
+            materials: Vec::new(),
+            objects: Vec::new(),
+            lights: Vec::new(),
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SceneError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|source| SceneError::Io { path: path.to_path_buf(), source })?;
+        SceneDescription::parse(BufReader::new(file), path)
+    }
+
+    /// Parses scene data from `reader`. `path` is used for error messages and for resolving
+    /// relative paths when building the scene.
+    pub fn parse<R: BufRead>(reader: R, path: &Path) -> Result<Self, SceneError> {
+        let mut scene = SceneDescription::new();
+        scene.path = path.to_path_buf();
+
+        for (i, line) in reader.lines().enumerate() {
+            let line_nr = i + 1;
+            let line = line.map_err(|source| SceneError::Io { path: path.to_path_buf(), source })?;
+            scene.parse_statement(&line, line_nr).map_err(|message| SceneError::Parse { path: path.to_path_buf(), line: line_nr, message })?;
+        }
+
+        scene.validate()?;
+        Ok(scene)
+    }
+
+    fn parse_statement(&mut self, line: &str, line_nr: usize) -> Result<(), String> {
+        let mut tokens = tokenize(line)?.into_iter();
+        let keyword = match tokens.next() {
+            Some(k) => k,
+            None => return Ok(()),
+        };
+
+        match keyword {
+            "render" => {
+                let f = Fields::parse(tokens, &[("width", 1), ("height", 1), ("samples", 1), ("output", 1)])?;
+                let r = &mut self.render;
+                r.width = f.usize("width")?.unwrap_or(r.width);
+                r.height = f.usize("height")?.unwrap_or(r.height);
+                r.samples = f.usize("samples")?.map_or(r.samples, |s| s as u32);
+                r.output = f.string("output").or(r.output.take());
+                if r.width == 0 || r.height == 0 {
+                    return Err(String::from("width and height must be at least 1"));
+                }
+            }
+            "tracer" => {
+                let f = Fields::parse(tokens, &[("integrator", 1), ("max_depth", 1), ("shading_samples", 1), ("samples_per_pixel", 1), ("seed", 1), ("ambient", 3)])?;
+                let t = &mut self.tracer;
+                if let Some(integrator) = f.string("integrator") {
+                    t.integrator = match integrator.as_str() {
+                        "whitted" => Integrator::Whitted,
+                        "path" => Integrator::PathTracing,
+                        other => return Err(format!("unknown integrator '{}', expected whitted or path", other)),
+                    };
+                }
+                t.max_depth = f.usize("max_depth")?.unwrap_or(t.max_depth);
+                t.shading_samples = f.usize("shading_samples")?.unwrap_or(t.shading_samples);
+                t.samples_per_pixel = f.usize("samples_per_pixel")?.unwrap_or(t.samples_per_pixel);
+                t.seed = f.usize("seed")?.map_or(t.seed, |s| s as u64);
+                t.ambient = f.color("ambient")?.unwrap_or(t.ambient);
+            }
+            "marcher" => {
+                let f = Fields::parse(tokens, &[("epsilon", 1), ("shadows", 1), ("shadow_softness", 1), ("ambient_occlusion", 1), ("specular", 1), ("flat_color", 1), ("ambient", 3)])?;
+                let m = &mut self.marcher;
+                m.epsilon = f.positive("epsilon")?.unwrap_or(m.epsilon);
+                m.shadows = f.bool("shadows")?.unwrap_or(m.shadows);
+                m.shadow_softness = f.positive("shadow_softness")?.unwrap_or(m.shadow_softness);
+                m.ambient_occlusion = f.bool("ambient_occlusion")?.unwrap_or(m.ambient_occlusion);
+                m.specular = f.bool("specular")?.unwrap_or(m.specular);
+                m.flat_color = f.bool("flat_color")?.unwrap_or(m.flat_color);
+                m.ambient = f.color("ambient")?.unwrap_or(m.ambient);
+            }
+            "environment" => {
+                let f = Fields::parse(tokens, &[("path", 1), ("radiance", 3), ("intensity", 1), ("rotation", 1)])?;
+                let path = f.string("path");
+                let radiance = f.radiance("radiance")?;
+                if path.is_some() == radiance.is_some() {
+                    return Err(String::from("environment needs either a path or a radiance"));
+                }
+                self.environment = Some(SceneEnvironment {
+                    path,
+                    radiance: radiance.unwrap_or(Radiance::black()),
+                    intensity: f.float("intensity")?.unwrap_or(1.0),
+                    rotation: f.float("rotation")?.unwrap_or(0.0),
+                    line: line_nr,
+                });
+            }
+            "camera" => {
+                let f = Fields::parse(tokens, &[
+                    ("position", 3), ("rotation", 3), ("look_at", 3), ("up", 3), ("fov", 1), ("aperture", 1), ("focus", 1),
+                    ("view_distance", 1), ("supersampling", 1), ("jitter", 1), ("filter", 1), ("adaptive", 1), ("seed", 1),
+                ])?;
+                let mut camera = self.camera;
+                camera.position = f.vector("position")?.unwrap_or(camera.position);
+                match (f.vector("rotation")?, f.vector("look_at")?) {
+                    (Some(_), Some(_)) => return Err(String::from("camera takes either a rotation or look_at")),
+                    (Some(rotation), None) => camera.rotation = rotation,
+                    (None, Some(target)) => {
+                        let up = f.vector("up")?.unwrap_or(V3::new(0.0, -1.0, 0.0));
+                        camera.rotation = RayCamera::look_at(camera.position, target, up).rotation;
+                    }
+                    (None, None) => {}
+                }
+                camera.fov = f.positive("fov")?.unwrap_or(camera.fov);
+                camera.aperture = f.float("aperture")?.unwrap_or(camera.aperture);
+                camera.focal_distance = f.positive("focus")?.unwrap_or(camera.focal_distance);
+                camera.view_distance = f.positive("view_distance")?.unwrap_or(camera.view_distance);
+
+                let s = &mut camera.supersampling;
+                s.grid = f.usize("supersampling")?.map_or(s.grid, |n| n.max(1));
+                s.jitter = f.bool("jitter")?.unwrap_or(s.jitter);
+                if let Some(filter) = f.string("filter") {
+                    s.filter = match filter.as_str() {
+                        "box" => Filter::Box,
+                        "tent" => Filter::Tent,
+                        "mitchell" => Filter::Mitchell,
+                        other => return Err(format!("unknown filter '{}', expected box, tent or mitchell", other)),
+                    };
+                }
+                if let Some(threshold) = f.float("adaptive")? {
+                    s.adaptive = Some(threshold);
+                }
+                s.seed = f.usize("seed")?.map_or(s.seed, |seed| seed as u64);
+                self.camera = camera;
+            }
+            "material" => {
+                let name = tokens.next().ok_or_else(|| String::from("material without name"))?;
+                if self.materials.iter().any(|m| m.name == name) {
+                    return Err(format!("material '{}' is already defined", name));
+                }
+                let f = Fields::parse(tokens, &[
+                    ("albedo", 3), ("roughness", 1), ("metalness", 1), ("emission", 3), ("ior", 1), ("transmission", 1),
+                    ("absorption", 3), ("albedo_texture", 1), ("roughness_texture", 1),
+                ])?;
+                let mut material = Material::new(f.color("albedo")?.unwrap_or(Color::WHITE), f.unit("roughness")?.unwrap_or(1.0));
+                material.metalness = f.unit("metalness")?.unwrap_or(material.metalness);
+                material.emission = f.radiance("emission")?.unwrap_or(material.emission);
+                material.ior = f.positive("ior")?.unwrap_or(material.ior);
+                material.transmission = f.unit("transmission")?.unwrap_or(material.transmission);
+                material.absorption = f.radiance("absorption")?.unwrap_or(material.absorption);
+                self.materials.push(SceneMaterial {
+                    name: name.to_string(),
+                    material,
+                    albedo_texture: f.string("albedo_texture"),
+                    roughness_texture: f.string("roughness_texture"),
+                    line: line_nr,
+                });
+            }
+            "light" => {
+                let kind = tokens.next().ok_or_else(|| String::from("light without kind"))?;
+                self.lights.push(parse_light(kind, tokens)?);
+            }
+            _ => {
+                let (shape, material) = parse_object(keyword, tokens)?;
+                self.objects.push(SceneObject { shape, material, line: line_nr });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks what can only be checked once the whole file is read.
+    fn validate(&self) -> Result<(), SceneError> {
+        for object in self.objects.iter() {
+            if let Some(name) = &object.material {
+                if self.material(name).is_none() {
+                    return Err(self.error(object.line, format!("unknown material '{}'", name)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn material(&self, name: &str) -> Option<&SceneMaterial> {
+        self.materials.iter().find(|m| m.name == name)
+    }
+
+    /// `path` relative to the directory of the scene file.
+    pub fn resolve(&self, path: &str) -> PathBuf {
+        self.path.parent().unwrap_or_else(|| Path::new("")).join(path)
+    }
+
+    /// Every file name the description refers to: output, environment, textures and meshes.
+    fn file_names(&self) -> impl Iterator<Item = &str> {
+        let environment = self.environment.as_ref().and_then(|e| e.path.as_deref());
+        let textures = self.materials.iter().flat_map(|m| m.albedo_texture.iter().chain(m.roughness_texture.iter()));
+        let meshes = self.objects.iter().filter_map(|o| match &o.shape {
+            Shape::Mesh { path, .. } => Some(path),
+            _ => None,
+        });
+        self.render.output.as_deref().into_iter().chain(environment).chain(textures.chain(meshes).map(String::as_str))
+    }
+
+    fn error(&self, line: usize, message: String) -> SceneError {
+        SceneError::Parse { path: self.path.clone(), line, message }
+    }
+
+    /// Loads the textures of all materials, every file once.
+    fn build_materials(&self) -> Result<HashMap<String, Material>, SceneError> {
+        let mut textures: HashMap<String, Arc<Texture>> = HashMap::new();
+        let mut load = |file: &str, line: usize| -> Result<Arc<Texture>, SceneError> {
+            if let Some(texture) = textures.get(file) {
+                return Ok(Arc::clone(texture));
+            }
+            let texture = Arc::new(Texture::load(self.resolve(file)).map_err(|source| SceneError::Image { path: self.path.clone(), line, source })?);
+            textures.insert(file.to_string(), Arc::clone(&texture));
+            Ok(texture)
+        };
+
+        let mut materials = HashMap::new();
+        for m in self.materials.iter() {
+            let mut material = m.material.clone();
+            if let Some(file) = &m.albedo_texture {
+                material.set_albedo_texture(load(file, m.line)?);
+            }
+            if let Some(file) = &m.roughness_texture {
+                material.set_roughness_texture(load(file, m.line)?);
+            }
+            materials.insert(m.name.clone(), material);
+        }
+        Ok(materials)
+    }
+
+    fn build_environment(&self) -> Result<Option<EnvironmentMap>, SceneError> {
+        let e = match &self.environment {
+            Some(e) => e,
+            None => return Ok(None),
+        };
+        let mut map = match &e.path {
+            Some(file) => EnvironmentMap::load(self.resolve(file)).map_err(|source| SceneError::Image { path: self.path.clone(), line: e.line, source })?,
+            None => EnvironmentMap::uniform(e.radiance),
+        };
+        map.set_intensity(e.intensity);
+        map.set_rotation(e.rotation);
+        Ok(Some(map))
+    }
+
+    /// The mesh of `object` where the file has it, with the material of the statement if it
+    /// names one.
+    fn load_mesh_source(&self, object: &SceneObject, material: &Material) -> Result<Simplex3D, SceneError> {
+        let path = match &object.shape {
+            Shape::Mesh { path, .. } => path,
+            _ => return Err(self.error(object.line, String::from("not a mesh"))),
+        };
+        let mut mesh = Simplex3D::load_obj(self.resolve(path)).map_err(|source| SceneError::Mesh { path: self.path.clone(), line: object.line, source })?;
+        if let Some(name) = &object.material {
+            mesh.materials = vec![MeshMaterial::new(name.clone(), material.clone())];
+            mesh.x.iter_mut().for_each(|f| f.material = Some(0));
+        }
+        Ok(mesh)
+    }
+
+    /// Placement of a mesh loaded by `load_mesh_source`: scaled and rotated around its center,
+    /// which then moves to `position` if there is one.
+    fn mesh_transform(object: &SceneObject, center: V3) -> Transform {
+        let (position, rotation, scale) = match &object.shape {
+            Shape::Mesh { position, rotation, scale, .. } => (position.unwrap_or(center), *rotation, *scale),
+            _ => return Transform::identity(),
+        };
+        let rotation = Quaternion::from_euler(rotation);
+        Transform::new(position - rotation.rotate(center * scale), rotation, scale)
+    }
+
+    /// Loads the mesh of a mesh statement and puts it in place, with the same placement the
+    /// ray tracer gives its instances.
+    fn load_mesh(&self, object: &SceneObject, material: &Material) -> Result<Simplex3D, SceneError> {
+        let mut mesh = self.load_mesh_source(object, material)?;
+        let t = SceneDescription::mesh_transform(object, mesh.m);
+        for f in mesh.x.iter_mut() {
+            let mut placed = Face::new(t.apply_point(f.r), t.apply_point(f.a), t.apply_point(f.b));
+            if let Some(vn) = f.vn {
+                placed.set_vertex_normals(vn.map(|n| t.apply_normal(n)));
+            }
+            placed.material = f.material;
+            *f = placed;
+        }
+        mesh.m = t.apply_point(mesh.m);
+        Ok(mesh)
+    }
+
+    /// Builds the scene for the ray tracer. SDF shapes cannot be ray traced and give an error.
+    pub fn to_raytracing(&self) -> Result<RayTracingScene, SceneError> {
+        let materials = self.build_materials()?;
+        let mut scene = RayTracingScene::new();
+        let t = &self.tracer;
+        scene.set_integrator(t.integrator);
+        scene.set_max_depth(t.max_depth);
+        scene.set_shading_samples(t.shading_samples);
+        scene.set_samples_per_pixel(t.samples_per_pixel);
+        scene.set_seed(t.seed);
+        scene.set_ambient_light(t.ambient);
+        if let Some(environment) = self.build_environment()? {
+            scene.set_environment(environment);
+        }
+
+        // every model is loaded once per material, repeated statements place instances of it
+        let mut meshes: HashMap<(&str, Option<&str>), (SharedMesh, V3)> = HashMap::new();
+        for object in self.objects.iter() {
+            let material = object_material(object, &materials);
+            match &object.shape {
+                Shape::Sphere { center, radius } => scene.add(Sphere::new(*center, *radius, material)),
+                Shape::Quad { center, size } => scene.add(quad(*center, *size, material)),
+                Shape::Line { from, to, thickness } => scene.add(line(*from, *to, *thickness, material)),
+                Shape::Mesh { path, .. } => {
+                    let (mesh, center) = match meshes.entry((path.as_str(), object.material.as_deref())) {
+                        Entry::Occupied(e) => e.into_mut(),
+                        Entry::Vacant(e) => {
+                            let mesh = self.load_mesh_source(object, &material)?;
+                            let center = mesh.m;
+                            e.insert((Arc::new(*Bvh::new(mesh)), center))
+                        }
+                    };
+                    scene.add(Instance::new(mesh.clone(), SceneDescription::mesh_transform(object, *center)));
+                }
+                _ => return Err(self.error(object.line, String::from("SDF shapes can only be ray marched"))),
+            }
+        }
+
+        self.lights.iter().for_each(|light| scene.add_light(light.clone()));
+        Ok(scene)
+    }
+
+    /// Builds the scene for the ray marcher. Meshes cannot be ray marched and give an error.
+    pub fn to_raymarching(&self) -> Result<RayMarchingScene, SceneError> {
+        let materials = self.build_materials()?;
+        let m = &self.marcher;
+        let mut scene = RayMarchingScene::new(m.epsilon);
+        scene.set_shadows(m.shadows);
+        scene.set_shadow_softness(m.shadow_softness);
+        scene.set_ambient_occlusion(m.ambient_occlusion);
+        scene.set_specular(m.specular);
+        scene.set_flat_color(m.flat_color);
+        scene.set_ambient_light(m.ambient);
+        if let Some(environment) = self.build_environment()? {
+            scene.set_environment(environment);
+        }
+
+        for object in self.objects.iter() {
+            let material = object_material(object, &materials);
+            match &object.shape {
+                Shape::Sphere { center, radius } => scene.add(Sphere::new(*center, *radius, material)),
+                Shape::Quad { center, size } => scene.add(quad(*center, *size, material)),
+                Shape::Line { from, to, thickness } => scene.add(line(*from, *to, *thickness, material)),
+                Shape::Mesh { .. } => return Err(self.error(object.line, String::from("meshes cannot be ray marched"))),
+                Shape::Plane { point, normal } => scene.add(Plane::new(*point, *normal, material)),
+                Shape::Torus { center, major, minor } => scene.add(Torus::new(*center, *major, *minor, material)),
+                Shape::Capsule { from, to, radius } => scene.add(Capsule::new(*from, *to, *radius, material)),
+                Shape::Cylinder { center, radius, half_height } => scene.add(Cylinder::new(*center, *radius, *half_height, material)),
+                Shape::Cone { tip, radius, height } => scene.add(Cone::new(*tip, *radius, *height, material)),
+                Shape::Box { center, half_extent, radius } => scene.add(RoundedBox::new(*center, *half_extent, *radius, material)),
+                Shape::Ellipsoid { center, radii } => scene.add(Ellipsoid::new(*center, *radii, material)),
+            }
+        }
+
+        self.lights.iter().for_each(|light| scene.add_light(light.clone()));
+        Ok(scene)
+    }
+
+    /// Builds the scene for the [`Rasterizer`], with the ambient light of the tracer as
+    /// background. SDF shapes cannot be projected and give an error.
+    pub fn to_projection(&self) -> Result<Rasterizer, SceneError> {
+        let materials = self.build_materials()?;
+        let mut rasterizer = Rasterizer::new();
+        rasterizer.set_background(self.tracer.ambient);
+
+        for object in self.objects.iter() {
+            let color = object_material(object, &materials).albedo;
+            match &object.shape {
+                Shape::Sphere { center, radius } => rasterizer.add_sphere(*center, *radius, color),
+                Shape::Quad { center, size } => rasterizer.add_quad(&Quad::new(*center, *size, color), color),
+                Shape::Line { from, to, thickness } => rasterizer.add_line(*from, *to, *thickness, color),
+                Shape::Mesh { .. } => rasterizer.add_mesh(&self.load_mesh(object, &object_material(object, &materials))?),
+                _ => return Err(self.error(object.line, String::from("SDF shapes can only be ray marched"))),
+            }
+        }
+        Ok(rasterizer)
+    }
+
+    /// Writes the description in the scene format, reading it back gives the same scene. Fails
+    /// without writing anything if a file name contains a double quote, the format has no way
+    /// to write one.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        if let Some(file) = self.file_names().find(|file| file.contains('"')) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("cannot write the file name {} to a scene", file)));
+        }
+        let mut file = File::create(path)?;
+        write!(file, "{}", self)
+    }
+}
+
+impl Default for SceneDescription {
+    fn default() -> Self {
+        SceneDescription::new()
+    }
+}
+
+impl fmt::Display for SceneDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# rust3d scene")?;
+
+        let r = &self.render;
+        write!(f, "render width {} height {} samples {}", r.width, r.height, r.samples)?;
+        if let Some(output) = &r.output {
+            write!(f, " output {}", quoted(output))?;
+        }
+        writeln!(f)?;
+
+        let t = &self.tracer;
+        let integrator = match t.integrator {
+            Integrator::Whitted => "whitted",
+            Integrator::PathTracing => "path",
+        };
+        writeln!(f, "tracer integrator {} max_depth {} shading_samples {} samples_per_pixel {} seed {} ambient {}",
+            integrator, t.max_depth, t.shading_samples, t.samples_per_pixel, t.seed, color(t.ambient))?;
+
+        let m = &self.marcher;
+        writeln!(f, "marcher epsilon {} shadows {} shadow_softness {} ambient_occlusion {} specular {} flat_color {} ambient {}",
+            m.epsilon, m.shadows, m.shadow_softness, m.ambient_occlusion, m.specular, m.flat_color, color(m.ambient))?;
+
+        if let Some(e) = &self.environment {
+            match &e.path {
+                Some(path) => write!(f, "environment path {}", quoted(path))?,
+                None => write!(f, "environment radiance {}", radiance(e.radiance))?,
+            }
+            writeln!(f, " intensity {} rotation {}", e.intensity, e.rotation)?;
+        }
+
+        let c = &self.camera;
+        write!(f, "camera position {} rotation {} fov {} aperture {} focus {} view_distance {}",
+            vector(c.position), vector(c.rotation), c.fov, c.aperture, c.focal_distance, c.view_distance)?;
+        let s = &c.supersampling;
+        if *s != Supersampling::none() {
+            let filter = match s.filter {
+                Filter::Box => "box",
+                Filter::Tent => "tent",
+                Filter::Mitchell => "mitchell",
+            };
+            write!(f, " supersampling {} jitter {} filter {} seed {}", s.grid, s.jitter, filter, s.seed)?;
+            if let Some(threshold) = s.adaptive {
+                write!(f, " adaptive {}", threshold)?;
+            }
+        }
+        writeln!(f)?;
+
+        for sm in self.materials.iter() {
+            let m = &sm.material;
+            write!(f, "material {} albedo {} roughness {} metalness {} emission {} ior {} transmission {} absorption {}",
+                sm.name, color(m.albedo), m.roughness, m.metalness, radiance(m.emission), m.ior, m.transmission, radiance(m.absorption))?;
+            if let Some(texture) = &sm.albedo_texture {
+                write!(f, " albedo_texture {}", quoted(texture))?;
+            }
+            if let Some(texture) = &sm.roughness_texture {
+                write!(f, " roughness_texture {}", quoted(texture))?;
+            }
+            writeln!(f)?;
+        }
+
+        for object in self.objects.iter() {
+            match &object.shape {
+                Shape::Sphere { center, radius } => write!(f, "sphere center {} radius {}", vector(*center), radius)?,
+                Shape::Quad { center, size } => write!(f, "quad center {} size {}", vector(*center), vector(*size))?,
+                Shape::Line { from, to, thickness } => write!(f, "line from {} to {} thickness {}", vector(*from), vector(*to), thickness)?,
+                Shape::Mesh { path, position, rotation, scale } => {
+                    write!(f, "mesh path {} rotation {} scale {}", quoted(path), vector(*rotation), vector(*scale))?;
+                    if let Some(position) = position {
+                        write!(f, " position {}", vector(*position))?;
+                    }
+                }
+                Shape::Plane { point, normal } => write!(f, "plane point {} normal {}", vector(*point), vector(*normal))?,
+                Shape::Torus { center, major, minor } => write!(f, "torus center {} major {} minor {}", vector(*center), major, minor)?,
+                Shape::Capsule { from, to, radius } => write!(f, "capsule from {} to {} radius {}", vector(*from), vector(*to), radius)?,
+                Shape::Cylinder { center, radius, half_height } => write!(f, "cylinder center {} radius {} half_height {}", vector(*center), radius, half_height)?,
+                Shape::Cone { tip, radius, height } => write!(f, "cone tip {} radius {} height {}", vector(*tip), radius, height)?,
+                Shape::Box { center, half_extent, radius } => write!(f, "box center {} half_extent {} radius {}", vector(*center), vector(*half_extent), radius)?,
+                Shape::Ellipsoid { center, radii } => write!(f, "ellipsoid center {} radii {}", vector(*center), vector(*radii))?,
+            }
+            if let Some(material) = &object.material {
+                write!(f, " material {}", material)?;
+            }
+            writeln!(f)?;
+        }
+
+        for light in self.lights.iter() {
+            match &light.kind {
+                LightKind::Point => write!(f, "light point position {}", vector(light.position))?,
+                LightKind::Directional { direction } => write!(f, "light directional direction {}", vector(*direction))?,
+                LightKind::Spot { direction, inner_angle, outer_angle } => write!(f, "light spot position {} direction {} inner {} outer {}",
+                    vector(light.position), vector(*direction), inner_angle, outer_angle)?,
+                LightKind::RectArea { u, v } => write!(f, "light rect position {} u {} v {}", vector(light.position), vector(*u), vector(*v))?,
+                LightKind::SphereArea { radius } => write!(f, "light sphere position {} radius {}", vector(light.position), radius)?,
+            }
+            write!(f, " color {} intensity {}", color(light.color), light.intensity)?;
+            let a = light.attenuation;
+            if a != Attenuation::none() {
+                write!(f, " attenuation {} {} {}", a.constant, a.linear, a.quadratic)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_light<'a, I: Iterator<Item = &'a str>>(kind: &str, tokens: I) -> Result<Light, String> {
+    let common = [("color", 3), ("intensity", 1), ("attenuation", 3)];
+    let keys: &[(&str, usize)] = match kind {
+        "point" => &[("position", 3)],
+        "directional" => &[("direction", 3)],
+        "spot" => &[("position", 3), ("direction", 3), ("inner", 1), ("outer", 1)],
+        "rect" => &[("position", 3), ("u", 3), ("v", 3)],
+        "sphere" => &[("position", 3), ("radius", 1)],
+        other => return Err(format!("unknown light '{}', expected point, directional, spot, rect or sphere", other)),
+    };
+    let f = Fields::parse(tokens, &[keys, &common].concat())?;
+
+    let color = f.color("color")?.unwrap_or(Color::WHITE);
+    let intensity = f.float("intensity")?.unwrap_or(1.0);
+    let mut light = match kind {
+        "point" => Light::point(f.required(f.vector("position")?, "position")?, color, intensity),
+        "directional" => Light::directional(f.required(f.vector("direction")?, "direction")?, color, intensity),
+        "spot" => Light::spot(
+            f.required(f.vector("position")?, "position")?,
+            f.required(f.vector("direction")?, "direction")?,
+            f.required(f.float("inner")?, "inner")?,
+            f.required(f.float("outer")?, "outer")?,
+            color,
+            intensity,
+        ),
+        "rect" => Light::rect_area(
+            f.required(f.vector("position")?, "position")?,
+            f.required(f.vector("u")?, "u")?,
+            f.required(f.vector("v")?, "v")?,
+            color,
+            intensity,
+        ),
+        _ => Light::sphere_area(f.required(f.vector("position")?, "position")?, f.required(f.positive("radius")?, "radius")?, color, intensity),
+    };
+    if let Some(a) = f.floats("attenuation")? {
+        light.set_attenuation(Attenuation::new(a[0], a[1], a[2]));
+    }
+    Ok(light)
+}
+
+fn parse_object<'a, I: Iterator<Item = &'a str>>(keyword: &str, tokens: I) -> Result<(Shape, Option<String>), String> {
+    let keys: &[(&str, usize)] = match keyword {
+        "sphere" => &[("center", 3), ("radius", 1)],
+        "quad" => &[("center", 3), ("size", 3)],
+        "line" => &[("from", 3), ("to", 3), ("thickness", 1)],
+        "mesh" => &[("path", 1), ("position", 3), ("rotation", 3), ("scale", 3)],
+        "plane" => &[("point", 3), ("normal", 3)],
+        "torus" => &[("center", 3), ("major", 1), ("minor", 1)],
+        "capsule" => &[("from", 3), ("to", 3), ("radius", 1)],
+        "cylinder" => &[("center", 3), ("radius", 1), ("half_height", 1)],
+        "cone" => &[("tip", 3), ("radius", 1), ("height", 1)],
+        "box" => &[("center", 3), ("half_extent", 3), ("radius", 1)],
+        "ellipsoid" => &[("center", 3), ("radii", 3)],
+        other => return Err(format!("unknown statement '{}'", other)),
+    };
+    let f = Fields::parse(tokens, &[keys, &[("material", 1)]].concat())?;
+
+    let vector = |key: &str| -> Result<V3, String> { f.required(f.vector(key)?, key) };
+    let positive = |key: &str| -> Result<f64, String> { f.required(f.positive(key)?, key) };
+    let shape = match keyword {
+        "sphere" => Shape::Sphere { center: vector("center")?, radius: positive("radius")? },
+        "quad" => Shape::Quad { center: vector("center")?, size: vector("size")? },
+        "line" => Shape::Line { from: vector("from")?, to: vector("to")?, thickness: positive("thickness")? },
+        "mesh" => Shape::Mesh {
+            path: f.required(f.string("path"), "path")?,
+            position: f.vector("position")?,
+            rotation: f.vector("rotation")?.unwrap_or(V3::new(0.0, 0.0, 0.0)),
+            scale: f.vector("scale")?.unwrap_or(V3::new(1.0, 1.0, 1.0)),
+        },
+        "plane" => Shape::Plane { point: vector("point")?, normal: vector("normal")? },
+        "torus" => Shape::Torus { center: vector("center")?, major: positive("major")?, minor: positive("minor")? },
+        "capsule" => Shape::Capsule { from: vector("from")?, to: vector("to")?, radius: positive("radius")? },
+        "cylinder" => Shape::Cylinder { center: vector("center")?, radius: positive("radius")?, half_height: positive("half_height")? },
+        "cone" => Shape::Cone { tip: vector("tip")?, radius: positive("radius")?, height: positive("height")? },
+        "box" => Shape::Box { center: vector("center")?, half_extent: vector("half_extent")?, radius: f.float("radius")?.unwrap_or(0.0) },
+        _ => Shape::Ellipsoid { center: vector("center")?, radii: vector("radii")? },
+    };
+    Ok((shape, f.string("material")))
+}
+
+/// `key value...` pairs of a statement, checked against the allowed keys and their number of
+/// values.
+struct Fields<'a> {
+    values: HashMap<&'a str, Vec<&'a str>>,
+}
+
+impl<'a> Fields<'a> {
+    fn parse<I: Iterator<Item = &'a str>>(mut tokens: I, keys: &[(&str, usize)]) -> Result<Self, String> {
+        let mut values = HashMap::new();
+        while let Some(key) = tokens.next() {
+            let count = match keys.iter().find(|(k, _)| *k == key) {
+                Some((_, count)) => *count,
+                None => {
+                    let known: Vec<&str> = keys.iter().map(|(k, _)| *k).collect();
+                    return Err(format!("unknown key '{}', expected one of {}", key, known.join(", ")));
+                }
+            };
+            let mut v = Vec::with_capacity(count);
+            while v.len() < count {
+                match tokens.next() {
+                    // a key where a value should be means values are missing
+                    Some(token) if !keys.iter().any(|(k, _)| *k == token) => v.push(token),
+                    _ => break,
+                }
+            }
+            if v.len() < count {
+                return Err(format!("'{}' needs {} value{}", key, count, if count == 1 { "" } else { "s" }));
+            }
+            if values.insert(key, v).is_some() {
+                return Err(format!("'{}' given twice", key));
+            }
+        }
+        Ok(Fields { values })
+    }
+
+    fn required<T>(&self, value: Option<T>, key: &str) -> Result<T, String> {
+        value.ok_or_else(|| format!("missing '{}'", key))
+    }
+
+    fn string(&self, key: &str) -> Option<String> {
+        self.values.get(key).map(|v| v[0].to_string())
+    }
+
+    fn floats(&self, key: &str) -> Result<Option<Vec<f64>>, String> {
+        let v = match self.values.get(key) {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        v.iter()
+            .map(|s| match s.parse::<f64>() {
+                Ok(x) if x.is_finite() => Ok(x),
+                _ => Err(format!("'{}' is not a valid number for '{}'", s, key)),
+            })
+            .collect::<Result<Vec<f64>, String>>()
+            .map(Some)
+    }
+
+    fn float(&self, key: &str) -> Result<Option<f64>, String> {
+        Ok(self.floats(key)?.map(|v| v[0]))
+    }
+
+    fn positive(&self, key: &str) -> Result<Option<f64>, String> {
+        match self.float(key)? {
+            Some(x) if x <= 0.0 => Err(format!("'{}' must be positive, got {}", key, x)),
+            x => Ok(x),
+        }
+    }
+
+    fn unit(&self, key: &str) -> Result<Option<f64>, String> {
+        match self.float(key)? {
+            Some(x) if !(0.0..=1.0).contains(&x) => Err(format!("'{}' must be between 0 and 1, got {}", key, x)),
+            x => Ok(x),
+        }
+    }
+
+    fn vector(&self, key: &str) -> Result<Option<V3>, String> {
+        Ok(self.floats(key)?.map(|v| V3::new(v[0], v[1], v[2])))
+    }
+
+    fn radiance(&self, key: &str) -> Result<Option<Radiance>, String> {
+        match self.floats(key)? {
+            Some(v) if v.iter().any(|&x| x < 0.0) => Err(format!("'{}' must not be negative", key)),
+            v => Ok(v.map(|v| Radiance::new(v[0], v[1], v[2]))),
+        }
+    }
+
+    fn usize(&self, key: &str) -> Result<Option<usize>, String> {
+        self.values
+            .get(key)
+            .map(|v| v[0].parse::<usize>().map_err(|_| format!("'{}' is not a valid count for '{}'", v[0], key)))
+            .transpose()
+    }
+
+    fn color(&self, key: &str) -> Result<Option<Color>, String> {
+        let v = match self.values.get(key) {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        let mut c = [0u8; 3];
+        for (i, s) in v.iter().enumerate() {
+            c[i] = s.parse::<u8>().map_err(|_| format!("'{}' is not a color channel from 0 to 255 for '{}'", s, key))?;
+        }
+        Ok(Some(Color::RGB(c[0], c[1], c[2])))
+    }
+
+    fn bool(&self, key: &str) -> Result<Option<bool>, String> {
+        self.values
+            .get(key)
+            .map(|v| match v[0] {
+                "true" => Ok(true),
+                "false" => Ok(false),
+                other => Err(format!("'{}' is not true or false for '{}'", other, key)),
+            })
+            .transpose()
+    }
+}
+
+fn object_material(object: &SceneObject, materials: &HashMap<String, Material>) -> Material {
+    match object.material.as_ref().and_then(|name| materials.get(name)) {
+        Some(material) => material.clone(),
+        None => Material::new(Color::WHITE, 1.0),
+    }
+}
+
+fn quad(center: V3, size: V3, material: Material) -> Quad {
+    let mut quad = Quad::new(center, size, material.albedo);
+    quad.set_material(material);
+    quad
+}
+
+fn line(from: V3, to: V3, thickness: f64, material: Material) -> Line {
+    let mut line = Line::new(from, to, thickness);
+    line.base_color = material.albedo;
+    line.material = material;
+    line
+}
+
+/// Splits a line into its values and drops the comment. A value in double quotes is one value
+/// even with spaces or `#` in it.
+fn tokenize(line: &str) -> Result<Vec<&str>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = line.trim_start();
+    while !rest.is_empty() && !rest.starts_with('#') {
+        let (token, after) = match rest.strip_prefix('"') {
+            Some(quoted) => match quoted.find('"') {
+                Some(end) => (&quoted[..end], &quoted[end + 1..]),
+                None => return Err(String::from("missing closing quote")),
+            },
+            None => {
+                let end = rest.find(|c: char| c.is_whitespace() || c == '#').unwrap_or(rest.len());
+                (&rest[..end], &rest[end..])
+            }
+        };
+        if token.contains('"') || !(after.is_empty() || after.starts_with(|c: char| c.is_whitespace() || c == '#')) {
+            return Err(String::from("quotes have to go around a whole value"));
+        }
+        tokens.push(token);
+        rest = after.trim_start();
+    }
+    Ok(tokens)
+}
+
+/// `value` as it is read back by `tokenize`, in quotes where it needs them.
+fn quoted(value: &str) -> String {
+    if value.is_empty() || value.contains(|c: char| c.is_whitespace() || c == '#') {
+        format!("\"{}\"", value)
+    } else {
+        value.to_string()
+    }
+}
+
+fn vector(v: V3) -> String {
+    format!("{} {} {}", v.x, v.y, v.z)
+}
+
+fn color(c: Color) -> String {
+    format!("{} {} {}", c.r, c.g, c.b)
+}
+
+fn radiance(r: Radiance) -> String {
+    format!("{} {} {}", r.r, r.g, r.b)
+}
@@ -0,0 +1,474 @@
+//! Hierarchy of named nodes, each with a transform relative to its parent.
+//!
+//! Payloads stay in the coordinates of their node; moving a node moves everything below it
+//! without touching any geometry. The renderers still take flat lists, [`SceneGraph::to_raytracing`]
+//! and [`SceneGraph::to_raymarching`] place the visible payloads with their world transforms.
+//!
+//! Limits: the scenes of the crate are not built from graphs yet, `Graph3D` still moves its
+//! parts one by one. There is no export for the rasterizer's `ProjectiveScene`, and payloads
+//! under a node scaled by 0 are left out of the exports.
+//!
+//! ```ignore
+//! let mut graph = SceneGraph::new();
+//! let arm = graph.add(graph.root(), Node::new("arm", Payload::Empty));
+//! let hand = graph.add(arm, Node::new("hand", Payload::ray_traced(Sphere::new(V3::new(2.0, 0.0, 0.0), 0.5, material))));
+//! graph.get_mut(arm).unwrap().transform.rot(V3::new(0.0, 0.0, 0.3)); // turns the hand as well
+//! let scene = graph.to_raytracing();
+//! ```
+
+use sdl2::pixels::Color;
+
+use crate::engine::lighting::{Light, LightKind, Material};
+use crate::engine::pathtracing::{RayTracingScene, RaytracingObject};
+use crate::engine::raymarching::{RayMarchingObject, RayMarchingScene};
+use crate::engine::utils::anker_label::AnkerLabel;
+use crate::engine::utils::rendering::{Collision, RaySphereable};
+use crate::engine::utils::transformation::{Matrix4, Transform, Transformable};
+use crate::geometry::aabb::Aabb;
+use crate::geometry::quaternion::Quaternion;
+use crate::geometry::vector3::Vector3 as V3;
+
+/// Handle of a node, stays valid until the node is removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// What a node shows. Groups that only move their children have no payload.
+pub enum Payload {
+    Empty,
+    RayTraced(Box<dyn RaytracingObject + Send + Sync>),
+    RayMarched(Box<dyn RayMarchingObject + Send + Sync>),
+    Light(Light),
+    Label(AnkerLabel),
+}
+
+impl Payload {
+    pub fn ray_traced(obj: impl RaytracingObject + 'static + Send + Sync) -> Self {
+        Payload::RayTraced(Box::new(obj))
+    }
+
+    pub fn ray_marched(obj: impl RayMarchingObject + 'static + Send + Sync) -> Self {
+        Payload::RayMarched(Box::new(obj))
+    }
+}
+
+pub struct Node {
+    pub name: String,
+    /// Placement relative to the parent.
+    pub transform: Transform,
+    /// Hidden nodes hide their children as well.
+    pub visible: bool,
+    pub payload: Payload,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+impl Node {
+    pub fn new(name: impl Into<String>, payload: Payload) -> Self {
+        Node {
+            name: name.into(),
+            transform: Transform::identity(),
+            visible: true,
+            payload,
+            parent: None,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+}
+
+pub struct SceneGraph {
+    nodes: Vec<Option<Node>>,
+}
+
+impl SceneGraph {
+    /// Graph with an empty node called `root`, which can't be removed.
+    pub fn new() -> Self {
+        SceneGraph { nodes: vec![Some(Node::new("root", Payload::Empty))] }
+    }
+
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    /// Adds `node` below `parent` and returns its handle.
+    ///
+    /// # Panics
+    /// If `parent` was removed.
+    pub fn add(&mut self, parent: NodeId, mut node: Node) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        let p = self.get_mut(parent).expect("parent node was removed");
+        p.children.push(id);
+        node.parent = Some(parent);
+        node.children.clear();
+        self.nodes.push(Some(node));
+        id
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(id.0).and_then(|n| n.as_ref())
+    }
+
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.nodes.get_mut(id.0).and_then(|n| n.as_mut())
+    }
+
+    /// The first node added with this name.
+    pub fn find(&self, name: &str) -> Option<NodeId> {
+        self.ids().find(|&id| self.nodes[id.0].as_ref().is_some_and(|n| n.name == name))
+    }
+
+    /// Node at a `/` separated path of names below the root, e.g. `robot/arm/hand`, for names
+    /// that are used more than once.
+    pub fn find_path(&self, path: &str) -> Option<NodeId> {
+        let mut id = self.root();
+        for name in path.split('/').filter(|s| !s.is_empty()) {
+            id = *self.get(id)?.children.iter().find(|&&c| self.nodes[c.0].as_ref().is_some_and(|n| n.name == name))?;
+        }
+        Some(id)
+    }
+
+    /// Handles of all nodes in the order they were added, the root first.
+    pub fn ids(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.nodes.iter().enumerate().filter(|(_, n)| n.is_some()).map(|(i, _)| NodeId(i))
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() <= 1
+    }
+
+    /// Removes the node with all of its children and returns it. The root stays.
+    pub fn remove(&mut self, id: NodeId) -> Option<Node> {
+        if id == self.root() {
+            return None;
+        }
+        let mut node = self.nodes.get_mut(id.0)?.take()?;
+        if let Some(parent) = node.parent.and_then(|p| self.get_mut(p)) {
+            parent.children.retain(|&c| c != id);
+        }
+        let mut stack = std::mem::take(&mut node.children);
+        while let Some(child) = stack.pop() {
+            if let Some(c) = self.nodes[child.0].take() {
+                stack.extend(c.children);
+            }
+        }
+        node.parent = None;
+        Some(node)
+    }
+
+    /// Moves `id` below `parent`, keeping its local transform. Returns false if a node doesn't
+    /// exist or `parent` lies below `id`.
+    pub fn set_parent(&mut self, id: NodeId, parent: NodeId) -> bool {
+        if self.get(id).is_none() || self.get(parent).is_none() || id == self.root() {
+            return false;
+        }
+        let mut ancestor = Some(parent);
+        while let Some(a) = ancestor {
+            if a == id {
+                return false;
+            }
+            ancestor = self.get(a).and_then(|n| n.parent);
+        }
+        let old = self.get(id).and_then(|n| n.parent);
+        if let Some(old) = old.and_then(|p| self.get_mut(p)) {
+            old.children.retain(|&c| c != id);
+        }
+        self.get_mut(parent).unwrap().children.push(id);
+        self.get_mut(id).unwrap().parent = Some(parent);
+        true
+    }
+
+    pub fn set_visible(&mut self, id: NodeId, visible: bool) {
+        if let Some(node) = self.get_mut(id) {
+            node.visible = visible;
+        }
+    }
+
+    /// Whether the node and all of its parents are visible.
+    pub fn is_visible(&self, id: NodeId) -> bool {
+        let mut current = Some(id);
+        while let Some(node) = current.and_then(|c| self.get(c)) {
+            if !node.visible {
+                return false;
+            }
+            current = node.parent;
+        }
+        current.is_none()
+    }
+
+    /// Object space of the node to world space.
+    pub fn world_matrix(&self, id: NodeId) -> Matrix4 {
+        let mut m = Matrix4::identity();
+        let mut current = Some(id);
+        while let Some(node) = current.and_then(|c| self.get(c)) {
+            m = node.transform.matrix() * m;
+            current = node.parent;
+        }
+        m
+    }
+
+    /// Calls `f` for every visible node with its world matrix, parents before their children.
+    pub fn visit<F: FnMut(NodeId, &Node, &Matrix4)>(&self, mut f: F) {
+        let mut stack = vec![(self.root(), Matrix4::identity())];
+        while let Some((id, parent)) = stack.pop() {
+            let node = match self.get(id) {
+                Some(node) if node.visible => node,
+                _ => continue,
+            };
+            let m = parent * node.transform.matrix();
+            f(id, node, &m);
+            stack.extend(node.children.iter().rev().map(|&c| (c, m)));
+        }
+    }
+
+    /// Visible lights in world space.
+    pub fn lights(&self) -> Vec<Light> {
+        let mut lights = Vec::new();
+        self.visit(|_, node, m| {
+            if let Payload::Light(light) = &node.payload {
+                lights.push(place_light(light, m));
+            }
+        });
+        lights
+    }
+
+    /// Visible labels, anchored at their world positions.
+    pub fn labels(&self) -> Vec<AnkerLabel> {
+        let mut labels = Vec::new();
+        self.visit(|_, node, m| {
+            if let Payload::Label(label) = &node.payload {
+                let mut label = label.clone();
+                let anchor = label.get_middle();
+                label.translate(m.transform_point(anchor) - anchor);
+                labels.push(label);
+            }
+        });
+        labels
+    }
+
+    /// Scene with the visible ray traced payloads and lights, with default settings otherwise.
+    pub fn to_raytracing(&self) -> RayTracingScene {
+        let mut scene = RayTracingScene::new();
+        self.visit(|_, node, m| {
+            if let Payload::RayTraced(obj) = &node.payload {
+                if let Some(placed) = Placed::new(RaytracingObject::clone(&**obj), *m) {
+                    scene.add(placed);
+                }
+            }
+        });
+        self.lights().into_iter().for_each(|light| scene.add_light(light));
+        scene
+    }
+
+    /// Scene with the visible ray marched payloads and lights, with default settings otherwise.
+    pub fn to_raymarching(&self, epsilon: f64) -> RayMarchingScene {
+        let mut scene = RayMarchingScene::new(epsilon);
+        self.visit(|_, node, m| {
+            if let Payload::RayMarched(obj) = &node.payload {
+                if let Some(placed) = Placed::new(RayMarchingObject::clone(&**obj), *m) {
+                    scene.add(placed);
+                }
+            }
+        });
+        self.lights().into_iter().for_each(|light| scene.add_light(light));
+        scene
+    }
+}
+
+impl Default for SceneGraph {
+    fn default() -> Self {
+        SceneGraph::new()
+    }
+}
+
+// transforming the graph moves the root and with it everything
+impl Transformable for SceneGraph {
+    fn rot_reverse(&mut self, r: V3) {
+        self.nodes[0].as_mut().unwrap().transform.rot_reverse(r);
+    }
+
+    fn rot(&mut self, r: V3) {
+        self.nodes[0].as_mut().unwrap().transform.rot(r);
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        self.nodes[0].as_mut().unwrap().transform.rot_by(p, r);
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.nodes[0].as_mut().unwrap().transform.translate(p);
+    }
+
+    fn scale(&mut self, p: V3) {
+        self.nodes[0].as_mut().unwrap().transform.scale(p);
+    }
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
+}
+
+fn place_light(light: &Light, m: &Matrix4) -> Light {
+    let mut light = light.clone();
+    light.position = m.transform_point(light.position);
+    match &mut light.kind {
+        LightKind::Directional { direction } | LightKind::Spot { direction, .. } => {
+            *direction = m.transform_vector(*direction).normalized();
+        }
+        LightKind::RectArea { u, v } => {
+            *u = m.transform_vector(*u);
+            *v = m.transform_vector(*v);
+        }
+        LightKind::SphereArea { radius } => *radius *= m.determinant().abs().cbrt(),
+        LightKind::Point => {}
+    }
+    light
+}
+
+/// Payload of the graph placed in world space by the matrix of its node, which unlike a
+/// [`Transform`] can shear when a child is turned inside a non-uniformly scaled parent.
+struct Placed<T> {
+    inner: T,
+    matrix: Matrix4,
+    inverse: Matrix4,
+}
+
+impl<T> Placed<T> {
+    /// `None` if `matrix` is singular, the payload would be flat and rays could not be moved
+    /// into its space.
+    fn new(inner: T, matrix: Matrix4) -> Option<Self> {
+        let inverse = matrix.inverse()?;
+        Some(Placed { inner, matrix, inverse })
+    }
+
+    /// Keeps the old placement if `matrix` is singular.
+    fn set_matrix(&mut self, matrix: Matrix4) {
+        if let Some(inverse) = matrix.inverse() {
+            self.matrix = matrix;
+            self.inverse = inverse;
+        }
+    }
+
+    fn origin(&self) -> V3 {
+        self.matrix.transform_point(V3::new(0.0, 0.0, 0.0))
+    }
+
+    fn to_object_space(&self, p0: V3, p: V3) -> (V3, V3, f64) {
+        let d = self.inverse.transform_vector(p);
+        let l = d.norm();
+        (self.inverse.transform_point(p0), d / l, l)
+    }
+
+    fn to_world_space(&self, c: Collision, n: Option<V3>, p: V3, l: f64) -> (Collision, Option<V3>) {
+        if !c.hit {
+            return (c, n);
+        }
+        let c = Collision { d: c.d / l * p.norm(), p: self.matrix.transform_point(c.p), ..c };
+        (c, n.map(|n| self.inverse.transposed().transform_vector(n).normalized()))
+    }
+}
+
+impl<T> Transformable for Placed<T> {
+    fn rot(&mut self, r: V3) {
+        let p = self.origin();
+        self.rot_by(p, r);
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        let turn = Matrix4::translation(p) * Matrix4::rotation(Quaternion::from_euler(r)) * Matrix4::translation(-p);
+        self.set_matrix(turn * self.matrix);
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.set_matrix(Matrix4::translation(p) * self.matrix);
+    }
+
+    fn scale(&mut self, p: V3) {
+        self.set_matrix(self.matrix * Matrix4::scaling(p));
+    }
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
+}
+
+type Traced = Box<dyn RaytracingObject + Send + Sync>;
+type Marched = Box<dyn RayMarchingObject + Send + Sync>;
+
+impl RaytracingObject for Placed<Traced> {
+    fn d(&self, p: V3) -> f64 {
+        self.inner.d(self.inverse.transform_point(p)) * self.matrix.min_stretch()
+    }
+
+    fn color(&self, p: V3) -> Color {
+        self.inner.color(self.inverse.transform_point(p))
+    }
+
+    fn is_colliding(&mut self, p0: V3, p: V3) -> bool {
+        self.get_collision(p0, p).hit
+    }
+
+    fn get_collision(&self, p0: V3, p: V3) -> Collision {
+        self.get_collision_with_normal(p0, p).0
+    }
+
+    fn get_collision_with_normal(&self, p0: V3, p: V3) -> (Collision, Option<V3>) {
+        let (o, d, l) = self.to_object_space(p0, p);
+        let (c, n) = self.inner.get_collision_with_normal(o, d);
+        self.to_world_space(c, n, p, l)
+    }
+
+    fn get_collision_with_material(&self, p0: V3, p: V3) -> (Collision, Option<V3>, Material) {
+        let (o, d, l) = self.to_object_space(p0, p);
+        let (c, n, m) = self.inner.get_collision_with_material(o, d);
+        let (c, n) = self.to_world_space(c, n, p, l);
+        (c, n, m)
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.inner.bounds().transformed(|p| self.matrix.transform_point(p))
+    }
+
+    fn clone(&self) -> Box<dyn RaytracingObject + Send + Sync> {
+        Box::new(Placed { inner: RaytracingObject::clone(&*self.inner), matrix: self.matrix, inverse: self.inverse })
+    }
+}
+
+impl RayMarchingObject for Placed<Marched> {
+    fn sdf(&self, p: V3) -> f64 {
+        self.inner.sdf(self.inverse.transform_point(p)) * self.matrix.min_stretch()
+    }
+
+    fn clone(&self) -> Box<dyn RayMarchingObject + Send + Sync> {
+        Box::new(Placed { inner: RayMarchingObject::clone(&*self.inner), matrix: self.matrix, inverse: self.inverse })
+    }
+
+    fn get_material(&self) -> &Material {
+        self.inner.get_material()
+    }
+
+    fn material_at(&self, p: V3) -> &Material {
+        self.inner.material_at(self.inverse.transform_point(p))
+    }
+}
@@ -0,0 +1,293 @@
+use crate::engine::lighting::Material;
+use crate::engine::raymarching::RayMarchingObject;
+use crate::engine::sdf::primitives::Frame;
+use crate::engine::utils::transformation::Transformable;
+use crate::geometry::vector3::Vector3 as V3;
+
+// Domain operations change the space an object is evaluated in instead of its distance: the
+// sample point is moved into the frame of the operation, changed there and handed to the inner
+// object in world coordinates again. Twist and bend stretch space, their distances are only
+// estimates; the half steps of `RayMarchingScene` cover moderate rates.
+
+type Node = Box<dyn RayMarchingObject + Send + Sync>;
+
+/// Copies of `inner` every `period` along the axes of the frame, 0 disables an axis. `inner`
+/// should fit into the cell around the origin of the frame.
+pub struct Repeat {
+    pub inner: Node,
+    pub frame: Frame,
+    pub period: V3,
+    /// Number of copies on each side of the original, unlimited if `None`.
+    pub limit: Option<V3>,
+}
+
+/// Rotates the xz plane of the frame proportionally to y, `rate` in radians per unit.
+pub struct Twist {
+    pub inner: Node,
+    pub frame: Frame,
+    pub rate: f64,
+}
+
+/// Bends the x axis of the frame into an arc around z, `rate` in radians per unit.
+pub struct Bend {
+    pub inner: Node,
+    pub frame: Frame,
+    pub rate: f64,
+}
+
+/// Mirrors the positive side of the selected axes of the frame onto the negative side.
+pub struct Mirror {
+    pub inner: Node,
+    pub frame: Frame,
+    pub axes: [bool; 3],
+}
+
+impl Repeat {
+    pub fn new(inner: impl RayMarchingObject + 'static + Send + Sync, origin: V3, period: V3) -> Self {
+        Repeat { inner: Box::new(inner), frame: Frame::new(origin), period, limit: None }
+    }
+
+    pub fn limited(inner: impl RayMarchingObject + 'static + Send + Sync, origin: V3, period: V3, limit: V3) -> Self {
+        Repeat { inner: Box::new(inner), frame: Frame::new(origin), period, limit: Some(limit) }
+    }
+
+    fn map(&self, p: V3) -> V3 {
+        let q = self.frame.to_local(p);
+        let cell = |x: f64, s: f64, l: Option<f64>| -> f64 {
+            if s <= 0.0 {
+                return x;
+            }
+            let mut c = (x / s).round();
+            if let Some(l) = l {
+                c = f64::clamp(c, -l, l);
+            }
+            x - s * c
+        };
+        let q = V3::new(
+            cell(q.x, self.period.x, self.limit.map(|l| l.x)),
+            cell(q.y, self.period.y, self.limit.map(|l| l.y)),
+            cell(q.z, self.period.z, self.limit.map(|l| l.z)),
+        );
+        self.frame.to_world(q)
+    }
+}
+
+impl Twist {
+    pub fn new(inner: impl RayMarchingObject + 'static + Send + Sync, origin: V3, rate: f64) -> Self {
+        Twist { inner: Box::new(inner), frame: Frame::new(origin), rate }
+    }
+
+    fn map(&self, p: V3) -> V3 {
+        let q = self.frame.to_local(p);
+        let (s, c) = f64::sin_cos(self.rate * q.y);
+        self.frame.to_world(V3::new(c * q.x - s * q.z, q.y, s * q.x + c * q.z))
+    }
+}
+
+impl Bend {
+    pub fn new(inner: impl RayMarchingObject + 'static + Send + Sync, origin: V3, rate: f64) -> Self {
+        Bend { inner: Box::new(inner), frame: Frame::new(origin), rate }
+    }
+
+    fn map(&self, p: V3) -> V3 {
+        let q = self.frame.to_local(p);
+        let (s, c) = f64::sin_cos(self.rate * q.x);
+        self.frame.to_world(V3::new(c * q.x - s * q.y, s * q.x + c * q.y, q.z))
+    }
+}
+
+impl Mirror {
+    pub fn new(inner: impl RayMarchingObject + 'static + Send + Sync, origin: V3, axes: [bool; 3]) -> Self {
+        Mirror { inner: Box::new(inner), frame: Frame::new(origin), axes }
+    }
+
+    fn map(&self, p: V3) -> V3 {
+        let mut q = self.frame.to_local(p);
+        if self.axes[0] {
+            q.x = q.x.abs();
+        }
+        if self.axes[1] {
+            q.y = q.y.abs();
+        }
+        if self.axes[2] {
+            q.z = q.z.abs();
+        }
+        self.frame.to_world(q)
+    }
+}
+
+impl RayMarchingObject for Repeat {
+    fn sdf(&self, p: V3) -> f64 {
+        self.inner.sdf(self.map(p))
+    }
+
+    fn clone(&self) -> Box<dyn RayMarchingObject + Send + Sync> {
+        Box::new(Repeat { inner: self.inner.clone(), frame: self.frame, period: self.period, limit: self.limit })
+    }
+
+    fn get_material(&self) -> &Material {
+        self.inner.get_material()
+    }
+
+    fn material_at(&self, p: V3) -> &Material {
+        self.inner.material_at(self.map(p))
+    }
+}
+
+impl RayMarchingObject for Twist {
+    fn sdf(&self, p: V3) -> f64 {
+        self.inner.sdf(self.map(p))
+    }
+
+    fn clone(&self) -> Box<dyn RayMarchingObject + Send + Sync> {
+        Box::new(Twist { inner: self.inner.clone(), frame: self.frame, rate: self.rate })
+    }
+
+    fn get_material(&self) -> &Material {
+        self.inner.get_material()
+    }
+
+    fn material_at(&self, p: V3) -> &Material {
+        self.inner.material_at(self.map(p))
+    }
+}
+
+impl RayMarchingObject for Bend {
+    fn sdf(&self, p: V3) -> f64 {
+        self.inner.sdf(self.map(p))
+    }
+
+    fn clone(&self) -> Box<dyn RayMarchingObject + Send + Sync> {
+        Box::new(Bend { inner: self.inner.clone(), frame: self.frame, rate: self.rate })
+    }
+
+    fn get_material(&self) -> &Material {
+        self.inner.get_material()
+    }
+
+    fn material_at(&self, p: V3) -> &Material {
+        self.inner.material_at(self.map(p))
+    }
+}
+
+impl RayMarchingObject for Mirror {
+    fn sdf(&self, p: V3) -> f64 {
+        self.inner.sdf(self.map(p))
+    }
+
+    fn clone(&self) -> Box<dyn RayMarchingObject + Send + Sync> {
+        Box::new(Mirror { inner: self.inner.clone(), frame: self.frame, axes: self.axes })
+    }
+
+    fn get_material(&self) -> &Material {
+        self.inner.get_material()
+    }
+
+    fn material_at(&self, p: V3) -> &Material {
+        self.inner.material_at(self.map(p))
+    }
+}
+
+// the frame and the inner object move together, rotations turn around the origin of the frame
+impl Transformable for Repeat {
+    fn rot(&mut self, r: V3) {
+        self.inner.rot_by(self.frame.origin, r);
+        self.frame.rot(r);
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        self.inner.rot_by(p, r);
+        self.frame.rot_by(p, r);
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.inner.translate(p);
+        self.frame.translate(p);
+    }
+
+    fn scale(&mut self, p: V3) {
+        self.inner.scale(p);
+        self.period.x *= p.x;
+        self.period.y *= p.y;
+        self.period.z *= p.z;
+    }
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
+}
+
+impl Transformable for Twist {
+    fn rot(&mut self, r: V3) {
+        self.inner.rot_by(self.frame.origin, r);
+        self.frame.rot(r);
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        self.inner.rot_by(p, r);
+        self.frame.rot_by(p, r);
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.inner.translate(p);
+        self.frame.translate(p);
+    }
+
+    fn scale(&mut self, p: V3) {
+        self.inner.scale(p);
+    }
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
+}
+
+impl Transformable for Bend {
+    fn rot(&mut self, r: V3) {
+        self.inner.rot_by(self.frame.origin, r);
+        self.frame.rot(r);
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        self.inner.rot_by(p, r);
+        self.frame.rot_by(p, r);
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.inner.translate(p);
+        self.frame.translate(p);
+    }
+
+    fn scale(&mut self, p: V3) {
+        self.inner.scale(p);
+    }
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
+}
+
+impl Transformable for Mirror {
+    fn rot(&mut self, r: V3) {
+        self.inner.rot_by(self.frame.origin, r);
+        self.frame.rot(r);
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        self.inner.rot_by(p, r);
+        self.frame.rot_by(p, r);
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.inner.translate(p);
+        self.frame.translate(p);
+    }
+
+    fn scale(&mut self, p: V3) {
+        self.inner.scale(p);
+    }
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
+}
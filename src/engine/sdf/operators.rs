@@ -0,0 +1,220 @@
+use crate::engine::lighting::Material;
+use crate::engine::raymarching::RayMarchingObject;
+use crate::engine::utils::transformation::Transformable;
+use crate::geometry::vector3::Vector3 as V3;
+
+type Node = Box<dyn RayMarchingObject + Send + Sync>;
+
+/// Polynomial smooth minimum, `k` is the width of the blended region. Falls back to `min`
+/// for `k <= 0`.
+pub fn smooth_min(a: f64, b: f64, k: f64) -> f64 {
+    if k <= 0.0 {
+        return f64::min(a, b);
+    }
+    let h = f64::max(k - (a - b).abs(), 0.0) / k;
+    f64::min(a, b) - h * h * k / 4.0
+}
+
+pub fn smooth_max(a: f64, b: f64, k: f64) -> f64 {
+    -smooth_min(-a, -b, k)
+}
+
+/// Everything inside `a` or `b`.
+pub struct Union {
+    pub a: Node,
+    pub b: Node,
+    /// Blend radius, 0 for a sharp edge.
+    pub k: f64,
+}
+
+/// Everything inside both `a` and `b`.
+pub struct Intersection {
+    pub a: Node,
+    pub b: Node,
+    pub k: f64,
+}
+
+/// Everything inside `a` but not inside `b`.
+pub struct Difference {
+    pub a: Node,
+    pub b: Node,
+    pub k: f64,
+}
+
+impl Union {
+    pub fn new(a: impl RayMarchingObject + 'static + Send + Sync, b: impl RayMarchingObject + 'static + Send + Sync) -> Self {
+        Union::smooth(a, b, 0.0)
+    }
+
+    pub fn smooth(a: impl RayMarchingObject + 'static + Send + Sync, b: impl RayMarchingObject + 'static + Send + Sync, k: f64) -> Self {
+        Union { a: Box::new(a), b: Box::new(b), k }
+    }
+}
+
+impl Intersection {
+    pub fn new(a: impl RayMarchingObject + 'static + Send + Sync, b: impl RayMarchingObject + 'static + Send + Sync) -> Self {
+        Intersection::smooth(a, b, 0.0)
+    }
+
+    pub fn smooth(a: impl RayMarchingObject + 'static + Send + Sync, b: impl RayMarchingObject + 'static + Send + Sync, k: f64) -> Self {
+        Intersection { a: Box::new(a), b: Box::new(b), k }
+    }
+}
+
+impl Difference {
+    pub fn new(a: impl RayMarchingObject + 'static + Send + Sync, b: impl RayMarchingObject + 'static + Send + Sync) -> Self {
+        Difference::smooth(a, b, 0.0)
+    }
+
+    pub fn smooth(a: impl RayMarchingObject + 'static + Send + Sync, b: impl RayMarchingObject + 'static + Send + Sync, k: f64) -> Self {
+        Difference { a: Box::new(a), b: Box::new(b), k }
+    }
+}
+
+impl RayMarchingObject for Union {
+    fn sdf(&self, p: V3) -> f64 {
+        smooth_min(self.a.sdf(p), self.b.sdf(p), self.k)
+    }
+
+    fn clone(&self) -> Box<dyn RayMarchingObject + Send + Sync> {
+        Box::new(Union { a: self.a.clone(), b: self.b.clone(), k: self.k })
+    }
+
+    fn get_material(&self) -> &Material {
+        self.a.get_material()
+    }
+
+    fn material_at(&self, p: V3) -> &Material {
+        if self.a.sdf(p) <= self.b.sdf(p) {
+            self.a.material_at(p)
+        } else {
+            self.b.material_at(p)
+        }
+    }
+}
+
+impl RayMarchingObject for Intersection {
+    fn sdf(&self, p: V3) -> f64 {
+        smooth_max(self.a.sdf(p), self.b.sdf(p), self.k)
+    }
+
+    fn clone(&self) -> Box<dyn RayMarchingObject + Send + Sync> {
+        Box::new(Intersection { a: self.a.clone(), b: self.b.clone(), k: self.k })
+    }
+
+    fn get_material(&self) -> &Material {
+        self.a.get_material()
+    }
+
+    // the surface belongs to whichever shape bounds the result there
+    fn material_at(&self, p: V3) -> &Material {
+        if self.a.sdf(p) >= self.b.sdf(p) {
+            self.a.material_at(p)
+        } else {
+            self.b.material_at(p)
+        }
+    }
+}
+
+impl RayMarchingObject for Difference {
+    fn sdf(&self, p: V3) -> f64 {
+        smooth_max(self.a.sdf(p), -self.b.sdf(p), self.k)
+    }
+
+    fn clone(&self) -> Box<dyn RayMarchingObject + Send + Sync> {
+        Box::new(Difference { a: self.a.clone(), b: self.b.clone(), k: self.k })
+    }
+
+    fn get_material(&self) -> &Material {
+        self.a.get_material()
+    }
+
+    // the surface carved out by `b` takes its material
+    fn material_at(&self, p: V3) -> &Material {
+        if self.a.sdf(p) >= -self.b.sdf(p) {
+            self.a.material_at(p)
+        } else {
+            self.b.material_at(p)
+        }
+    }
+}
+
+// Transformations are passed on to both operands, the same way `RayMarchingScene` passes them
+// on to its objects.
+impl Transformable for Union {
+    fn rot(&mut self, r: V3) {
+        self.a.rot(r);
+        self.b.rot(r);
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        self.a.rot_by(p, r);
+        self.b.rot_by(p, r);
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.a.translate(p);
+        self.b.translate(p);
+    }
+
+    fn scale(&mut self, p: V3) {
+        self.a.scale(p);
+        self.b.scale(p);
+    }
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
+}
+
+impl Transformable for Intersection {
+    fn rot(&mut self, r: V3) {
+        self.a.rot(r);
+        self.b.rot(r);
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        self.a.rot_by(p, r);
+        self.b.rot_by(p, r);
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.a.translate(p);
+        self.b.translate(p);
+    }
+
+    fn scale(&mut self, p: V3) {
+        self.a.scale(p);
+        self.b.scale(p);
+    }
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
+}
+
+impl Transformable for Difference {
+    fn rot(&mut self, r: V3) {
+        self.a.rot(r);
+        self.b.rot(r);
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        self.a.rot_by(p, r);
+        self.b.rot_by(p, r);
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.a.translate(p);
+        self.b.translate(p);
+    }
+
+    fn scale(&mut self, p: V3) {
+        self.a.scale(p);
+        self.b.scale(p);
+    }
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
+}
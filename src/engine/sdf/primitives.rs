@@ -0,0 +1,497 @@
+use crate::engine::lighting::Material;
+use crate::engine::raymarching::RayMarchingObject;
+use crate::engine::utils::transformation::Transformable;
+use crate::geometry::vector3::Vector3 as V3;
+
+/// Position and orientation of a shape. Primitives are defined around the origin of their local
+/// coordinates with the y axis as their main axis (pointing down, like the world y axis).
+#[derive(Debug, Clone, Copy)]
+pub struct Frame {
+    pub origin: V3,
+    pub x: V3,
+    pub y: V3,
+    pub z: V3,
+}
+
+impl Frame {
+    /// Frame at `origin` aligned with the world axes.
+    pub fn new(origin: V3) -> Self {
+        Frame {
+            origin,
+            x: V3::new(1.0, 0.0, 0.0),
+            y: V3::new(0.0, 1.0, 0.0),
+            z: V3::new(0.0, 0.0, 1.0),
+        }
+    }
+
+    pub fn to_local(&self, p: V3) -> V3 {
+        let mut d = p;
+        d.subtract(self.origin);
+        V3::new(d.dt(self.x), d.dt(self.y), d.dt(self.z))
+    }
+
+    pub fn to_world(&self, q: V3) -> V3 {
+        let mut p = self.origin;
+        for (axis, t) in [(self.x, q.x), (self.y, q.y), (self.z, q.z)] {
+            let mut a = axis;
+            a.scale(t);
+            p.add(a);
+        }
+        p
+    }
+
+    /// Rotates the axes, the origin stays in place.
+    pub fn rot(&mut self, r: V3) {
+        self.x.rotate(r);
+        self.y.rotate(r);
+        self.z.rotate(r);
+    }
+
+    pub fn rot_by(&mut self, p: V3, r: V3) {
+        self.origin.rot_by(p, r);
+        self.rot(r);
+    }
+
+    pub fn translate(&mut self, p: V3) {
+        self.origin.add(p);
+    }
+}
+
+fn length2(x: f64, y: f64) -> f64 {
+    f64::hypot(x, y)
+}
+
+/// Infinite plane through `point`, everything on the side `normal` points away from is inside.
+#[derive(Debug, Clone)]
+pub struct Plane {
+    pub point: V3,
+    pub normal: V3,
+    pub material: Material,
+}
+
+impl Plane {
+    pub fn new(point: V3, normal: V3, material: Material) -> Self {
+        let mut normal = normal;
+        normal.normalize();
+        Plane { point, normal, material }
+    }
+}
+
+impl Transformable for Plane {
+    fn rot(&mut self, r: V3) {
+        self.normal.rotate(r);
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        self.point.rot_by(p, r);
+        self.normal.rotate(r);
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.point.add(p);
+    }
+
+    fn scale(&mut self, p: V3) {}
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
+}
+
+impl RayMarchingObject for Plane {
+    fn sdf(&self, p: V3) -> f64 {
+        let mut d = p;
+        d.subtract(self.point);
+        d.dt(self.normal)
+    }
+
+    fn clone(&self) -> Box<dyn RayMarchingObject + Send + Sync> {
+        Box::new(Clone::clone(self))
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+}
+
+/// Ring around the local y axis: `major` is the radius of the ring, `minor` that of the tube.
+#[derive(Debug, Clone)]
+pub struct Torus {
+    pub frame: Frame,
+    pub major: f64,
+    pub minor: f64,
+    pub material: Material,
+}
+
+impl Torus {
+    pub fn new(m: V3, major: f64, minor: f64, material: Material) -> Self {
+        Torus { frame: Frame::new(m), major, minor, material }
+    }
+
+    fn local_sdf(&self, q: V3) -> f64 {
+        length2(length2(q.x, q.z) - self.major, q.y) - self.minor
+    }
+}
+
+impl Transformable for Torus {
+    fn rot(&mut self, r: V3) {
+        self.frame.rot(r);
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        self.frame.rot_by(p, r);
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.frame.translate(p);
+    }
+
+    fn scale(&mut self, p: V3) {
+        self.major *= p.x;
+        self.minor *= p.x;
+    }
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
+}
+
+impl RayMarchingObject for Torus {
+    fn sdf(&self, p: V3) -> f64 {
+        self.local_sdf(self.frame.to_local(p))
+    }
+
+    fn clone(&self) -> Box<dyn RayMarchingObject + Send + Sync> {
+        Box::new(Clone::clone(self))
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+}
+
+/// Line segment from `a` to `b` with round caps of radius `r`.
+#[derive(Debug, Clone)]
+pub struct Capsule {
+    pub a: V3,
+    pub b: V3,
+    pub r: f64,
+    pub material: Material,
+}
+
+impl Capsule {
+    pub fn new(a: V3, b: V3, r: f64, material: Material) -> Self {
+        Capsule { a, b, r, material }
+    }
+
+    fn middle(&self) -> V3 {
+        let mut m = self.a;
+        m.add(self.b);
+        m.scale(0.5);
+        m
+    }
+}
+
+impl Transformable for Capsule {
+    fn rot(&mut self, r: V3) {
+        let m = self.middle();
+        self.a.rot_by(m, r);
+        self.b.rot_by(m, r);
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        self.a.rot_by(p, r);
+        self.b.rot_by(p, r);
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.a.add(p);
+        self.b.add(p);
+    }
+
+    fn scale(&mut self, p: V3) {
+        self.r *= p.x;
+    }
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
+}
+
+impl RayMarchingObject for Capsule {
+    fn sdf(&self, p: V3) -> f64 {
+        let mut pa = p;
+        pa.subtract(self.a);
+        let mut ba = self.b;
+        ba.subtract(self.a);
+        let len_sq = ba.norm_sq();
+        let h = if len_sq > 0.0 { f64::clamp(pa.dt(ba) / len_sq, 0.0, 1.0) } else { 0.0 };
+        ba.scale(h);
+        pa.subtract(ba);
+        pa.norm() - self.r
+    }
+
+    fn clone(&self) -> Box<dyn RayMarchingObject + Send + Sync> {
+        Box::new(Clone::clone(self))
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+}
+
+/// Capped cylinder along the local y axis, `half_height` above and below its center.
+#[derive(Debug, Clone)]
+pub struct Cylinder {
+    pub frame: Frame,
+    pub r: f64,
+    pub half_height: f64,
+    pub material: Material,
+}
+
+impl Cylinder {
+    pub fn new(m: V3, r: f64, half_height: f64, material: Material) -> Self {
+        Cylinder { frame: Frame::new(m), r, half_height, material }
+    }
+
+    fn local_sdf(&self, q: V3) -> f64 {
+        let dx = length2(q.x, q.z) - self.r;
+        let dy = q.y.abs() - self.half_height;
+        f64::min(f64::max(dx, dy), 0.0) + length2(dx.max(0.0), dy.max(0.0))
+    }
+}
+
+impl Transformable for Cylinder {
+    fn rot(&mut self, r: V3) {
+        self.frame.rot(r);
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        self.frame.rot_by(p, r);
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.frame.translate(p);
+    }
+
+    fn scale(&mut self, p: V3) {
+        self.r *= p.x;
+        self.half_height *= p.y;
+    }
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
+}
+
+impl RayMarchingObject for Cylinder {
+    fn sdf(&self, p: V3) -> f64 {
+        self.local_sdf(self.frame.to_local(p))
+    }
+
+    fn clone(&self) -> Box<dyn RayMarchingObject + Send + Sync> {
+        Box::new(Clone::clone(self))
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+}
+
+/// Solid cone with its tip at the center of the frame and a flat base of radius `r` at
+/// `height` along the local y axis, i.e. below the tip.
+#[derive(Debug, Clone)]
+pub struct Cone {
+    pub frame: Frame,
+    pub r: f64,
+    pub height: f64,
+    pub material: Material,
+}
+
+impl Cone {
+    pub fn new(tip: V3, r: f64, height: f64, material: Material) -> Self {
+        Cone { frame: Frame::new(tip), r, height, material }
+    }
+
+    // exact distance, see https://iquilezles.org/articles/distfunctions/
+    fn local_sdf(&self, q: V3) -> f64 {
+        // profile of the cone: from the tip (0, 0) to the rim (r, -height)
+        let (qx, qy) = (self.r, -self.height);
+        let (wx, wy) = (length2(q.x, q.z), -q.y);
+
+        let t = f64::clamp((wx * qx + wy * qy) / (qx * qx + qy * qy), 0.0, 1.0);
+        let (ax, ay) = (wx - qx * t, wy - qy * t);
+        let (bx, by) = (wx - qx * f64::clamp(wx / qx, 0.0, 1.0), wy - qy);
+
+        let d = f64::min(ax * ax + ay * ay, bx * bx + by * by);
+        // qy is negative
+        let s = f64::max(-(wx * qy - wy * qx), -(wy - qy));
+        d.sqrt() * s.signum()
+    }
+}
+
+impl Transformable for Cone {
+    fn rot(&mut self, r: V3) {
+        self.frame.rot(r);
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        self.frame.rot_by(p, r);
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.frame.translate(p);
+    }
+
+    fn scale(&mut self, p: V3) {
+        self.r *= p.x;
+        self.height *= p.y;
+    }
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
+}
+
+impl RayMarchingObject for Cone {
+    fn sdf(&self, p: V3) -> f64 {
+        self.local_sdf(self.frame.to_local(p))
+    }
+
+    fn clone(&self) -> Box<dyn RayMarchingObject + Send + Sync> {
+        Box::new(Clone::clone(self))
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+}
+
+/// Box with rounded edges. `half_extent` is the outer size, the edges are rounded with
+/// radius `radius`.
+#[derive(Debug, Clone)]
+pub struct RoundedBox {
+    pub frame: Frame,
+    pub half_extent: V3,
+    pub radius: f64,
+    pub material: Material,
+}
+
+impl RoundedBox {
+    pub fn new(m: V3, half_extent: V3, radius: f64, material: Material) -> Self {
+        RoundedBox { frame: Frame::new(m), half_extent, radius, material }
+    }
+
+    fn local_sdf(&self, q: V3) -> f64 {
+        let r = self.radius;
+        let dx = q.x.abs() - self.half_extent.x + r;
+        let dy = q.y.abs() - self.half_extent.y + r;
+        let dz = q.z.abs() - self.half_extent.z + r;
+        let outside = V3::new(dx.max(0.0), dy.max(0.0), dz.max(0.0)).norm();
+        let inside = f64::min(f64::max(dx, f64::max(dy, dz)), 0.0);
+        outside + inside - r
+    }
+}
+
+impl Transformable for RoundedBox {
+    fn rot(&mut self, r: V3) {
+        self.frame.rot(r);
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        self.frame.rot_by(p, r);
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.frame.translate(p);
+    }
+
+    fn scale(&mut self, p: V3) {
+        self.half_extent.x *= p.x;
+        self.half_extent.y *= p.y;
+        self.half_extent.z *= p.z;
+        self.radius *= f64::min(p.x, f64::min(p.y, p.z));
+    }
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
+}
+
+impl RayMarchingObject for RoundedBox {
+    fn sdf(&self, p: V3) -> f64 {
+        self.local_sdf(self.frame.to_local(p))
+    }
+
+    fn clone(&self) -> Box<dyn RayMarchingObject + Send + Sync> {
+        Box::new(Clone::clone(self))
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+}
+
+/// Ellipsoid with the given radii along the local axes. The distance is a bound, not exact,
+/// which is all sphere tracing needs.
+#[derive(Debug, Clone)]
+pub struct Ellipsoid {
+    pub frame: Frame,
+    pub radii: V3,
+    pub material: Material,
+}
+
+impl Ellipsoid {
+    pub fn new(m: V3, radii: V3, material: Material) -> Self {
+        Ellipsoid { frame: Frame::new(m), radii, material }
+    }
+
+    fn local_sdf(&self, q: V3) -> f64 {
+        let r = self.radii;
+        let k0 = V3::new(q.x / r.x, q.y / r.y, q.z / r.z).norm();
+        let k1 = V3::new(q.x / (r.x * r.x), q.y / (r.y * r.y), q.z / (r.z * r.z)).norm();
+        if k1 == 0.0 {
+            // the center
+            return -f64::min(r.x, f64::min(r.y, r.z));
+        }
+        k0 * (k0 - 1.0) / k1
+    }
+}
+
+impl Transformable for Ellipsoid {
+    fn rot(&mut self, r: V3) {
+        self.frame.rot(r);
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        self.frame.rot_by(p, r);
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.frame.translate(p);
+    }
+
+    fn scale(&mut self, p: V3) {
+        self.radii.x *= p.x;
+        self.radii.y *= p.y;
+        self.radii.z *= p.z;
+    }
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
+}
+
+impl RayMarchingObject for Ellipsoid {
+    fn sdf(&self, p: V3) -> f64 {
+        self.local_sdf(self.frame.to_local(p))
+    }
+
+    fn clone(&self) -> Box<dyn RayMarchingObject + Send + Sync> {
+        Box::new(Clone::clone(self))
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+}
@@ -0,0 +1,399 @@
+use sdl2::pixels::Color;
+
+use crate::engine::lighting::Material;
+use crate::engine::pathtracing::PathtracingObject;
+use crate::engine::simplex3d_bvh::bvh_node::{BvhHit, BvhNode};
+use crate::engine::utils::{rendering::Collision, transformation::Transformable};
+use crate::geometry::aabb::{axis, inverse_direction, Aabb};
+use crate::geometry::face::{Face as F, UV};
+use crate::geometry::simplex3d::Simplex3D;
+use crate::geometry::vector3::Vector3 as V3;
+
+const SAH_BINS: usize = 12;
+const MAX_LEAF_SIZE: usize = 4;
+const TRAVERSAL_COST: f64 = 1.0;
+const INTERSECTION_COST: f64 = 1.0;
+
+/// Bounding volume hierarchy over the faces of a [`Simplex3D`], built with the binned
+/// surface area heuristic. Drop-in alternative to the sphere tree in
+/// [`crate::engine::simplex3d_sphere_tree::poly_tree::PolyTree`].
+#[derive(Debug, Clone)]
+pub struct Bvh {
+    pub m: V3,
+    pub nodes: Vec<BvhNode>,
+    /// Face indices into `source.x`, ordered so that every leaf covers a contiguous range.
+    pub indices: Vec<usize>,
+    pub source: Simplex3D,
+}
+
+impl Bvh {
+    pub fn new(p: Simplex3D) -> Box<Bvh> {
+        let mut bvh = Bvh {
+            m: p.m,
+            nodes: Vec::new(),
+            indices: Vec::new(),
+            source: p,
+        };
+        bvh.build();
+        Box::new(bvh)
+    }
+
+    /// Rebuilds the hierarchy from scratch.
+    pub fn build(&mut self) {
+        let face_bounds: Vec<Aabb> = self.source.x.iter().map(Aabb::from_face).collect();
+        let centroids: Vec<V3> = face_bounds.iter().map(|b| b.centroid()).collect();
+
+        self.indices = (0..self.source.x.len()).collect();
+        self.nodes = vec![BvhNode::leaf(Aabb::empty(), 0, self.indices.len())];
+
+        if !self.indices.is_empty() {
+            self.subdivide(0, &face_bounds, &centroids);
+        }
+    }
+
+    /// Recomputes all node bounds for the current face positions, keeping the topology.
+    /// Cheap compared to [`Bvh::build`], but the tree quality degrades if faces move a lot.
+    pub fn refit(&mut self) {
+        // an empty mesh has a single leaf without faces, which looks like an inner node
+        if self.indices.is_empty() {
+            return;
+        }
+        // children are always stored after their parent
+        for i in (0..self.nodes.len()).rev() {
+            let node = self.nodes[i];
+            let bounds = if node.is_leaf() {
+                let mut b = Aabb::empty();
+                for &fi in self.indices[node.first..node.first + node.count].iter() {
+                    b = b.union(&Aabb::from_face(&self.source.x[fi]));
+                }
+                b
+            } else {
+                self.nodes[node.first].bounds.union(&self.nodes[node.first + 1].bounds)
+            };
+            self.nodes[i].bounds = bounds;
+        }
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        match self.nodes.first() {
+            Some(n) => n.bounds,
+            None => Aabb::empty(),
+        }
+    }
+
+    pub fn goto(&mut self, p: V3) {
+        let mut p_ = p;
+        p_.subtract(self.m);
+        self.translate(p_);
+    }
+
+    fn subdivide(&mut self, node_index: usize, face_bounds: &[Aabb], centroids: &[V3]) {
+        let first = self.nodes[node_index].first;
+        let count = self.nodes[node_index].count;
+
+        let mut bounds = Aabb::empty();
+        let mut centroid_bounds = Aabb::empty();
+        for &fi in self.indices[first..first + count].iter() {
+            bounds = bounds.union(&face_bounds[fi]);
+            centroid_bounds.grow(centroids[fi]);
+        }
+        self.nodes[node_index].bounds = bounds;
+
+        if count <= MAX_LEAF_SIZE {
+            return;
+        }
+
+        let (split_axis, split_bin, split_cost) = Self::find_sah_split(
+            &self.indices[first..first + count],
+            face_bounds,
+            centroids,
+            &centroid_bounds,
+        );
+
+        let leaf_cost = count as f64 * INTERSECTION_COST;
+        if split_bin == 0 || split_cost >= leaf_cost {
+            return;
+        }
+
+        let lo = axis(centroid_bounds.min, split_axis);
+        let scale = SAH_BINS as f64 / (axis(centroid_bounds.max, split_axis) - lo);
+
+        // partition in place: faces left of the split plane go to the front
+        let mut i = first;
+        let mut j = first + count;
+        while i < j {
+            let c = axis(centroids[self.indices[i]], split_axis);
+            let bin = usize::min(((c - lo) * scale) as usize, SAH_BINS - 1);
+            if bin < split_bin {
+                i += 1;
+            } else {
+                j -= 1;
+                self.indices.swap(i, j);
+            }
+        }
+
+        let left_count = i - first;
+        if left_count == 0 || left_count == count {
+            return;
+        }
+
+        let left = self.nodes.len();
+        self.nodes.push(BvhNode::leaf(Aabb::empty(), first, left_count));
+        self.nodes.push(BvhNode::leaf(Aabb::empty(), i, count - left_count));
+        self.nodes[node_index].first = left;
+        self.nodes[node_index].count = 0;
+
+        self.subdivide(left, face_bounds, centroids);
+        self.subdivide(left + 1, face_bounds, centroids);
+    }
+
+    /// Returns (axis, bin, cost) of the cheapest split; bin 0 means no split was found.
+    fn find_sah_split(indices: &[usize], face_bounds: &[Aabb], centroids: &[V3], centroid_bounds: &Aabb) -> (usize, usize, f64) {
+        let mut best = (0, 0, f64::INFINITY);
+        let parent_area = {
+            let mut b = Aabb::empty();
+            for &fi in indices.iter() {
+                b = b.union(&face_bounds[fi]);
+            }
+            b.surface_area()
+        };
+        if parent_area <= 0.0 {
+            return best;
+        }
+
+        for a in 0..3 {
+            let lo = axis(centroid_bounds.min, a);
+            let hi = axis(centroid_bounds.max, a);
+            if hi - lo <= 1e-12 {
+                continue;
+            }
+            let scale = SAH_BINS as f64 / (hi - lo);
+
+            let mut bin_bounds = [Aabb::empty(); SAH_BINS];
+            let mut bin_counts = [0usize; SAH_BINS];
+            for &fi in indices.iter() {
+                let bin = usize::min(((axis(centroids[fi], a) - lo) * scale) as usize, SAH_BINS - 1);
+                bin_counts[bin] += 1;
+                bin_bounds[bin] = bin_bounds[bin].union(&face_bounds[fi]);
+            }
+
+            // sweep from the right to get the area/count of every right partition
+            let mut right_area = [0.0; SAH_BINS];
+            let mut right_count = [0usize; SAH_BINS];
+            let mut acc = Aabb::empty();
+            let mut n = 0;
+            for b in (1..SAH_BINS).rev() {
+                acc = acc.union(&bin_bounds[b]);
+                n += bin_counts[b];
+                right_area[b] = acc.surface_area();
+                right_count[b] = n;
+            }
+
+            let mut acc = Aabb::empty();
+            let mut n = 0;
+            for b in 1..SAH_BINS {
+                acc = acc.union(&bin_bounds[b - 1]);
+                n += bin_counts[b - 1];
+                if n == 0 || right_count[b] == 0 {
+                    continue;
+                }
+                let cost = TRAVERSAL_COST
+                    + INTERSECTION_COST * (acc.surface_area() * n as f64 + right_area[b] * right_count[b] as f64) / parent_area;
+                if cost < best.2 {
+                    best = (a, b, cost);
+                }
+            }
+        }
+
+        best
+    }
+
+    /// Möller–Trumbore. Returns (t, beta, gamma) with the same barycentric convention as
+    /// [`F::get_beta_gamma`]: `hit = r + beta * (a - r) + gamma * (b - r)`.
+    pub fn intersect_face(f: &F, p0: V3, p: V3) -> Option<(f64, f64, f64)> {
+        let mut e1 = f.a;
+        e1.subtract(f.r);
+        let mut e2 = f.b;
+        e2.subtract(f.r);
+
+        let mut h = p;
+        h.cross(e2);
+        let det = e1.dt(h);
+        if f64::abs(det) < 1e-12 {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+
+        let mut s = p0;
+        s.subtract(f.r);
+        let beta = inv_det * s.dt(h);
+        if !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+
+        let mut q = s;
+        q.cross(e1);
+        let gamma = inv_det * p.dt(q);
+        if gamma < 0.0 || beta + gamma > 1.0 {
+            return None;
+        }
+
+        let t = inv_det * e2.dt(q);
+        if t > 1e-9 {
+            Some((t, beta, gamma))
+        } else {
+            None
+        }
+    }
+
+    /// Closest face hit along `p0 + t * p`.
+    pub fn closest_hit(&self, p0: V3, p: V3) -> Option<BvhHit> {
+        if self.nodes.is_empty() || self.indices.is_empty() {
+            return None;
+        }
+
+        let inv_dir = inverse_direction(p);
+        let mut best: Option<BvhHit> = None;
+        let mut best_t = f64::INFINITY;
+
+        let mut stack: Vec<usize> = Vec::with_capacity(64);
+        if self.nodes[0].bounds.hit(p0, inv_dir, best_t).is_some() {
+            stack.push(0);
+        }
+
+        while let Some(ni) = stack.pop() {
+            let node = &self.nodes[ni];
+            if node.is_leaf() {
+                for &fi in self.indices[node.first..node.first + node.count].iter() {
+                    if let Some((t, beta, gamma)) = Self::intersect_face(&self.source.x[fi], p0, p) {
+                        if t < best_t {
+                            best_t = t;
+                            best = Some(BvhHit { t, face: fi, bg: (beta, gamma) });
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let l = node.first;
+            let r = node.first + 1;
+            let tl = self.nodes[l].bounds.hit(p0, inv_dir, best_t);
+            let tr = self.nodes[r].bounds.hit(p0, inv_dir, best_t);
+
+            // push the farther child first so the nearer one is visited next
+            match (tl, tr) {
+                (Some(a), Some(b)) => {
+                    if a < b {
+                        stack.push(r);
+                        stack.push(l);
+                    } else {
+                        stack.push(l);
+                        stack.push(r);
+                    }
+                }
+                (Some(_), None) => stack.push(l),
+                (None, Some(_)) => stack.push(r),
+                (None, None) => {}
+            }
+        }
+
+        best
+    }
+
+    fn collision_from_hit(&self, p0: V3, p: V3, hit: &BvhHit) -> Collision {
+        let mut pc = p;
+        pc.scale(hit.t);
+        pc.add(p0);
+
+        let uv = self.source.tm.get(hit.face).copied().unwrap_or(UV::empty());
+        let c = self.source.surface_color(self.source.x[hit.face].material, &uv, hit.bg);
+
+        Collision { d: pc.d(p0), p: pc, hit: true, c }
+    }
+}
+
+impl Transformable for Bvh {
+    fn rot_reverse(&mut self, r_: V3) {
+        self.source.rot_reverse(r_);
+        self.refit();
+    }
+
+    fn rot(&mut self, r_: V3) {
+        self.source.rot(r_);
+        self.refit();
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        for f in self.source.x.iter_mut() {
+            f.rot(r, p);
+        }
+        self.source.m.rot_by(p, r);
+        self.m = self.source.m;
+        self.refit();
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.source.translate(p);
+        self.m.translate(p.x, p.y, p.z);
+        self.refit();
+    }
+
+    fn scale(&mut self, p: V3) {
+        self.source.scale(p);
+        self.build();
+    }
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
+}
+
+impl PathtracingObject for Bvh {
+    fn d(&self, p: V3) -> f64 {
+        // distance to the bounding box, 0 inside
+        let b = self.bounds();
+        let dx = f64::max(f64::max(b.min.x - p.x, p.x - b.max.x), 0.0);
+        let dy = f64::max(f64::max(b.min.y - p.y, p.y - b.max.y), 0.0);
+        let dz = f64::max(f64::max(b.min.z - p.z, p.z - b.max.z), 0.0);
+        f64::sqrt(dx * dx + dy * dy + dz * dz)
+    }
+
+    fn color(&self, _p: V3) -> Color {
+        self.source.base_color
+    }
+
+    fn is_colliding(&mut self, p0: V3, p: V3) -> bool {
+        self.closest_hit(p0, p).is_some()
+    }
+
+    fn get_collision(&self, p0: V3, p: V3) -> Collision {
+        self.get_collision_with_normal(p0, p).0
+    }
+
+    fn get_collision_with_normal(&self, p0: V3, p: V3) -> (Collision, Option<V3>) {
+        match self.closest_hit(p0, p) {
+            Some(hit) => (self.collision_from_hit(p0, p, &hit), Some(self.source.x[hit.face].normal_at(hit.bg))),
+            None => (Collision::empty(), None),
+        }
+    }
+
+    fn get_collision_with_material(&self, p0: V3, p: V3) -> (Collision, Option<V3>, Material) {
+        match self.closest_hit(p0, p) {
+            Some(hit) => {
+                let m = self.source.face_material(hit.face, hit.bg);
+                let mut c = self.collision_from_hit(p0, p, &hit);
+                c.c = m.albedo;
+                (c, Some(self.source.x[hit.face].normal_at(hit.bg)), m)
+            }
+            None => (Collision::empty(), None, Material::new(self.source.base_color, 1.0)),
+        }
+    }
+
+    fn bounds(&self) -> Aabb {
+        Bvh::bounds(self)
+    }
+
+    fn clone(&self) -> Box<dyn PathtracingObject + Send + Sync + 'static> {
+        Box::new(Clone::clone(self))
+    }
+}
@@ -0,0 +1,30 @@
+use crate::geometry::aabb::Aabb;
+
+/// Node of a flattened [`super::bvh::Bvh`].
+///
+/// For leaves `count > 0` and the node covers `indices[first..first + count]`,
+/// inner nodes have `count == 0` and their children at `first` and `first + 1`.
+#[derive(Debug, Clone, Copy)]
+pub struct BvhNode {
+    pub bounds: Aabb,
+    pub first: usize,
+    pub count: usize,
+}
+
+impl BvhNode {
+    pub fn leaf(bounds: Aabb, first: usize, count: usize) -> Self {
+        BvhNode { bounds, first, count }
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        self.count > 0
+    }
+}
+
+/// Closest triangle hit found while traversing a [`super::bvh::Bvh`].
+#[derive(Debug, Clone, Copy)]
+pub struct BvhHit {
+    pub t: f64,
+    pub face: usize,
+    pub bg: (f64, f64),
+}
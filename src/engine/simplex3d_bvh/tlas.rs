@@ -0,0 +1,180 @@
+use crate::engine::simplex3d_bvh::bvh_node::BvhNode;
+use crate::geometry::aabb::{axis, inverse_direction, Aabb};
+use crate::geometry::vector3::Vector3 as V3;
+
+const MAX_LEAF_SIZE: usize = 2;
+/// Boxes are grown by this fraction of their size so hits on their surface are not lost to
+/// rounding.
+const PADDING: f64 = 1e-7;
+
+/// Top level acceleration structure: a hierarchy over the world space bounds of the objects of
+/// a scene, which in turn may have their own hierarchy like a mesh [`super::bvh::Bvh`]. It only
+/// stores indices into the object list of the scene; the objects are tested by the callbacks
+/// of [`Tlas::closest`] and [`Tlas::any`]. Laid out like the `Bvh`, built by median splits.
+#[derive(Debug, Clone)]
+pub struct Tlas {
+    pub nodes: Vec<BvhNode>,
+    /// Object indices, ordered so that every leaf covers a contiguous range.
+    pub indices: Vec<usize>,
+    /// Objects without finite bounds, they are tested for every ray.
+    pub unbounded: Vec<usize>,
+}
+
+impl Tlas {
+    /// Hierarchy over objects with the given bounds. Objects with empty bounds are never hit
+    /// and left out.
+    pub fn build(bounds: &[Aabb]) -> Self {
+        let mut tlas = Tlas { nodes: Vec::new(), indices: Vec::new(), unbounded: Vec::new() };
+        for (i, b) in bounds.iter().enumerate() {
+            if b.is_empty() {
+                continue;
+            }
+            if b.is_finite() {
+                tlas.indices.push(i);
+            } else {
+                tlas.unbounded.push(i);
+            }
+        }
+        let bounds: Vec<Aabb> = bounds.iter().map(pad).collect();
+        tlas.nodes.push(BvhNode::leaf(Aabb::empty(), 0, tlas.indices.len()));
+        if !tlas.indices.is_empty() {
+            tlas.subdivide(0, &bounds);
+        }
+        tlas
+    }
+
+    /// Recomputes the node bounds for objects that moved, keeping the tree. Returns false if an
+    /// object changed between finite, infinite and empty bounds, the tree has to be built again
+    /// then.
+    pub fn refit(&mut self, bounds: &[Aabb]) -> bool {
+        let bounded = bounds.iter().filter(|b| b.is_finite() && !b.is_empty()).count();
+        if bounded != self.indices.len() || self.indices.iter().any(|&i| i >= bounds.len() || !bounds[i].is_finite() || bounds[i].is_empty()) {
+            return false;
+        }
+        if self.unbounded.iter().any(|&i| i >= bounds.len() || bounds[i].is_finite() || bounds[i].is_empty()) {
+            return false;
+        }
+        if self.indices.is_empty() {
+            return true;
+        }
+        // children are always stored after their parent
+        for i in (0..self.nodes.len()).rev() {
+            let node = self.nodes[i];
+            self.nodes[i].bounds = if node.is_leaf() {
+                self.indices[node.first..node.first + node.count].iter().fold(Aabb::empty(), |b, &oi| b.union(&pad(&bounds[oi])))
+            } else {
+                self.nodes[node.first].bounds.union(&self.nodes[node.first + 1].bounds)
+            };
+        }
+        true
+    }
+
+    /// Bounds of all objects, infinite if there are unbounded ones.
+    pub fn bounds(&self) -> Aabb {
+        if !self.unbounded.is_empty() {
+            return Aabb::infinite();
+        }
+        self.nodes.first().map_or(Aabb::empty(), |n| n.bounds)
+    }
+
+    fn subdivide(&mut self, node_index: usize, bounds: &[Aabb]) {
+        let BvhNode { first, count, .. } = self.nodes[node_index];
+        let range = first..first + count;
+        let node_bounds = self.indices[range.clone()].iter().fold(Aabb::empty(), |b, &i| b.union(&bounds[i]));
+        self.nodes[node_index].bounds = node_bounds;
+        if count <= MAX_LEAF_SIZE {
+            return;
+        }
+
+        let mut centroids = Aabb::empty();
+        self.indices[range.clone()].iter().for_each(|&i| centroids.grow(bounds[i].centroid()));
+        let split_axis = centroids.longest_axis();
+        let half = count / 2;
+        self.indices[range].select_nth_unstable_by(half, |&a, &b| {
+            axis(bounds[a].centroid(), split_axis).total_cmp(&axis(bounds[b].centroid(), split_axis))
+        });
+
+        let left = self.nodes.len();
+        self.nodes.push(BvhNode::leaf(Aabb::empty(), first, half));
+        self.nodes.push(BvhNode::leaf(Aabb::empty(), first + half, count - half));
+        self.nodes[node_index] = BvhNode { bounds: node_bounds, first: left, count: 0 };
+        self.subdivide(left, bounds);
+        self.subdivide(left + 1, bounds);
+    }
+
+    /// Offers the objects along the ray from `p0` in direction `p` to `hit` roughly front to
+    /// back, which returns the distance from `p0` to its hit. Objects whose bounds start behind
+    /// the closest hit so far are skipped.
+    pub fn closest<F: FnMut(usize) -> Option<f64>>(&self, p0: V3, p: V3, mut hit: F) {
+        let mut best = f64::INFINITY;
+        for &i in self.unbounded.iter() {
+            if let Some(d) = hit(i) {
+                best = best.min(d);
+            }
+        }
+        self.traverse(p0, p, best, |i, best| {
+            if let Some(d) = hit(i) {
+                *best = best.min(d);
+            }
+            false
+        });
+    }
+
+    /// Whether `hit` accepts any object along the ray within `max_d`, stops at the first one.
+    /// Meant for shadow rays, which don't need the closest hit.
+    pub fn any<F: FnMut(usize) -> bool>(&self, p0: V3, p: V3, max_d: f64, mut hit: F) -> bool {
+        if self.unbounded.iter().any(|&i| hit(i)) {
+            return true;
+        }
+        self.traverse(p0, p, max_d, |i, _| hit(i))
+    }
+
+    /// Visits the leaves whose bounds the ray enters before the distance `t_max`, the nearer
+    /// child first. `visit` can lower `t_max` and stop the traversal by returning true.
+    fn traverse<F: FnMut(usize, &mut f64) -> bool>(&self, p0: V3, p: V3, t_max: f64, mut visit: F) -> bool {
+        if self.indices.is_empty() {
+            return false;
+        }
+        // with a unit direction the slab test measures distances
+        let inv_dir = inverse_direction(p.normalized());
+        let mut t_max = t_max;
+        let mut stack: Vec<usize> = Vec::with_capacity(32);
+        if self.nodes[0].bounds.hit(p0, inv_dir, t_max).is_some() {
+            stack.push(0);
+        }
+        while let Some(ni) = stack.pop() {
+            let node = self.nodes[ni];
+            // the closest hit may have moved in front of the node since it was pushed
+            if node.bounds.hit(p0, inv_dir, t_max).is_none() {
+                continue;
+            }
+            if node.is_leaf() {
+                for &oi in self.indices[node.first..node.first + node.count].iter() {
+                    if visit(oi, &mut t_max) {
+                        return true;
+                    }
+                }
+                continue;
+            }
+            let (l, r) = (node.first, node.first + 1);
+            match (self.nodes[l].bounds.hit(p0, inv_dir, t_max), self.nodes[r].bounds.hit(p0, inv_dir, t_max)) {
+                (Some(a), Some(b)) if a < b => stack.extend([r, l]),
+                (Some(_), Some(_)) => stack.extend([l, r]),
+                (Some(_), None) => stack.push(l),
+                (None, Some(_)) => stack.push(r),
+                (None, None) => {}
+            }
+        }
+        false
+    }
+}
+
+fn pad(b: &Aabb) -> Aabb {
+    if b.is_empty() || !b.is_finite() {
+        return *b;
+    }
+    let e = b.extent();
+    let margin = PADDING * (e.x.abs() + e.y.abs() + e.z.abs() + 1.0);
+    let m = V3::new(margin, margin, margin);
+    Aabb::new(b.min - m, b.max + m)
+}
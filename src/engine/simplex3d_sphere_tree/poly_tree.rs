@@ -3,9 +3,11 @@ use std::thread;
 use sdl2::pixels::Color;
 
 use crate::engine::utils::{rendering::{RayRenderScene, RayRenderable, Collision, RaySphereable}, transformation::Transformable};
+use crate::engine::lighting::Material;
 use crate::engine::pathtracing::PathtracingObject;
 use crate::engine::simplex3d_sphere_tree::poly_tree_element::PolyTreeElement;
 use crate::geometry::face::{Face as F, UV};
+use crate::geometry::aabb::Aabb;
 use crate::geometry::vector3::Vector3 as V3;
 use crate::geometry::simplex3d::Simplex3D;
 
@@ -74,31 +76,7 @@ impl PathtracingObject for PolyTree {
                     }
                 }
             }
-            let mut c : Collision = Collision {d: bd, p: ptcf_closest.p, hit: true, c: self.source.base_color };
-            let uv = ptcf_closest.uv;
-            let y = (uv.r.0 + ptcf_closest.bg.0 * (uv.a.0 - uv.r.0) + ptcf_closest.bg.1 * (uv.b.0 - uv.r.0));
-            let x = 1.0 - (uv.r.1 + ptcf_closest.bg.0 * (uv.a.1 - uv.r.1) + ptcf_closest.bg.1 * (uv.b.1 - uv.r.1));
-
-            let ty = (x * self.source.th as f64) as u32;
-            let tx = (y * self.source.tw as f64) as u32;
-
-            let pos = ((tx + ty * self.source.th) * 3) as usize;
-
-            //print!("{} -- ", pos);
-
-            if !self.source.has_t {
-                c.c = self.source.base_color;
-            }
-            else if pos >= self.source.tf.len() {
-                c.c = self.source.base_color;
-            }
-            else {
-                let r = self.source.tf[pos];
-                let g = self.source.tf[pos + 1];
-                let b = self.source.tf[pos + 2];
-
-                c.c = Color::RGB(r, g, b);
-            }
+            let c : Collision = Collision {d: bd, p: ptcf_closest.p, hit: true, c: self.source.surface_color(ptcf_closest.material, ptcf_closest.uv, ptcf_closest.bg) };
             return c;
         }
 
@@ -121,34 +99,38 @@ impl PathtracingObject for PolyTree {
                 }
             }
 
-            let mut c : Collision = Collision {d: bd, p: ptcf_closest.p, hit: true, c: self.source.base_color };
-            let uv = ptcf_closest.uv;
-            let y = (uv.r.0 + ptcf_closest.bg.0 * (uv.a.0 - uv.r.0) + ptcf_closest.bg.1 * (uv.b.0 - uv.r.0));
-            let x = 1.0 - (uv.r.1 + ptcf_closest.bg.0 * (uv.a.1 - uv.r.1) + ptcf_closest.bg.1 * (uv.b.1 - uv.r.1));
-
-            let ty = (x * self.source.th as f64) as u32;
-            let tx = (y * self.source.tw as f64) as u32;
-
-            let pos = ((tx + ty * self.source.th) * 3) as usize;
+            let c : Collision = Collision {d: bd, p: ptcf_closest.p, hit: true, c: self.source.surface_color(ptcf_closest.material, ptcf_closest.uv, ptcf_closest.bg) };
+            return (c, Some(ptcf_closest.n));
+        }
 
-            if !self.source.has_t {
-                c.c = self.source.base_color;
-            }
-            else if pos >= self.source.tf.len() {
-                c.c = self.source.base_color;
-            }
-            else {
-                let r = self.source.tf[pos];
-                let g = self.source.tf[pos + 1];
-                let b = self.source.tf[pos + 2];
+        (Collision{d: bd, p: p0, hit: false, c: Color::RED}, None)
+    }
 
-                c.c = Color::RGB(r, g, b);
+    fn get_collision_with_material(&self, p0: V3, p: V3) -> (Collision, Option<V3>, Material) {
+        let mut ptcf_closest = PolyTreeCollisionFeedback::empty();
+        let mut bd : f64 = f64::MAX;
+        let ptcf_vec = self.root.get_collision(p0, p);
+        if !ptcf_vec.is_empty() {
+            for ptcf in ptcf_vec {
+                if ptcf.hit {
+                    let d : f64 = ptcf.p.d(p0);
+                    if (d < bd) {
+                        bd = d;
+                        ptcf_closest = ptcf;
+                    }
+                }
             }
 
-            return (c, Some(ptcf_closest.n));
+            let m = self.source.surface_material(ptcf_closest.material, ptcf_closest.uv, ptcf_closest.bg);
+            let c : Collision = Collision {d: bd, p: ptcf_closest.p, hit: true, c: m.albedo };
+            return (c, Some(ptcf_closest.n), m);
         }
 
-        (Collision{d: bd, p: p0, hit: false, c: Color::RED}, None)
+        (Collision{d: bd, p: p0, hit: false, c: Color::RED}, None, Material::new(self.source.base_color, 1.0))
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.source.bounds()
     }
 
     fn clone(&self) -> Box<dyn PathtracingObject + Send + Sync + 'static> {
@@ -172,13 +172,15 @@ impl PolyTreeElement {
                                 + bg_.1 * (self.faces[i].b.z - self.faces[i].r.z),
                         };
                         let d: f64 = pc_.d(p0);
+                        let mut to_hit = pc_;
+                        to_hit.subtract(p0);
 
-                        if (d < bd) {
+                        if (d < bd && to_hit.dt(p) > 1e-9) {
                             pc = pc_;
                             bg = bg_;
                             bd = d;
                             i_ = i;
-                            n = self.faces[i].n;
+                            n = self.faces[i].normal_at(bg_);
                             did_hit = true;
                         }
                     }
@@ -188,9 +190,10 @@ impl PolyTreeElement {
                 return vec![PolyTreeCollisionFeedback {
                     hit: true,
                     p: pc,
-                    uv: &self.uvs[i_],
+                    uv: self.uvs.get(i_).unwrap_or(&UV{r: (0.0, 0.0), a: (0.0, 0.0), b: (0.0, 0.0)}),
                     n,
                     bg,
+                    material: self.faces[i_].material,
                 }];
             } else {
                 return Vec::new();
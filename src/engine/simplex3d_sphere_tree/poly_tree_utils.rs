@@ -8,10 +8,11 @@ pub struct PolyTreeCollisionFeedback<'a> {
     pub uv: &'a UV,
     pub n: V3,
     pub bg : (f64, f64),
+    pub material: Option<usize>,
 }
 
 impl PolyTreeCollisionFeedback<'_> {
     pub fn empty () -> Self {
-        PolyTreeCollisionFeedback{hit: false, p: V3{x: 0.0, y: 0.0, z: 0.0}, uv: &UV{r: (0.0, 0.0), a: (0.0, 0.0), b: (0.0, 0.0)}, n: V3{x: 0.0, y: 0.0, z: 0.0}, bg: (0.0, 0.0)}
+        PolyTreeCollisionFeedback{hit: false, p: V3{x: 0.0, y: 0.0, z: 0.0}, uv: &UV{r: (0.0, 0.0), a: (0.0, 0.0), b: (0.0, 0.0)}, n: V3{x: 0.0, y: 0.0, z: 0.0}, bg: (0.0, 0.0), material: None}
     }
 }
\ No newline at end of file
@@ -0,0 +1,350 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use sdl2::pixels::Color;
+
+use crate::engine::camera::RayCamera;
+use crate::engine::utils::rendering::RayRenderable;
+use crate::engine::utils::virtual_canvas::VirtualCanvas;
+
+pub const DEFAULT_TILE_SIZE: usize = 32;
+
+type Task = Box<dyn FnOnce() + Send>;
+
+/// Part of the image that is rendered in one piece.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TileRect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// A rendered tile, `pixels` are column-major (`x * height + y` within the tile).
+pub struct Tile<T = Color> {
+    pub rect: TileRect,
+    pub pixels: Vec<T>,
+}
+
+/// Persistent pool of worker threads that render frames tile by tile. Idle workers take the
+/// next open tile of the oldest job, so expensive parts of the image do not hold up the others.
+pub struct TileRenderer {
+    pub tile_size: usize,
+    sender: Option<Sender<Task>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+/// Tiles of an image in progress, see [`TileRenderer::render_tiles`]. Iterating blocks until
+/// the next tile is done. Dropping the job cancels it.
+pub struct TileJob<T> {
+    pub width: usize,
+    pub height: usize,
+    /// Number of tiles of the image.
+    pub total: usize,
+    received: usize,
+    tiles: Receiver<Tile<T>>,
+    cancelled: Arc<AtomicBool>,
+    failed: Arc<AtomicBool>,
+}
+
+/// A frame in progress, see [`TileRenderer::render`]. Dropping the job cancels it.
+pub struct RenderJob {
+    pub width: usize,
+    pub height: usize,
+    /// Number of tiles of the frame.
+    pub total: usize,
+    frame: VirtualCanvas,
+    tiles: TileJob<Color>,
+}
+
+impl TileRenderer {
+    /// Pool with `threads` workers, at least one.
+    pub fn new(threads: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Task>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let workers = (0..threads.max(1)).map(|_| {
+            let receiver = Arc::clone(&receiver);
+            thread::spawn(move || loop {
+                let task = receiver.lock().unwrap().recv();
+                match task {
+                    // a panicking task must not take the worker with it
+                    Ok(task) => {
+                        let _ = panic::catch_unwind(AssertUnwindSafe(task));
+                    }
+                    // the pool was dropped
+                    Err(_) => break,
+                }
+            })
+        }).collect();
+
+        TileRenderer { tile_size: DEFAULT_TILE_SIZE, sender: Some(sender), workers }
+    }
+
+    pub fn set_tile_size(&mut self, tile_size: usize) {
+        self.tile_size = tile_size.max(1);
+    }
+
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Tiles of a `w` x `h` image, the ones closest to the center first so that partial
+    /// results show the interesting part early.
+    pub fn tiles(&self, w: usize, h: usize) -> Vec<TileRect> {
+        let s = self.tile_size;
+        let mut tiles = Vec::new();
+        for y in (0..h).step_by(s) {
+            for x in (0..w).step_by(s) {
+                tiles.push(TileRect { x, y, width: usize::min(s, w - x), height: usize::min(s, h - y) });
+            }
+        }
+
+        let distance = |t: &TileRect| {
+            let dx = (t.x + t.width / 2) as f64 - w as f64 / 2.0;
+            let dy = (t.y + t.height / 2) as f64 - h as f64 / 2.0;
+            dx * dx + dy * dy
+        };
+        tiles.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+        tiles
+    }
+
+    /// Queues all tiles of a `w` x `h` frame of `obj` as seen by `camera` and returns at once.
+    pub fn render<R: RayRenderable + 'static>(&self, camera: &RayCamera, obj: Arc<R>, w: usize, h: usize) -> RenderJob {
+        let camera = *camera;
+        let tiles = self.render_tiles(w, h, move |rect| camera.render_tile(&*obj, rect, w, h));
+        RenderJob { width: w, height: h, total: tiles.total, frame: VirtualCanvas::new(w, h), tiles }
+    }
+
+    /// Queues all tiles of a `w` x `h` image and returns at once. The workers call `render` for
+    /// every tile, it returns the values of the tile column-major.
+    pub fn render_tiles<T, F>(&self, w: usize, h: usize, render: F) -> TileJob<T>
+    where
+        T: Send + 'static,
+        F: Fn(TileRect) -> Vec<T> + Send + Sync + 'static,
+    {
+        let tiles = self.tiles(w, h);
+        let (tx, rx) = mpsc::channel::<Tile<T>>();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let failed = Arc::new(AtomicBool::new(false));
+        let job = TileJob {
+            width: w,
+            height: h,
+            total: tiles.len(),
+            received: 0,
+            tiles: rx,
+            cancelled: Arc::clone(&cancelled),
+            failed: Arc::clone(&failed),
+        };
+
+        let render = Arc::new(render);
+        let sender = self.sender.as_ref().expect("tile renderer was shut down");
+        for rect in tiles {
+            let render = Arc::clone(&render);
+            let tx = tx.clone();
+            let cancelled = Arc::clone(&cancelled);
+            let failed = Arc::clone(&failed);
+
+            let _ = sender.send(Box::new(move || {
+                if cancelled.load(Ordering::Relaxed) {
+                    return;
+                }
+                match panic::catch_unwind(AssertUnwindSafe(|| render(rect))) {
+                    Ok(pixels) => {
+                        let _ = tx.send(Tile { rect, pixels });
+                    }
+                    // the image can never be complete, the other tiles are skipped
+                    Err(_) => {
+                        failed.store(true, Ordering::Relaxed);
+                        cancelled.store(true, Ordering::Relaxed);
+                    }
+                }
+            }));
+        }
+        job
+    }
+
+    /// Like [`TileRenderer::render_tiles`], calling `pixel(x, y)` for every pixel of the tiles.
+    pub fn render_pixels<T, F>(&self, w: usize, h: usize, pixel: F) -> TileJob<T>
+    where
+        T: Send + 'static,
+        F: Fn(usize, usize) -> T + Send + Sync + 'static,
+    {
+        self.render_tiles(w, h, move |r| {
+            let mut values = Vec::with_capacity(r.width * r.height);
+            for x in r.x..r.x + r.width {
+                for y in r.y..r.y + r.height {
+                    values.push(pixel(x, y));
+                }
+            }
+            values
+        })
+    }
+}
+
+impl Drop for TileRenderer {
+    fn drop(&mut self) {
+        // closing the queue ends the workers after their current task
+        drop(self.sender.take());
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl<T> TileJob<T> {
+    /// Skips all tiles that have not been started yet.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Whether rendering a tile panicked. The job is cancelled then and never finishes.
+    pub fn is_failed(&self) -> bool {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    /// Tiles handed out so far.
+    pub fn finished_tiles(&self) -> usize {
+        self.received
+    }
+
+    /// Share of finished tiles, 0 to 1.
+    pub fn progress(&self) -> f64 {
+        if self.total == 0 {
+            return 1.0;
+        }
+        self.received as f64 / self.total as f64
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.received == self.total
+    }
+
+    /// The next finished tile if there is one, without blocking.
+    pub fn try_next(&mut self) -> Option<Tile<T>> {
+        let tile = self.tiles.try_recv().ok()?;
+        self.received += 1;
+        Some(tile)
+    }
+
+    /// Blocks until all tiles are done and puts them together row-major (`y * width + x`),
+    /// `None` if the job was cancelled before or failed.
+    pub fn wait(mut self) -> Option<Vec<T>>
+    where
+        T: Clone + Default,
+    {
+        let w = self.width;
+        let mut values = vec![T::default(); w * self.height];
+        for tile in &mut self {
+            let r = tile.rect;
+            for (k, v) in tile.pixels.into_iter().enumerate() {
+                values[(r.y + k % r.height) * w + r.x + k / r.height] = v;
+            }
+        }
+        if !self.is_finished() {
+            return None;
+        }
+        Some(values)
+    }
+}
+
+impl<T> Iterator for TileJob<T> {
+    type Item = Tile<T>;
+
+    /// Blocks until the next tile is done, `None` once all tiles are in or the job was cancelled
+    /// or failed.
+    fn next(&mut self) -> Option<Tile<T>> {
+        if self.is_finished() {
+            return None;
+        }
+        let tile = self.tiles.recv().ok()?;
+        self.received += 1;
+        Some(tile)
+    }
+}
+
+impl<T> Drop for TileJob<T> {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+impl RenderJob {
+    /// Skips all tiles that have not been started yet.
+    pub fn cancel(&self) {
+        self.tiles.cancel();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.tiles.is_cancelled()
+    }
+
+    /// Whether rendering a tile panicked, see [`TileJob::is_failed`].
+    pub fn is_failed(&self) -> bool {
+        self.tiles.is_failed()
+    }
+
+    /// Tiles copied into the frame so far.
+    pub fn finished_tiles(&self) -> usize {
+        self.tiles.finished_tiles()
+    }
+
+    /// Share of finished tiles, 0 to 1.
+    pub fn progress(&self) -> f64 {
+        self.tiles.progress()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.tiles.is_finished()
+    }
+
+    /// The frame with all tiles received so far, the others are still transparent.
+    pub fn frame(&self) -> &VirtualCanvas {
+        &self.frame
+    }
+
+    /// Copies the tiles that finished since the last call into the frame without blocking and
+    /// returns where they are.
+    pub fn poll(&mut self) -> Vec<TileRect> {
+        let mut rects = Vec::new();
+        while let Some(tile) = self.tiles.try_next() {
+            rects.push(tile.rect);
+            self.insert(tile);
+        }
+        rects
+    }
+
+    /// Blocks until the next tile is done, `None` once all tiles are in or the job was cancelled
+    /// or failed.
+    pub fn next_tile(&mut self) -> Option<TileRect> {
+        let tile = self.tiles.next()?;
+        let rect = tile.rect;
+        self.insert(tile);
+        Some(rect)
+    }
+
+    /// Blocks until the frame is complete, `None` if the job was cancelled before or failed.
+    pub fn wait(mut self) -> Option<VirtualCanvas> {
+        while self.next_tile().is_some() {}
+        if !self.is_finished() {
+            return None;
+        }
+        Some(self.frame)
+    }
+
+    fn insert(&mut self, tile: Tile) {
+        let r = tile.rect;
+        for x in 0..r.width {
+            for y in 0..r.height {
+                self.frame.draw_sdl2_color(r.x + x, r.y + y, tile.pixels[x * r.height + y]);
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::engine::utils::radiance::Radiance;
+use crate::engine::utils::virtual_canvas::VirtualCanvas;
+
+/// Running per-pixel sum of path traced frames. Every frame adds one more estimate per
+/// pixel, the average converges towards the noise-free image.
+#[derive(Debug, Clone)]
+pub struct Accumulator {
+    pub width: usize,
+    pub height: usize,
+    pub sum: Vec<Radiance>,
+    pub frames: u32,
+}
+
+impl Accumulator {
+    pub fn new(width: usize, height: usize) -> Self {
+        Accumulator {
+            width,
+            height,
+            sum: vec![Radiance::black(); width * height],
+            frames: 0,
+        }
+    }
+
+    /// Drops all accumulated frames, e.g. after the camera or the scene changed.
+    pub fn reset(&mut self) {
+        self.sum.iter_mut().for_each(|s| *s = Radiance::black());
+        self.frames = 0;
+    }
+
+    /// Adds one frame in row-major order (`y * width + x`).
+    pub fn add_frame(&mut self, frame: &[Radiance]) {
+        assert_eq!(frame.len(), self.sum.len(), "frame size does not match the accumulator");
+        for (s, f) in self.sum.iter_mut().zip(frame.iter()) {
+            *s += *f;
+        }
+        self.frames += 1;
+    }
+
+    /// Average of all frames at `(x, y)`.
+    pub fn get(&self, x: usize, y: usize) -> Radiance {
+        if self.frames == 0 {
+            return Radiance::black();
+        }
+        self.sum[y * self.width + x] / self.frames as f64
+    }
+
+    pub fn to_canvas(&self, target: &mut VirtualCanvas) {
+        for y in 0..usize::min(self.height, target.height) {
+            for x in 0..usize::min(self.width, target.width) {
+                target.draw_sdl2_color(x, y, self.get(x, y).to_color());
+            }
+        }
+    }
+}
@@ -5,8 +5,9 @@ use crate::{engine::utils::{rendering::{RayRenderScene, RayRenderable}, transfor
 use crate::engine::lighting::Material;
 use crate::geometry::vector3::Vector3 as V3;
 
-use super::{rendering::RaySphereable, rendering_ui::UiElement};
+use super::{rendering::RaySphereable, rendering_ui::UiElement, virtual_canvas::VirtualCanvas};
 
+#[derive(Clone)]
 pub struct AnkerLabel {
     pub text: String,
     pub sphere: Sphere,
@@ -104,7 +105,7 @@ impl AnkerLabel {
 
         AnkerLabel {
             text: text_,
-            sphere: Sphere::new(V3{x: x_,y: y_,z: z_}, 0.005, Material{ color: Color::YELLOW, diffuse: 1.0 }),
+            sphere: Sphere::new(V3{x: x_,y: y_,z: z_}, 0.005, Material::new(Color::YELLOW, 1.0)),
             visible: true,
             texture: texture,
             texture_size: ((max_x - min_x) as u32, (max_y - min_y) as u32),
@@ -119,11 +120,11 @@ impl RaySphereable for AnkerLabel {
     }
     
     fn get_radius(&self) -> f64 {
-        todo!()
+        self.sphere.get_radius()
     }
     
     fn get_middle(&self) -> V3 {
-        todo!()
+        self.sphere.get_middle()
     }
 }
 
@@ -138,6 +139,19 @@ impl UiElement for AnkerLabel {
             }
         }
     }
+
+    fn render_to(&self, canvas: &mut VirtualCanvas, x: i32, y: i32) {
+        let w = self.texture_size.0;
+        for i in 0..self.texture_size.1 {
+            for j in 0..self.texture_size.0 {
+                let (px, py) = (j as i32 + x, i as i32 + y);
+                if px < 0 || py < 0 || px as usize >= canvas.width || py as usize >= canvas.height {
+                    continue;
+                }
+                canvas.draw_sdl2_color(px as usize, py as usize, self.texture[(i * w + j) as usize]);
+            }
+        }
+    }
 }
 
 impl Transformable for AnkerLabel {
@@ -0,0 +1,146 @@
+use sdl2::pixels::Color;
+
+use crate::engine::utils::random::Rng;
+
+/// Reconstruction filter that weights the samples of a pixel by their offset from its center,
+/// in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filter {
+    /// Equal weights over the pixel.
+    Box,
+    /// Linear falloff, reaches one pixel into the neighbors.
+    Tent,
+    /// Mitchell-Netravali with B = C = 1/3, reaches two pixels into the neighbors. Its negative
+    /// lobes sharpen edges a little.
+    Mitchell,
+}
+
+impl Filter {
+    /// Half width of the filter in pixels.
+    pub fn radius(&self) -> f64 {
+        match self {
+            Filter::Box => 0.5,
+            Filter::Tent => 1.0,
+            Filter::Mitchell => 2.0,
+        }
+    }
+
+    /// Weight of a sample at offset `(dx, dy)` from the pixel center.
+    pub fn weight(&self, dx: f64, dy: f64) -> f64 {
+        self.weight_1d(dx) * self.weight_1d(dy)
+    }
+
+    fn weight_1d(&self, x: f64) -> f64 {
+        let x = x.abs();
+        match self {
+            Filter::Box => if x <= 0.5 { 1.0 } else { 0.0 },
+            Filter::Tent => f64::max(0.0, 1.0 - x),
+            Filter::Mitchell => {
+                let (b, c) = (1.0 / 3.0, 1.0 / 3.0);
+                if x < 1.0 {
+                    ((12.0 - 9.0 * b - 6.0 * c) * x * x * x + (-18.0 + 12.0 * b + 6.0 * c) * x * x + (6.0 - 2.0 * b)) / 6.0
+                } else if x < 2.0 {
+                    ((-b - 6.0 * c) * x * x * x + (6.0 * b + 30.0 * c) * x * x + (-12.0 * b - 48.0 * c) * x + (8.0 * b + 24.0 * c)) / 6.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// How many rays the ray camera shoots per pixel. The samples cover the footprint of the
+/// filter on an `grid` x `grid` raster, optionally jittered inside their cells.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Supersampling {
+    /// Samples per side, 1 without jitter is a single ray through the center of the pixel.
+    pub grid: usize,
+    pub jitter: bool,
+    pub filter: Filter,
+    /// Only pixels that differ from one of their neighbors by more than this (0 to 1, per
+    /// channel) get the full grid, all others keep their single center ray.
+    pub adaptive: Option<f64>,
+    /// Changes the jitter, renders with different seeds can be averaged.
+    pub seed: u64,
+}
+
+impl Supersampling {
+    pub fn none() -> Self {
+        Supersampling { grid: 1, jitter: false, filter: Filter::Box, adaptive: None, seed: 0 }
+    }
+
+    /// Regular `n` x `n` grid.
+    pub fn grid(n: usize) -> Self {
+        Supersampling { grid: n.max(1), jitter: false, filter: Filter::Box, adaptive: None, seed: 0 }
+    }
+
+    /// Stratified `n` x `n` grid with one random sample per cell.
+    pub fn jittered(n: usize) -> Self {
+        Supersampling { grid: n.max(1), jitter: true, filter: Filter::Box, adaptive: None, seed: 0 }
+    }
+
+    pub fn set_filter(&mut self, filter: Filter) {
+        self.filter = filter;
+    }
+
+    pub fn set_adaptive(&mut self, threshold: f64) {
+        self.adaptive = Some(threshold);
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    /// False for a single ray through the pixel center.
+    pub fn is_enabled(&self) -> bool {
+        self.grid > 1 || self.jitter
+    }
+
+    /// Sample offsets from the pixel center and their filter weights. `rng` is only used with
+    /// jitter.
+    pub fn samples(&self, rng: &mut Rng) -> Vec<(f64, f64, f64)> {
+        let n = self.grid.max(1);
+        let r = self.filter.radius();
+        let mut samples = Vec::with_capacity(n * n);
+        for a in 0..n {
+            for b in 0..n {
+                let (u, v) = if self.jitter { (rng.next_f64(), rng.next_f64()) } else { (0.5, 0.5) };
+                let dx = ((a as f64 + u) / n as f64 * 2.0 - 1.0) * r;
+                let dy = ((b as f64 + v) / n as f64 * 2.0 - 1.0) * r;
+                samples.push((dx, dy, self.filter.weight(dx, dy)));
+            }
+        }
+        samples
+    }
+}
+
+impl Default for Supersampling {
+    fn default() -> Self {
+        Supersampling::none()
+    }
+}
+
+/// Pixels of a column-major (`x * height + y`) image that differ from one of their four
+/// neighbors by more than `threshold` in any channel, as `(x, y)`.
+pub fn edge_pixels(pixels: &[Color], w: usize, h: usize, threshold: f64) -> Vec<(usize, usize)> {
+    let limit = threshold * 255.0;
+    let differs = |a: Color, b: Color| {
+        let d = |x: u8, y: u8| (x as f64 - y as f64).abs();
+        d(a.r, b.r) > limit || d(a.g, b.g) > limit || d(a.b, b.b) > limit
+    };
+
+    let mut edges = Vec::new();
+    for j in 0..w {
+        for i in 0..h {
+            let c = pixels[j * h + i];
+            let edge = (j > 0 && differs(c, pixels[(j - 1) * h + i]))
+                || (j + 1 < w && differs(c, pixels[(j + 1) * h + i]))
+                || (i > 0 && differs(c, pixels[j * h + i - 1]))
+                || (i + 1 < h && differs(c, pixels[j * h + i + 1]));
+            if edge {
+                edges.push((j, i));
+            }
+        }
+    }
+    edges
+}
@@ -0,0 +1,208 @@
+use std::path::{Path, PathBuf};
+
+use image::ImageResult;
+use sdl2::pixels::Color;
+
+use crate::engine::utils::hdr::HdrBuffer;
+use crate::engine::utils::radiance::Radiance;
+use crate::engine::utils::random::hash_u64;
+use crate::engine::utils::rendering::SurfaceHit;
+use crate::engine::utils::virtual_canvas::VirtualCanvas;
+use crate::geometry::vector3::Vector3 as V3;
+
+/// Which auxiliary buffers a render fills next to the color image.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct AovSelection {
+    /// Distance of the hit along the view direction of the camera.
+    pub depth: bool,
+    pub normal: bool,
+    pub albedo: bool,
+    pub object_id: bool,
+    pub position: bool,
+}
+
+impl AovSelection {
+    pub fn none() -> Self {
+        AovSelection::default()
+    }
+
+    pub fn all() -> Self {
+        AovSelection { depth: true, normal: true, albedo: true, object_id: true, position: true }
+    }
+}
+
+/// Color image and the selected auxiliary outputs (AOVs) of one render, all row-major
+/// (`y * width + x`). Pixels whose center ray hits nothing hold infinite depth, no object and
+/// zero in the other buffers.
+#[derive(Debug, Clone)]
+pub struct AovBuffers {
+    pub width: usize,
+    pub height: usize,
+    pub color: VirtualCanvas,
+    pub depth: Option<Vec<f64>>,
+    pub normal: Option<Vec<V3>>,
+    pub albedo: Option<Vec<Color>>,
+    pub object_id: Option<Vec<Option<usize>>>,
+    pub position: Option<Vec<V3>>,
+}
+
+impl AovBuffers {
+    pub fn new(width: usize, height: usize, selection: AovSelection) -> Self {
+        let n = width * height;
+        let zero = V3::new(0.0, 0.0, 0.0);
+        AovBuffers {
+            width,
+            height,
+            color: VirtualCanvas::new(width, height),
+            depth: selection.depth.then(|| vec![f64::INFINITY; n]),
+            normal: selection.normal.then(|| vec![zero; n]),
+            albedo: selection.albedo.then(|| vec![Color::BLACK; n]),
+            object_id: selection.object_id.then(|| vec![None; n]),
+            position: selection.position.then(|| vec![zero; n]),
+        }
+    }
+
+    pub fn selection(&self) -> AovSelection {
+        AovSelection {
+            depth: self.depth.is_some(),
+            normal: self.normal.is_some(),
+            albedo: self.albedo.is_some(),
+            object_id: self.object_id.is_some(),
+            position: self.position.is_some(),
+        }
+    }
+
+    /// Stores `hit` at (x, y) in every selected buffer, `depth` is its linear depth.
+    pub fn set(&mut self, x: usize, y: usize, hit: Option<SurfaceHit>, depth: f64) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let k = y * self.width + x;
+        let zero = V3::new(0.0, 0.0, 0.0);
+        if let Some(buffer) = self.depth.as_mut() {
+            buffer[k] = if hit.is_some() { depth } else { f64::INFINITY };
+        }
+        if let Some(buffer) = self.normal.as_mut() {
+            buffer[k] = hit.and_then(|h| h.normal).unwrap_or(zero);
+        }
+        if let Some(buffer) = self.albedo.as_mut() {
+            buffer[k] = hit.map_or(Color::BLACK, |h| h.albedo);
+        }
+        if let Some(buffer) = self.object_id.as_mut() {
+            buffer[k] = hit.map(|h| h.object);
+        }
+        if let Some(buffer) = self.position.as_mut() {
+            buffer[k] = hit.map_or(zero, |h| h.p);
+        }
+    }
+
+    /// Depth as gray levels, white at the nearest hit and dark at the farthest, black where
+    /// nothing was hit.
+    pub fn depth_canvas(&self) -> Option<VirtualCanvas> {
+        let depth = self.depth.as_ref()?;
+        let finite = depth.iter().copied().filter(|d| d.is_finite());
+        let near = finite.clone().fold(f64::INFINITY, f64::min);
+        let far = finite.fold(f64::NEG_INFINITY, f64::max);
+        let range = if far > near { far - near } else { 1.0 };
+        Some(self.canvas(|k| {
+            let d = depth[k];
+            if !d.is_finite() {
+                return Color::BLACK;
+            }
+            let v = (255.0 - 223.0 * (d - near) / range).round() as u8;
+            Color::RGB(v, v, v)
+        }))
+    }
+
+    /// Normals mapped from -1..1 to 0..255 per axis, the usual normal map encoding.
+    pub fn normal_canvas(&self) -> Option<VirtualCanvas> {
+        let normal = self.normal.as_ref()?;
+        Some(self.canvas(|k| Radiance::new(normal[k].x * 0.5 + 0.5, normal[k].y * 0.5 + 0.5, normal[k].z * 0.5 + 0.5).to_color()))
+    }
+
+    pub fn albedo_canvas(&self) -> Option<VirtualCanvas> {
+        let albedo = self.albedo.as_ref()?;
+        Some(self.canvas(|k| albedo[k]))
+    }
+
+    /// Every object in its own arbitrary but stable color, black where nothing was hit.
+    pub fn object_id_canvas(&self) -> Option<VirtualCanvas> {
+        let object_id = self.object_id.as_ref()?;
+        Some(self.canvas(|k| match object_id[k] {
+            Some(id) => {
+                let h = hash_u64(id as u64 + 1);
+                // keep every id clearly brighter than the background
+                Color::RGB(64 + (h & 0xbf) as u8, 64 + ((h >> 8) & 0xbf) as u8, 64 + ((h >> 16) & 0xbf) as u8)
+            }
+            None => Color::BLACK,
+        }))
+    }
+
+    /// Linear depth in every channel, unscaled.
+    pub fn depth_hdr(&self) -> Option<HdrBuffer> {
+        let depth = self.depth.as_ref()?;
+        Some(self.hdr(|k| Radiance::gray(depth[k])))
+    }
+
+    /// World space normals, unscaled.
+    pub fn normal_hdr(&self) -> Option<HdrBuffer> {
+        let normal = self.normal.as_ref()?;
+        Some(self.hdr(|k| Radiance::new(normal[k].x, normal[k].y, normal[k].z)))
+    }
+
+    /// World space hit positions.
+    pub fn position_hdr(&self) -> Option<HdrBuffer> {
+        let position = self.position.as_ref()?;
+        Some(self.hdr(|k| Radiance::new(position[k].x, position[k].y, position[k].z)))
+    }
+
+    /// Writes the color image and every selected buffer to `dir` as separate files named
+    /// `<name>_<pass>`: PNG previews of all passes, plus float OpenEXR for depth, normal and
+    /// position. Returns the written paths.
+    pub fn save<P: AsRef<Path>>(&self, dir: P, name: &str) -> ImageResult<Vec<PathBuf>> {
+        let dir = dir.as_ref();
+        let mut written = Vec::new();
+        let mut png = |pass: &str, canvas: Option<VirtualCanvas>| -> ImageResult<()> {
+            if let Some(canvas) = canvas {
+                let path = dir.join(format!("{}_{}.png", name, pass));
+                canvas.save_png(&path)?;
+                written.push(path);
+            }
+            Ok(())
+        };
+        png("color", Some(self.color.clone()))?;
+        png("depth", self.depth_canvas())?;
+        png("normal", self.normal_canvas())?;
+        png("albedo", self.albedo_canvas())?;
+        png("object_id", self.object_id_canvas())?;
+
+        for (pass, buffer) in [("depth", self.depth_hdr()), ("normal", self.normal_hdr()), ("position", self.position_hdr())] {
+            if let Some(buffer) = buffer {
+                let path = dir.join(format!("{}_{}.exr", name, pass));
+                buffer.save_exr(&path)?;
+                written.push(path);
+            }
+        }
+        Ok(written)
+    }
+
+    fn canvas<F: Fn(usize) -> Color>(&self, f: F) -> VirtualCanvas {
+        let mut canvas = VirtualCanvas::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                canvas.draw_sdl2_color(x, y, f(y * self.width + x));
+            }
+        }
+        canvas
+    }
+
+    fn hdr<F: Fn(usize) -> Radiance>(&self, f: F) -> HdrBuffer {
+        let mut buffer = HdrBuffer::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                buffer.set(x, y, f(y * self.width + x));
+            }
+        }
+        buffer
+    }
+}
@@ -0,0 +1,303 @@
+use std::thread;
+
+use sdl2::pixels::Color;
+
+use crate::engine::utils::aov::AovBuffers;
+use crate::engine::utils::hdr::HdrBuffer;
+use crate::engine::utils::radiance::Radiance;
+use crate::engine::utils::virtual_canvas::VirtualCanvas;
+use crate::geometry::vector3::Vector3 as V3;
+
+/// 1D weights of the B3 spline the wavelet filter is built from, for offsets 0, 1 and 2.
+const KERNEL: [f64; 3] = [3.0 / 8.0, 1.0 / 4.0, 1.0 / 16.0];
+
+/// Edge-avoiding à-trous wavelet filter (Dammertz et al.) for images with few samples per
+/// pixel. Every iteration blurs with a 5 x 5 kernel whose taps are twice as far apart as in the
+/// previous one, and each tap is weighted down where the guide buffers of an [`AovBuffers`]
+/// show an edge: another object, a bend in the normals, a jump in depth or a different color.
+///
+/// With `demodulate_albedo` the lighting is filtered on its own and multiplied with the
+/// albedo afterwards, so textures and color edges stay sharp while shadows get smoothed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Denoiser {
+    /// Each iteration doubles the reach, 3 iterations reach 14 pixels.
+    pub iterations: usize,
+    /// How much the colors of two pixels may differ before they stop being mixed. Halves with
+    /// every iteration, so the wide passes leave remaining edges alone.
+    pub sigma_color: f64,
+    /// Exponent on the cosine between two normals, higher keeps creases sharper.
+    pub sigma_normal: f64,
+    /// Tolerated depth difference in multiples of the change expected from the slope of the
+    /// surface.
+    pub sigma_depth: f64,
+    pub demodulate_albedo: bool,
+    pub threads: usize,
+}
+
+impl Denoiser {
+    pub fn new() -> Self {
+        Denoiser {
+            iterations: 3,
+            sigma_color: 2.0,
+            sigma_normal: 64.0,
+            sigma_depth: 1.0,
+            demodulate_albedo: true,
+            threads: 1,
+        }
+    }
+
+    pub fn set_iterations(&mut self, iterations: usize) {
+        self.iterations = iterations;
+    }
+
+    pub fn set_sigma_color(&mut self, sigma: f64) {
+        self.sigma_color = sigma;
+    }
+
+    pub fn set_sigma_normal(&mut self, sigma: f64) {
+        self.sigma_normal = sigma;
+    }
+
+    pub fn set_sigma_depth(&mut self, sigma: f64) {
+        self.sigma_depth = sigma;
+    }
+
+    pub fn set_demodulate_albedo(&mut self, demodulate: bool) {
+        self.demodulate_albedo = demodulate;
+    }
+
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    /// Filtered copy of `color`. `guide` must have the same size, buffers it does not have are
+    /// not used as edges. Pixels where the guide saw no surface are copied unchanged.
+    pub fn denoise(&self, color: &HdrBuffer, guide: &AovBuffers) -> HdrBuffer {
+        assert!(color.width == guide.width && color.height == guide.height, "guide buffers do not match the image");
+        let (w, h) = (color.width, color.height);
+
+        let hit: Vec<bool> = (0..w * h)
+            .map(|k| match (&guide.depth, &guide.object_id) {
+                (Some(depth), _) => depth[k].is_finite(),
+                (None, Some(object_id)) => object_id[k].is_some(),
+                (None, None) => true,
+            })
+            .collect();
+        let albedo: Vec<Radiance> = (0..w * h)
+            .map(|k| match &guide.albedo {
+                Some(albedo) if self.demodulate_albedo => albedo_factor(albedo[k]),
+                _ => Radiance::gray(1.0),
+            })
+            .collect();
+        // the lighting of black albedo channels is unknown, it must not spread to neighbors
+        let known: Vec<[bool; 3]> = (0..w * h)
+            .map(|k| match &guide.albedo {
+                Some(albedo) if self.demodulate_albedo => [albedo[k].r > 0, albedo[k].g > 0, albedo[k].b > 0],
+                _ => [true; 3],
+            })
+            .collect();
+        let gradient = guide.depth.as_ref().map(|depth| depth_gradient(depth, w, h));
+
+        let mut current: Vec<Radiance> = color.pixels.iter().zip(albedo.iter()).map(|(&c, &a)| c / a).collect();
+        for iteration in 0..self.iterations {
+            let pass = Pass {
+                denoiser: self,
+                guide,
+                hit: &hit,
+                known: &known,
+                gradient: gradient.as_deref(),
+                input: &current,
+                step: 1 << iteration,
+                sigma_color: self.sigma_color / (1 << iteration) as f64,
+            };
+            current = pass.run(self.threads);
+        }
+
+        let mut result = color.clone();
+        for k in 0..w * h {
+            if hit[k] {
+                let filtered = channels(current[k] * albedo[k]);
+                let original = channels(color.pixels[k]);
+                let pick = |c: usize| if known[k][c] { filtered[c] } else { original[c] };
+                result.pixels[k] = Radiance::new(pick(0), pick(1), pick(2));
+            }
+        }
+        result
+    }
+
+    /// [`Denoiser::denoise`] for an 8 bit image, like the frames of the ray camera.
+    pub fn denoise_canvas(&self, canvas: &VirtualCanvas, guide: &AovBuffers) -> VirtualCanvas {
+        let mut color = HdrBuffer::new(canvas.width, canvas.height);
+        for y in 0..canvas.height {
+            for x in 0..canvas.width {
+                color.set(x, y, Radiance::from_color(canvas.get_sdl2_color(x, y)));
+            }
+        }
+        let filtered = self.denoise(&color, guide);
+
+        let mut result = VirtualCanvas::new(canvas.width, canvas.height);
+        for y in 0..canvas.height {
+            for x in 0..canvas.width {
+                result.draw_sdl2_color(x, y, filtered.get(x, y).to_color());
+            }
+        }
+        result
+    }
+}
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Denoiser::new()
+    }
+}
+
+/// One iteration of the filter.
+struct Pass<'a> {
+    denoiser: &'a Denoiser,
+    guide: &'a AovBuffers,
+    hit: &'a [bool],
+    known: &'a [[bool; 3]],
+    gradient: Option<&'a [(f64, f64)]>,
+    input: &'a [Radiance],
+    step: usize,
+    sigma_color: f64,
+}
+
+impl Pass<'_> {
+    /// Filters all rows, split into bands over `threads` threads.
+    fn run(&self, threads: usize) -> Vec<Radiance> {
+        let (w, h) = (self.guide.width, self.guide.height);
+        let mut output = vec![Radiance::black(); w * h];
+        if w == 0 || h == 0 {
+            return output;
+        }
+        let rows = h.div_ceil(threads.max(1));
+        thread::scope(|scope| {
+            for (band, chunk) in output.chunks_mut(rows * w).enumerate() {
+                scope.spawn(move || {
+                    for (offset, out) in chunk.iter_mut().enumerate() {
+                        let k = band * rows * w + offset;
+                        *out = self.filter(k % w, k / w);
+                    }
+                });
+            }
+        });
+        output
+    }
+
+    fn filter(&self, x: usize, y: usize) -> Radiance {
+        let w = self.guide.width;
+        let p = y * w + x;
+        if !self.hit[p] {
+            return self.input[p];
+        }
+
+        let mut sum = [0.0; 3];
+        let mut weights = [0.0; 3];
+        for dy in -2i64..=2 {
+            for dx in -2i64..=2 {
+                let qx = x as i64 + dx * self.step as i64;
+                let qy = y as i64 + dy * self.step as i64;
+                if qx < 0 || qy < 0 || qx >= w as i64 || qy >= self.guide.height as i64 {
+                    continue;
+                }
+                let q = qy as usize * w + qx as usize;
+                if !self.hit[q] {
+                    continue;
+                }
+                let kernel = KERNEL[dx.unsigned_abs() as usize] * KERNEL[dy.unsigned_abs() as usize];
+                let weight = kernel * self.edge_weight(p, q, (dx * self.step as i64) as f64, (dy * self.step as i64) as f64);
+                let value = channels(self.input[q]);
+                for c in 0..3 {
+                    if self.known[q][c] {
+                        sum[c] += value[c] * weight;
+                        weights[c] += weight;
+                    }
+                }
+            }
+        }
+
+        let own = channels(self.input[p]);
+        let channel = |c: usize| if weights[c] > 0.0 { sum[c] / weights[c] } else { own[c] };
+        Radiance::new(channel(0), channel(1), channel(2))
+    }
+
+    /// How much pixel `q` at offset `(dx, dy)` may contribute to pixel `p`, 1 where no guide
+    /// buffer shows an edge between them.
+    fn edge_weight(&self, p: usize, q: usize, dx: f64, dy: f64) -> f64 {
+        if let Some(object_id) = &self.guide.object_id {
+            if object_id[p] != object_id[q] {
+                return 0.0;
+            }
+        }
+
+        let mut weight = 1.0;
+        if let Some(normal) = &self.guide.normal {
+            let (np, nq) = (normal[p], normal[q]);
+            // objects without normals leave zero vectors
+            if !is_zero(np) && !is_zero(nq) {
+                weight *= f64::max(0.0, np.dt(nq)).powf(self.denoiser.sigma_normal);
+            }
+        }
+        if let (Some(depth), Some(gradient)) = (&self.guide.depth, self.gradient) {
+            let (gx, gy) = gradient[p];
+            let expected = (gx * dx).abs() + (gy * dy).abs();
+            let tolerance = self.denoiser.sigma_depth * expected + 1e-4 * depth[p].abs() + 1e-9;
+            weight *= f64::exp(-(depth[p] - depth[q]).abs() / tolerance);
+        }
+        if self.sigma_color > 0.0 {
+            let (a, b) = (channels(self.input[p]), channels(self.input[q]));
+            let distance: f64 = (0..3)
+                .filter(|&c| self.known[p][c] && self.known[q][c])
+                .map(|c| (a[c] - b[c]) * (a[c] - b[c]))
+                .sum();
+            weight *= f64::exp(-distance / (self.sigma_color * self.sigma_color));
+        }
+        weight
+    }
+}
+
+fn is_zero(v: V3) -> bool {
+    v.x == 0.0 && v.y == 0.0 && v.z == 0.0
+}
+
+fn channels(c: Radiance) -> [f64; 3] {
+    [c.r, c.g, c.b]
+}
+
+/// Albedo to divide the lighting by, black channels are left as they are.
+fn albedo_factor(c: Color) -> Radiance {
+    let channel = |v: u8| if v == 0 { 1.0 } else { v as f64 / 255.0 };
+    Radiance::new(channel(c.r), channel(c.g), channel(c.b))
+}
+
+/// Change of depth per pixel in x and y. Takes the smaller one-sided difference, so pixels
+/// on the outline of an object do not pick up the jump to the background.
+fn depth_gradient(depth: &[f64], w: usize, h: usize) -> Vec<(f64, f64)> {
+    let slope = |d: f64, a: Option<f64>, b: Option<f64>| {
+        [a, b]
+            .iter()
+            .flatten()
+            .filter(|n| n.is_finite())
+            .map(|n| (n - d).abs())
+            .fold(None, |m: Option<f64>, v| Some(m.map_or(v, |m| m.min(v))))
+            .unwrap_or(0.0)
+    };
+
+    let mut gradient = Vec::with_capacity(w * h);
+    for y in 0..h {
+        for x in 0..w {
+            let d = depth[y * w + x];
+            if !d.is_finite() {
+                gradient.push((0.0, 0.0));
+                continue;
+            }
+            let left = (x > 0).then(|| depth[y * w + x - 1]);
+            let right = (x + 1 < w).then(|| depth[y * w + x + 1]);
+            let up = (y > 0).then(|| depth[(y - 1) * w + x]);
+            let down = (y + 1 < h).then(|| depth[(y + 1) * w + x]);
+            gradient.push((slope(d, left, right), slope(d, up, down)));
+        }
+    }
+    gradient
+}
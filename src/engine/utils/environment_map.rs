@@ -0,0 +1,93 @@
+use std::f64::consts::PI;
+use std::path::Path;
+
+use image::ImageResult;
+
+use crate::engine::utils::radiance::Radiance;
+use crate::geometry::vector3::Vector3 as V3;
+
+/// Light arriving from infinitely far away, stored as an equirectangular (latitude/longitude)
+/// image. The top row is straight up (-y), the center column looks along +x.
+#[derive(Debug, Clone)]
+pub struct EnvironmentMap {
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<Radiance>,
+    pub intensity: f64,
+    /// Rotation around the vertical axis in radians.
+    pub rotation: f64,
+    average: Radiance,
+}
+
+impl EnvironmentMap {
+    /// Map from row-major linear radiance values.
+    pub fn new(width: usize, height: usize, data: Vec<Radiance>) -> Self {
+        assert_eq!(data.len(), width * height, "environment map size does not match its data");
+        let average = EnvironmentMap::weighted_average(width, height, &data);
+        EnvironmentMap { width, height, data, intensity: 1.0, rotation: 0.0, average }
+    }
+
+    /// The same radiance from every direction.
+    pub fn uniform(radiance: Radiance) -> Self {
+        EnvironmentMap::new(1, 1, vec![radiance])
+    }
+
+    /// Loads any format `image` can read. HDR formats (`.hdr`, `.exr`) keep their range,
+    /// 8 bit formats are taken like every other 8 bit color of the renderers, see
+    /// [`Radiance::from_color`], so the background shows the colors of the image.
+    pub fn load<P: AsRef<Path>>(path: P) -> ImageResult<Self> {
+        let rgb = image::open(path)?.into_rgb32f();
+        let (w, h) = (rgb.width() as usize, rgb.height() as usize);
+        let data = rgb
+            .pixels()
+            .map(|p| Radiance::new(p[0] as f64, p[1] as f64, p[2] as f64))
+            .collect();
+        Ok(EnvironmentMap::new(w, h, data))
+    }
+
+    pub fn set_intensity(&mut self, intensity: f64) {
+        self.intensity = intensity;
+    }
+
+    pub fn set_rotation(&mut self, rotation: f64) {
+        self.rotation = rotation;
+    }
+
+    /// Radiance arriving from direction `dir` (pointing away from the scene).
+    pub fn lookup(&self, dir: V3) -> Radiance {
+        let mut d = dir;
+        d.normalize();
+
+        let theta = f64::acos(f64::clamp(-d.y, -1.0, 1.0));
+        let phi = f64::atan2(-d.z, d.x) + self.rotation;
+        let u = 0.5 + phi / (2.0 * PI);
+        let v = theta / PI;
+
+        let x = ((u * self.width as f64).floor() as i64).rem_euclid(self.width as i64) as usize;
+        let y = usize::min((v * self.height as f64) as usize, self.height - 1);
+        self.data[y * self.width + x] * self.intensity
+    }
+
+    /// Mean radiance over the sphere, used as ambient term by the shading that does not
+    /// integrate over directions.
+    pub fn average(&self) -> Radiance {
+        self.average * self.intensity
+    }
+
+    // rows near the poles cover less solid angle
+    fn weighted_average(width: usize, height: usize, data: &[Radiance]) -> Radiance {
+        let mut sum = Radiance::black();
+        let mut weights = 0.0;
+        for y in 0..height {
+            let w = f64::sin(PI * (y as f64 + 0.5) / height as f64);
+            for x in 0..width {
+                sum += data[y * width + x] * w;
+            }
+            weights += w * width as f64;
+        }
+        if weights <= 0.0 {
+            return Radiance::black();
+        }
+        sum / weights
+    }
+}
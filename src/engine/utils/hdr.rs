@@ -0,0 +1,167 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use image::codecs::hdr::HdrEncoder;
+use image::{ImageFormat, ImageResult, Rgb, Rgb32FImage};
+use sdl2::pixels::Color;
+
+use crate::engine::utils::accumulator::Accumulator;
+use crate::engine::utils::radiance::Radiance;
+use crate::engine::utils::virtual_canvas::VirtualCanvas;
+
+/// Curve that maps unbounded radiance to the 0 to 1 range of a display.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToneMapping {
+    /// Cuts everything above 1, like converting straight to `Color`.
+    Clamp,
+    /// `L / (1 + L)` on the luminance, keeps the hue of bright colors.
+    Reinhard,
+    /// Narkowicz's fit of the ACES filmic curve, with a slight toe and a soft shoulder.
+    Aces,
+}
+
+/// Turns linear radiance into display colors: exposure, then the tone curve, then optionally
+/// the sRGB transfer function.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToneMapper {
+    pub operator: ToneMapping,
+    /// In stops, every stop doubles the brightness.
+    pub exposure: f64,
+    /// Encode with the sRGB curve. Off by default: the renderers take 8 bit colors (albedos,
+    /// textures, 8 bit environment maps) as they are, so their radiance is already encoded and
+    /// a second encoding would wash the image out. Only for scenes whose colors are all linear.
+    pub srgb: bool,
+}
+
+/// Linear RGB image, row-major (`y * width + x`). Keeps values above 1, so bright lights and
+/// several lights on one spot survive until tone mapping.
+#[derive(Debug, Clone)]
+pub struct HdrBuffer {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Radiance>,
+}
+
+/// sRGB transfer function, linear 0 to 1 in, encoded 0 to 1 out.
+pub fn srgb_encode(x: f64) -> f64 {
+    let x = x.clamp(0.0, 1.0);
+    if x <= 0.0031308 {
+        12.92 * x
+    } else {
+        1.055 * x.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Inverse of [`srgb_encode`].
+pub fn srgb_decode(x: f64) -> f64 {
+    let x = x.clamp(0.0, 1.0);
+    if x <= 0.04045 {
+        x / 12.92
+    } else {
+        ((x + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+impl ToneMapper {
+    pub fn new(operator: ToneMapping) -> Self {
+        ToneMapper { operator, exposure: 0.0, srgb: false }
+    }
+
+    pub fn set_exposure(&mut self, stops: f64) {
+        self.exposure = stops;
+    }
+
+    pub fn set_srgb(&mut self, srgb: bool) {
+        self.srgb = srgb;
+    }
+
+    /// Display value of `radiance`, every channel in 0 to 1.
+    pub fn map(&self, radiance: Radiance) -> Radiance {
+        let c = radiance * f64::powf(2.0, self.exposure);
+        let c = match self.operator {
+            ToneMapping::Clamp => c,
+            ToneMapping::Reinhard => {
+                let l = c.luminance();
+                if l <= 0.0 { c } else { c * (1.0 / (1.0 + l)) }
+            }
+            ToneMapping::Aces => {
+                let aces = |x: f64| {
+                    let x = x.max(0.0);
+                    (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+                };
+                Radiance::new(aces(c.r), aces(c.g), aces(c.b))
+            }
+        };
+
+        let channel = |x: f64| if self.srgb { srgb_encode(x) } else { x.clamp(0.0, 1.0) };
+        Radiance::new(channel(c.r), channel(c.g), channel(c.b))
+    }
+
+    pub fn to_color(&self, radiance: Radiance) -> Color {
+        self.map(radiance).to_color()
+    }
+}
+
+impl Default for ToneMapper {
+    fn default() -> Self {
+        ToneMapper::new(ToneMapping::Aces)
+    }
+}
+
+impl HdrBuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        HdrBuffer { width, height, pixels: vec![Radiance::black(); width * height] }
+    }
+
+    /// Average of all frames in `acc`.
+    pub fn from_accumulator(acc: &Accumulator) -> Self {
+        let mut buffer = HdrBuffer::new(acc.width, acc.height);
+        for y in 0..acc.height {
+            for x in 0..acc.width {
+                buffer.set(x, y, acc.get(x, y));
+            }
+        }
+        buffer
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Radiance {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, radiance: Radiance) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = radiance;
+        }
+    }
+
+    /// Tone mapped copy for display or 8 bit formats.
+    pub fn to_canvas(&self, tone_mapper: &ToneMapper) -> VirtualCanvas {
+        let mut canvas = VirtualCanvas::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                canvas.draw_sdl2_color(x, y, tone_mapper.to_color(self.get(x, y)));
+            }
+        }
+        canvas
+    }
+
+    pub fn to_rgb32f_image(&self) -> Rgb32FImage {
+        Rgb32FImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            let c = self.get(x as usize, y as usize);
+            Rgb([c.r as f32, c.g as f32, c.b as f32])
+        })
+    }
+
+    /// Writes the raw linear values as Radiance RGBE (`.hdr`).
+    pub fn save_hdr<P: AsRef<Path>>(&self, path: P) -> ImageResult<()> {
+        let file = BufWriter::new(File::create(path)?);
+        let pixels: Vec<Rgb<f32>> = self.to_rgb32f_image().pixels().copied().collect();
+        HdrEncoder::new(file).encode(&pixels, self.width, self.height)
+    }
+
+    /// Writes the raw linear values as 32 bit float OpenEXR (`.exr`).
+    pub fn save_exr<P: AsRef<Path>>(&self, path: P) -> ImageResult<()> {
+        self.to_rgb32f_image().save_with_format(path, ImageFormat::OpenExr)
+    }
+}
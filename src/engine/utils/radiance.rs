@@ -0,0 +1,104 @@
+use std::ops::{Add, AddAssign, Div, Mul, Sub};
+
+use sdl2::pixels::Color;
+
+/// Linear RGB light quantity. 1.0 corresponds to a fully saturated 8 bit channel,
+/// values above 1.0 are valid and get clamped only when converted back to a `Color`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Radiance {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+}
+
+impl Radiance {
+    pub fn new(r: f64, g: f64, b: f64) -> Self {
+        Radiance { r, g, b }
+    }
+
+    pub fn black() -> Self {
+        Radiance::new(0.0, 0.0, 0.0)
+    }
+
+    pub fn gray(v: f64) -> Self {
+        Radiance::new(v, v, v)
+    }
+
+    pub fn from_color(c: Color) -> Self {
+        Radiance::new(c.r as f64 / 255.0, c.g as f64 / 255.0, c.b as f64 / 255.0)
+    }
+
+    /// Clamps to 0 to 1 and rounds to the nearest 8 bit value, so `from_color` and `to_color`
+    /// give back the same color. See `ToneMapper` for a less harsh way to fit bright values.
+    pub fn to_color(self) -> Color {
+        Color::RGB(
+            (self.r.clamp(0.0, 1.0) * 255.0).round() as u8,
+            (self.g.clamp(0.0, 1.0) * 255.0).round() as u8,
+            (self.b.clamp(0.0, 1.0) * 255.0).round() as u8,
+        )
+    }
+
+    pub fn max_component(self) -> f64 {
+        f64::max(self.r, f64::max(self.g, self.b))
+    }
+
+    pub fn luminance(self) -> f64 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
+    pub fn is_black(self) -> bool {
+        self.r <= 0.0 && self.g <= 0.0 && self.b <= 0.0
+    }
+}
+
+impl Add for Radiance {
+    type Output = Radiance;
+    fn add(self, o: Radiance) -> Radiance {
+        Radiance::new(self.r + o.r, self.g + o.g, self.b + o.b)
+    }
+}
+
+impl Sub for Radiance {
+    type Output = Radiance;
+    fn sub(self, o: Radiance) -> Radiance {
+        Radiance::new(self.r - o.r, self.g - o.g, self.b - o.b)
+    }
+}
+
+impl AddAssign for Radiance {
+    fn add_assign(&mut self, o: Radiance) {
+        self.r += o.r;
+        self.g += o.g;
+        self.b += o.b;
+    }
+}
+
+/// Component-wise product, e.g. light color times albedo.
+impl Mul for Radiance {
+    type Output = Radiance;
+    fn mul(self, o: Radiance) -> Radiance {
+        Radiance::new(self.r * o.r, self.g * o.g, self.b * o.b)
+    }
+}
+
+/// Component-wise quotient, e.g. removing the albedo from shaded light.
+impl Div for Radiance {
+    type Output = Radiance;
+    fn div(self, o: Radiance) -> Radiance {
+        Radiance::new(self.r / o.r, self.g / o.g, self.b / o.b)
+    }
+}
+
+impl Mul<f64> for Radiance {
+    type Output = Radiance;
+    fn mul(self, s: f64) -> Radiance {
+        Radiance::new(self.r * s, self.g * s, self.b * s)
+    }
+}
+
+impl Div<f64> for Radiance {
+    type Output = Radiance;
+    fn div(self, s: f64) -> Radiance {
+        Radiance::new(self.r / s, self.g / s, self.b / s)
+    }
+}
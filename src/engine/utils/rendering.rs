@@ -1,9 +1,12 @@
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, OnceLock, RwLock};
 
+use crate::engine::simplex3d_bvh::tlas::Tlas;
+use crate::geometry::aabb::Aabb;
 use crate::geometry::face::UV;
 use crate::geometry::vector3::Vector3 as V3;
 
 use sdl2::pixels::Color;
+use crate::engine::utils::radiance::Radiance;
 use crate::engine::utils::transformation::Transformable;
 use crate::engine::pathtracing::PathtracingObject;
 use crate::engine::raymarching::RayMarchingObject;
@@ -49,6 +52,10 @@ pub trait RaySphereable {
         //is it behind me?
         let mut m = self.get_middle().clone();
         m.subtract(p0);
+        //rays starting inside (e.g. refracted ones) can hit it in any direction
+        if (m.norm() <= self.get_radius()) {
+            return true;
+        }
         let proj = m.dt(p);
         if (proj < 0.0) {
             return false;
@@ -66,49 +73,161 @@ pub trait RaySphereable {
 }
 
 
+/// Geometry and surface of the first hit along a ray, for the auxiliary outputs of the camera.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SurfaceHit {
+    pub d: f64,
+    pub p: V3,
+    /// Unit normal facing the side the ray came from, if the object knows it.
+    pub normal: Option<V3>,
+    /// Base color of the material without any lighting.
+    pub albedo: Color,
+    /// Index of the object that was hit, counted over all objects below the renderable.
+    pub object: usize,
+}
+
 pub trait RayRenderable: Send + Sync {
     fn get_collision(&self, p0: V3, p: V3, radius: f64) -> Collision;
+
+    /// Linear color seen along the ray before it gets clamped into a `Color`. Renderers that
+    /// shade in `Radiance` return values above 1 here.
+    fn get_radiance(&self, p0: V3, p: V3, radius: f64) -> Radiance {
+        Radiance::from_color(self.get_collision(p0, p, radius).c)
+    }
+
+    /// First hit along the ray, `None` on a miss. The default only knows the distance and the
+    /// shaded color.
+    fn get_surface(&self, p0: V3, p: V3, radius: f64) -> Option<SurfaceHit> {
+        let c = self.get_collision(p0, p, radius);
+        if !c.hit {
+            return None;
+        }
+        Some(SurfaceHit { d: c.d, p: c.p, normal: None, albedo: c.c, object: 0 })
+    }
+
+    /// `get_collision` and `get_surface` of the same ray. The default traces it twice, renderers
+    /// that can should shade the surface they found.
+    fn get_collision_with_surface(&self, p0: V3, p: V3, radius: f64) -> (Collision, Option<SurfaceHit>) {
+        (self.get_collision(p0, p, radius), self.get_surface(p0, p, radius))
+    }
+
+    /// Number of objects `get_surface` tells apart.
+    fn object_count(&self) -> usize {
+        1
+    }
+
+    /// Box around everything that can be hit, rays missing it are not traced. The default is
+    /// infinite.
+    fn bounds(&self) -> Aabb {
+        Aabb::infinite()
+    }
 }
 
+/// Several renderables drawn as one, e.g. a ray traced and a ray marched scene. Rays only
+/// test the objects whose bounds they pass through.
 pub struct RayRenderScene {
-    pub objects: Vec<Box<dyn RayRenderable>>
+    objects: Vec<Box<dyn RayRenderable>>,
+    /// Index of the first object of every renderable in `get_surface`.
+    offsets: Vec<usize>,
+    /// Hierarchy over the object bounds, built on the first ray after a change.
+    tlas: OnceLock<Tlas>,
 }
 
 impl RayRenderScene {
     pub fn new() -> Self {
         RayRenderScene {
-            objects: Vec::new()
+            objects: Vec::new(),
+            offsets: Vec::new(),
+            tlas: OnceLock::new(),
         }
     }
 
     pub fn wrap(&mut self, obj: Box<dyn RayRenderable>) {
+        self.offsets.push(self.object_count());
         self.objects.push(obj);
+        self.tlas = OnceLock::new();
     }
 
     pub fn read(&self) -> &dyn RayRenderable {
         self
     }
+
+    pub fn objects(&self) -> &[Box<dyn RayRenderable>] {
+        &self.objects
+    }
+
+    fn tlas(&self) -> &Tlas {
+        self.tlas.get_or_init(|| {
+            let bounds: Vec<Aabb> = self.objects.iter().map(|o| o.bounds()).collect();
+            Tlas::build(&bounds)
+        })
+    }
+
+    /// Index of the object with the closest hit and the hit.
+    fn closest_collision(&self, p0: V3, p: V3, radius: f64) -> Option<(usize, Collision)> {
+        let mut closest: Option<(usize, Collision)> = None;
+        self.tlas().closest(p0, p, |i| {
+            let c = self.objects[i].get_collision(p0, p, radius);
+            if !c.hit {
+                return None;
+            }
+            if closest.is_none_or(|(_, b)| c.d < b.d) {
+                closest = Some((i, c));
+            }
+            Some((c.p - p0).norm())
+        });
+        closest
+    }
 }
 
 impl RayRenderable for RayRenderScene {
     fn get_collision(&self, p0: V3, p: V3, radius: f64) -> Collision {
-        let mut c: Collision = Collision::empty();
-        let mut bd: f64 = f64::MAX;
-        let mut i: usize = 0;
-        let mut bg: (f64, f64) = (0.0, 0.0);
-
-        for po in self.objects.iter() {
-            let c_ = po.get_collision(p0, p, radius);
-            if (c_.hit) {
-                let mut p2 = c_.p.clone();
-                p2.subtract(p0);
-                let d = p2.norm();
-                if (c_.d < bd) {
-                    c = c_;
-                    bd = c_.d;
+        self.closest_collision(p0, p, radius).map_or(Collision::empty(), |(_, c)| c)
+    }
+
+    fn get_radiance(&self, p0: V3, p: V3, radius: f64) -> Radiance {
+        match self.closest_collision(p0, p, radius) {
+            Some((i, _)) => self.objects[i].get_radiance(p0, p, radius),
+            None => Radiance::black(),
+        }
+    }
+
+    fn get_collision_with_surface(&self, p0: V3, p: V3, radius: f64) -> (Collision, Option<SurfaceHit>) {
+        let mut closest: Option<(Collision, Option<SurfaceHit>)> = None;
+        self.tlas().closest(p0, p, |i| {
+            let (c, mut hit) = self.objects[i].get_collision_with_surface(p0, p, radius);
+            if !c.hit {
+                return None;
+            }
+            if closest.is_none_or(|(b, _)| c.d < b.d) {
+                if let Some(hit) = hit.as_mut() {
+                    hit.object += self.offsets[i];
                 }
+                closest = Some((c, hit));
             }
-        }
-        c
+            Some((c.p - p0).norm())
+        });
+        closest.unwrap_or((Collision::empty(), None))
+    }
+
+    fn get_surface(&self, p0: V3, p: V3, radius: f64) -> Option<SurfaceHit> {
+        let mut closest: Option<SurfaceHit> = None;
+        self.tlas().closest(p0, p, |i| {
+            let mut hit = self.objects[i].get_surface(p0, p, radius)?;
+            if closest.is_none_or(|c| hit.d < c.d) {
+                hit.object += self.offsets[i];
+                closest = Some(hit);
+            }
+            Some((hit.p - p0).norm())
+        });
+        closest
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.tlas().bounds()
+    }
+
+    fn object_count(&self) -> usize {
+        self.objects.iter().map(|po| po.object_count()).sum()
     }
 }
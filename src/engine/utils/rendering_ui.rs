@@ -1,5 +1,8 @@
 use sdl2::{render::Canvas, video::Window};
 
+use super::virtual_canvas::VirtualCanvas;
+
 pub trait UiElement {
     fn render(&self, canvas: &mut Canvas<Window>, x: i32, y: i32);
+    fn render_to(&self, canvas: &mut VirtualCanvas, x: i32, y: i32);
 }
@@ -0,0 +1,105 @@
+use crate::engine::utils::random::Rng;
+use crate::geometry::vector3::Vector3 as V3;
+
+/// Two unit vectors perpendicular to `normal` and to each other.
+pub fn tangent_frame(normal: V3) -> (V3, V3) {
+    let mut tangent = if f64::abs(normal.x) < 0.9 {
+        V3::new(1.0, 0.0, 0.0)
+    } else {
+        V3::new(0.0, 1.0, 0.0)
+    };
+    tangent.cross(normal);
+    tangent.normalize();
+
+    let mut bitangent = normal;
+    bitangent.cross(tangent);
+    bitangent.normalize();
+
+    (tangent, bitangent)
+}
+
+/// Cosine-weighted direction on the hemisphere around `normal` (pdf = cos θ / π).
+pub fn sample_cosine_hemisphere(normal: V3, rng: &mut Rng) -> V3 {
+    let (tangent, bitangent) = tangent_frame(normal);
+    let r1 = rng.next_f64();
+    let r2 = rng.next_f64();
+
+    let phi = 2.0 * std::f64::consts::PI * r1;
+    let r = r2.sqrt();
+
+    let mut dir = tangent;
+    dir.scale(r * phi.cos());
+    let mut b = bitangent;
+    b.scale(r * phi.sin());
+    let mut n = normal;
+    n.scale(f64::sqrt(f64::max(1.0 - r2, 0.0)));
+
+    dir.add(b);
+    dir.add(n);
+    dir.normalize();
+    dir
+}
+
+/// Direction in a Phong lobe with exponent `shininess` around `axis`. Samples that end up
+/// below the surface are mirrored back, so the lobe keeps its energy at grazing angles.
+pub fn sample_glossy(axis: V3, normal: V3, shininess: f64, rng: &mut Rng) -> V3 {
+    let (tangent, bitangent) = tangent_frame(axis);
+    let cos_t = f64::powf(rng.next_f64(), 1.0 / (shininess + 1.0));
+    let sin_t = f64::sqrt(f64::max(1.0 - cos_t * cos_t, 0.0));
+    let phi = 2.0 * std::f64::consts::PI * rng.next_f64();
+
+    let mut dir = tangent;
+    dir.scale(sin_t * phi.cos());
+    let mut b = bitangent;
+    b.scale(sin_t * phi.sin());
+    let mut a = axis;
+    a.scale(cos_t);
+    dir.add(b);
+    dir.add(a);
+
+    let below = dir.dt(normal);
+    if below < 0.0 {
+        let mut n = normal;
+        n.scale(2.0 * below);
+        dir.subtract(n);
+    }
+    dir.normalize();
+    dir
+}
+
+/// Direction of the light that a Blinn-Phong lobe with exponent `shininess` reflects towards
+/// `view_dir`, and its pdf in solid angle. The half vector is drawn around `normal` with pdf
+/// (n + 1) / 2π · cos^n θ, the direction can end up below the surface.
+pub fn sample_blinn_phong(normal: V3, view_dir: V3, shininess: f64, rng: &mut Rng) -> (V3, f64) {
+    let (tangent, bitangent) = tangent_frame(normal);
+    let cos_t = f64::powf(rng.next_f64(), 1.0 / (shininess + 1.0));
+    let sin_t = f64::sqrt(f64::max(1.0 - cos_t * cos_t, 0.0));
+    let phi = 2.0 * std::f64::consts::PI * rng.next_f64();
+    let half = tangent * (sin_t * phi.cos()) + bitangent * (sin_t * phi.sin()) + normal * cos_t;
+
+    let vdoth = view_dir.dt(half);
+    let dir = half * (2.0 * vdoth) - view_dir;
+    let pdf_half = (shininess + 1.0) / (2.0 * std::f64::consts::PI) * f64::powf(cos_t, shininess);
+    let pdf = if vdoth > 0.0 { pdf_half / (4.0 * vdoth) } else { 0.0 };
+    (dir.normalized(), pdf)
+}
+
+/// Direction in the cone of half angle `acos(cos_max)` around the unit vector `axis`, uniform
+/// in solid angle for uniform `u` and `v` in [0, 1).
+pub fn sample_cone(axis: V3, cos_max: f64, u: f64, v: f64) -> V3 {
+    let (tangent, bitangent) = tangent_frame(axis);
+    let cos_t = 1.0 - u * (1.0 - cos_max);
+    let sin_t = f64::sqrt(f64::max(1.0 - cos_t * cos_t, 0.0));
+    let phi = 2.0 * std::f64::consts::PI * v;
+
+    let mut dir = tangent;
+    dir.scale(sin_t * phi.cos());
+    let mut b = bitangent;
+    b.scale(sin_t * phi.sin());
+    let mut a = axis;
+    a.scale(cos_t);
+    dir.add(b);
+    dir.add(a);
+    dir.normalize();
+    dir
+}
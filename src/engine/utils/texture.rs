@@ -0,0 +1,39 @@
+use std::path::Path;
+
+use image::ImageResult;
+use sdl2::pixels::Color;
+
+/// RGB8 image sampled with wrapping (repeat) uv coordinates.
+#[derive(Debug, Clone)]
+pub struct Texture {
+    pub w: u32,
+    pub h: u32,
+    pub data: Vec<u8>,
+}
+
+impl Texture {
+    pub fn new(w: u32, h: u32, data: Vec<u8>) -> Self {
+        Texture { w, h, data }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> ImageResult<Texture> {
+        let img = image::open(path)?.to_rgb8();
+        Ok(Texture::new(img.width(), img.height(), img.into_raw()))
+    }
+
+    /// Nearest texel at `(u, v)`, where v = 0 is the bottom row as in Wavefront files.
+    pub fn sample(&self, u: f64, v: f64) -> Color {
+        if self.w == 0 || self.h == 0 {
+            return Color::BLACK;
+        }
+
+        let x = ((u * self.w as f64).floor() as i64).rem_euclid(self.w as i64) as usize;
+        let y = (((1.0 - v) * self.h as f64).floor() as i64).rem_euclid(self.h as i64) as usize;
+
+        let pos = (x + y * self.w as usize) * 3;
+        match self.data.get(pos..pos + 3) {
+            Some(p) => Color::RGB(p[0], p[1], p[2]),
+            None => Color::BLACK,
+        }
+    }
+}
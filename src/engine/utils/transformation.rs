@@ -1,15 +1,359 @@
+use std::ops::Mul;
+
+use crate::engine::utils::rendering::Collision;
+use crate::geometry::quaternion::Quaternion;
 use crate::geometry::vector3::Vector3 as V3;
 
 pub const PI: f64 = 3.14159265358979323846264338327950288_f64; // 3.1415926535897931f64
 pub const TWO_PI: f64 = 6.28318530717958647692528676655900576_f64; // 6.2831853071795862f64 //copilot did this, maybe not exactly correct
 
 pub trait Transformable {
+    /// Undoes `rot(r)`. Negating the angles is not enough as the axes are applied in order, the
+    /// default rotates by the Euler angles of the inverse rotation.
     fn rot_reverse(&mut self, r : V3) {
-        self.rot(V3{x: TWO_PI - r.x, y: TWO_PI - r.y, z: TWO_PI - r.z});
+        self.rot(Quaternion::from_euler(r).conjugate().to_euler());
     }
     fn rot(&mut self, r : V3);
     fn rot_by(&mut self, p : V3, r : V3);
     fn translate(&mut self, p : V3);
     fn scale(&mut self, p : V3);
     fn transform(&mut self) -> Box<&mut dyn Transformable>;
-}
\ No newline at end of file
+}
+/// Wrapper that counts the changes to a scene or an object: every transformation and every
+/// mutable access increases `version`. Renderers that keep results between frames compare the
+/// version to notice that they have to start over.
+pub struct Watched<T> {
+    inner: T,
+    version: u64,
+}
+
+impl<T> Watched<T> {
+    pub fn new(inner: T) -> Self {
+        Watched { inner, version: 0 }
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn get(&self) -> &T {
+        &self.inner
+    }
+
+    /// Mutable access, counts as a change.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.version += 1;
+        &mut self.inner
+    }
+}
+
+impl<T: Transformable> Transformable for Watched<T> {
+    fn rot_reverse(&mut self, r: V3) {
+        self.get_mut().rot_reverse(r);
+    }
+
+    fn rot(&mut self, r: V3) {
+        self.get_mut().rot(r);
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        self.get_mut().rot_by(p, r);
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.get_mut().translate(p);
+    }
+
+    fn scale(&mut self, p: V3) {
+        self.get_mut().scale(p);
+    }
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
+}
+
+/// Homogeneous 4x4 matrix, rows first, applied to column vectors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix4 {
+    pub m: [[f64; 4]; 4],
+}
+
+impl Matrix4 {
+    pub fn new(m: [[f64; 4]; 4]) -> Self {
+        Matrix4 { m }
+    }
+
+    pub fn identity() -> Self {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            row[i] = 1.0;
+        }
+        Matrix4 { m }
+    }
+
+    pub fn translation(p: V3) -> Self {
+        let mut t = Matrix4::identity();
+        t.m[0][3] = p.x;
+        t.m[1][3] = p.y;
+        t.m[2][3] = p.z;
+        t
+    }
+
+    pub fn scaling(s: V3) -> Self {
+        let mut t = Matrix4::identity();
+        t.m[0][0] = s.x;
+        t.m[1][1] = s.y;
+        t.m[2][2] = s.z;
+        t
+    }
+
+    pub fn rotation(q: Quaternion) -> Self {
+        let r = q.to_matrix();
+        let mut t = Matrix4::identity();
+        for (row, r) in t.m.iter_mut().zip(r.iter()) {
+            row[..3].copy_from_slice(r);
+        }
+        t
+    }
+
+    pub fn transposed(&self) -> Matrix4 {
+        let mut t = Matrix4::identity();
+        for i in 0..4 {
+            for j in 0..4 {
+                t.m[i][j] = self.m[j][i];
+            }
+        }
+        t
+    }
+
+    /// Gauss-Jordan elimination with partial pivoting, `None` if the matrix is singular.
+    pub fn inverse(&self) -> Option<Matrix4> {
+        let mut a = self.m;
+        let mut inv = Matrix4::identity().m;
+        for col in 0..4 {
+            let pivot = (col..4).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+            if a[pivot][col].abs() < 1e-12 {
+                return None;
+            }
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+            let f = a[col][col];
+            for j in 0..4 {
+                a[col][j] /= f;
+                inv[col][j] /= f;
+            }
+            for row in 0..4 {
+                if row == col {
+                    continue;
+                }
+                let f = a[row][col];
+                for j in 0..4 {
+                    a[row][j] -= f * a[col][j];
+                    inv[row][j] -= f * inv[col][j];
+                }
+            }
+        }
+        Some(Matrix4 { m: inv })
+    }
+
+    pub fn transform_point(&self, p: V3) -> V3 {
+        let m = &self.m;
+        let w = m[3][0] * p.x + m[3][1] * p.y + m[3][2] * p.z + m[3][3];
+        (self.transform_vector(p) + V3::new(m[0][3], m[1][3], m[2][3])) / w
+    }
+
+    /// Applies only the linear part, for directions.
+    pub fn transform_vector(&self, v: V3) -> V3 {
+        let m = &self.m;
+        V3::new(
+            m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+            m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+            m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z,
+        )
+    }
+
+    /// Determinant of the linear part.
+    pub fn determinant(&self) -> f64 {
+        let m = &self.m;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1]) - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    /// Smallest factor the linear part stretches any direction by, its smallest singular value.
+    /// Distances in object space times this are a lower bound of the distance in world space.
+    pub fn min_stretch(&self) -> f64 {
+        // smallest eigenvalue of AᵀA, which is symmetric, in closed form
+        let mut b = [[0.0; 3]; 3];
+        for (i, row) in b.iter_mut().enumerate() {
+            for (j, v) in row.iter_mut().enumerate() {
+                *v = (0..3).map(|k| self.m[k][i] * self.m[k][j]).sum();
+            }
+        }
+        let p1 = b[0][1] * b[0][1] + b[0][2] * b[0][2] + b[1][2] * b[1][2];
+        let smallest = if p1 == 0.0 {
+            b[0][0].min(b[1][1]).min(b[2][2])
+        } else {
+            let q = (b[0][0] + b[1][1] + b[2][2]) / 3.0;
+            let p = f64::sqrt(((b[0][0] - q).powi(2) + (b[1][1] - q).powi(2) + (b[2][2] - q).powi(2) + 2.0 * p1) / 6.0);
+            let mut c = Matrix4::identity();
+            for (i, (row, b)) in c.m.iter_mut().zip(b.iter()).enumerate() {
+                for (j, v) in row[..3].iter_mut().enumerate() {
+                    *v = (b[j] - if i == j { q } else { 0.0 }) / p;
+                }
+            }
+            let phi = f64::acos(f64::clamp(c.determinant() / 2.0, -1.0, 1.0)) / 3.0;
+            q + 2.0 * p * f64::cos(phi + 2.0 * PI / 3.0)
+        };
+        smallest.max(0.0).sqrt()
+    }
+}
+
+impl Default for Matrix4 {
+    fn default() -> Self {
+        Matrix4::identity()
+    }
+}
+
+/// `a * b` applies `b` first, then `a`.
+impl Mul for Matrix4 {
+    type Output = Matrix4;
+
+    fn mul(self, o: Matrix4) -> Matrix4 {
+        let mut t = [[0.0; 4]; 4];
+        for (i, row) in t.iter_mut().enumerate() {
+            for (j, v) in row.iter_mut().enumerate() {
+                *v = (0..4).map(|k| self.m[i][k] * o.m[k][j]).sum();
+            }
+        }
+        Matrix4 { m: t }
+    }
+}
+
+/// Placement of an object: scaled along its own axes first, then rotated, then moved to
+/// `translation`. An [`Instance`] keeps its object in object space and stores the transform
+/// instead of moving vertices, so repeated transformations don't accumulate rounding errors.
+/// The shapes and meshes themselves (`Sphere`, `Face`, `Simplex3D`, `PolyTree`) still move
+/// their geometry when they are transformed, wrap them in an instance to avoid that.
+///
+/// [`Instance`]: crate::engine::instance::Instance
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub translation: V3,
+    pub rotation: Quaternion,
+    pub scale: V3,
+}
+
+impl Transform {
+    pub fn new(translation: V3, rotation: Quaternion, scale: V3) -> Self {
+        Transform { translation, rotation: rotation.normalized(), scale }
+    }
+
+    pub fn identity() -> Self {
+        Transform::new(V3::new(0.0, 0.0, 0.0), Quaternion::identity(), V3::new(1.0, 1.0, 1.0))
+    }
+
+    pub fn matrix(&self) -> Matrix4 {
+        Matrix4::translation(self.translation) * Matrix4::rotation(self.rotation) * Matrix4::scaling(self.scale)
+    }
+
+    /// Computed from the parts, without inverting `matrix()`.
+    pub fn inverse_matrix(&self) -> Matrix4 {
+        let s = V3::new(1.0 / self.scale.x, 1.0 / self.scale.y, 1.0 / self.scale.z);
+        Matrix4::scaling(s) * Matrix4::rotation(self.rotation.conjugate()) * Matrix4::translation(-self.translation)
+    }
+
+    /// Object space to world space.
+    pub fn apply_point(&self, p: V3) -> V3 {
+        self.translation + self.apply_vector(p)
+    }
+
+    pub fn apply_vector(&self, v: V3) -> V3 {
+        self.rotation.rotate(v * self.scale)
+    }
+
+    /// Normals scale inversely to keep them perpendicular to the surface, the result is normalized.
+    pub fn apply_normal(&self, n: V3) -> V3 {
+        self.rotation.rotate(n / self.scale).normalized()
+    }
+
+    /// World space to object space.
+    pub fn inverse_point(&self, p: V3) -> V3 {
+        self.inverse_vector(p - self.translation)
+    }
+
+    pub fn inverse_vector(&self, v: V3) -> V3 {
+        self.rotation.conjugate().rotate(v) / self.scale
+    }
+
+    /// Origin and unit direction of the ray from `p0` along `p` in object space, and how much
+    /// longer `p` is there. Hand the last one to `apply_collision`.
+    pub fn inverse_ray(&self, p0: V3, p: V3) -> (V3, V3, f64) {
+        let d = self.inverse_vector(p);
+        let l = d.norm();
+        (self.inverse_point(p0), d / l, l)
+    }
+
+    /// Moves a hit of a ray from `inverse_ray` back into world space, with `d` as the world
+    /// distance along `p`.
+    pub fn apply_collision(&self, c: Collision, n: Option<V3>, p: V3, l: f64) -> (Collision, Option<V3>) {
+        if !c.hit {
+            return (c, n);
+        }
+        let c = Collision { d: c.d / l * p.norm(), p: self.apply_point(c.p), ..c };
+        (c, n.map(|n| self.apply_normal(n)))
+    }
+
+    /// Smallest stretch of the transform, distances in object space times this are a lower bound
+    /// of the distance in world space.
+    pub fn min_scale(&self) -> f64 {
+        let s = self.scale.abs();
+        s.x.min(s.y).min(s.z)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::identity()
+    }
+}
+
+/// The combined matrix, `a * b` applies `b` first. Scaling after a rotation can shear, which
+/// a `Transform` cannot hold, so the result is a matrix.
+impl Mul for Transform {
+    type Output = Matrix4;
+
+    fn mul(self, o: Transform) -> Matrix4 {
+        self.matrix() * o.matrix()
+    }
+}
+
+// rotations turn around `translation`, the origin of the object space
+impl Transformable for Transform {
+    fn rot_reverse(&mut self, r: V3) {
+        self.rotation = (Quaternion::from_euler(r).conjugate() * self.rotation).normalized();
+    }
+
+    fn rot(&mut self, r: V3) {
+        self.rotation = (Quaternion::from_euler(r) * self.rotation).normalized();
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        let q = Quaternion::from_euler(r);
+        self.translation = p + q.rotate(self.translation - p);
+        self.rotation = (q * self.rotation).normalized();
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.translation += p;
+    }
+
+    /// Scales along the axes of the object.
+    fn scale(&mut self, p: V3) {
+        self.scale *= p;
+    }
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
+}
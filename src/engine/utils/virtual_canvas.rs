@@ -1,4 +1,7 @@
-use sdl2::{rect::Point, render::Canvas, video::Window};
+use std::path::Path;
+
+use image::{ImageFormat, ImageResult, RgbImage, RgbaImage};
+use sdl2::{rect::Point, render::{Canvas, RenderTarget}};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Color {
@@ -14,6 +17,17 @@ impl Color {
     }
 }
 
+impl From<sdl2::pixels::Color> for Color {
+    fn from(c: sdl2::pixels::Color) -> Self {
+        Color::new(c.r, c.g, c.b, c.a)
+    }
+}
+
+/// RGBA8 framebuffer that lives entirely in memory.
+///
+/// Every renderer can draw into it without a window; the SDL canvas is just one
+/// way of presenting it (see [`VirtualCanvas::present`]), writing an image file is another.
+#[derive(Debug, Clone)]
 pub struct VirtualCanvas {
     pub width: usize,
     pub height: usize,
@@ -29,21 +43,96 @@ impl VirtualCanvas {
         }
     }
 
+    /// Builds a canvas from the column-major pixel order produced by
+    /// `RayCamera::render_frame_multi` (all rows of column 0 first, then column 1, ...).
+    pub fn from_column_major(pixels: &[sdl2::pixels::Color], width: usize, height: usize) -> Self {
+        assert_eq!(pixels.len(), width * height, "pixel count does not match {}x{}", width, height);
+        let mut canvas = VirtualCanvas::new(width, height);
+        for x in 0..width {
+            for y in 0..height {
+                canvas.draw_sdl2_color(x, y, pixels[x * height + y]);
+            }
+        }
+        canvas
+    }
+
     pub fn draw_pixel(&mut self, x: usize, y: usize, color: Color) {
         let pos = (x + y * (self.width)) * 4;
-        self.pixels[pos + 0] = color.r;
+        self.pixels[pos] = color.r;
         self.pixels[pos + 1] = color.g;
         self.pixels[pos + 2] = color.b;
         self.pixels[pos + 3] = color.a;
     }
 
-    pub fn get_sdl2_color(&self, x: usize, y: usize) -> sdl2::pixels::Color {
+    pub fn draw_sdl2_color(&mut self, x: usize, y: usize, color: sdl2::pixels::Color) {
+        self.draw_pixel(x, y, Color::from(color));
+    }
+
+    /// Fills a `size` x `size` block, clipped at the canvas border.
+    pub fn fill_block(&mut self, x: usize, y: usize, size: usize, color: sdl2::pixels::Color) {
+        for bx in x..usize::min(x + size, self.width) {
+            for by in y..usize::min(y + size, self.height) {
+                self.draw_sdl2_color(bx, by, color);
+            }
+        }
+    }
+
+    pub fn get_pixel(&self, x: usize, y: usize) -> Color {
         let pos = (x + y * (self.width)) * 4;
-        sdl2::pixels::Color::RGBA(
-            self.pixels[pos + 0],
+        Color::new(
+            self.pixels[pos],
             self.pixels[pos + 1],
             self.pixels[pos + 2],
             self.pixels[pos + 3]
         )
     }
-}
\ No newline at end of file
+
+    pub fn get_sdl2_color(&self, x: usize, y: usize) -> sdl2::pixels::Color {
+        let c = self.get_pixel(x, y);
+        sdl2::pixels::Color::RGBA(c.r, c.g, c.b, c.a)
+    }
+
+    pub fn clear(&mut self, color: Color) {
+        for p in self.pixels.chunks_exact_mut(4) {
+            p.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+    }
+
+    pub fn to_rgba_image(&self) -> RgbaImage {
+        RgbaImage::from_raw(self.width as u32, self.height as u32, self.pixels.clone())
+            .expect("canvas buffer has the wrong size")
+    }
+
+    pub fn to_rgb_image(&self) -> RgbImage {
+        let rgb: Vec<u8> = self.pixels
+            .chunks_exact(4)
+            .flat_map(|p| [p[0], p[1], p[2]])
+            .collect();
+        RgbImage::from_raw(self.width as u32, self.height as u32, rgb)
+            .expect("canvas buffer has the wrong size")
+    }
+
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> ImageResult<()> {
+        self.to_rgba_image().save_with_format(path, ImageFormat::Png)
+    }
+
+    /// Writes a binary PPM (P6); the alpha channel is dropped.
+    pub fn save_ppm<P: AsRef<Path>>(&self, path: P) -> ImageResult<()> {
+        self.to_rgb_image().save_with_format(path, ImageFormat::Pnm)
+    }
+
+    /// Draws the buffer onto an SDL canvas. Fully transparent pixels are skipped,
+    /// so a partially filled buffer can be layered over what is already on screen.
+    pub fn present<T: RenderTarget>(&self, canvas: &mut Canvas<T>) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let c = self.get_sdl2_color(x, y);
+                if c.a == 0 {
+                    continue;
+                }
+                canvas.set_draw_color(c);
+                let _ = canvas.draw_point(Point::new(x as i32, y as i32));
+            }
+        }
+    }
+}
@@ -0,0 +1,52 @@
+pub const CHUNK_SIZE: usize = 16;
+
+/// Material slot of a voxel, `AIR` (0) is empty, everything else indexes
+/// `VoxelWorld::materials` with an offset of one.
+pub type Voxel = u8;
+
+pub const AIR: Voxel = 0;
+
+/// Dense block of `CHUNK_SIZE`³ voxels.
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pub voxels: Vec<Voxel>,
+    pub solid: usize,
+}
+
+impl Chunk {
+    pub fn new() -> Self {
+        Chunk {
+            voxels: vec![AIR; CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE],
+            solid: 0,
+        }
+    }
+
+    fn index(x: usize, y: usize, z: usize) -> usize {
+        x + CHUNK_SIZE * (y + CHUNK_SIZE * z)
+    }
+
+    pub fn get(&self, x: usize, y: usize, z: usize) -> Voxel {
+        self.voxels[Self::index(x, y, z)]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, z: usize, v: Voxel) {
+        let i = Self::index(x, y, z);
+        let old = self.voxels[i];
+        if old == AIR && v != AIR {
+            self.solid += 1;
+        } else if old != AIR && v == AIR {
+            self.solid -= 1;
+        }
+        self.voxels[i] = v;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.solid == 0
+    }
+}
+
+impl Default for Chunk {
+    fn default() -> Self {
+        Self::new()
+    }
+}
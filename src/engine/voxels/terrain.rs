@@ -0,0 +1,86 @@
+use sdl2::pixels::Color;
+
+use crate::engine::lighting::Material;
+use crate::engine::voxels::voxel_world::VoxelWorld;
+use crate::math::noise::PerlinNoise;
+
+/// Minecraft-like height map terrain: fbm Perlin noise decides the column height,
+/// the depth below the surface and the absolute height decide the material.
+#[derive(Debug, Clone, Copy)]
+pub struct TerrainGenerator {
+    pub seed: u64,
+    /// Noise frequency per voxel.
+    pub frequency: f64,
+    pub octaves: usize,
+    pub base_height: i64,
+    pub amplitude: f64,
+    /// Columns below this height are flooded, `None` disables water.
+    pub water_level: Option<i64>,
+    /// Columns above this height get a snow cap.
+    pub snow_level: i64,
+}
+
+impl TerrainGenerator {
+    pub fn new(seed: u64) -> Self {
+        TerrainGenerator {
+            seed,
+            frequency: 0.035,
+            octaves: 4,
+            base_height: 8,
+            amplitude: 10.0,
+            water_level: Some(5),
+            snow_level: 15,
+        }
+    }
+
+    pub fn height_at(&self, noise: &PerlinNoise, x: i64, z: i64) -> i64 {
+        let n = noise.fbm2(x as f64 * self.frequency, z as f64 * self.frequency, self.octaves, 2.0, 0.5);
+        self.base_height + (n * self.amplitude).round() as i64
+    }
+
+    /// Generates the columns `0..size_x` x `0..size_z` into `world`, registering the needed materials.
+    pub fn generate(&self, world: &mut VoxelWorld, size_x: i64, size_z: i64) {
+        let noise = PerlinNoise::new(self.seed);
+
+        let grass = world.add_material(Material::new(Color::RGB(86, 160, 62), 1.0));
+        let dirt = world.add_material(Material::new(Color::RGB(121, 85, 58), 1.0));
+        let stone = world.add_material(Material::new(Color::RGB(125, 125, 130), 1.0));
+        let sand = world.add_material(Material::new(Color::RGB(219, 205, 150), 1.0));
+        let snow = world.add_material(Material::new(Color::RGB(240, 244, 250), 1.0));
+        let water = world.add_material(Material::new(Color::RGB(50, 100, 200), 1.0));
+
+        for x in 0..size_x {
+            for z in 0..size_z {
+                let h = i64::max(self.height_at(&noise, x, z), 0);
+                let shore = match self.water_level {
+                    Some(w) => h <= w + 1,
+                    None => false,
+                };
+
+                for y in 0..=h {
+                    let depth = h - y;
+                    let v = if depth == 0 {
+                        if h >= self.snow_level {
+                            snow
+                        } else if shore {
+                            sand
+                        } else {
+                            grass
+                        }
+                    } else if depth < 3 {
+                        if shore { sand } else { dirt }
+                    } else {
+                        stone
+                    };
+                    world.set_voxel(x, y, z, v);
+                }
+
+                if let Some(w) = self.water_level {
+                    for y in (h + 1)..=w {
+                        world.set_voxel(x, y, z, water);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,371 @@
+use std::collections::HashMap;
+
+use sdl2::pixels::Color;
+
+use crate::engine::lighting::Material;
+use crate::engine::pathtracing::PathtracingObject;
+use crate::engine::utils::{rendering::{Collision, RayRenderable}, transformation::Transformable};
+use crate::engine::voxels::chunk::{Chunk, Voxel, AIR, CHUNK_SIZE};
+use crate::geometry::aabb::{inverse_direction, Aabb};
+use crate::geometry::vector3::Vector3 as V3;
+
+pub type ChunkKey = (i64, i64, i64);
+
+#[derive(Debug, Clone, Copy)]
+pub struct VoxelHit {
+    /// Euclidean distance from the ray origin.
+    pub d: f64,
+    pub p: V3,
+    pub voxel: (i64, i64, i64),
+    pub normal: V3,
+    pub material: Voxel,
+}
+
+/// Sparse voxel grid made of `CHUNK_SIZE`³ chunks. Voxel `(x, y, z)` covers the cube
+/// `origin + voxel_size * [x, x+1] x [y, y+1] x [z, z+1]`. The grid is always axis aligned:
+/// `rot_by` only moves the origin, rotating the world in place or scaling it unevenly is a
+/// bug that debug builds catch. An [`Instance`](crate::engine::instance::Instance) of the world
+/// can be rotated and stretched freely.
+#[derive(Debug, Clone)]
+pub struct VoxelWorld {
+    pub chunks: HashMap<ChunkKey, Chunk>,
+    pub materials: Vec<Material>,
+    pub voxel_size: f64,
+    pub origin: V3,
+    /// Light direction used when the world is rendered on its own as a `RayRenderable`.
+    pub sun: V3,
+    // inclusive voxel bounds of everything ever set, min > max while empty
+    min: (i64, i64, i64),
+    max: (i64, i64, i64),
+}
+
+fn split(c: i64) -> (i64, usize) {
+    let s = CHUNK_SIZE as i64;
+    (c.div_euclid(s), c.rem_euclid(s) as usize)
+}
+
+impl VoxelWorld {
+    pub fn new(voxel_size: f64) -> Self {
+        let mut sun = V3::new(-0.4, -1.0, 0.3);
+        sun.normalize();
+        VoxelWorld {
+            chunks: HashMap::new(),
+            materials: Vec::new(),
+            voxel_size,
+            origin: V3::empty(),
+            sun,
+            min: (i64::MAX, i64::MAX, i64::MAX),
+            max: (i64::MIN, i64::MIN, i64::MIN),
+        }
+    }
+
+    /// Registers a material and returns the voxel value that refers to it.
+    pub fn add_material(&mut self, m: Material) -> Voxel {
+        assert!(self.materials.len() < Voxel::MAX as usize, "a voxel world holds at most 255 materials");
+        self.materials.push(m);
+        self.materials.len() as Voxel
+    }
+
+    pub fn material(&self, v: Voxel) -> Option<&Material> {
+        if v == AIR {
+            None
+        } else {
+            self.materials.get(v as usize - 1)
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.0 > self.max.0
+    }
+
+    pub fn get_voxel(&self, x: i64, y: i64, z: i64) -> Voxel {
+        let (cx, lx) = split(x);
+        let (cy, ly) = split(y);
+        let (cz, lz) = split(z);
+        match self.chunks.get(&(cx, cy, cz)) {
+            Some(c) => c.get(lx, ly, lz),
+            None => AIR,
+        }
+    }
+
+    pub fn set_voxel(&mut self, x: i64, y: i64, z: i64, v: Voxel) {
+        let (cx, lx) = split(x);
+        let (cy, ly) = split(y);
+        let (cz, lz) = split(z);
+
+        if v == AIR {
+            // bounds stay conservative, they only have to contain every solid voxel
+            if let Some(c) = self.chunks.get_mut(&(cx, cy, cz)) {
+                c.set(lx, ly, lz, AIR);
+                if c.is_empty() {
+                    self.chunks.remove(&(cx, cy, cz));
+                }
+            }
+            return;
+        }
+
+        self.chunks.entry((cx, cy, cz)).or_default().set(lx, ly, lz, v);
+        self.min = (i64::min(self.min.0, x), i64::min(self.min.1, y), i64::min(self.min.2, z));
+        self.max = (i64::max(self.max.0, x), i64::max(self.max.1, y), i64::max(self.max.2, z));
+    }
+
+    /// Fills the inclusive box `from..=to`.
+    pub fn fill(&mut self, from: (i64, i64, i64), to: (i64, i64, i64), v: Voxel) {
+        for x in from.0..=to.0 {
+            for y in from.1..=to.1 {
+                for z in from.2..=to.2 {
+                    self.set_voxel(x, y, z, v);
+                }
+            }
+        }
+    }
+
+    /// A solid voxel with at least one empty neighbor.
+    pub fn is_surface(&self, x: i64, y: i64, z: i64) -> bool {
+        self.get_voxel(x, y, z) != AIR
+            && [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)]
+                .iter()
+                .any(|(dx, dy, dz)| self.get_voxel(x + dx, y + dy, z + dz) == AIR)
+    }
+
+    /// World space bounds of all solid voxels.
+    pub fn bounds(&self) -> Aabb {
+        if self.is_empty() {
+            return Aabb::empty();
+        }
+        let s = self.voxel_size;
+        let o = self.origin;
+        Aabb::new(
+            V3::new(o.x + self.min.0 as f64 * s, o.y + self.min.1 as f64 * s, o.z + self.min.2 as f64 * s),
+            V3::new(o.x + (self.max.0 + 1) as f64 * s, o.y + (self.max.1 + 1) as f64 * s, o.z + (self.max.2 + 1) as f64 * s),
+        )
+    }
+
+    /// Amanatides–Woo traversal of the grid along `p0 + t * p`, up to the distance `max_d`.
+    pub fn trace(&self, p0: V3, p: V3, max_d: f64) -> Option<VoxelHit> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let s = self.voxel_size;
+        let len = p.norm();
+        if len == 0.0 {
+            return None;
+        }
+
+        // grid space: voxel (x, y, z) is the unit cube [x, x+1]..., the direction stays the same
+        let o = V3::new((p0.x - self.origin.x) / s, (p0.y - self.origin.y) / s, (p0.z - self.origin.z) / s);
+        let grid_bounds = Aabb::new(
+            V3::new(self.min.0 as f64, self.min.1 as f64, self.min.2 as f64),
+            V3::new((self.max.0 + 1) as f64, (self.max.1 + 1) as f64, (self.max.2 + 1) as f64),
+        );
+        let max_t = max_d / (s * len);
+        let inv = inverse_direction(p);
+        let t_enter = grid_bounds.hit(o, inv, max_t)?;
+
+        let dir = [p.x, p.y, p.z];
+        let origin = [o.x, o.y, o.z];
+        let lo = [self.min.0, self.min.1, self.min.2];
+        let hi = [self.max.0, self.max.1, self.max.2];
+
+        let mut voxel = [0i64; 3];
+        let mut step = [0i64; 3];
+        let mut t_max = [f64::INFINITY; 3];
+        let mut t_delta = [f64::INFINITY; 3];
+        let mut normal = [0.0; 3];
+
+        // the axis whose slab was entered last is the face we came through
+        let mut entry_axis = 0;
+        let mut entry_t = f64::NEG_INFINITY;
+
+        for a in 0..3 {
+            let pos = origin[a] + dir[a] * t_enter;
+            voxel[a] = i64::clamp(pos.floor() as i64, lo[a], hi[a]);
+            if dir[a] > 0.0 {
+                step[a] = 1;
+                t_max[a] = ((voxel[a] + 1) as f64 - origin[a]) / dir[a];
+                t_delta[a] = 1.0 / dir[a];
+                let te = (lo[a] as f64 - origin[a]) / dir[a];
+                if te > entry_t {
+                    entry_t = te;
+                    entry_axis = a;
+                }
+            } else if dir[a] < 0.0 {
+                step[a] = -1;
+                t_max[a] = (voxel[a] as f64 - origin[a]) / dir[a];
+                t_delta[a] = -1.0 / dir[a];
+                let te = ((hi[a] + 1) as f64 - origin[a]) / dir[a];
+                if te > entry_t {
+                    entry_t = te;
+                    entry_axis = a;
+                }
+            }
+        }
+        // a ray starting inside the grid entered no slab, the first boundary it crosses is the
+        // one out of its start cell
+        if entry_t < 0.0 {
+            entry_axis = (0..3).filter(|a| step[*a] != 0).fold(entry_axis, |b, a| if t_max[a] < t_max[b] { a } else { b });
+        }
+        normal[entry_axis] = -(step[entry_axis] as f64);
+
+        let mut t = t_enter;
+        let mut chunk_key: Option<ChunkKey> = None;
+        let mut chunk: Option<&Chunk> = None;
+
+        loop {
+            let (cx, lx) = split(voxel[0]);
+            let (cy, ly) = split(voxel[1]);
+            let (cz, lz) = split(voxel[2]);
+            if chunk_key != Some((cx, cy, cz)) {
+                chunk_key = Some((cx, cy, cz));
+                chunk = self.chunks.get(&(cx, cy, cz)).filter(|c| !c.is_empty());
+            }
+
+            if let Some(c) = chunk {
+                let v = c.get(lx, ly, lz);
+                if v != AIR {
+                    let mut hit = p;
+                    hit.scale(t * s);
+                    hit.add(p0);
+                    return Some(VoxelHit {
+                        d: t * s * len,
+                        p: hit,
+                        voxel: (voxel[0], voxel[1], voxel[2]),
+                        normal: V3::new(normal[0], normal[1], normal[2]),
+                        material: v,
+                    });
+                }
+            }
+
+            let a = if t_max[0] < t_max[1] {
+                if t_max[0] < t_max[2] { 0 } else { 2 }
+            } else if t_max[1] < t_max[2] {
+                1
+            } else {
+                2
+            };
+
+            voxel[a] += step[a];
+            t = t_max[a];
+            t_max[a] += t_delta[a];
+            normal = [0.0; 3];
+            normal[a] = -(step[a] as f64);
+
+            if t > max_t || voxel[a] < lo[a] || voxel[a] > hi[a] {
+                return None;
+            }
+        }
+    }
+
+    fn collision_from_hit(&self, hit: &VoxelHit) -> Collision {
+        let c = match self.material(hit.material) {
+            Some(m) => m.albedo,
+            None => Color::RGB(0, 0, 0),
+        };
+        Collision { d: hit.d, p: hit.p, hit: true, c }
+    }
+}
+
+impl RayRenderable for VoxelWorld {
+    /// Flat shading with a single directional light, for rendering the world without a scene.
+    fn get_collision(&self, p0: V3, p: V3, radius: f64) -> Collision {
+        match self.trace(p0, p, radius) {
+            Some(hit) => {
+                let mut c = self.collision_from_hit(&hit);
+                let mut to_sun = self.sun;
+                to_sun.scale(-1.0);
+                let light = 0.35 + 0.65 * f64::max(hit.normal.dt(to_sun), 0.0);
+                c.c = Color::RGB(
+                    (c.c.r as f64 * light) as u8,
+                    (c.c.g as f64 * light) as u8,
+                    (c.c.b as f64 * light) as u8,
+                );
+                c
+            }
+            None => Collision::empty(),
+        }
+    }
+}
+
+impl PathtracingObject for VoxelWorld {
+    fn d(&self, p: V3) -> f64 {
+        let b = self.bounds();
+        let dx = f64::max(f64::max(b.min.x - p.x, p.x - b.max.x), 0.0);
+        let dy = f64::max(f64::max(b.min.y - p.y, p.y - b.max.y), 0.0);
+        let dz = f64::max(f64::max(b.min.z - p.z, p.z - b.max.z), 0.0);
+        f64::sqrt(dx * dx + dy * dy + dz * dz)
+    }
+
+    fn color(&self, _p: V3) -> Color {
+        match self.materials.first() {
+            Some(m) => m.albedo,
+            None => Color::RGB(0, 0, 0),
+        }
+    }
+
+    fn is_colliding(&mut self, p0: V3, p: V3) -> bool {
+        self.trace(p0, p, f64::MAX).is_some()
+    }
+
+    fn get_collision(&self, p0: V3, p: V3) -> Collision {
+        self.get_collision_with_normal(p0, p).0
+    }
+
+    fn get_collision_with_normal(&self, p0: V3, p: V3) -> (Collision, Option<V3>) {
+        match self.trace(p0, p, f64::MAX) {
+            Some(hit) => (self.collision_from_hit(&hit), Some(hit.normal)),
+            None => (Collision::empty(), None),
+        }
+    }
+
+    fn get_collision_with_material(&self, p0: V3, p: V3) -> (Collision, Option<V3>, Material) {
+        match self.trace(p0, p, f64::MAX) {
+            Some(hit) => {
+                let c = self.collision_from_hit(&hit);
+                let m = match self.material(hit.material) {
+                    Some(m) => m.clone(),
+                    None => Material::new(c.c, 1.0),
+                };
+                (c, Some(hit.normal), m)
+            }
+            None => (Collision::empty(), None, Material::new(Color::BLACK, 1.0)),
+        }
+    }
+
+    fn bounds(&self) -> Aabb {
+        VoxelWorld::bounds(self)
+    }
+
+    fn clone(&self) -> Box<dyn PathtracingObject + Send + Sync> {
+        Box::new(Clone::clone(self))
+    }
+}
+
+impl Transformable for VoxelWorld {
+    // the grid stays axis aligned and has one voxel size, a rotated or stretched world has to
+    // be placed with an `Instance` instead
+    fn rot_reverse(&mut self, r: V3) {
+        debug_assert!(r == V3::empty(), "a voxel world cannot rotate, place it with an Instance");
+    }
+
+    fn rot(&mut self, r: V3) {
+        debug_assert!(r == V3::empty(), "a voxel world cannot rotate, place it with an Instance");
+    }
+
+    fn rot_by(&mut self, p: V3, r: V3) {
+        self.origin.rot_by(p, r);
+    }
+
+    fn translate(&mut self, p: V3) {
+        self.origin.add(p);
+    }
+
+    fn scale(&mut self, p: V3) {
+        debug_assert!(p.x == p.y && p.y == p.z, "a voxel world only scales uniformly, place it with an Instance");
+        self.voxel_size *= p.x;
+    }
+
+    fn transform(&mut self) -> Box<&mut dyn Transformable> {
+        Box::new(self)
+    }
+}
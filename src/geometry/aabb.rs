@@ -0,0 +1,168 @@
+use crate::geometry::face::Face;
+use crate::geometry::vector3::Vector3 as V3;
+
+/// Axis aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: V3,
+    pub max: V3,
+}
+
+impl Aabb {
+    pub fn new(min: V3, max: V3) -> Self {
+        Aabb { min, max }
+    }
+
+    /// An inverted box that contains nothing; growing it by any point yields that point.
+    pub fn empty() -> Self {
+        Aabb {
+            min: V3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            max: V3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        }
+    }
+
+    /// Box around all of space, for objects whose extent is not known.
+    pub fn infinite() -> Self {
+        Aabb {
+            min: V3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            max: V3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+        }
+    }
+
+    pub fn from_face(f: &Face) -> Self {
+        let mut b = Aabb::empty();
+        b.grow(f.r);
+        b.grow(f.a);
+        b.grow(f.b);
+        b
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    pub fn is_finite(&self) -> bool {
+        [self.min.x, self.min.y, self.min.z, self.max.x, self.max.y, self.max.z].iter().all(|v| v.is_finite())
+    }
+
+    pub fn grow(&mut self, p: V3) {
+        self.min = V3::new(f64::min(self.min.x, p.x), f64::min(self.min.y, p.y), f64::min(self.min.z, p.z));
+        self.max = V3::new(f64::max(self.max.x, p.x), f64::max(self.max.y, p.y), f64::max(self.max.z, p.z));
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        let mut b = *self;
+        if !other.is_empty() {
+            b.grow(other.min);
+            b.grow(other.max);
+        }
+        b
+    }
+
+    pub fn centroid(&self) -> V3 {
+        V3::new(
+            (self.min.x + self.max.x) * 0.5,
+            (self.min.y + self.max.y) * 0.5,
+            (self.min.z + self.max.z) * 0.5,
+        )
+    }
+
+    pub fn extent(&self) -> V3 {
+        let mut e = self.max;
+        e.subtract(self.min);
+        e
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let e = self.extent();
+        2.0 * (e.x * e.y + e.y * e.z + e.z * e.x)
+    }
+
+    /// Index of the longest axis (0 = x, 1 = y, 2 = z).
+    pub fn longest_axis(&self) -> usize {
+        let e = self.extent();
+        if e.x >= e.y && e.x >= e.z {
+            0
+        } else if e.y >= e.z {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// The eight corners, used to bound a transformed box.
+    pub fn corners(&self) -> [V3; 8] {
+        let (a, b) = (self.min, self.max);
+        [
+            V3::new(a.x, a.y, a.z),
+            V3::new(b.x, a.y, a.z),
+            V3::new(a.x, b.y, a.z),
+            V3::new(b.x, b.y, a.z),
+            V3::new(a.x, a.y, b.z),
+            V3::new(b.x, a.y, b.z),
+            V3::new(a.x, b.y, b.z),
+            V3::new(b.x, b.y, b.z),
+        ]
+    }
+
+    /// Box around the corners moved by `f`, e.g. into world space. Boxes that aren't finite
+    /// stay infinite and empty boxes stay empty.
+    pub fn transformed<F: Fn(V3) -> V3>(&self, f: F) -> Aabb {
+        if self.is_empty() {
+            return *self;
+        }
+        if !self.is_finite() {
+            return Aabb::infinite();
+        }
+        let mut b = Aabb::empty();
+        self.corners().iter().for_each(|&c| b.grow(f(c)));
+        b
+    }
+
+    /// Slab test. `inv_dir` is the componentwise reciprocal of the ray direction.
+    /// Returns the entry distance if the box is hit within `(0, t_max)`.
+    pub fn hit(&self, p0: V3, inv_dir: V3, t_max: f64) -> Option<f64> {
+        let mut t_near = 0.0;
+        let mut t_far = t_max;
+
+        for axis in 0..3 {
+            let (o, inv, lo, hi) = match axis {
+                0 => (p0.x, inv_dir.x, self.min.x, self.max.x),
+                1 => (p0.y, inv_dir.y, self.min.y, self.max.y),
+                _ => (p0.z, inv_dir.z, self.min.z, self.max.z),
+            };
+            let mut t1 = (lo - o) * inv;
+            let mut t2 = (hi - o) * inv;
+            if t1 > t2 {
+                std::mem::swap(&mut t1, &mut t2);
+            }
+            // NaN (0 * inf) compares false and leaves the interval unchanged.
+            if t1 > t_near {
+                t_near = t1;
+            }
+            if t2 < t_far {
+                t_far = t2;
+            }
+            if t_near > t_far {
+                return None;
+            }
+        }
+
+        Some(t_near)
+    }
+}
+
+pub fn axis(v: V3, axis: usize) -> f64 {
+    match axis {
+        0 => v.x,
+        1 => v.y,
+        _ => v.z,
+    }
+}
+
+pub fn inverse_direction(p: V3) -> V3 {
+    V3::new(1.0 / p.x, 1.0 / p.y, 1.0 / p.z)
+}
@@ -8,7 +8,11 @@ pub struct Face {
     pub b: V3,
     pub n: V3,
     pub m: V3,
-    pub radius: f64
+    pub radius: f64,
+    /// Vertex normals at r, a and b, if the source mesh provided them.
+    pub vn: Option<[V3; 3]>,
+    /// Index into the materials of the owning mesh, `None` uses the mesh's base color.
+    pub material: Option<usize>
 }
 
 impl RaySphereable for Face {
@@ -29,10 +33,42 @@ impl Face {
             b : b_,
             n : Face::calculate_norm(r_, a_, b_),
             m: Face::calculate_middle(r_, a_, b_),
-            radius: Face::calculate_radius(Face::calculate_middle(r_, a_, b_), r_, a_, b_)
+            radius: Face::calculate_radius(Face::calculate_middle(r_, a_, b_), r_, a_, b_),
+            vn: None,
+            material: None
         }
     }
 
+    pub fn set_vertex_normals(&mut self, vn: [V3; 3]) {
+        self.vn = Some(vn);
+    }
+
+    /// Normal for shading at barycentric coordinates `bg` as returned by `get_beta_gamma`: the
+    /// vertex normals interpolated and normalized, or the face normal `n` without them.
+    pub fn normal_at(&self, bg: (f64, f64)) -> V3 {
+        match self.vn {
+            Some([nr, na, nb]) => {
+                let n = nr * (1.0 - bg.0 - bg.1) + na * bg.0 + nb * bg.1;
+                if n.norm() > 0.0 { n.normalized() } else { self.n }
+            }
+            None => self.n,
+        }
+    }
+
+    /// Interior angle at each corner, in the order r, a, b.
+    pub fn corner_angles(&self) -> [f64; 3] {
+        let angle = |p: V3, q1: V3, q2: V3| {
+            let (u, v) = (q1 - p, q2 - p);
+            let l = u.norm() * v.norm();
+            if l > 0.0 { (u.dt(v) / l).clamp(-1.0, 1.0).acos() } else { 0.0 }
+        };
+        [angle(self.r, self.a, self.b), angle(self.a, self.b, self.r), angle(self.b, self.r, self.a)]
+    }
+
+    pub fn set_material(&mut self, material: usize) {
+        self.material = Some(material);
+    }
+
     pub fn d(_p : V3) {
 
     }
@@ -113,6 +149,12 @@ impl Face {
         self.a.rot_reverse(r_);
         self.b.rot_reverse(r_);
 
+        if let Some(vn) = self.vn.as_mut() {
+            for n in vn.iter_mut() {
+                n.rot_reverse(r_);
+            }
+        }
+
         self.r.add(p);
         self.a.add(p);
         self.b.add(p);
@@ -131,6 +173,12 @@ impl Face {
         self.a.rot_by(p, r_);
         self.b.rot_by(p, r_);
         self.m.rot_by(p, r_);
+
+        if let Some(vn) = self.vn.as_mut() {
+            for n in vn.iter_mut() {
+                n.rotate(r_);
+            }
+        }
         self.update();
  	}
     
@@ -168,6 +216,22 @@ impl Face {
         self.r.z = m.z + rz;
 
         self.update();
+
+        // normals transform with the inverse scale. The cofactors point the same way and stay
+        // finite for a zero axis, which flattens the normals onto that axis.
+        if let Some(vn) = self.vn.as_mut() {
+            let sign = if p.x * p.y * p.z < 0.0 { -1.0 } else { 1.0 };
+            let cofactors = V3::new(p.y * p.z, p.x * p.z, p.x * p.y) * sign;
+            for n in vn.iter_mut() {
+                let scaled = V3::new(n.x * cofactors.x, n.y * cofactors.y, n.z * cofactors.z);
+                if scaled.norm() > 0.0 {
+                    *n = scaled.normalized();
+                } else if self.n.norm() > 0.0 {
+                    // the normal lay in the flattened plane, only the face is left to go by
+                    *n = self.n.normalized();
+                }
+            }
+        }
     }
 }
 
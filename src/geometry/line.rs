@@ -156,7 +156,7 @@ impl PathtracingObject for Line {
             m: self.m.clone(),
             thickness: self.thickness,
             base_color: self.base_color,
-            material: self.material,
+            material: self.material.clone(),
         })
     }
 }
@@ -178,7 +178,7 @@ impl RayMarchingObject for Line {
             m: self.m.clone(),
             thickness: self.thickness,
             base_color: self.base_color,
-            material: self.material,
+            material: self.material.clone(),
         })
     }
 }
@@ -3,9 +3,10 @@ use crate::engine::lighting::Material;
 use crate::engine::pathtracing::RaytracingObject;
 use crate::engine::raymarching::RayMarchingObject;
 use crate::engine::utils::{rendering::{Collision, RayRenderScene, RayRenderable}, transformation::Transformable};
+use crate::geometry::aabb::Aabb;
 use crate::geometry::vector3::Vector3;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 //ToDO this is horror, who thought of this struct?
 pub struct Quad {
     pub x: [Vector3; 8],
@@ -16,7 +17,7 @@ pub struct Quad {
     rx: f64,
     ry: f64,
     rz: f64,
-    base_color: Color,
+    material: Material,
 }
 
 impl Quad {
@@ -110,7 +111,7 @@ impl Quad {
             rx: 0.0,
             ry: 0.0,
             rz: 0.0,
-            base_color: c,
+            material: Material::new(c, 1.0),
         }
     }
 
@@ -198,11 +199,15 @@ impl Quad {
             rx: 0.0,
             ry: 0.0,
             rz: 0.0,
-            base_color: c,
+            material: Material::new(c, 1.0),
         }
     }
 
-    pub fn has_point(self, p: Vector3) -> u32 {
+    pub fn set_material(&mut self, material: Material) {
+        self.material = material;
+    }
+
+    pub fn has_point(&self, p: Vector3) -> u32 {
         if true {
             return 5;
         } else {
@@ -210,7 +215,7 @@ impl Quad {
         }
     }
 
-    pub fn find_s_index(self, p: Vector3) -> u32 {
+    pub fn find_s_index(&self, p: Vector3) -> u32 {
         let mut min_d: Vector3 = self.s[0];
         let mut result: u32 = 1;
         for i in 0..6 {
@@ -222,7 +227,7 @@ impl Quad {
         return result;
     }
 
-    pub fn mins(self) -> [f64; 6] {
+    pub fn mins(&self) -> [f64; 6] {
         let mut result: [f64; 6] = [
             self.x[3].x,
             self.x[3].y,
@@ -254,7 +259,7 @@ impl Quad {
         return result;
     }
 
-    pub fn nearest_point_to(self, p: Vector3) -> Vector3 {
+    pub fn nearest_point_to(&self, p: Vector3) -> Vector3 {
         let mut pc: Vector3 = p.clone();
 
         let mut o: Vector3 = self.x[3];
@@ -305,7 +310,7 @@ impl Quad {
         return o;
     }
 
-    pub fn d_(self, p: Vector3) -> f64 {
+    pub fn d_(&self, p: Vector3) -> f64 {
         if p.d(self.m) > self.r_outer {
             //doesnt work i think
             //println!("{}", self.r_outer);
@@ -315,7 +320,7 @@ impl Quad {
         }
     }
 
-    pub fn d_rounded(self, p: Vector3) -> f64 {
+    pub fn d_rounded(&self, p: Vector3) -> f64 {
         //trash
         let mut pc: Vector3 = p.clone();
 
@@ -457,12 +462,12 @@ impl RayMarchingObject for Quad {
             rx: self.rx,
             ry: self.ry,
             rz: self.rz,
-            base_color: self.base_color,
+            material: self.material.clone(),
         });
     }
 
     fn get_material(&self) -> &Material {
-        todo!()
+        &self.material
     }
 }
 
@@ -472,7 +477,7 @@ impl RaytracingObject for Quad {
     }
 
     fn color(&self, _p: Vector3) -> Color {
-        self.base_color
+        self.material.albedo
     }
 
     fn is_colliding(&mut self, p0: Vector3, p: Vector3) -> bool {
@@ -515,6 +520,7 @@ impl RaytracingObject for Quad {
         let mut tmin = -f64::INFINITY;
         let mut tmax = f64::INFINITY;
         let mut n_enter_local = Vector3::new(0.0, 0.0, 0.0);
+        let mut n_exit_local = Vector3::new(0.0, 0.0, 0.0);
 
         // X slab
         if dx.abs() < 1e-12 {
@@ -535,7 +541,10 @@ impl RaytracingObject for Quad {
                 tmin = t1;
                 n_enter_local = n1;
             }
-            tmax = tmax.min(t2);
+            if t2 < tmax {
+                tmax = t2;
+                n_exit_local = n2;
+            }
             if tmin > tmax {
                 return (Collision::empty(), None);
             }
@@ -560,7 +569,10 @@ impl RaytracingObject for Quad {
                 tmin = t1;
                 n_enter_local = n1;
             }
-            tmax = tmax.min(t2);
+            if t2 < tmax {
+                tmax = t2;
+                n_exit_local = n2;
+            }
             if tmin > tmax {
                 return (Collision::empty(), None);
             }
@@ -585,17 +597,20 @@ impl RaytracingObject for Quad {
                 tmin = t1;
                 n_enter_local = n1;
             }
-            tmax = tmax.min(t2);
+            if t2 < tmax {
+                tmax = t2;
+                n_exit_local = n2;
+            }
             if tmin > tmax {
                 return (Collision::empty(), None);
             }
         }
 
-        let t_hit = if tmin > 1e-6 {
-            tmin
+        let (t_hit, n_local) = if tmin > 1e-6 {
+            (tmin, n_enter_local)
         } else if tmax > 1e-6 {
             // inside box: exit intersection
-            tmax
+            (tmax, n_exit_local)
         } else {
             return (Collision::empty(), None);
         };
@@ -604,22 +619,17 @@ impl RaytracingObject for Quad {
         hit.scale(t_hit);
         hit.add(p0);
 
-        // Local normal -> world normal
+        // Local normal -> world normal, always pointing out of the box
         let mut n_world = Vector3::new(0.0, 0.0, 0.0);
-        if n_enter_local.x != 0.0 {
+        if n_local.x != 0.0 {
             n_world = ux;
-            n_world.scale(n_enter_local.x);
-        } else if n_enter_local.y != 0.0 {
+            n_world.scale(n_local.x);
+        } else if n_local.y != 0.0 {
             n_world = uy;
-            n_world.scale(n_enter_local.y);
-        } else if n_enter_local.z != 0.0 {
+            n_world.scale(n_local.y);
+        } else if n_local.z != 0.0 {
             n_world = uz;
-            n_world.scale(n_enter_local.z);
-        }
-
-        // Ensure outward against ray direction
-        if n_world.dt(p) > 0.0 {
-            n_world.scale(-1.0);
+            n_world.scale(n_local.z);
         }
 
         (
@@ -627,13 +637,24 @@ impl RaytracingObject for Quad {
                 d: t_hit,
                 p: hit,
                 hit: true,
-                c: self.base_color,
+                c: self.material.albedo,
             },
             Some(n_world),
         )
     }
 
+    fn get_collision_with_material(&self, p0: Vector3, p: Vector3) -> (Collision, Option<Vector3>, Material) {
+        let (c, n) = self.get_collision_with_normal(p0, p);
+        (c, n, self.material.clone())
+    }
+
+    fn bounds(&self) -> Aabb {
+        let mut b = Aabb::empty();
+        self.x.iter().for_each(|&p| b.grow(p));
+        b
+    }
+
     fn clone(&self) -> Box<dyn RaytracingObject + Send + Sync> {
-        Box::new(*self)
+        Box::new(Clone::clone(self))
     }
 }
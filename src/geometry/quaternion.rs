@@ -0,0 +1,149 @@
+use std::ops::Mul;
+
+use crate::geometry::vector3::Vector3 as V3;
+
+/// Rotation as a unit quaternion `w + xi + yj + zk`. Unlike Euler angles, rotations compose
+/// by multiplication and the inverse is just the conjugate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quaternion {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Quaternion {
+    pub fn new(w: f64, x: f64, y: f64, z: f64) -> Self {
+        Quaternion { w, x, y, z }
+    }
+
+    pub fn identity() -> Self {
+        Quaternion::new(1.0, 0.0, 0.0, 0.0)
+    }
+
+    /// Rotation by `angle` radians around `axis`, which does not need to be normalized.
+    pub fn from_axis_angle(axis: V3, angle: f64) -> Self {
+        let n = axis.norm();
+        if n == 0.0 {
+            return Quaternion::identity();
+        }
+        let (s, c) = f64::sin_cos(angle / 2.0);
+        Quaternion::new(c, axis.x / n * s, axis.y / n * s, axis.z / n * s)
+    }
+
+    /// The rotation `Vector3::rotate(r)` applies: around x first, then y, then z.
+    pub fn from_euler(r: V3) -> Self {
+        let x = Quaternion::from_axis_angle(V3::new(1.0, 0.0, 0.0), r.x);
+        let y = Quaternion::from_axis_angle(V3::new(0.0, 1.0, 0.0), r.y);
+        let z = Quaternion::from_axis_angle(V3::new(0.0, 0.0, 1.0), r.z);
+        z * y * x
+    }
+
+    /// Euler angles for `Vector3::rotate`, the inverse of `from_euler`. At y = ±90° only the
+    /// sum of the x and z rotation is defined, it is put into x.
+    pub fn to_euler(&self) -> V3 {
+        let m = self.to_matrix();
+        let y = f64::asin(f64::clamp(-m[2][0], -1.0, 1.0));
+        if m[2][0].abs() < 1.0 - 1e-12 {
+            V3::new(f64::atan2(m[2][1], m[2][2]), y, f64::atan2(m[1][0], m[0][0]))
+        } else {
+            V3::new(f64::atan2(-m[1][2], m[1][1]), y, 0.0)
+        }
+    }
+
+    pub fn dot(&self, other: Quaternion) -> f64 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn norm(&self) -> f64 {
+        self.dot(*self).sqrt()
+    }
+
+    pub fn normalized(&self) -> Quaternion {
+        let n = self.norm();
+        if n == 0.0 {
+            return Quaternion::identity();
+        }
+        Quaternion::new(self.w / n, self.x / n, self.y / n, self.z / n)
+    }
+
+    pub fn conjugate(&self) -> Quaternion {
+        Quaternion::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    /// The opposite rotation, also correct for quaternions that drifted away from unit length.
+    pub fn inverse(&self) -> Quaternion {
+        let n2 = self.dot(*self);
+        let c = self.conjugate();
+        Quaternion::new(c.w / n2, c.x / n2, c.y / n2, c.z / n2)
+    }
+
+    /// `v` rotated, expects a unit quaternion.
+    pub fn rotate(&self, v: V3) -> V3 {
+        let u = V3::new(self.x, self.y, self.z);
+        let t = u.crossed(v) * 2.0;
+        v + t * self.w + u.crossed(t)
+    }
+
+    /// Rotation matrix, rows first.
+    pub fn to_matrix(&self) -> [[f64; 3]; 3] {
+        let Quaternion { w, x, y, z } = *self;
+        [
+            [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - w * z), 2.0 * (x * z + w * y)],
+            [2.0 * (x * y + w * z), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - w * x)],
+            [2.0 * (x * z - w * y), 2.0 * (y * z + w * x), 1.0 - 2.0 * (x * x + y * y)],
+        ]
+    }
+
+    /// Interpolates along the shorter arc between two unit quaternions with constant speed.
+    pub fn slerp(&self, other: Quaternion, t: f64) -> Quaternion {
+        let mut cos = self.dot(other);
+        let mut other = other;
+        if cos < 0.0 {
+            cos = -cos;
+            other = Quaternion::new(-other.w, -other.x, -other.y, -other.z);
+        }
+        let (a, b) = if cos > 1.0 - 1e-9 {
+            (1.0 - t, t)
+        } else {
+            let angle = cos.acos();
+            let s = angle.sin();
+            (f64::sin((1.0 - t) * angle) / s, f64::sin(t * angle) / s)
+        };
+        Quaternion::new(
+            a * self.w + b * other.w,
+            a * self.x + b * other.x,
+            a * self.y + b * other.y,
+            a * self.z + b * other.z,
+        )
+        .normalized()
+    }
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Quaternion::identity()
+    }
+}
+
+/// `a * b` rotates by `b` first, then by `a`.
+impl Mul for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, o: Quaternion) -> Quaternion {
+        Quaternion::new(
+            self.w * o.w - self.x * o.x - self.y * o.y - self.z * o.z,
+            self.w * o.x + self.x * o.w + self.y * o.z - self.z * o.y,
+            self.w * o.y - self.x * o.z + self.y * o.w + self.z * o.x,
+            self.w * o.z + self.x * o.y - self.y * o.x + self.z * o.w,
+        )
+    }
+}
+
+impl Mul<V3> for Quaternion {
+    type Output = V3;
+
+    fn mul(self, v: V3) -> V3 {
+        self.rotate(v)
+    }
+}
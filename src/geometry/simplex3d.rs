@@ -1,18 +1,36 @@
+use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::{BufReader, BufRead};
+use std::path::Path;
 
 use image::imageops::colorops::contrast_in_place;
 use image::io::Reader as ImageReader;
 use image::{Pixels, GenericImageView};
 
+use crate::engine::lighting::Material;
 use crate::engine::pathtracing::PathtracingObject;
 use crate::engine::utils::{rendering::{RayRenderScene, RayRenderable, Collision, RaySphereable}, transformation::Transformable};
+use crate::geometry::aabb::Aabb;
 use crate::geometry::vector3::Vector3 as V3;
 use crate::geometry::face::{Face as F, UV};
+use crate::geometry::wavefront::{self, ObjError};
 
 
 use sdl2::pixels::Color;
 
+/// Named material of a mesh, usually one `newmtl` block of a `.mtl` file.
+#[derive(Debug, Clone)]
+pub struct MeshMaterial {
+    pub name: String,
+    pub material: Material,
+}
+
+impl MeshMaterial {
+    pub fn new(name: String, material: Material) -> Self {
+        MeshMaterial { name, material }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Simplex3D {
     pub m : V3,
@@ -22,7 +40,8 @@ pub struct Simplex3D {
     pub tw: u32, 
     pub th: u32,
     pub base_color: Color, 
-    pub has_t: bool
+    pub has_t: bool,
+    pub materials: Vec<MeshMaterial>
 }
 
 impl Simplex3D {
@@ -35,7 +54,8 @@ impl Simplex3D {
             tw: 0, 
             th: 0,
             base_color: Color::RGB(0,0,0),
-            has_t: false
+            has_t: false,
+            materials: Vec::new()
         }
     }    
 
@@ -48,7 +68,8 @@ impl Simplex3D {
             tw: tw_, 
             th: th_,
             base_color: Color::RGB(0,0,0),
-            has_t: true
+            has_t: true,
+            materials: Vec::new()
         }
     }  
 
@@ -61,71 +82,150 @@ impl Simplex3D {
             tw: p.tw, 
             th: p.th,
             base_color: p.base_color,
-            has_t: p.has_t
+            has_t: p.has_t,
+            materials: p.materials.clone()
         }
     }
 
+    /// Loads a Wavefront `.obj` file with the materials and textures of its `.mtl` files.
+    pub fn load_obj<P: AsRef<Path>>(path: P) -> Result<Self, ObjError> {
+        wavefront::load_obj(path)
+    }
+
+    /// Loads `f` and maps the texture `tf` onto every face, overriding the `.mtl` materials.
+    /// Panics if either file cannot be loaded, use `load_obj` to handle errors.
     pub fn parse_wavefront(f: &String, tf: &String) -> Self { 
-        let mut vertices : Vec<V3> = Vec::new(); 
-        let mut middle : V3 = V3{x: 0.0, y: 0.0, z: 0.0};
-
-        let mut uvs : Vec<(f64, f64)> = Vec::new();
-
-        let mut faces : Vec<F> = Vec::new();
-        let mut texture_map : Vec<UV> = Vec::new();
-
-        let reader = BufReader::new(File::open(f).expect("Cannot open file"));
-         
-        for line in reader.lines() { 
-            let mut s = line.expect("");
-            let ss = s.split_off(2);
-            let mut es = ss.split_whitespace(); 
-
-            if (s.eq("v ")) {
-                let x_: f64 = es.next().expect("").parse::<>().unwrap();
-                let y_: f64 = es.next().expect("").parse::<>().unwrap();
-                let z_: f64 = es.next().expect("").parse::<>().unwrap();
- 
-                //println!("V {}, {}, {} ", x_, y_, z_);
-
-                vertices.push(V3{x: x_, y: y_, z: z_});
-                middle.add(V3{x: x_, y: y_, z: z_});
-            } 
-            else if (s.eq("vt")) {
-                let x_: f64 = es.next().expect("").parse::<>().unwrap();
-                let y_: f64 = es.next().expect("").parse::<>().unwrap();
-            
-                uvs.push((x_, y_));
+        let mut mesh = Self::load_obj(f).unwrap_or_else(|e| panic!("{}", e));
+
+        let img = image::open(tf).unwrap_or_else(|e| panic!("{}: {}", tf, e));
+
+        let img_width = img.dimensions().0;
+        let img_height = img.dimensions().1;
+
+        let rgb: Vec<u8> = img.to_rgb8().to_vec();
+        println!("Texture Loaded: w{}, h{}, total{}", img_width, img_height, rgb.len());
+
+        mesh.tf = rgb;
+        mesh.tw = img_width;
+        mesh.th = img_height;
+        mesh.has_t = true;
+        mesh
+    }
+
+    /// Color of the surface at barycentric coordinates `bg` of a face with texture map `uv`.
+    /// Falls back to `base_color` for untextured meshes and out-of-range lookups.
+    pub fn texture_color(&self, uv: &UV, bg: (f64, f64)) -> Color {
+        if !self.has_t {
+            return self.base_color;
+        }
+
+        let y = uv.r.0 + bg.0 * (uv.a.0 - uv.r.0) + bg.1 * (uv.b.0 - uv.r.0);
+        let x = 1.0 - (uv.r.1 + bg.0 * (uv.a.1 - uv.r.1) + bg.1 * (uv.b.1 - uv.r.1));
+
+        let ty = (x * self.th as f64) as u32;
+        let tx = (y * self.tw as f64) as u32;
+
+        let pos = ((tx + ty * self.th) * 3) as usize;
+
+        if pos + 2 >= self.tf.len() {
+            self.base_color
+        } else {
+            Color::RGB(self.tf[pos], self.tf[pos + 1], self.tf[pos + 2])
+        }
+    }
+
+    /// Material of a face with the given material slot at barycentric coordinates `bg`, with
+    /// textures resolved. Faces without a material are matte in `base_color`, the mesh texture
+    /// set by `parse_wavefront` overrides the albedo of all faces.
+    pub fn surface_material(&self, material: Option<usize>, uv: &UV, bg: (f64, f64)) -> Material {
+        let mut m = match material.and_then(|m| self.materials.get(m)) {
+            Some(mm) => {
+                let u = uv.r.0 + bg.0 * (uv.a.0 - uv.r.0) + bg.1 * (uv.b.0 - uv.r.0);
+                let v = uv.r.1 + bg.0 * (uv.a.1 - uv.r.1) + bg.1 * (uv.b.1 - uv.r.1);
+                mm.material.sample(u, v)
             }
-            else if (s.eq("f ")) {
-                let mut _1 = es.next().expect("").split("/");
-                let mut _2 = es.next().expect("").split("/");
-                let mut _3 = es.next().expect("").split("/");
+            None => Material::new(self.base_color, 1.0),
+        };
 
-                let ri : usize = _1.next().expect("").parse::<usize>().unwrap() - 1;
-                let ai : usize = _2.next().expect("").parse::<usize>().unwrap() - 1;
-                let bi : usize = _3.next().expect("").parse::<usize>().unwrap() - 1;
+        if self.has_t {
+            m.albedo = self.texture_color(uv, bg);
+        }
+        m
+    }
 
-                let tri : usize = _1.next().expect("").parse::<usize>().unwrap() - 1;
-                let tai : usize = _2.next().expect("").parse::<usize>().unwrap() - 1;
-                let tbi : usize = _3.next().expect("").parse::<usize>().unwrap() - 1;
+    pub fn surface_color(&self, material: Option<usize>, uv: &UV, bg: (f64, f64)) -> Color {
+        self.surface_material(material, uv, bg).albedo
+    }
+
+    pub fn face_material(&self, i: usize, bg: (f64, f64)) -> Material {
+        let uv = self.tm.get(i).copied().unwrap_or(UV::empty());
+        self.surface_material(self.x[i].material, &uv, bg)
+    }
+
+    fn face_color(&self, i: usize, bg: (f64, f64)) -> Color {
+        self.face_material(i, bg).albedo
+    }
 
-                faces.push(F::new(vertices[ri], vertices[ai], vertices[bi]));
-                texture_map.push(UV{r: uvs[tri], a: uvs[tai], b: uvs[tbi]});
+    /// Replaces the vertex normals of all faces with smooth ones. At every corner the normals of
+    /// the faces sharing the vertex are averaged, weighted by their angle at the vertex, so a
+    /// face split in two does not count twice. Faces whose normals differ by more than
+    /// `crease_angle` radians don't contribute to each other and keep a sharp edge, e.g. 0.5
+    /// keeps the edges of a cube but smooths a finely tesselated sphere.
+    pub fn compute_vertex_normals(&mut self, crease_angle: f64) {
+        let key = |p: V3| [p.x.to_bits(), p.y.to_bits(), p.z.to_bits()];
+        let mut around: HashMap<[u64; 3], Vec<(V3, f64)>> = HashMap::new();
+        for f in self.x.iter().filter(|f| f.n.norm() > 0.0) {
+            let n = f.n.normalized();
+            for (p, angle) in [f.r, f.a, f.b].into_iter().zip(f.corner_angles()) {
+                around.entry(key(p)).or_default().push((n, angle));
             }
         }
 
-        middle.scale(1.0 / vertices.len() as f64);
+        let cos_crease = crease_angle.cos();
+        for f in self.x.iter_mut().filter(|f| f.n.norm() > 0.0) {
+            let n = f.n.normalized();
+            let vn = [f.r, f.a, f.b].map(|p| {
+                let mut sum = V3::new(0.0, 0.0, 0.0);
+                for &(other, angle) in around[&key(p)].iter().filter(|(other, _)| other.dt(n) >= cos_crease) {
+                    sum += other * angle;
+                }
+                if sum.norm() > 0.0 { sum.normalized() } else { n }
+            });
+            f.set_vertex_normals(vn);
+        }
+    }
 
-        let mut img = image::open(tf).unwrap();
+    /// Drops the vertex normals, the mesh is shaded flat with the face normals.
+    pub fn clear_vertex_normals(&mut self) {
+        self.x.iter_mut().for_each(|f| f.vn = None);
+    }
 
-        let img_width = img.dimensions().0;
-        let img_height = img.dimensions().1;
+    /// Brute force search for the closest face hit by the ray: index, barycentric coordinates,
+    /// hit point and distance.
+    fn closest_face(&self, p0: V3, p: V3) -> Option<(usize, (f64, f64), V3, f64)> {
+        let mut best: Option<(usize, (f64, f64), V3, f64)> = None;
 
-        let rgb: Vec<u8> = img.to_rgb8().to_vec();
-        println!("Texture Loaded: w{}, h{}, total{}", img_width, img_height, rgb.len());
+        for (i, f) in self.x.iter().enumerate() {
+            if (f.is_colliding(p0, p)) {
+                let bg = f.get_beta_gamma(p0, p);
+                if (bg.0 <= 1.0 && bg.0 >= 0.0 && bg.1 <= 1.0 && bg.1 >= 0.0  && bg.0 + bg.1 <= 1.0) {
+                    let pc: V3 = V3{
+                        x: f.r.x + bg.0 * (f.a.x - f.r.x) + bg.1 * (f.b.x - f.r.x),
+                        y: f.r.y + bg.0 * (f.a.y - f.r.y) + bg.1 * (f.b.y - f.r.y),
+                        z: f.r.z + bg.0 * (f.a.z - f.r.z) + bg.1 * (f.b.z - f.r.z)
+                    };
+                    let d : f64 = pc.d(p0);
+                    let mut to_hit = pc;
+                    to_hit.subtract(p0);
 
-        return Self::new_textured(middle, faces, texture_map, rgb, img_width, img_height);
+                    if to_hit.dt(p) > 1e-9 && best.is_none_or(|b| d < b.3) {
+                        best = Some((i, bg, pc, d));
+                    }
+                }
+            }
+        }
+
+        best
     }
 
 }
@@ -172,6 +272,9 @@ impl PathtracingObject for Simplex3D {
     fn clone(&self) -> Box<dyn PathtracingObject + Send + Sync + 'static> {
         return Box::new(Simplex3D::new_from(&self));
     }
+    fn bounds(&self) -> Aabb {
+        self.x.iter().fold(Aabb::empty(), |b, f| b.union(&Aabb::from_face(f)))
+    }
     fn d(&self, p: V3) -> f64 {
         return 0.0; //todo
     }
@@ -182,112 +285,23 @@ impl PathtracingObject for Simplex3D {
         return true;
     }
 	fn get_collision(&self, p0: V3, p: V3) -> Collision {
-        let mut c : Collision = Collision::empty();
-        let mut bd : f64 = f64::MAX; 
-        let mut i : usize = 0;
-        let mut bg : (f64, f64) = (0.0, 0.0);
-        
-        for (i_, f) in self.x.iter().enumerate() {
-            if (f.is_colliding(p0, p)) {
-                let bg_ = f.get_beta_gamma(p0, p); 
-                if (bg_.0 <= 1.0 && bg_.0 >= 0.0 && bg_.1 <= 1.0 && bg_.1 >= 0.0  && bg_.0 + bg_.1 <= 1.0) {
-                    let pc: V3 = V3{
-                        x: f.r.x + bg_.0 * (f.a.x - f.r.x) + bg_.1 * (f.b.x - f.r.x), 
-                        y: f.r.y + bg_.0 * (f.a.y - f.r.y) + bg_.1 * (f.b.y - f.r.y), 
-                        z: f.r.z + bg_.0 * (f.a.z - f.r.z) + bg_.1 * (f.b.z - f.r.z)  
-                    }; 
-                    let d : f64 = pc.d(p0); 
-                    
-                    if (d < bd) {
-                        bg = bg_;
-                        bd = d; 
-                        i = i_;
-                        c = Collision{d, p: pc, hit: true, c: self.base_color};
-                    }
-                }
-            }
-        }
-
-        if (c.hit && self.has_t) {   
-            
-            let uv = self.tm[i];
-            let y = (uv.r.0 + bg.0 * (uv.a.0 - uv.r.0) + bg.1 * (uv.b.0 - uv.r.0));
-            let x = 1.0 - (uv.r.1 + bg.0 * (uv.a.1 - uv.r.1) + bg.1 * (uv.b.1 - uv.r.1));
-
-            //println!("{}, {}, {}, {}, {}, {}, {}", uv.r.1, (uv.a.1 - uv.r.1), (uv.b.1 - uv.r.1), bg.0, bg.1, x, y);
-
-            let ty = (x * self.th as f64) as u32;
-            let tx = (y * self.tw as f64) as u32;
-
-            let pos = ((tx + ty * self.th) * 3) as usize;
-
-            if pos >= self.tf.len() {
-                c.c = Color::RED;
-            }
-            else {
-                let r = self.tf[pos]; 
-                let g = self.tf[pos + 1];
-                let b = self.tf[pos + 2];
-
-                c.c = Color::RGB(r, g, b);
-            }
-        }
-
-        return c;
+        self.get_collision_with_normal(p0, p).0
     }
 
     fn get_collision_with_normal(&self, p0: V3, p: V3) -> (Collision, Option<V3>) {
-        let mut c : Collision = Collision::empty();
-        let mut bd : f64 = f64::MAX;
-        let mut i : usize = 0;
-        let mut bg : (f64, f64) = (0.0, 0.0);
-        let mut n: Option<V3> = None;
-
-        for (i_, f) in self.x.iter().enumerate() {
-            if (f.is_colliding(p0, p)) {
-                let bg_ = f.get_beta_gamma(p0, p);
-                if (bg_.0 <= 1.0 && bg_.0 >= 0.0 && bg_.1 <= 1.0 && bg_.1 >= 0.0  && bg_.0 + bg_.1 <= 1.0) {
-                    let pc: V3 = V3{
-                        x: f.r.x + bg_.0 * (f.a.x - f.r.x) + bg_.1 * (f.b.x - f.r.x),
-                        y: f.r.y + bg_.0 * (f.a.y - f.r.y) + bg_.1 * (f.b.y - f.r.y),
-                        z: f.r.z + bg_.0 * (f.a.z - f.r.z) + bg_.1 * (f.b.z - f.r.z)
-                    };
-                    let d : f64 = pc.d(p0);
-
-                    if (d < bd) {
-                        bg = bg_;
-                        bd = d;
-                        i = i_;
-                        c = Collision{d, p: pc, hit: true, c: self.base_color};
-                        n = Some(f.n);
-                    }
-                }
-            }
+        match self.closest_face(p0, p) {
+            Some((i, bg, pc, d)) => (Collision{d, p: pc, hit: true, c: self.face_color(i, bg)}, Some(self.x[i].normal_at(bg))),
+            None => (Collision::empty(), None),
         }
+    }
 
-        if (c.hit && self.has_t) {
-
-            let uv = self.tm[i];
-            let y = (uv.r.0 + bg.0 * (uv.a.0 - uv.r.0) + bg.1 * (uv.b.0 - uv.r.0));
-            let x = 1.0 - (uv.r.1 + bg.0 * (uv.a.1 - uv.r.1) + bg.1 * (uv.b.1 - uv.r.1));
-
-            let ty = (x * self.th as f64) as u32;
-            let tx = (y * self.tw as f64) as u32;
-
-            let pos = ((tx + ty * self.th) * 3) as usize;
-
-            if pos >= self.tf.len() {
-                c.c = Color::RED;
-            }
-            else {
-                let r = self.tf[pos];
-                let g = self.tf[pos + 1];
-                let b = self.tf[pos + 2];
-
-                c.c = Color::RGB(r, g, b);
+    fn get_collision_with_material(&self, p0: V3, p: V3) -> (Collision, Option<V3>, Material) {
+        match self.closest_face(p0, p) {
+            Some((i, bg, pc, d)) => {
+                let m = self.face_material(i, bg);
+                (Collision{d, p: pc, hit: true, c: m.albedo}, Some(self.x[i].normal_at(bg)), m)
             }
+            None => (Collision::empty(), None, Material::new(self.base_color, 1.0)),
         }
-
-        (c, n)
     }
-}
\ No newline at end of file
+}
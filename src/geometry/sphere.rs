@@ -5,17 +5,16 @@ use crate::engine::raymarching::RayMarchingObject;
 use crate::engine::utils::rendering::Collision;
 use crate::engine::utils::rendering::RaySphereable;
 use crate::engine::utils::{rendering::{RayRenderScene, RayRenderable}, transformation::Transformable};
+use crate::geometry::aabb::Aabb;
 use crate::geometry::vector3::Vector3;
 
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 pub struct Sphere {
 	m: Vector3,
     r: f64,
     mat: Material,
     disco: bool,
     facet_scale: f64,
-    mirror: bool,
-    reflectivity: f64,
 }
 
 impl Sphere {
@@ -26,8 +25,6 @@ impl Sphere {
             mat: mat_,
             disco: false,
             facet_scale: 14.0,
-            mirror: false,
-            reflectivity: 0.0,
         }
     }
 
@@ -38,8 +35,6 @@ impl Sphere {
             mat: Material::new(Color::RGB(210, 210, 210), 1.0),
             disco: true,
             facet_scale: 18.0,
-            mirror: false,
-            reflectivity: 0.0,
         }
     }
 
@@ -47,19 +42,21 @@ impl Sphere {
         Sphere {
             m: p,
             r: r_,
-            mat: Material::new(Color::RGB(210, 210, 220), 1.0),
+            mat: Material::mirror(Color::RGB(220, 220, 235)),
             disco: false,
             facet_scale: 14.0,
-            mirror: true,
-            reflectivity: 0.9,
         }
     }
 
-    pub fn d_(self, p : Vector3) -> f64 {
+    pub fn set_material(&mut self, mat: Material) {
+        self.mat = mat;
+    }
+
+    pub fn d_(&self, p : Vector3) -> f64 {
         return self.m.d(p) - self.r;
     }
 
-    pub fn nearest_point_to(self, p : Vector3) -> Vector3 {
+    pub fn nearest_point_to(&self, p : Vector3) -> Vector3 {
         let mut v : Vector3 = self.m.clone();
         let mut res : Vector3 = self.m.clone();
         v.subtract(p);
@@ -77,7 +74,7 @@ impl RaytracingObject for Sphere {
     }
 
     fn color(&self, _p: Vector3) -> Color {
-        self.mat.color
+        self.mat.albedo
     }
 
     fn is_colliding(&mut self, p0: Vector3, p: Vector3) -> bool {
@@ -121,7 +118,7 @@ impl RaytracingObject for Sphere {
         n.subtract(self.m);
         n.normalize();
 
-        let mut color = self.mat.color;
+        let mut color = self.mat.albedo;
         if self.disco {
             let theta = f64::atan2(n.z, n.x);
             let phi = f64::acos(f64::clamp(n.y, -1.0, 1.0));
@@ -132,8 +129,6 @@ impl RaytracingObject for Sphere {
             } else {
                 color = Color::RGB(90, 90, 105);
             }
-        } else if self.mirror {
-            color = Color::RGB(220, 220, 235);
         }
 
         (
@@ -147,12 +142,18 @@ impl RaytracingObject for Sphere {
         )
     }
 
-    fn clone(&self) -> Box<dyn RaytracingObject + Send + Sync> {
-        Box::new(*self)
+    fn get_collision_with_material(&self, p0: Vector3, p: Vector3) -> (Collision, Option<Vector3>, Material) {
+        let (c, n) = self.get_collision_with_normal(p0, p);
+        (c, n, self.mat.with_albedo(c.c))
     }
 
-    fn reflectivity(&self, _p: Vector3) -> f64 {
-        self.reflectivity
+    fn bounds(&self) -> Aabb {
+        let r = Vector3::new(self.r, self.r, self.r);
+        Aabb::new(self.m - r, self.m + r)
+    }
+
+    fn clone(&self) -> Box<dyn RaytracingObject + Send + Sync> {
+        Box::new(Clone::clone(self))
     }
 }
 
@@ -195,14 +196,6 @@ impl RayMarchingObject for Sphere {
         &self.mat
     }
     fn clone(&self) -> Box<dyn RayMarchingObject + Send + Sync> {
-        Box::new(Sphere {
-            m: self.m,
-            r: self.r,
-            mat: self.mat,
-            disco: self.disco,
-            facet_scale: self.facet_scale,
-            mirror: self.mirror,
-            reflectivity: self.reflectivity,
-        })
+        Box::new(Clone::clone(self))
     }
 }
\ No newline at end of file
@@ -1,4 +1,5 @@
 use std::cmp;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
 pub struct Vector3 {
@@ -75,6 +76,57 @@ impl Vector3 {
 		self.z = z;
 	}
 
+	/// Unit vector in the same direction, see [`Vector3::normalize`] for the in place variant.
+	pub fn normalized(self) -> Vector3 {
+		let mut v = self;
+		v.normalize();
+		v
+	}
+
+	/// Cross product `self x p`, see [`Vector3::cross`] for the in place variant.
+	pub fn crossed(self, p: Vector3) -> Vector3 {
+		let mut v = self;
+		v.cross(p);
+		v
+	}
+
+	/// Linear interpolation, `self` at `t = 0` and `p` at `t = 1`.
+	pub fn lerp(self, p: Vector3, t: f64) -> Vector3 {
+		self + (p - self) * t
+	}
+
+	/// Mirrors the direction `self` at a surface with the unit `normal`.
+	pub fn reflect(self, normal: Vector3) -> Vector3 {
+		self - normal * (2.0 * self.dt(normal))
+	}
+
+	/// Unit direction of the unit vector `self` after passing a surface with the unit `normal`
+	/// facing against it, `eta` is the ratio of the indices of refraction. `None` on total
+	/// internal reflection.
+	pub fn refract(self, normal: Vector3, eta: f64) -> Option<Vector3> {
+		let cos_i = -self.dt(normal);
+		let sin2_t = eta * eta * (1.0 - cos_i * cos_i);
+		if sin2_t > 1.0 {
+			return None;
+		}
+		let cos_t = f64::sqrt(1.0 - sin2_t);
+		Some((self * eta + normal * (eta * cos_i - cos_t)).normalized())
+	}
+
+	/// Component-wise minimum.
+	pub fn min(self, p: Vector3) -> Vector3 {
+		Vector3::new(self.x.min(p.x), self.y.min(p.y), self.z.min(p.z))
+	}
+
+	/// Component-wise maximum.
+	pub fn max(self, p: Vector3) -> Vector3 {
+		Vector3::new(self.x.max(p.x), self.y.max(p.y), self.z.max(p.z))
+	}
+
+	pub fn abs(self) -> Vector3 {
+		Vector3::new(self.x.abs(), self.y.abs(), self.z.abs())
+	}
+
 	pub fn rot_by(&mut self, p0 : Vector3, r: Vector3)  {
 		self.subtract(p0);
 		self.rotate(r);
@@ -132,4 +184,110 @@ impl Vector3 {
 		 	     + tp.y * (cos_x * sin_y * sin_z - sin_x * cos_z)
 		  	     + tp.z * (cos_x * cos_y);
 	}
-}
\ No newline at end of file
+}
+
+impl From<[f64; 3]> for Vector3 {
+	fn from(v: [f64; 3]) -> Self {
+		Vector3::new(v[0], v[1], v[2])
+	}
+}
+
+impl From<Vector3> for [f64; 3] {
+	fn from(v: Vector3) -> Self {
+		[v.x, v.y, v.z]
+	}
+}
+
+impl Add for Vector3 {
+	type Output = Vector3;
+	fn add(self, p: Vector3) -> Vector3 {
+		Vector3::new(self.x + p.x, self.y + p.y, self.z + p.z)
+	}
+}
+
+impl Sub for Vector3 {
+	type Output = Vector3;
+	fn sub(self, p: Vector3) -> Vector3 {
+		Vector3::new(self.x - p.x, self.y - p.y, self.z - p.z)
+	}
+}
+
+impl Neg for Vector3 {
+	type Output = Vector3;
+	fn neg(self) -> Vector3 {
+		Vector3::new(-self.x, -self.y, -self.z)
+	}
+}
+
+impl Mul<f64> for Vector3 {
+	type Output = Vector3;
+	fn mul(self, s: f64) -> Vector3 {
+		Vector3::new(self.x * s, self.y * s, self.z * s)
+	}
+}
+
+impl Mul<Vector3> for f64 {
+	type Output = Vector3;
+	fn mul(self, p: Vector3) -> Vector3 {
+		p * self
+	}
+}
+
+/// Component-wise product.
+impl Mul for Vector3 {
+	type Output = Vector3;
+	fn mul(self, p: Vector3) -> Vector3 {
+		Vector3::new(self.x * p.x, self.y * p.y, self.z * p.z)
+	}
+}
+
+impl Div<f64> for Vector3 {
+	type Output = Vector3;
+	fn div(self, s: f64) -> Vector3 {
+		Vector3::new(self.x / s, self.y / s, self.z / s)
+	}
+}
+
+/// Component-wise quotient.
+impl Div for Vector3 {
+	type Output = Vector3;
+	fn div(self, p: Vector3) -> Vector3 {
+		Vector3::new(self.x / p.x, self.y / p.y, self.z / p.z)
+	}
+}
+
+impl AddAssign for Vector3 {
+	fn add_assign(&mut self, p: Vector3) {
+		*self = *self + p;
+	}
+}
+
+impl SubAssign for Vector3 {
+	fn sub_assign(&mut self, p: Vector3) {
+		*self = *self - p;
+	}
+}
+
+impl MulAssign<f64> for Vector3 {
+	fn mul_assign(&mut self, s: f64) {
+		*self = *self * s;
+	}
+}
+
+impl MulAssign for Vector3 {
+	fn mul_assign(&mut self, p: Vector3) {
+		*self = *self * p;
+	}
+}
+
+impl DivAssign<f64> for Vector3 {
+	fn div_assign(&mut self, s: f64) {
+		*self = *self / s;
+	}
+}
+
+impl DivAssign for Vector3 {
+	fn div_assign(&mut self, p: Vector3) {
+		*self = *self / p;
+	}
+}
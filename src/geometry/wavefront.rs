@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use image::ImageError;
+use sdl2::pixels::Color;
+
+use crate::engine::lighting::Material;
+use crate::engine::utils::radiance::Radiance;
+use crate::engine::utils::texture::Texture;
+use crate::geometry::face::{Face as F, UV};
+use crate::geometry::simplex3d::{MeshMaterial, Simplex3D};
+use crate::geometry::vector3::Vector3 as V3;
+
+/// Error while reading a `.obj` or `.mtl` file. Parse and texture errors carry the 1-based
+/// line number.
+#[derive(Debug)]
+pub enum ObjError {
+    Io { path: PathBuf, source: io::Error },
+    Parse { path: PathBuf, line: usize, message: String },
+    Texture { path: PathBuf, line: usize, source: Box<ImageError> },
+    NoFaces { path: PathBuf },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            ObjError::Parse { path, line, message } => write!(f, "{}:{}: {}", path.display(), line, message),
+            ObjError::Texture { path, line, source } => write!(f, "{}:{}: {}", path.display(), line, source),
+            ObjError::NoFaces { path } => write!(f, "{}: no faces found", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ObjError::Io { source, .. } => Some(source),
+            ObjError::Texture { source, .. } => Some(source.as_ref()),
+            ObjError::Parse { .. } | ObjError::NoFaces { .. } => None,
+        }
+    }
+}
+
+/// Kd used by Blender when a material does not specify one.
+const DEFAULT_KD: Color = Color::RGB(204, 204, 204);
+
+/// Loads a Wavefront `.obj` file including the materials of its `mtllib` statements.
+/// Textures (`map_Kd`) are resolved relative to the `.mtl` file, a texture that cannot be
+/// loaded fails the mesh like a missing `.mtl` does.
+pub fn load_obj<P: AsRef<Path>>(path: P) -> Result<Simplex3D, ObjError> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|source| ObjError::Io { path: path.to_path_buf(), source })?;
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    parse_obj(BufReader::new(file), path, dir)
+}
+
+/// Parses `.obj` data from `reader`. `path` is only used for error messages,
+/// `mtllib` statements are resolved relative to `dir`.
+pub fn parse_obj<R: BufRead>(reader: R, path: &Path, dir: &Path) -> Result<Simplex3D, ObjError> {
+    let mut vertices: Vec<V3> = Vec::new();
+    let mut uvs: Vec<(f64, f64)> = Vec::new();
+    let mut normals: Vec<V3> = Vec::new();
+
+    let mut faces: Vec<F> = Vec::new();
+    let mut texture_map: Vec<UV> = Vec::new();
+
+    let mut materials: Vec<MeshMaterial> = Vec::new();
+    let mut material_ids: HashMap<String, usize> = HashMap::new();
+    let mut current: Option<usize> = None;
+
+    for (i, line) in reader.lines().enumerate() {
+        let line_nr = i + 1;
+        let line = line.map_err(|source| ObjError::Io { path: path.to_path_buf(), source })?;
+        let err = |message: String| ObjError::Parse { path: path.to_path_buf(), line: line_nr, message };
+
+        let line = strip_comment(&line);
+        let mut es = line.split_whitespace();
+        let keyword = match es.next() {
+            Some(k) => k,
+            None => continue,
+        };
+
+        match keyword {
+            "v" => {
+                let v = parse_floats(&mut es, 3, 3).map_err(err)?;
+                vertices.push(V3::new(v[0], v[1], v[2]));
+            }
+            "vt" => {
+                let v = parse_floats(&mut es, 1, 2).map_err(err)?;
+                uvs.push((v[0], *v.get(1).unwrap_or(&0.0)));
+            }
+            "vn" => {
+                let v = parse_floats(&mut es, 3, 3).map_err(err)?;
+                normals.push(V3::new(v[0], v[1], v[2]));
+            }
+            "f" => {
+                let mut corners = Vec::new();
+                for token in es {
+                    corners.push(parse_corner(token, vertices.len(), uvs.len(), normals.len()).map_err(err)?);
+                }
+                if corners.len() < 3 {
+                    return Err(err(format!("face needs at least 3 vertices, got {}", corners.len())));
+                }
+
+                // fan triangulation, fine for the convex polygons exporters write
+                for k in 1..corners.len() - 1 {
+                    let (r, a, b) = (corners[0], corners[k], corners[k + 1]);
+                    let mut f = F::new(vertices[r.0], vertices[a.0], vertices[b.0]);
+                    f.material = current;
+
+                    if let (Some(nr), Some(na), Some(nb)) = (r.2, a.2, b.2) {
+                        f.set_vertex_normals([normals[nr], normals[na], normals[nb]]);
+                    }
+
+                    let uv = match (r.1, a.1, b.1) {
+                        (Some(tr), Some(ta), Some(tb)) => UV { r: uvs[tr], a: uvs[ta], b: uvs[tb] },
+                        _ => UV::empty(),
+                    };
+
+                    faces.push(f);
+                    texture_map.push(uv);
+                }
+            }
+            "mtllib" => {
+                let names: Vec<&str> = es.collect();
+                if names.is_empty() {
+                    return Err(err(String::from("mtllib without file name")));
+                }
+                for name in names {
+                    for m in load_mtl(dir.join(name))? {
+                        material_ids.insert(m.name.clone(), materials.len());
+                        materials.push(m);
+                    }
+                }
+            }
+            "usemtl" => {
+                let name = es.next().ok_or_else(|| err(String::from("usemtl without material name")))?;
+                // unknown names get a default material, like most viewers do
+                let id = *material_ids.entry(name.to_string()).or_insert_with(|| {
+                    materials.push(MeshMaterial::new(name.to_string(), Material::new(DEFAULT_KD, 1.0)));
+                    materials.len() - 1
+                });
+                current = Some(id);
+            }
+            // grouping, smoothing and free-form statements do not affect the triangles
+            _ => {}
+        }
+    }
+
+    if faces.is_empty() {
+        return Err(ObjError::NoFaces { path: path.to_path_buf() });
+    }
+
+    let mut middle = V3::empty();
+    for v in vertices.iter() {
+        middle.add(*v);
+    }
+    middle.scale(1.0 / vertices.len() as f64);
+
+    let mut mesh = Simplex3D::new(middle, faces);
+    mesh.tm = texture_map;
+    mesh.materials = materials;
+    Ok(mesh)
+}
+
+/// Loads all materials of a `.mtl` file. Supported are `Kd`, `Ke`, `Ns`, `Ni`, `d`/`Tr`, the
+/// PBR extension `Pr`/`Pm` and the texture maps `map_Kd` and `map_Pr`.
+pub fn load_mtl<P: AsRef<Path>>(path: P) -> Result<Vec<MeshMaterial>, ObjError> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|source| ObjError::Io { path: path.to_path_buf(), source })?;
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    parse_mtl(BufReader::new(file), path, dir)
+}
+
+/// Parses `.mtl` data from `reader`, textures are resolved relative to `dir`.
+pub fn parse_mtl<R: BufRead>(reader: R, path: &Path, dir: &Path) -> Result<Vec<MeshMaterial>, ObjError> {
+    let mut materials: Vec<MeshMaterial> = Vec::new();
+
+    for (i, line) in reader.lines().enumerate() {
+        let line_nr = i + 1;
+        let line = line.map_err(|source| ObjError::Io { path: path.to_path_buf(), source })?;
+        let err = |message: String| ObjError::Parse { path: path.to_path_buf(), line: line_nr, message };
+
+        let line = strip_comment(&line);
+        let mut es = line.split_whitespace();
+        let keyword = match es.next() {
+            Some(k) => k,
+            None => continue,
+        };
+
+        if keyword == "newmtl" {
+            let name = es.next().ok_or_else(|| err(String::from("newmtl without material name")))?;
+            materials.push(MeshMaterial::new(name.to_string(), Material::new(DEFAULT_KD, 1.0)));
+            continue;
+        }
+
+        let m = match materials.last_mut() {
+            Some(m) => m,
+            None => return Err(err(format!("'{}' before the first newmtl", keyword))),
+        };
+
+        match keyword {
+            "Kd" => {
+                let v = parse_floats(&mut es, 3, 3).map_err(err)?;
+                m.material.albedo = Color::RGB(to_u8(v[0]), to_u8(v[1]), to_u8(v[2]));
+            }
+            "Ke" => {
+                let v = parse_floats(&mut es, 3, 3).map_err(err)?;
+                m.material.emission = Radiance::new(v[0], v[1], v[2]);
+            }
+            "Ns" => {
+                // phong exponent to roughness, the inverse of `Material::shininess`
+                let v = parse_floats(&mut es, 1, 1).map_err(err)?;
+                m.material.roughness = f64::sqrt(2.0 / (v[0].max(0.0) + 2.0)).sqrt();
+            }
+            "Ni" => {
+                let v = parse_floats(&mut es, 1, 1).map_err(err)?;
+                m.material.ior = v[0];
+            }
+            "d" => {
+                let v = parse_floats(&mut es, 1, 1).map_err(err)?;
+                m.material.transmission = (1.0 - v[0]).clamp(0.0, 1.0);
+            }
+            "Tr" => {
+                let v = parse_floats(&mut es, 1, 1).map_err(err)?;
+                m.material.transmission = v[0].clamp(0.0, 1.0);
+            }
+            "Pr" => {
+                let v = parse_floats(&mut es, 1, 1).map_err(err)?;
+                m.material.roughness = v[0].clamp(0.0, 1.0);
+            }
+            "Pm" => {
+                let v = parse_floats(&mut es, 1, 1).map_err(err)?;
+                m.material.metalness = v[0].clamp(0.0, 1.0);
+            }
+            "map_Kd" | "map_Pr" => {
+                let file = texture_file(line.trim()[keyword.len()..].trim_start())
+                    .ok_or_else(|| err(format!("{} without file name", keyword)))?;
+                let file = file.replace('\\', "/");
+                let texture = Texture::load(dir.join(&file))
+                    .map_err(|source| ObjError::Texture { path: path.to_path_buf(), line: line_nr, source: Box::new(source) })?;
+                if keyword == "map_Kd" {
+                    m.material.albedo_texture = Some(Arc::new(texture));
+                } else {
+                    m.material.roughness_texture = Some(Arc::new(texture));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(materials)
+}
+
+/// File name of a texture statement after the keyword: options (`-s 1 1 1`, `-clamp on`, ...)
+/// come first, the rest of the line is the file name and may contain spaces.
+fn texture_file(mut args: &str) -> Option<&str> {
+    while args.starts_with('-') {
+        let (option, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+        args = rest.trim_start();
+        let values = match option {
+            // up to three numbers
+            "-o" | "-s" | "-t" => 3,
+            "-mm" => 2,
+            _ => 1,
+        };
+        for i in 0..values {
+            let (value, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+            if value.is_empty() || (i > 0 && value.parse::<f64>().is_err()) {
+                break;
+            }
+            args = rest.trim_start();
+        }
+    }
+    if args.is_empty() {
+        None
+    } else {
+        Some(args)
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    match line.find('#') {
+        Some(i) => &line[..i],
+        None => line,
+    }
+}
+
+fn to_u8(c: f64) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn parse_floats<'a, I: Iterator<Item = &'a str>>(es: &mut I, min: usize, max: usize) -> Result<Vec<f64>, String> {
+    let mut v = Vec::new();
+    for token in es.take(max) {
+        v.push(token.parse::<f64>().map_err(|_| format!("invalid number '{}'", token))?);
+    }
+    if v.len() < min {
+        return Err(format!("expected {} numbers, got {}", min, v.len()));
+    }
+    Ok(v)
+}
+
+/// Resolves a 1-based (or negative, relative to the end) index into a 0-based one.
+fn resolve_index(token: &str, len: usize, what: &str) -> Result<usize, String> {
+    let i: i64 = token.parse().map_err(|_| format!("invalid {} index '{}'", what, token))?;
+    let resolved = if i > 0 { i - 1 } else { len as i64 + i };
+    if i == 0 || resolved < 0 || resolved >= len as i64 {
+        return Err(format!("{} index {} out of range (have {})", what, i, len));
+    }
+    Ok(resolved as usize)
+}
+
+/// Parses one `v`, `v/vt`, `v//vn` or `v/vt/vn` face corner.
+fn parse_corner(token: &str, nv: usize, nt: usize, nn: usize) -> Result<(usize, Option<usize>, Option<usize>), String> {
+    let mut parts = token.split('/');
+    let v = resolve_index(parts.next().unwrap_or(""), nv, "vertex")?;
+    let t = match parts.next() {
+        Some(s) if !s.is_empty() => Some(resolve_index(s, nt, "texture coordinate")?),
+        _ => None,
+    };
+    let n = match parts.next() {
+        Some(s) if !s.is_empty() => Some(resolve_index(s, nn, "normal")?),
+        _ => None,
+    };
+    if parts.next().is_some() {
+        return Err(format!("invalid face corner '{}'", token));
+    }
+    Ok((v, t, n))
+}
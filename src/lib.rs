@@ -3,9 +3,14 @@ extern crate core;
 
 pub mod engine {
     pub mod camera;
+    pub mod tile_renderer;
+    pub mod progressive;
     pub mod projection_camera;
     pub mod pathtracing;
     pub mod raymarching;
+    pub mod scene_file;
+    pub mod scene_graph;
+    pub mod instance;
     pub mod lighting;
     pub mod gameplay {
         pub mod movement;
@@ -13,6 +18,7 @@ pub mod engine {
     pub mod projection {
         pub mod projection;
         pub mod raster;
+        pub mod rasterizer;
     }
     pub mod utils {
         pub mod anker_label;
@@ -21,6 +27,16 @@ pub mod engine {
         pub mod rendering_ui;
         pub mod js_canvas;
         pub mod virtual_canvas;
+        pub mod texture;
+        pub mod random;
+        pub mod sampling;
+        pub mod environment_map;
+        pub mod radiance;
+        pub mod accumulator;
+        pub mod antialiasing;
+        pub mod hdr;
+        pub mod aov;
+        pub mod denoise;
         pub mod raster_sorting;
     }
     pub mod simplex3d_sphere_tree {
@@ -28,6 +44,21 @@ pub mod engine {
         pub mod poly_tree_element;
         pub mod poly_tree_utils;
     }
+    pub mod simplex3d_bvh {
+        pub mod bvh;
+        pub mod bvh_node;
+        pub mod tlas;
+    }
+    pub mod sdf {
+        pub mod primitives;
+        pub mod operators;
+        pub mod domain;
+    }
+    pub mod voxels {
+        pub mod chunk;
+        pub mod voxel_world;
+        pub mod terrain;
+    }
     pub mod drawing {
         pub mod drawing;
         pub mod lines;
@@ -38,8 +69,11 @@ pub mod engine {
 
 pub mod geometry {
     pub mod simplex3d;
+    pub mod wavefront;
     pub mod face;
+    pub mod aabb;
     pub mod vector3;
+    pub mod quaternion;
     pub mod point;
     pub mod cube;
     pub mod quad;
@@ -70,6 +104,7 @@ pub mod math {
 
     pub mod vector;
     pub mod functions;
+    pub mod noise;
     pub mod utils {
         pub mod graph_utils;
         pub mod assertion_utils;
@@ -19,12 +19,14 @@ use rust3d::engine::lighting::Material;
 use rust3d::engine::raymarching::RayMarchingScene;
 use rust3d::engine::utils::rendering::RaySphereable;
 use rust3d::engine::utils::rendering_ui::UiElement;
-use rust3d::engine::utils::transformation::{PI, TWO_PI};
+use rust3d::engine::utils::transformation::{PI, TWO_PI, Watched};
 use rust3d::engine::utils::{rendering::{RayRenderScene, RayRenderable}, transformation::Transformable};
 use rust3d::geometry::face::Face;
 use rust3d::geometry::quad::Quad;
 use rust3d::geometry::vector3::Vector3 as V;
 use rust3d::engine::camera::RayCamera;
+use rust3d::engine::utils::virtual_canvas::VirtualCanvas;
+use rust3d::engine::progressive::ProgressiveRenderer;
 use rust3d::engine::pathtracing::PathTracingScene;
 use rust3d::engine::pathtracing::PathtracingObject;
 use rust3d::geometry::simplex3d::Simplex3D;
@@ -37,7 +39,6 @@ const W : usize = 500;
 const H : usize = 500;
 const FRAMERATE : u32 = 60;
 const NANOS : u32 = 1_000_000_000 / FRAMERATE;
-const VARIABLE_RENDER_SPEED : u8 = 35;
 const TURN_SPEED : f64 = 0.0035;
 
 /// ## TODO
@@ -50,7 +51,7 @@ const TURN_SPEED : f64 = 0.0035;
 /// - [x] maybe stop rendering when nothing changes
 /// - [ ] implement goto for transformable
 /// - [x] rot_reverse is buggy for simplex3d_sphere_tree
-/// - [ ] implement AABB Tree as drop-in Replacement for the sphere-tree -> BVH
+/// - [x] implement AABB Tree as drop-in Replacement for the sphere-tree -> BVH
 /// - [ ] replace the r_ etc. in the constructors with corrected names → M{r: r_} → M{r}.
 ///
 /// ## Ideas / Directions for future development
@@ -108,7 +109,7 @@ pub fn main() -> Result<(), String>{
     //t1.scale(V{x: 0.1, y: 0.1, z: 0.1});
     pa_objs.add(t1);
 
-    let mut g1 = Graph3D::new(p1, FunctionR2ToR::new(Box::new(|x, y| - x*x -  y*y + 1.0)), vec!["x", "y", "z"]);
+    let mut g1 = Graph3D::new(Clone::clone(&p1), FunctionR2ToR::new(Box::new(|x, y| - x*x -  y*y + 1.0)), vec!["x", "y", "z"]);
     g1.rot(V{x: PI / 2., y: 0.0, z: 0.0});
     let root = p1.x[7];
     let mut label1 = rust3d::engine::utils::anker_label::AnkerLabel::new(root.x, root.y, root.z, String::from("Root"), &font, 24.0, Color::RED, Color::WHITE);
@@ -117,15 +118,13 @@ pub fn main() -> Result<(), String>{
     //rm_objs.add(s1);
 
     let rm_objs = Arc::new(RwLock::new(rm_objs));
-    let pa_objs = Arc::new(RwLock::new(pa_objs));   
+    let pa_objs = Arc::new(RwLock::new(Watched::new(pa_objs)));   
     
 	let mut camera : RayCamera = RayCamera::new(V{x: -3.0, y: 0.0, z: 0.0}, 0.0, 0.0, 0.0);
     
-    let mut stage = 1;
-    let mut modulus_size = 300;
-    let mut change_modulus = 0;
-    let mut block_size = 20;
-    let mut motion = true; //first render without this condition
+    // starts over by itself whenever the camera moves or the scene changes
+    let mut progressive = ProgressiveRenderer::new(W, H, 8);
+    let mut objs: Option<(u64, Arc<RayRenderScene>)> = None;
     //println!("Starting main Loop");
     'running: loop {
         for event in event_pump.poll_iter() {
@@ -147,11 +146,6 @@ pub fn main() -> Result<(), String>{
             //println!("Relative - X = {:?}, Y = {:?}", state.x(), state.y());
             //let rot_z = TURN_SPEED * state.y() as f64;
             let rot_y = TURN_SPEED * state.x() as f64;
-            if (rot_y != 0.0) { //rot_z != 0.0 || 
-                motion = true;
-                //stage = 1;
-                block_size = 10;
-            }   
             /*
             if (rot_z > 0.0) {
                 g1.rot(V{x: 0.0, y: 0.0, z: rot_z});
@@ -160,13 +154,9 @@ pub fn main() -> Result<(), String>{
                 g1.rot_reverse(V{x: 0.0, y: 0.0, z: - rot_z});
             }*/
 
-            if (rot_y > 0.0) {
-                //g1.rot(V{x: 0.0, y: rot_y, z: 0.0});
-                pa_objs.write().unwrap().get(0).rot(V{x: 0.0, y: rot_y, z: 0.0});
-            }
-            else {
-                //g1.rot_reverse(V{x: 0.0, y: - rot_y, z: 0.0});
-                pa_objs.write().unwrap().get(0).rot(V{x: 0.0, y: rot_y, z: 0.0});
+            // rotating by 0 would still count as a change and restart the preview
+            if rot_y != 0.0 {
+                pa_objs.write().unwrap().get_mut().get(0).rot(V{x: 0.0, y: rot_y, z: 0.0});
             }
         }
 
@@ -180,59 +170,19 @@ pub fn main() -> Result<(), String>{
         //rm_objs.write().unwrap().get(1).translate(V{x: 0.01, y: 0.01, z: 0.01});
 
        
-        let mut objs: RayRenderScene = RayRenderScene::new();
-        
-        objs.wrap(Box::new(PathTracingScene::wrapup(&pa_objs.read().unwrap())));
-        //objs.wrap(Box::new(RayMarchingObjects::wrapup(&rm_objs.read().unwrap())));
-        //objs.wrap(Box::new(Graph3D::wrapup(&g1)));
-        //camera.rot(V{x: 0.0, y: 0.1, z: 0.0});
-        
-        //println!("transformation took {}ms", now.elapsed().as_millis());
-        
-        //println!("Starting rendering {} {}" , stage, modulus_size);
+        // take a new snapshot of the scene only after it changed
+        let version = pa_objs.read().unwrap().version();
+        if objs.as_ref().map_or(true, |(v, _)| *v != version) {
+            let mut scene: RayRenderScene = RayRenderScene::new();
+            scene.wrap(Box::new(PathTracingScene::wrapup(pa_objs.read().unwrap().get())));
+            //scene.wrap(Box::new(RayMarchingObjects::wrapup(&rm_objs.read().unwrap())));
+            //scene.wrap(Box::new(Graph3D::wrapup(&g1)));
+            objs = Some((version, Arc::new(scene)));
+        }
+        let scene = Arc::clone(&objs.as_ref().unwrap().1);
 
-        //render_multi(&mut canvas, objs, camera, &W, &H);
-        //canvas.present();
-        if (motion) {
-            render_multi(&mut canvas, objs, camera, &W, &H);
-            canvas.present();
-            /*camera.render_and_draw_modulus_block(&mut canvas, &objs, block_size, stage, modulus_size / block_size, W, H);
-
-            let diff = now.elapsed().as_nanos();
-            if ((diff as u32) < NANOS) {
-                ::std::thread::sleep(Duration::new(0, NANOS - diff as u32));
-                if (modulus_size > 1) {
-                    change_modulus -= 1;
-                }
-            }
-            else {
-                change_modulus += 1;
-            }
-            stage += 1;
-            if (stage >= modulus_size / block_size) {
-                stage = 0;
-                if (block_size > 1) {
-                    block_size /= 2;
-                    if block_size < 1 {
-                        block_size = 1;
-                        if (change_modulus > 0) {
-                            modulus_size += VARIABLE_RENDER_SPEED as usize;
-                        }
-                        else if (change_modulus < 0) {
-                            modulus_size -= VARIABLE_RENDER_SPEED as usize;
-                            if (modulus_size < 1) {
-                                modulus_size = 1;
-                            }
-                        }
-                        change_modulus = 0;
-                    }
-                }
-                else {
-                    motion = false;
-                    //camera.render_anker_labels(&g1, &mut canvas, W, H);
-                }
-            }
-            */
+        if (progressive.step(&camera, scene, version)) {
+            progressive.frame().present(&mut canvas);
             canvas.present();
         }
         else {
@@ -246,47 +196,7 @@ pub fn render_mod(canvas : &mut Canvas<Window>, objs : RayRenderScene, camera :
     let w = canvas.window().drawable_size().0 as usize;
     let h = canvas.window().drawable_size().1 as usize;
     let section = camera.render_modulus(&objs, w, h, stage, modulus);
-    camera.draw_modulus(&section, canvas, stage, modulus, *w_, *h_);
-}
-
-pub fn render_multi(canvas : &mut Canvas<Window>, objs : RayRenderScene, camera : RayCamera, w_ : &usize, h_ : &usize) {
-    //let w = canvas.window().drawable_size().0 as usize;
-    //let h = canvas.window().drawable_size().1 as usize;
-    //canvas.clear();
-    //println!("Setting up threads...");
-    let now = Instant::now();
-
-    let (tx, rx) = mpsc::channel::<(usize, Vec<Color>)>();
-    let n = 8;
-    let camera_arc = Arc::new(camera);
-    let objs =  Arc::new(objs);
-
-    for i in 0..n {
-        let camera_arc = Arc::clone(&camera_arc);
-        let objs = Arc::clone(&objs);
-        let tx = tx.clone();
-        let w_ = w_.clone();
-        let h_ = h_.clone();
-
-        thread::spawn(move || {
-            //let obj_mutex_cloned = Arc::clone(&obj_mutex_cloned);
-            let section = camera_arc.render_modulus_multi(objs, w_, h_, i, n);
-            tx.send((i.to_owned(), section));
-        });
-    }
-    //println!("Setup took {}ms", now.elapsed().as_millis());
-    //println!("Started rendering without issues");
-    let now = Instant::now();
-
-    for i in 0..n {
-        let section = rx.recv().unwrap();
-
-        camera.draw_modulus(&section.1, canvas, section.0, n, *w_, *h_);
-
-        //println!("Thread {} finished rendering", section.0);
-    }
-
-    //println!("Render took {}ms", now.elapsed().as_millis());
+    let mut layer = VirtualCanvas::new(w, h);
+    camera.draw_modulus(&section, &mut layer, stage, modulus);
+    layer.present(canvas);
 }
-
-    
@@ -64,9 +64,9 @@ impl Graph3D {
         axis_.add(line2);
         axis_.add(line3);
         Graph3D {
-            content: f.create_graph(bounds, 0.05),
-            bounds,
+            content: f.create_graph(Clone::clone(&bounds), 0.05),
             m : bounds.m,
+            bounds,
             color: Color::WHITE,
             axis: axis_,
             grid: Vec::new(),
@@ -0,0 +1,94 @@
+/// Classic (improved) Perlin gradient noise with a seeded permutation table.
+#[derive(Debug, Clone)]
+pub struct PerlinNoise {
+    perm: [u8; 512],
+}
+
+impl PerlinNoise {
+    pub fn new(seed: u64) -> Self {
+        let mut p: [u8; 256] = [0; 256];
+        for (i, v) in p.iter_mut().enumerate() {
+            *v = i as u8;
+        }
+
+        // Fisher-Yates with a small LCG, so the same seed always gives the same terrain
+        let mut state = seed ^ 0x9E37_79B9_7F4A_7C15;
+        for i in (1..256).rev() {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let j = ((state >> 33) % (i as u64 + 1)) as usize;
+            p.swap(i, j);
+        }
+
+        let mut perm = [0; 512];
+        for i in 0..512 {
+            perm[i] = p[i & 255];
+        }
+        PerlinNoise { perm }
+    }
+
+    fn fade(t: f64) -> f64 {
+        t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+    }
+
+    fn lerp(t: f64, a: f64, b: f64) -> f64 {
+        a + t * (b - a)
+    }
+
+    fn grad(hash: u8, x: f64, y: f64, z: f64) -> f64 {
+        let h = hash & 15;
+        let u = if h < 8 { x } else { y };
+        let v = if h < 4 {
+            y
+        } else if h == 12 || h == 14 {
+            x
+        } else {
+            z
+        };
+        (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+    }
+
+    /// Noise value in roughly [-1, 1].
+    pub fn noise3(&self, x: f64, y: f64, z: f64) -> f64 {
+        let xi = (x.floor() as i64 & 255) as usize;
+        let yi = (y.floor() as i64 & 255) as usize;
+        let zi = (z.floor() as i64 & 255) as usize;
+        let (x, y, z) = (x - x.floor(), y - y.floor(), z - z.floor());
+        let (u, v, w) = (Self::fade(x), Self::fade(y), Self::fade(z));
+
+        let p = &self.perm;
+        let a = p[xi] as usize + yi;
+        let aa = p[a] as usize + zi;
+        let ab = p[a + 1] as usize + zi;
+        let b = p[xi + 1] as usize + yi;
+        let ba = p[b] as usize + zi;
+        let bb = p[b + 1] as usize + zi;
+
+        Self::lerp(w,
+            Self::lerp(v,
+                Self::lerp(u, Self::grad(p[aa], x, y, z), Self::grad(p[ba], x - 1.0, y, z)),
+                Self::lerp(u, Self::grad(p[ab], x, y - 1.0, z), Self::grad(p[bb], x - 1.0, y - 1.0, z))),
+            Self::lerp(v,
+                Self::lerp(u, Self::grad(p[aa + 1], x, y, z - 1.0), Self::grad(p[ba + 1], x - 1.0, y, z - 1.0)),
+                Self::lerp(u, Self::grad(p[ab + 1], x, y - 1.0, z - 1.0), Self::grad(p[bb + 1], x - 1.0, y - 1.0, z - 1.0))))
+    }
+
+    pub fn noise2(&self, x: f64, y: f64) -> f64 {
+        self.noise3(x, y, 0.5)
+    }
+
+    /// Fractal brownian motion: `octaves` layers of noise, each `lacunarity` times the frequency
+    /// and `gain` times the amplitude of the previous one. Normalized to roughly [-1, 1].
+    pub fn fbm2(&self, x: f64, y: f64, octaves: usize, lacunarity: f64, gain: f64) -> f64 {
+        let mut sum = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        let mut norm = 0.0;
+        for _ in 0..octaves {
+            sum += amplitude * self.noise2(x * frequency, y * frequency);
+            norm += amplitude;
+            amplitude *= gain;
+            frequency *= lacunarity;
+        }
+        if norm > 0.0 { sum / norm } else { 0.0 }
+    }
+}
@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use sdl2::pixels::Color;
+    use rust3d::engine::camera::RayCamera;
+    use rust3d::engine::utils::antialiasing::{Filter, Supersampling};
+    use rust3d::engine::utils::random::Rng;
+    use rust3d::engine::utils::rendering::{Collision, RayRenderable};
+    use rust3d::engine::utils::virtual_canvas::VirtualCanvas;
+    use rust3d::geometry::vector3::Vector3 as V3;
+
+    /// White on one side of a slanted edge through the image center, a black miss on the other.
+    struct Edge {
+        rays: AtomicUsize,
+    }
+
+    impl Edge {
+        fn new() -> Self {
+            Edge { rays: AtomicUsize::new(0) }
+        }
+    }
+
+    impl RayRenderable for Edge {
+        fn get_collision(&self, p0: V3, p: V3, _radius: f64) -> Collision {
+            self.rays.fetch_add(1, Ordering::Relaxed);
+            let mut c = Collision::empty();
+            c.p = p0;
+            if p.z + 0.3 * p.y > 0.0 {
+                c.hit = true;
+                c.c = Color::WHITE;
+            } else {
+                c.c = Color::BLACK;
+            }
+            c
+        }
+    }
+
+    const W: usize = 32;
+    const H: usize = 24;
+
+    fn camera(supersampling: Supersampling) -> RayCamera {
+        let mut camera = RayCamera::new(V3::new(0.0, 0.0, 0.0), 0.0, 0.0, 0.0);
+        camera.set_supersampling(supersampling);
+        camera
+    }
+
+    fn is_gray(c: Color) -> bool {
+        c.g > 0 && c.g < 255
+    }
+
+    #[test]
+    fn test_filter_weights() {
+        for filter in [Filter::Box, Filter::Tent, Filter::Mitchell] {
+            let r = filter.radius();
+            assert!(filter.weight(0.0, 0.0) > 0.0);
+            assert_eq!(filter.weight(r + 0.01, 0.0), 0.0);
+            assert_eq!(filter.weight(0.0, -r - 0.01), 0.0);
+            assert_eq!(filter.weight(0.3, -0.2), filter.weight(-0.3, 0.2));
+        }
+        assert_eq!(Filter::Box.weight(0.49, -0.49), 1.0);
+        assert!((Filter::Tent.weight(0.5, 0.5) - 0.25).abs() < 1e-12);
+        assert!((Filter::Mitchell.weight(0.0, 0.0) - (16.0f64 / 18.0).powi(2)).abs() < 1e-12);
+        // negative lobe
+        assert!(Filter::Mitchell.weight(1.5, 0.0) < 0.0);
+
+        // the samples cover the footprint of the filter
+        let mut supersampling = Supersampling::jittered(4);
+        supersampling.set_filter(Filter::Tent);
+        let samples = supersampling.samples(&mut Rng::new(7));
+        assert_eq!(samples.len(), 16);
+        assert!(samples.iter().all(|&(dx, dy, w)| dx.abs() <= 1.0 && dy.abs() <= 1.0 && w >= 0.0));
+        assert!(samples.iter().map(|s| s.2).sum::<f64>() > 0.0);
+    }
+
+    #[test]
+    fn test_single_ray_by_default() {
+        let edge = Edge::new();
+        let camera = camera(Supersampling::none());
+        let pixels = camera.render_modulus(&edge, W, H, 0, 1);
+        assert_eq!(edge.rays.load(Ordering::Relaxed), W * H);
+        for j in 0..W {
+            for i in 0..H {
+                let v = camera.ray_direction(j as f64 + 0.5, i as f64 + 0.5, W, H);
+                assert_eq!(pixels[j * H + i], edge.get_collision(camera.position, v, 100.0).c);
+            }
+        }
+        assert!(!pixels.iter().any(|&c| is_gray(c)));
+    }
+
+    #[test]
+    fn test_supersampled_edge() {
+        for filter in [Filter::Box, Filter::Tent, Filter::Mitchell] {
+            let mut supersampling = Supersampling::grid(4);
+            supersampling.set_filter(filter);
+            let pixels = camera(supersampling).render_modulus(&Edge::new(), W, H, 0, 1);
+
+            // every row crosses the edge once and gets at least one pixel in between
+            for i in 0..H {
+                let row: Vec<Color> = (0..W).map(|j| pixels[j * H + i]).collect();
+                assert!(row.iter().any(|&c| is_gray(c)), "{:?} row {}", filter, i);
+            }
+            // far away from the edge nothing changes
+            assert_eq!(pixels[0], Color::WHITE);
+            assert_eq!(pixels[W * H - 1], Color::BLACK);
+        }
+    }
+
+    #[test]
+    fn test_multi_matches_single() {
+        let mut supersampling = Supersampling::jittered(3);
+        supersampling.set_filter(Filter::Mitchell);
+        let camera = camera(supersampling);
+        let edge = Arc::new(Edge::new());
+
+        let single = camera.render_modulus(&*edge, W, H, 0, 1);
+        assert_eq!(camera.render_frame_multi(Arc::clone(&edge), W, H, 5), single);
+
+        let mut a = VirtualCanvas::new(W, H);
+        let mut b = VirtualCanvas::new(W, H);
+        camera.render(&*edge, &mut a);
+        camera.render_multi(Arc::clone(&edge), &mut b, 3);
+        assert_eq!(a.pixels, b.pixels);
+    }
+
+    #[test]
+    fn test_adaptive() {
+        let mut adaptive = Supersampling::grid(4);
+        adaptive.set_adaptive(0.1);
+        let edge = Arc::new(Edge::new());
+        let pixels = camera(adaptive).render_frame_multi(Arc::clone(&edge), W, H, 4);
+
+        // one ray per pixel and 16 more only along the edge
+        let rays = edge.rays.load(Ordering::Relaxed);
+        assert!(rays > W * H && rays < W * H + 16 * 4 * H, "{}", rays);
+
+        let full = camera(Supersampling::grid(4)).render_frame_multi(Arc::new(Edge::new()), W, H, 4);
+        for i in 0..H {
+            assert!((0..W).any(|j| is_gray(pixels[j * H + i])));
+            for j in 0..W {
+                if is_gray(pixels[j * H + i]) {
+                    assert_eq!(pixels[j * H + i], full[j * H + i]);
+                }
+            }
+        }
+
+        // the single threaded renderer refines the same pixels
+        let mut canvas = VirtualCanvas::new(W, H);
+        camera(adaptive).render(&*edge, &mut canvas);
+        let mut expected = VirtualCanvas::new(W, H);
+        for j in 0..W {
+            for i in 0..H {
+                expected.draw_sdl2_color(j, i, pixels[j * H + i]);
+            }
+        }
+        assert_eq!(canvas.pixels, expected.pixels);
+    }
+
+    #[test]
+    fn test_section_ignores_misses() {
+        let camera = camera(Supersampling::grid(4));
+        let section = camera.render_section(0, 0, W, H, &Edge::new(), W, H);
+        // misses never darken a pixel, pixels without any hit stay transparent
+        assert!(section.iter().all(|&c| c == Color::WHITE || c.a == 0));
+        assert!(section.iter().any(|&c| c.a == 0));
+        assert!(section.iter().any(|&c| c == Color::WHITE));
+    }
+}
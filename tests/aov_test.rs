@@ -0,0 +1,184 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use sdl2::pixels::Color;
+    use rust3d::engine::camera::RayCamera;
+    use rust3d::engine::lighting::Material;
+    use rust3d::engine::pathtracing::RayTracingScene;
+    use rust3d::engine::raymarching::RayMarchingScene;
+    use rust3d::engine::utils::aov::{AovBuffers, AovSelection};
+    use rust3d::engine::utils::rendering::{Collision, RayRenderScene, RayRenderable, SurfaceHit};
+    use rust3d::engine::utils::virtual_canvas::VirtualCanvas;
+    use rust3d::geometry::sphere::Sphere;
+    use rust3d::geometry::vector3::Vector3 as V3;
+
+    // odd sizes put a pixel center on the view direction
+    const W: usize = 21;
+    const H: usize = 11;
+
+    fn close(a: f64, b: f64, eps: f64) -> bool {
+        (a - b).abs() < eps
+    }
+
+    /// A red ball straight ahead and a blue one further away to the right.
+    fn two_spheres() -> RayTracingScene {
+        let mut scene = RayTracingScene::new();
+        scene.add(Sphere::new(V3::new(3.0, 0.0, 0.0), 1.0, Material::new(Color::RGB(200, 0, 0), 1.0)));
+        scene.add(Sphere::new(V3::new(6.0, 0.0, -4.0), 1.5, Material::new(Color::RGB(0, 0, 200), 1.0)));
+        scene
+    }
+
+    fn camera() -> RayCamera {
+        RayCamera::new(V3::new(0.0, 0.0, 0.0), 0.0, 0.0, 0.0)
+    }
+
+    fn get<T: Copy>(buffer: &Option<Vec<T>>, x: usize, y: usize) -> T {
+        buffer.as_ref().unwrap()[y * W + x]
+    }
+
+    /// Column of the image that looks towards `p`.
+    fn column_of(p: V3) -> usize {
+        (0..W).min_by(|&a, &b| {
+            let angle = |x: usize| {
+                let v = camera().ray_direction(x as f64 + 0.5, H as f64 / 2.0, W, H);
+                let mut p = p;
+                p.normalize();
+                -v.dt(p)
+            };
+            angle(a).partial_cmp(&angle(b)).unwrap()
+        }).unwrap()
+    }
+
+    #[test]
+    fn test_buffers() {
+        let scene = two_spheres();
+        let buffers = camera().render_aovs(&scene, W, H, AovSelection::all());
+        let (cx, cy) = (W / 2, H / 2);
+
+        assert!(close(get(&buffers.depth, cx, cy), 2.0, 1e-6));
+        let n = get(&buffers.normal, cx, cy);
+        assert!(close(n.x, -1.0, 1e-6) && close(n.y, 0.0, 1e-6) && close(n.z, 0.0, 1e-6), "{:?}", n);
+        assert_eq!(get(&buffers.albedo, cx, cy), Color::RGB(200, 0, 0));
+        assert_eq!(get(&buffers.object_id, cx, cy), Some(0));
+        assert!(close(get(&buffers.position, cx, cy).x, 2.0, 1e-6));
+
+        let bx = column_of(V3::new(6.0, 0.0, -4.0));
+        assert!(bx > cx);
+        assert_eq!(get(&buffers.object_id, bx, cy), Some(1));
+        assert_eq!(get(&buffers.albedo, bx, cy), Color::RGB(0, 0, 200));
+        // linear depth is measured along the view direction, not along the ray
+        let p = get(&buffers.position, bx, cy);
+        assert!(close(get(&buffers.depth, bx, cy), p.x, 1e-6));
+        assert!(get(&buffers.depth, bx, cy) > 4.0);
+
+        // nothing in the corners
+        assert_eq!(get(&buffers.depth, 0, 0), f64::INFINITY);
+        assert_eq!(get(&buffers.object_id, 0, 0), None);
+        assert_eq!(get(&buffers.albedo, 0, 0), Color::BLACK);
+
+        // the color image is the one of the plain renderer
+        let mut color = VirtualCanvas::new(W, H);
+        camera().render(&scene, &mut color);
+        assert_eq!(buffers.color.pixels, color.pixels);
+    }
+
+    /// Counts the rays that reach `scene`.
+    struct Counted {
+        scene: RayTracingScene,
+        rays: AtomicUsize,
+    }
+
+    impl RayRenderable for Counted {
+        fn get_collision(&self, p0: V3, p: V3, radius: f64) -> Collision {
+            self.rays.fetch_add(1, Ordering::Relaxed);
+            self.scene.get_collision(p0, p, radius)
+        }
+
+        fn get_surface(&self, p0: V3, p: V3, radius: f64) -> Option<SurfaceHit> {
+            self.rays.fetch_add(1, Ordering::Relaxed);
+            self.scene.get_surface(p0, p, radius)
+        }
+
+        fn get_collision_with_surface(&self, p0: V3, p: V3, radius: f64) -> (Collision, Option<SurfaceHit>) {
+            self.rays.fetch_add(1, Ordering::Relaxed);
+            self.scene.get_collision_with_surface(p0, p, radius)
+        }
+    }
+
+    #[test]
+    fn test_one_ray_per_pixel() {
+        let counted = Arc::new(Counted { scene: two_spheres(), rays: AtomicUsize::new(0) });
+        let buffers = camera().render_aovs(&*counted, W, H, AovSelection::all());
+        assert_eq!(counted.rays.load(Ordering::Relaxed), W * H);
+        let multi = camera().render_aovs_multi(Arc::clone(&counted), W, H, AovSelection::all(), 3);
+        assert_eq!(counted.rays.load(Ordering::Relaxed), 2 * W * H);
+        assert_eq!(multi.object_id, buffers.object_id);
+        assert_eq!(multi.color.pixels, buffers.color.pixels);
+    }
+
+    #[test]
+    fn test_selection_and_multi() {
+        let scene = Arc::new(two_spheres());
+        let selection = AovSelection { depth: true, object_id: true, ..AovSelection::none() };
+        let buffers = camera().render_aovs(&*scene, W, H, selection);
+        assert_eq!(buffers.selection(), selection);
+        assert!(buffers.normal.is_none() && buffers.albedo.is_none() && buffers.position.is_none());
+
+        let multi = camera().render_aovs_multi(Arc::clone(&scene), W, H, selection, 3);
+        assert_eq!(multi.depth, buffers.depth);
+        assert_eq!(multi.object_id, buffers.object_id);
+        assert_eq!(multi.color.pixels, buffers.color.pixels);
+    }
+
+    #[test]
+    fn test_scenes() {
+        // the marcher stops a little in front of the surface
+        let mut marched = RayMarchingScene::new(0.0001);
+        marched.add(Sphere::new(V3::new(3.0, 0.0, 0.0), 1.0, Material::new(Color::RGB(0, 180, 0), 1.0)));
+        marched.add(Sphere::new(V3::new(6.0, 0.0, -4.0), 1.5, Material::new(Color::RGB(0, 0, 200), 1.0)));
+        let buffers = camera().render_aovs(&marched, W, H, AovSelection::all());
+        assert!(close(get(&buffers.depth, W / 2, H / 2), 2.0, 1e-3));
+        assert!(close(get(&buffers.normal, W / 2, H / 2).x, -1.0, 1e-3));
+        assert_eq!(get(&buffers.albedo, W / 2, H / 2), Color::RGB(0, 180, 0));
+        assert_eq!(get(&buffers.object_id, W / 2, H / 2), Some(0));
+        assert_eq!(get(&buffers.object_id, column_of(V3::new(6.0, 0.0, -4.0)), H / 2), Some(1));
+        assert_eq!(get(&buffers.object_id, 0, 0), None);
+
+        // ids of nested scenes follow each other
+        let mut behind = RayTracingScene::new();
+        behind.add(Sphere::new(V3::new(6.0, 0.0, 4.0), 1.5, Material::new(Color::WHITE, 1.0)));
+        let mut scene = RayRenderScene::new();
+        scene.wrap(Box::new(two_spheres()));
+        scene.wrap(Box::new(behind));
+        assert_eq!(scene.object_count(), 3);
+        let buffers = camera().render_aovs(&scene, W, H, AovSelection::all());
+        assert_eq!(get(&buffers.object_id, W / 2, H / 2), Some(0));
+        assert_eq!(get(&buffers.object_id, column_of(V3::new(6.0, 0.0, 4.0)), H / 2), Some(2));
+    }
+
+    #[test]
+    fn test_export() {
+        let buffers: AovBuffers = camera().render_aovs(&two_spheres(), W, H, AovSelection::all());
+        let dir = std::env::temp_dir().join("rust3d_aov_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let written = buffers.save(&dir, "spheres").unwrap();
+        let names: Vec<String> = written.iter().map(|p| p.file_name().unwrap().to_string_lossy().into_owned()).collect();
+        for name in ["color.png", "depth.png", "normal.png", "albedo.png", "object_id.png", "depth.exr", "normal.exr", "position.exr"] {
+            assert!(names.contains(&format!("spheres_{}", name)), "{:?}", names);
+        }
+
+        let depth = image::open(dir.join("spheres_depth.exr")).unwrap().to_rgb32f();
+        assert_eq!(depth.dimensions(), (W as u32, H as u32));
+        assert!(close(depth.get_pixel(W as u32 / 2, H as u32 / 2).0[0] as f64, 2.0, 1e-5));
+
+        // the preview is brightest on the nearest ball and black on the background
+        let preview = image::open(dir.join("spheres_depth.png")).unwrap().to_rgb8();
+        assert_eq!(preview.get_pixel(W as u32 / 2, H as u32 / 2).0, [255, 255, 255]);
+        assert_eq!(preview.get_pixel(0, 0).0, [0, 0, 0]);
+
+        let ids = buffers.object_id_canvas().unwrap();
+        assert_ne!(ids.get_sdl2_color(W / 2, H / 2), ids.get_sdl2_color(column_of(V3::new(6.0, 0.0, -4.0)), H / 2));
+    }
+}
@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use rust3d::engine::pathtracing::PathtracingObject;
+    use rust3d::engine::simplex3d_bvh::bvh::Bvh;
+    use rust3d::engine::utils::transformation::Transformable;
+    use rust3d::geometry::face::Face;
+    use rust3d::geometry::simplex3d::Simplex3D;
+    use rust3d::geometry::vector3::Vector3 as V3;
+
+    /// Height field over a grid, enough faces to force several levels of the tree.
+    fn terrain(n: usize) -> Simplex3D {
+        let h = |x: f64, z: f64| (x * 1.3).sin() * (z * 0.7).cos() * 0.5;
+        let mut faces = Vec::new();
+        for i in 0..n {
+            for j in 0..n {
+                let (x0, z0) = (i as f64 * 0.25 - 2.0, j as f64 * 0.25 - 2.0);
+                let (x1, z1) = (x0 + 0.25, z0 + 0.25);
+                let p00 = V3::new(x0, h(x0, z0), z0);
+                let p10 = V3::new(x1, h(x1, z0), z0);
+                let p01 = V3::new(x0, h(x0, z1), z1);
+                let p11 = V3::new(x1, h(x1, z1), z1);
+                faces.push(Face::new(p00, p10, p01));
+                faces.push(Face::new(p11, p01, p10));
+            }
+        }
+        Simplex3D::new(V3::new(0.0, 0.0, 0.0), faces)
+    }
+
+    /// Closest hit by testing every face.
+    fn brute_force(mesh: &Simplex3D, p0: V3, p: V3) -> Option<f64> {
+        mesh.x
+            .iter()
+            .filter_map(|f| Bvh::intersect_face(f, p0, p))
+            .map(|(t, _, _)| t)
+            .fold(None, |best: Option<f64>, t| Some(best.map_or(t, |b| f64::min(b, t))))
+    }
+
+    fn rays() -> Vec<(V3, V3)> {
+        let mut seed: u64 = 7;
+        let mut next = move || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 11) as f64 / (1u64 << 53) as f64
+        };
+        (0..300)
+            .map(|_| {
+                let p0 = V3::new(next() * 4.0 - 2.0, 3.0, next() * 4.0 - 2.0);
+                let mut p = V3::new(next() - 0.5, -1.0, next() - 0.5);
+                p.normalize();
+                (p0, p)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_bvh_matches_brute_force() {
+        let mesh = terrain(16);
+        let bvh = Bvh::new(Clone::clone(&mesh));
+        assert!(bvh.nodes.len() > 1);
+
+        for (p0, p) in rays() {
+            let expected = brute_force(&mesh, p0, p);
+            let got = bvh.closest_hit(p0, p).map(|h| h.t);
+            match (expected, got) {
+                (Some(a), Some(b)) => assert!((a - b).abs() < 1e-9, "{} != {}", a, b),
+                (None, None) => {}
+                _ => panic!("hit mismatch: {:?} vs {:?}", expected, got),
+            }
+        }
+    }
+
+    #[test]
+    fn test_bvh_after_translation() {
+        let mut bvh = *Bvh::new(terrain(8));
+        bvh.translate(V3::new(0.0, -10.0, 0.0));
+
+        let (c, n) = bvh.get_collision_with_normal(V3::new(-1.1, 5.0, -1.1), V3::new(0.0, -1.0, 0.0));
+        assert!(c.hit);
+        assert!(n.is_some());
+        assert!(c.p.y < -9.0 && c.p.y > -11.0);
+    }
+
+    #[test]
+    fn test_empty_mesh() {
+        let mut bvh = *Bvh::new(Simplex3D::new(V3::new(0.0, 0.0, 0.0), Vec::new()));
+        bvh.translate(V3::new(1.0, 0.0, 0.0));
+        bvh.rot(V3::new(0.0, 0.5, 0.0));
+        bvh.scale(V3::new(2.0, 2.0, 2.0));
+        assert!(bvh.bounds().is_empty());
+        assert!(!bvh.get_collision(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0)).hit);
+    }
+
+    #[test]
+    fn test_bvh_miss() {
+        let bvh = Bvh::new(terrain(8));
+        let c = bvh.get_collision(V3::new(0.0, 3.0, 0.0), V3::new(0.0, 1.0, 0.0));
+        assert!(!c.hit);
+    }
+}
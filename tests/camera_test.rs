@@ -0,0 +1,190 @@
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+    use std::sync::{Arc, Mutex};
+
+    use sdl2::pixels::Color;
+    use rust3d::engine::camera::RayCamera;
+    use rust3d::engine::lighting::Material;
+    use rust3d::engine::pathtracing::{Integrator, RayTracingScene};
+    use rust3d::engine::utils::accumulator::Accumulator;
+    use rust3d::engine::utils::rendering::{Collision, RayRenderable};
+    use rust3d::engine::utils::virtual_canvas::VirtualCanvas;
+    use rust3d::geometry::quad::Quad;
+    use rust3d::geometry::sphere::Sphere;
+    use rust3d::geometry::vector3::Vector3 as V3;
+
+    fn assert_v3_eq(a: V3, b: V3, eps: f64) {
+        assert!(a.d(b) < eps, "{:?} != {:?}", a, b);
+    }
+
+    fn normalized(x: f64, y: f64, z: f64) -> V3 {
+        let mut v = V3::new(x, y, z);
+        v.normalize();
+        v
+    }
+
+    #[test]
+    fn test_center_ray() {
+        let camera = RayCamera::new(V3::new(0.0, 0.0, 0.0), 0.0, 0.0, 0.0);
+        for (w, h) in [(640, 480), (101, 57), (1, 1)] {
+            let center = camera.ray_direction(w as f64 / 2.0, h as f64 / 2.0, w, h);
+            assert_v3_eq(center, V3::new(1.0, 0.0, 0.0), 1e-12);
+        }
+
+        // positive pitch looks down, positive yaw to the right
+        let camera = RayCamera::new(V3::new(0.0, 0.0, 0.0), 0.3, 0.0, 0.0);
+        assert_v3_eq(camera.ray_direction(50.0, 50.0, 100, 100), V3::new(f64::cos(0.3), f64::sin(0.3), 0.0), 1e-12);
+        let camera = RayCamera::new(V3::new(0.0, 0.0, 0.0), 0.0, 0.3, 0.0);
+        assert_v3_eq(camera.ray_direction(50.0, 50.0, 100, 100), V3::new(f64::cos(0.3), 0.0, -f64::sin(0.3)), 1e-12);
+    }
+
+    #[test]
+    fn test_corner_rays() {
+        let mut camera = RayCamera::new(V3::new(0.0, 0.0, 0.0), 0.0, 0.0, 0.0);
+        camera.set_fov(PI / 2.0);
+        let (w, h) = (200, 100);
+
+        // 45° up and down, the horizontal extent follows the aspect ratio: tan = 2
+        assert_v3_eq(camera.ray_direction(100.0, 0.0, w, h), normalized(1.0, -1.0, 0.0), 1e-12);
+        assert_v3_eq(camera.ray_direction(100.0, 100.0, w, h), normalized(1.0, 1.0, 0.0), 1e-12);
+        assert_v3_eq(camera.ray_direction(200.0, 50.0, w, h), normalized(1.0, 0.0, -2.0), 1e-12);
+        // top left and bottom right corners
+        assert_v3_eq(camera.ray_direction(0.0, 0.0, w, h), normalized(1.0, -1.0, 2.0), 1e-12);
+        assert_v3_eq(camera.ray_direction(200.0, 100.0, w, h), normalized(1.0, 1.0, -2.0), 1e-12);
+    }
+
+    #[test]
+    fn test_square_pixels() {
+        // one pixel step is the same distance on the image plane in both directions
+        let camera = RayCamera::new(V3::new(0.0, 0.0, 0.0), 0.0, 0.0, 0.0);
+        let (w, h) = (320, 180);
+        let at = |x: f64, y: f64| {
+            let mut v = camera.ray_direction(x, y, w, h);
+            v.scale(1.0 / v.x);
+            v
+        };
+        let dx = at(161.0, 90.0).d(at(160.0, 90.0));
+        let dy = at(160.0, 91.0).d(at(160.0, 90.0));
+        assert!((dx - dy).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_look_at() {
+        let position = V3::new(1.0, -2.0, 3.0);
+        let target = V3::new(4.0, 0.0, -1.0);
+        let camera = RayCamera::look_at(position, target, V3::new(0.0, -1.0, 0.0));
+
+        let mut expected = target;
+        expected.subtract(position);
+        expected.normalize();
+        assert_v3_eq(camera.ray_direction(32.0, 24.0, 64, 48), expected, 1e-9);
+
+        // the image is upright: the top row looks further up than the bottom row
+        let (_, right, up) = camera.basis();
+        assert!(up.y < 0.0);
+        assert!(right.dt(up).abs() < 1e-9);
+        assert!(camera.ray_direction(32.0, 0.0, 64, 48).y < camera.ray_direction(32.0, 48.0, 64, 48).y);
+
+        // rolled upside down
+        let camera = RayCamera::look_at(V3::new(0.0, 0.0, 0.0), V3::new(5.0, 0.0, 0.0), V3::new(0.0, 1.0, 0.0));
+        let (_, right, up) = camera.basis();
+        assert_v3_eq(up, V3::new(0.0, 1.0, 0.0), 1e-9);
+        assert_v3_eq(right, V3::new(0.0, 0.0, 1.0), 1e-9);
+    }
+
+    #[test]
+    fn test_thin_lens_focus() {
+        let mut camera = RayCamera::new(V3::new(0.0, 0.0, 0.0), 0.1, -0.2, 0.0);
+        let (x, y, w, h) = (10.0, 30.0, 64, 48);
+        let (origin, dir) = camera.lens_ray(x, y, w, h, 0.3, 0.7);
+        assert_eq!(origin, camera.position);
+        assert_v3_eq(dir, camera.ray_direction(x, y, w, h), 1e-12);
+
+        camera.set_lens(0.5, 4.0);
+        let (forward, _, _) = camera.basis();
+        let mut focus = None;
+        for (u, v) in [(0.0, 0.0), (1.0, 0.25), (0.5, 0.5), (0.9, 0.8)] {
+            let (origin, dir) = camera.lens_ray(x, y, w, h, u, v);
+            // the origin lies on the lens
+            let mut offset = origin;
+            offset.subtract(camera.position);
+            assert!(offset.norm() <= 0.25 + 1e-12);
+            assert!(offset.dt(forward).abs() < 1e-12);
+
+            // all rays meet on the focal plane
+            let t = (4.0 - offset.dt(forward)) / dir.dt(forward);
+            let mut p = dir;
+            p.scale(t);
+            p.add(origin);
+            match focus {
+                None => focus = Some(p),
+                Some(f) => assert_v3_eq(p, f, 1e-9),
+            }
+        }
+    }
+
+    #[test]
+    fn test_depth_of_field() {
+        // a sphere in focus keeps a sharp edge, one out of focus gets blurred
+        let render = |focal_distance: f64| {
+            let mut scene = RayTracingScene::new();
+            scene.set_integrator(Integrator::PathTracing);
+            scene.set_ambient_light(Color::RGB(0, 0, 0));
+            let mut wall = Quad::new(V3::new(20.0, 0.0, 0.0), V3::new(0.1, 40.0, 40.0), Color::WHITE);
+            wall.set_material(Material::emissive(Color::WHITE, 1.0));
+            scene.add(wall);
+            scene.add(Sphere::new(V3::new(5.0, 0.0, 0.0), 1.0, Material::new(Color::RGB(0, 0, 0), 1.0)));
+
+            let mut camera = RayCamera::new(V3::new(0.0, 0.0, 0.0), 0.0, 0.0, 0.0);
+            camera.set_fov(PI / 6.0);
+            camera.set_lens(0.8, focal_distance);
+            let mut acc = Accumulator::new(48, 48);
+            let scene = Arc::new(scene);
+            for _ in 0..32 {
+                camera.render_progressive_multi(Arc::clone(&scene), &mut acc, 4);
+            }
+            acc
+        };
+
+        // mixed values in the row through the center of the sphere
+        let blurred_pixels = |acc: &Accumulator| (0..48).filter(|&x| {
+            let g = acc.get(x, 24).g;
+            g > 0.1 && g < 0.9
+        }).count();
+
+        let sharp = render(4.9);
+        let blurry = render(15.0);
+        assert!(blurred_pixels(&sharp) <= 4, "{}", blurred_pixels(&sharp));
+        assert!(blurred_pixels(&blurry) > 8, "{}", blurred_pixels(&blurry));
+        assert!(sharp.get(24, 24).g < 0.05);
+    }
+
+    /// Remembers how far every ray was allowed to go.
+    struct Radii(Mutex<Vec<f64>>);
+
+    impl RayRenderable for Radii {
+        fn get_collision(&self, _p0: V3, _p: V3, radius: f64) -> Collision {
+            self.0.lock().unwrap().push(radius);
+            Collision::empty()
+        }
+    }
+
+    #[test]
+    fn test_view_distance() {
+        let mut camera = RayCamera::new(V3::new(0.0, 0.0, 0.0), 0.0, 0.0, 0.0);
+        camera.view_distance = 7.5;
+        let radii = Radii(Mutex::new(Vec::new()));
+        let mut canvas = VirtualCanvas::new(8, 6);
+
+        camera.render(&radii, &mut canvas);
+        camera.render_modulus(&radii, 8, 6, 1, 3);
+        camera.render_modulus_block(&mut canvas, &radii, 2, 0, 2);
+        camera.render_section(1, 1, 4, 4, &radii, 8, 6);
+        camera.render_pixel_at(2, 3, &mut canvas, &radii);
+
+        let radii = radii.0.lock().unwrap();
+        assert!(!radii.is_empty());
+        assert!(radii.iter().all(|&r| r == 7.5), "{:?}", radii);
+    }
+}
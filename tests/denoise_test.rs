@@ -0,0 +1,173 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use sdl2::pixels::Color;
+    use rust3d::engine::camera::RayCamera;
+    use rust3d::engine::lighting::{Light, Material};
+    use rust3d::engine::pathtracing::RayTracingScene;
+    use rust3d::engine::progressive::ProgressiveRenderer;
+    use rust3d::engine::utils::aov::{AovBuffers, AovSelection};
+    use rust3d::engine::utils::denoise::Denoiser;
+    use rust3d::engine::utils::hdr::HdrBuffer;
+    use rust3d::engine::utils::radiance::Radiance;
+    use rust3d::engine::utils::random::Rng;
+    use rust3d::engine::utils::rendering::SurfaceHit;
+    use rust3d::engine::utils::virtual_canvas::VirtualCanvas;
+    use rust3d::geometry::quad::Quad;
+    use rust3d::geometry::sphere::Sphere;
+    use rust3d::geometry::vector3::Vector3 as V3;
+
+    const W: usize = 32;
+    const H: usize = 24;
+
+    /// Guide of a flat wall facing the camera, split into object 0 on the left and object 1 on
+    /// the right, with `albedo` per pixel.
+    fn wall<F: Fn(usize, usize) -> Color>(albedo: F) -> AovBuffers {
+        let mut guide = AovBuffers::new(W, H, AovSelection::all());
+        for y in 0..H {
+            for x in 0..W {
+                let hit = SurfaceHit {
+                    d: 5.0,
+                    p: V3::new(5.0, y as f64, x as f64),
+                    normal: Some(V3::new(-1.0, 0.0, 0.0)),
+                    albedo: albedo(x, y),
+                    object: if x < W / 2 { 0 } else { 1 },
+                };
+                guide.set(x, y, Some(hit), 5.0);
+            }
+        }
+        guide
+    }
+
+    fn noisy<F: Fn(usize, usize) -> f64>(f: F, amount: f64) -> HdrBuffer {
+        let mut rng = Rng::new(7);
+        let mut buffer = HdrBuffer::new(W, H);
+        for y in 0..H {
+            for x in 0..W {
+                buffer.set(x, y, Radiance::gray(f(x, y) + rng.range(-amount, amount)));
+            }
+        }
+        buffer
+    }
+
+    fn error<F: Fn(usize, usize) -> f64>(buffer: &HdrBuffer, f: F) -> f64 {
+        let mut sum = 0.0;
+        for y in 0..H {
+            for x in 0..W {
+                sum += (buffer.get(x, y).g - f(x, y)).powi(2);
+            }
+        }
+        (sum / (W * H) as f64).sqrt()
+    }
+
+    #[test]
+    fn test_removes_noise() {
+        let guide = wall(|_, _| Color::WHITE);
+        let flat = |_: usize, _: usize| 0.5;
+        let input = noisy(flat, 0.3);
+        let output = Denoiser::new().denoise(&input, &guide);
+        assert!(error(&output, flat) < error(&input, flat) / 4.0, "{} {}", error(&input, flat), error(&output, flat));
+
+        // the same with several threads
+        let mut threaded = Denoiser::new();
+        threaded.set_threads(5);
+        assert_eq!(threaded.denoise(&input, &guide).pixels, output.pixels);
+    }
+
+    #[test]
+    fn test_keeps_edges() {
+        // two objects with different lighting, the noise goes but the step between them stays
+        let guide = wall(|_, _| Color::WHITE);
+        let step = |x: usize, _: usize| if x < W / 2 { 0.2 } else { 0.8 };
+        let output = Denoiser::new().denoise(&noisy(step, 0.1), &guide);
+        for y in 0..H {
+            assert!((output.get(W / 2 - 1, y).g - 0.2).abs() < 0.1, "{:?}", output.get(W / 2 - 1, y));
+            assert!((output.get(W / 2, y).g - 0.8).abs() < 0.1, "{:?}", output.get(W / 2, y));
+        }
+
+        // a texture under even light comes out unchanged, even in its black channels
+        let checker = |x: usize, y: usize| if (x / 3 + y / 3) % 2 == 0 { Color::RGB(255, 0, 40) } else { Color::RGB(30, 200, 90) };
+        let guide = wall(checker);
+        let mut lit = HdrBuffer::new(W, H);
+        for y in 0..H {
+            for x in 0..W {
+                lit.set(x, y, Radiance::from_color(checker(x, y)) * 0.6);
+            }
+        }
+        let output = Denoiser::new().denoise(&lit, &guide);
+        for (a, b) in output.pixels.iter().zip(lit.pixels.iter()) {
+            assert_eq!(a.to_color(), b.to_color(), "{:?} {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_background_is_copied() {
+        let mut guide = AovBuffers::new(W, H, AovSelection { depth: true, normal: true, ..AovSelection::none() });
+        let hit = SurfaceHit { d: 2.0, p: V3::new(2.0, 0.0, 0.0), normal: Some(V3::new(-1.0, 0.0, 0.0)), albedo: Color::WHITE, object: 0 };
+        for y in 0..H {
+            for x in W / 2..W {
+                guide.set(x, y, Some(hit), 2.0);
+            }
+        }
+        let input = noisy(|_, _| 0.5, 0.3);
+        let output = Denoiser::new().denoise(&input, &guide);
+        for y in 0..H {
+            for x in 0..W / 2 {
+                assert_eq!(output.get(x, y), input.get(x, y));
+            }
+            assert_ne!(output.get(W - 1, y), input.get(W - 1, y));
+        }
+    }
+
+    /// Soft shadows of a ball on a floor under an area light.
+    fn soft_shadows(shading_samples: usize) -> RayTracingScene {
+        let mut scene = RayTracingScene::new();
+        scene.set_shading_samples(shading_samples);
+        scene.add(Quad::new(V3::new(0.0, 1.0, 0.0), V3::new(20.0, 0.1, 20.0), Color::RGB(200, 200, 200)));
+        scene.add(Sphere::new(V3::new(3.0, 0.4, 0.0), 0.5, Material::new(Color::RGB(200, 80, 60), 1.0)));
+        scene.add_light(Light::rect_area(V3::new(3.0, -2.0, 0.5), V3::new(2.0, 0.0, 0.0), V3::new(0.0, 0.0, -2.0), Color::WHITE, 8.0));
+        scene
+    }
+
+    fn rmse(a: &VirtualCanvas, b: &VirtualCanvas) -> f64 {
+        let sum: f64 = a.pixels.iter().zip(b.pixels.iter()).map(|(&x, &y)| (x as f64 - y as f64).powi(2)).sum();
+        (sum / a.pixels.len() as f64).sqrt()
+    }
+
+    #[test]
+    fn test_low_sample_render() {
+        let camera = RayCamera::new(V3::new(0.0, -0.5, 0.0), 0.3, 0.0, 0.0);
+        let noisy = camera.render_aovs(&soft_shadows(2), 48, 32, AovSelection::all());
+        let mut reference = VirtualCanvas::new(48, 32);
+        camera.render(&soft_shadows(128), &mut reference);
+
+        let denoised = Denoiser::new().denoise_canvas(&noisy.color, &noisy);
+        let (before, after) = (rmse(&noisy.color, &reference), rmse(&denoised, &reference));
+        assert!(after < before * 0.6, "{} {}", before, after);
+    }
+
+    #[test]
+    fn test_progressive_preview() {
+        let camera = RayCamera::new(V3::new(0.0, -0.5, 0.0), 0.3, 0.0, 0.0);
+        let scene = Arc::new(soft_shadows(1));
+        let mut reference = VirtualCanvas::new(48, 32);
+        camera.render(&soft_shadows(128), &mut reference);
+
+        let mut errors = Vec::new();
+        for denoiser in [None, Some(Denoiser::new())] {
+            let mut renderer = ProgressiveRenderer::new(48, 32, 2);
+            renderer.set_start_block(1);
+            renderer.set_denoiser(denoiser);
+            let start = Instant::now();
+            while renderer.samples() < 2 {
+                renderer.step(&camera, Arc::clone(&scene), 0);
+                assert!(start.elapsed() < Duration::from_secs(20), "no progress");
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            errors.push(rmse(renderer.frame(), &reference));
+        }
+        assert!(errors[1] < errors[0] * 0.6, "{:?}", errors);
+    }
+}
@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use sdl2::pixels::Color;
+    use rust3d::engine::camera::RayCamera;
+    use rust3d::engine::lighting::Material;
+    use rust3d::engine::pathtracing::RayTracingScene;
+    use rust3d::engine::utils::virtual_canvas::VirtualCanvas;
+    use rust3d::geometry::sphere::Sphere;
+    use rust3d::geometry::vector3::Vector3 as V3;
+
+    fn sphere_scene() -> RayTracingScene {
+        let mut scene = RayTracingScene::new();
+        scene.add(Sphere::new(V3::new(3.0, 0.0, 0.0), 1.0, Material::new(Color::RGB(200, 40, 40), 1.0)));
+        scene
+    }
+
+    #[test]
+    fn test_render_without_window() {
+        let camera = RayCamera::new(V3::new(0.0, 0.0, 0.0), 0.0, 0.0, 0.0);
+        let mut frame = VirtualCanvas::new(32, 24);
+        camera.render(&sphere_scene(), &mut frame);
+
+        let hits = (0..32)
+            .flat_map(|x| (0..24).map(move |y| (x, y)))
+            .filter(|(x, y)| frame.get_pixel(*x, *y).r == 200)
+            .count();
+        assert!(hits > 0);
+        assert!(hits < 32 * 24);
+    }
+
+    #[test]
+    fn test_render_multi_matches_single_threaded() {
+        let camera = RayCamera::new(V3::new(0.0, 0.0, 0.0), 0.0, 0.0, 0.0);
+        let mut single = VirtualCanvas::new(20, 10);
+        camera.render(&sphere_scene(), &mut single);
+
+        let mut multi = VirtualCanvas::new(20, 10);
+        camera.render_multi(Arc::new(sphere_scene()), &mut multi, 3);
+
+        assert_eq!(single.pixels, multi.pixels);
+    }
+
+    #[test]
+    fn test_export_png_and_ppm() {
+        let mut frame = VirtualCanvas::new(4, 3);
+        frame.draw_sdl2_color(1, 2, Color::RGB(10, 20, 30));
+
+        let dir = std::env::temp_dir();
+        let png = dir.join("rust3d_framebuffer_test.png");
+        let ppm = dir.join("rust3d_framebuffer_test.ppm");
+        frame.save_png(&png).unwrap();
+        frame.save_ppm(&ppm).unwrap();
+
+        let loaded = image::open(&png).unwrap().to_rgba8();
+        assert_eq!(loaded.get_pixel(1, 2).0, [10, 20, 30, 255]);
+        let loaded = image::open(&ppm).unwrap().to_rgb8();
+        assert_eq!(loaded.dimensions(), (4, 3));
+        assert_eq!(loaded.get_pixel(1, 2).0, [10, 20, 30]);
+    }
+}
@@ -0,0 +1,140 @@
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::BufReader;
+    use std::sync::Arc;
+
+    use image::codecs::hdr::HdrDecoder;
+
+    use sdl2::pixels::Color;
+    use rust3d::engine::camera::RayCamera;
+    use rust3d::engine::lighting::{Light, Material};
+    use rust3d::engine::pathtracing::RayTracingScene;
+    use rust3d::engine::raymarching::RayMarchingScene;
+    use rust3d::engine::utils::hdr::{srgb_decode, srgb_encode, HdrBuffer, ToneMapper, ToneMapping};
+    use rust3d::engine::utils::radiance::Radiance;
+    use rust3d::engine::utils::rendering::RayRenderable;
+    use rust3d::engine::utils::virtual_canvas::VirtualCanvas;
+    use rust3d::geometry::sphere::Sphere;
+    use rust3d::geometry::vector3::Vector3 as V3;
+
+    fn close(a: f64, b: f64, eps: f64) -> bool {
+        (a - b).abs() < eps
+    }
+
+    /// A white ball lit head-on by two lights that are far too bright together.
+    fn bright_scene() -> RayTracingScene {
+        let mut scene = RayTracingScene::new();
+        scene.add(Sphere::new(V3::new(3.0, 0.0, 0.0), 1.0, Material::new(Color::WHITE, 1.0)));
+        scene.add_light(Light::point(V3::new(0.0, 0.0, 0.5), Color::WHITE, 4.0));
+        scene.add_light(Light::point(V3::new(0.0, 0.0, -0.5), Color::WHITE, 4.0));
+        scene
+    }
+
+    #[test]
+    fn test_srgb() {
+        for x in [0.0, 0.001, 0.02, 0.18, 0.5, 1.0] {
+            assert!(close(srgb_decode(srgb_encode(x)), x, 1e-12), "{}", x);
+        }
+        assert!(close(srgb_encode(0.5), 0.7354, 1e-4));
+        assert!(close(srgb_encode(0.002), 0.002 * 12.92, 1e-12));
+        assert!(close(srgb_encode(3.0), 1.0, 1e-12));
+    }
+
+    #[test]
+    fn test_tone_mapping() {
+        let mut linear = ToneMapper::new(ToneMapping::Clamp);
+        linear.set_srgb(false);
+        for c in [Color::RGB(0, 0, 0), Color::RGB(12, 200, 255), Color::RGB(128, 64, 32)] {
+            assert_eq!(linear.to_color(Radiance::from_color(c)), c);
+        }
+        assert_eq!(linear.to_color(Radiance::gray(7.0)), Color::WHITE);
+
+        // one stop doubles the light
+        linear.set_exposure(1.0);
+        assert!(close(linear.map(Radiance::gray(0.25)).g, 0.5, 1e-12));
+        linear.set_exposure(-2.0);
+        assert!(close(linear.map(Radiance::gray(2.0)).g, 0.5, 1e-12));
+
+        for operator in [ToneMapping::Reinhard, ToneMapping::Aces] {
+            let mut mapper = ToneMapper::new(operator);
+            mapper.set_srgb(false);
+            assert!(mapper.map(Radiance::black()).is_black());
+            // bright values stay apart instead of all ending up at 1
+            let values: Vec<f64> = [0.1, 0.5, 1.0, 2.0, 4.0].iter().map(|&x| mapper.map(Radiance::gray(x)).g).collect();
+            assert!(values.windows(2).all(|v| v[0] < v[1]), "{:?} {:?}", operator, values);
+            assert!(values[4] < 1.0);
+        }
+        // 8 bit colors are not encoded a second time
+        for c in [Color::RGB(12, 200, 255), Color::RGB(128, 64, 32)] {
+            assert_eq!(ToneMapper::new(ToneMapping::Clamp).to_color(Radiance::from_color(c)), c);
+        }
+
+        // Reinhard keeps the hue of saturated colors
+        let mapped = ToneMapper::new(ToneMapping::Reinhard).map(Radiance::new(8.0, 4.0, 0.0));
+        assert!(mapped.r > mapped.g && mapped.b == 0.0);
+    }
+
+    #[test]
+    fn test_bright_lights_do_not_saturate() {
+        let scene = bright_scene();
+        let radiance = scene.get_radiance(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 100.0);
+        assert!(radiance.g > 1.5, "{:?}", radiance);
+        // the clamped color saturates but never wraps around
+        assert_eq!(scene.get_collision(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 100.0).c, Color::WHITE);
+
+        let mut marched = RayMarchingScene::new(0.001);
+        marched.add(Sphere::new(V3::new(3.0, 0.0, 0.0), 1.0, Material::new(Color::WHITE, 1.0)));
+        marched.add_light(Light::point(V3::new(0.0, 0.0, 0.5), Color::WHITE, 4.0));
+        marched.add_light(Light::point(V3::new(0.0, 0.0, -0.5), Color::WHITE, 4.0));
+        let radiance = marched.get_radiance(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 100.0);
+        assert!(radiance.g > 1.5, "{:?}", radiance);
+        assert_eq!(marched.get_collision(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 100.0).c, Color::WHITE);
+    }
+
+    #[test]
+    fn test_render_hdr() {
+        let camera = RayCamera::new(V3::new(0.0, 0.0, 0.0), 0.0, 0.0, 0.0);
+        let scene = Arc::new(bright_scene());
+        let mut hdr = HdrBuffer::new(20, 12);
+        camera.render_hdr(&*scene, &mut hdr);
+        assert!(hdr.get(10, 6).g > 1.5);
+
+        let mut multi = HdrBuffer::new(20, 12);
+        camera.render_hdr_multi(Arc::clone(&scene), &mut multi, 3);
+        assert_eq!(hdr.pixels, multi.pixels);
+
+        // clamped and without gamma the buffer gives the same image as the 8 bit renderer
+        let mut ldr = VirtualCanvas::new(20, 12);
+        camera.render(&*scene, &mut ldr);
+        let mut clamp = ToneMapper::new(ToneMapping::Clamp);
+        clamp.set_srgb(false);
+        assert_eq!(hdr.to_canvas(&clamp).pixels, ldr.pixels);
+    }
+
+    #[test]
+    fn test_export() {
+        let mut hdr = HdrBuffer::new(5, 3);
+        hdr.set(1, 2, Radiance::new(0.25, 3.5, 120.0));
+        hdr.set(4, 0, Radiance::new(0.5, 0.5, 0.5));
+
+        let dir = std::env::temp_dir();
+        let exr = dir.join("rust3d_hdr_test.exr");
+        let rgbe = dir.join("rust3d_hdr_test.hdr");
+        hdr.save_exr(&exr).unwrap();
+        hdr.save_hdr(&rgbe).unwrap();
+
+        let loaded = image::open(&exr).unwrap().to_rgb32f();
+        assert_eq!(loaded.dimensions(), (5, 3));
+        assert_eq!(loaded.get_pixel(1, 2).0, [0.25, 3.5, 120.0]);
+        assert_eq!(loaded.get_pixel(4, 0).0, [0.5, 0.5, 0.5]);
+
+        // RGBE shares one exponent per pixel, the small channels lose precision
+        let decoder = HdrDecoder::new(BufReader::new(File::open(&rgbe).unwrap())).unwrap();
+        assert_eq!((decoder.metadata().width, decoder.metadata().height), (5, 3));
+        let loaded = decoder.read_image_hdr().unwrap();
+        let p = loaded[2 * 5 + 1].0;
+        assert!(close(p[2] as f64, 120.0, 1.0) && close(p[1] as f64, 3.5, 0.5), "{:?}", p);
+        assert!(close(loaded[4].0[0] as f64, 0.5, 0.01));
+    }
+}
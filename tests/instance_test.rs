@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use sdl2::pixels::Color;
+    use rust3d::engine::instance::Instance;
+    use rust3d::engine::lighting::Material;
+    use rust3d::engine::pathtracing::RaytracingObject;
+    use rust3d::engine::scene_file::SceneDescription;
+    use rust3d::engine::simplex3d_bvh::bvh::Bvh;
+    use rust3d::engine::utils::transformation::{Transform, Transformable};
+    use rust3d::geometry::quaternion::Quaternion;
+    use rust3d::geometry::simplex3d::Simplex3D;
+    use rust3d::geometry::vector3::Vector3 as V3;
+
+    fn cube() -> Simplex3D {
+        Simplex3D::load_obj("demo_assets/models/default_cube.obj").unwrap()
+    }
+
+    #[test]
+    fn test_matches_moved_mesh() {
+        // the same cube once moved vertex by vertex and once as an instance
+        let r = V3::new(0.3, 0.5, -0.2);
+        let mut moved = cube();
+        moved.rot(r);
+        moved.translate(V3::new(6.0, 0.5, -0.5));
+        let moved = Bvh::new(moved);
+
+        let shared: Arc<dyn RaytracingObject + Send + Sync> = Arc::new(*Bvh::new(cube()));
+        let center = cube().m;
+        let rotation = Quaternion::from_euler(r);
+        let transform = Transform::new(V3::new(6.0, 0.5, -0.5) + center - rotation.rotate(center), rotation, V3::new(1.0, 1.0, 1.0));
+        let instance = Instance::new(shared, transform);
+
+        let p0 = V3::new(0.0, 0.0, 0.0);
+        for dir in [V3::new(1.0, 0.0, 0.0), V3::new(1.0, 0.05, -0.05), V3::new(1.0, -0.08, 0.03)] {
+            let dir = dir.normalized();
+            let (a, na) = instance.get_collision_with_normal(p0, dir);
+            let (b, nb) = moved.get_collision_with_normal(p0, dir);
+            assert!(a.hit && b.hit);
+            assert!((a.d - b.d).abs() < 1e-9, "{} != {}", a.d, b.d);
+            assert!((a.p - b.p).norm() < 1e-9);
+            // the mesh leaves its normals unnormalized
+            assert!((na.unwrap() - nb.unwrap().normalized()).norm() < 1e-9);
+        }
+        assert!(!instance.get_collision(p0, V3::new(-1.0, 0.0, 0.0)).hit);
+    }
+
+    #[test]
+    fn test_shared_mesh() {
+        let shared: Arc<dyn RaytracingObject + Send + Sync> = Arc::new(*Bvh::new(cube()));
+        let mut first = Instance::new(shared.clone(), Transform::identity());
+        first.translate(V3::new(5.0, 0.0, 0.0));
+        let mut herd: Vec<Instance> = (1..=3).map(|i| first.place(Transform::new(V3::new(5.0, 0.0, 4.0 * i as f64), Quaternion::identity(), V3::new(1.0, 2.0, 1.0)))).collect();
+        herd.push(first);
+        let boxed = RaytracingObject::clone(&herd[0]);
+        assert_eq!(Arc::strong_count(&shared), 6);
+
+        // transforming one instance leaves the mesh and the others alone
+        herd[3].scale(V3::new(2.0, 2.0, 2.0));
+        let c = herd[3].get_collision(V3::new(0.0, -0.01, 0.01), V3::new(1.0, 0.0, 0.0));
+        assert!(c.hit && c.d < 4.0 - 0.5);
+        let c = boxed.get_collision(V3::new(0.0, -0.01, 4.01), V3::new(1.0, 0.0, 0.0));
+        assert!(c.hit && (c.d - 4.0).abs() < 1e-3, "{}", c.d);
+
+        // a material of the instance replaces the one of the mesh
+        let gold = Material::new(Color::RGB(250, 200, 60), 0.3);
+        herd[1].set_material(gold.clone());
+        let (c, _, m) = herd[1].get_collision_with_material(V3::new(0.0, -0.01, 8.01), V3::new(1.0, 0.0, 0.0));
+        assert!(c.hit);
+        assert_eq!(c.c, gold.albedo);
+        assert_eq!(m.roughness, gold.roughness);
+        assert_ne!(herd[0].get_collision(V3::new(0.0, -0.01, 4.01), V3::new(1.0, 0.0, 0.0)).c, gold.albedo);
+    }
+
+    #[test]
+    fn test_scene_file_meshes() {
+        // every mesh statement becomes an object, placed like before
+        let text = "
+material red albedo 200 40 40
+mesh path demo_assets/models/default_cube.obj position 5 0 0
+mesh path demo_assets/models/default_cube.obj position 5 0 4 rotation 0 0.7 0 scale 1 2 1
+mesh path demo_assets/models/default_cube.obj position 5 0 8 material red
+";
+        let scene = SceneDescription::parse(Cursor::new(text), Path::new("herd.scene")).unwrap().to_raytracing().unwrap();
+        assert_eq!(scene.objects().len(), 3);
+        let rasterized = SceneDescription::parse(Cursor::new(text), Path::new("herd.scene")).unwrap().to_projection().unwrap();
+        for (i, z) in [0.0, 4.0, 8.0].iter().enumerate() {
+            let c = scene.objects()[i].get_collision(V3::new(0.0, 0.0, *z + 0.01), V3::new(1.0, 0.0, 0.0));
+            assert!(c.hit);
+            // the projection renderer still gets the faces moved into place
+            let nearest = rasterized.triangles.iter().filter(|t| (t.a.z - z).abs() < 3.0).map(|t| t.a.x.min(t.b.x).min(t.c.x)).fold(f64::INFINITY, f64::min);
+            assert!((c.p.x - nearest).abs() < 0.5, "{} {}", c.p.x, nearest);
+        }
+        assert_eq!(scene.objects()[2].get_collision(V3::new(0.0, 0.0, 8.01), V3::new(1.0, 0.0, 0.0)).c, Color::RGB(200, 40, 40));
+    }
+}
@@ -0,0 +1,273 @@
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use sdl2::pixels::Color;
+    use rust3d::engine::lighting::{Attenuation, Light, Material};
+    use rust3d::engine::pathtracing::{Integrator, RayTracingScene};
+    use rust3d::engine::raymarching::RayMarchingScene;
+    use rust3d::engine::utils::environment_map::EnvironmentMap;
+    use rust3d::engine::utils::radiance::Radiance;
+    use rust3d::engine::utils::random::Rng;
+    use rust3d::engine::utils::rendering::RayRenderable;
+    use rust3d::geometry::quad::Quad;
+    use rust3d::geometry::sphere::Sphere;
+    use rust3d::geometry::vector3::Vector3 as V3;
+
+    fn assert_approx_eq(a: f64, b: f64, eps: f64) {
+        assert!((a - b).abs() < eps, "{} != {}", a, b);
+    }
+
+    /// Mean of `n * n` stratified samples of the light at `p`.
+    fn mean_sample(light: &Light, p: V3, n: usize) -> Radiance {
+        let mut sum = Radiance::black();
+        for i in 0..n {
+            for j in 0..n {
+                let s = light.sample(p, (i as f64 + 0.5) / n as f64, (j as f64 + 0.5) / n as f64);
+                sum += s.radiance;
+            }
+        }
+        sum / (n * n) as f64
+    }
+
+    #[test]
+    fn test_point_light_attenuation() {
+        let mut light = Light::point(V3::new(0.0, -4.0, 0.0), Color::WHITE, 2.0);
+        let s = light.sample(V3::new(0.0, 0.0, 0.0), 0.5, 0.5);
+        assert_approx_eq(s.distance, 4.0, 1e-12);
+        assert_approx_eq(s.direction.y, -1.0, 1e-12);
+        assert_approx_eq(s.radiance.g, 2.0, 1e-12);
+
+        light.set_attenuation(Attenuation::inverse_square());
+        assert_approx_eq(light.sample(V3::new(0.0, 0.0, 0.0), 0.5, 0.5).radiance.g, 2.0 / 16.0, 1e-12);
+        assert_approx_eq(Attenuation::new(1.0, 0.5, 0.0).factor(2.0), 0.5, 1e-12);
+    }
+
+    #[test]
+    fn test_directional_light() {
+        let light = Light::directional(V3::new(0.0, 2.0, 0.0), Color::WHITE, 0.7);
+        let s = light.sample(V3::new(3.0, 1.0, -2.0), 0.1, 0.9);
+        assert!(s.distance.is_infinite());
+        assert_approx_eq(s.direction.y, -1.0, 1e-12);
+        assert_approx_eq(s.radiance.r, 0.7, 1e-12);
+
+        // sun at 60° from the zenith on a white floor, no shadow rays can hit anything
+        let mut scene = RayTracingScene::new();
+        scene.set_integrator(Integrator::PathTracing);
+        scene.set_ambient_light(Color::RGB(0, 0, 0));
+        scene.set_max_depth(1);
+        scene.add(Quad::new(V3::new(0.0, 1.0, 0.0), V3::new(10.0, 0.1, 10.0), Color::WHITE));
+        let mut sun = V3::new(f64::sin(PI / 3.0), f64::cos(PI / 3.0), 0.0);
+        sun.normalize();
+        scene.add_light(Light::directional(sun, Color::WHITE, 1.0));
+
+        let mut rng = Rng::new(1);
+        let mut dir = V3::new(0.2, 1.0, 0.1);
+        dir.normalize();
+        let l = scene.trace_path(V3::new(0.0, 0.0, 0.0), dir, &mut rng);
+        assert!(l.g > 0.4 && l.g < 0.5, "{}", l.g);
+    }
+
+    #[test]
+    fn test_spot_light_cone() {
+        let light = Light::spot(V3::new(0.0, 0.0, 0.0), V3::new(0.0, 1.0, 0.0), 0.2, 0.4, Color::WHITE, 1.0);
+        let at = |angle: f64| light.sample(V3::new(f64::sin(angle), f64::cos(angle), 0.0), 0.5, 0.5).radiance.g;
+
+        assert_approx_eq(at(0.0), 1.0, 1e-12);
+        assert_approx_eq(at(0.19), 1.0, 1e-12);
+        assert_approx_eq(at(0.41), 0.0, 1e-12);
+        assert!(at(0.3) > 0.0 && at(0.3) < 1.0);
+        assert!(at(0.25) > at(0.35));
+        assert_approx_eq(at(PI), 0.0, 1e-12);
+    }
+
+    #[test]
+    fn test_rect_area_light() {
+        // 2x2 light at y = -3 (above), emitting downwards
+        let light = Light::rect_area(V3::new(0.0, -3.0, 0.0), V3::new(2.0, 0.0, 0.0), V3::new(0.0, 0.0, -2.0), Color::WHITE, 1.0);
+
+        for i in 0..10 {
+            let s = light.sample(V3::new(0.0, 0.0, 0.0), i as f64 / 10.0, 0.3);
+            assert!(s.direction.y < 0.0);
+            assert!(s.distance >= 3.0);
+        }
+        // a bit less than a point light with intensity 4 (area x intensity) in the middle
+        let below = mean_sample(&light, V3::new(0.0, 0.0, 0.0), 8);
+        assert!(below.g > 0.35 && below.g < 4.0 / 9.0, "{}", below.g);
+
+        // one sided: nothing above the light
+        assert!(mean_sample(&light, V3::new(0.0, -5.0, 0.0), 4).is_black());
+    }
+
+    #[test]
+    fn test_sphere_area_light() {
+        let center = V3::new(0.0, -5.0, 0.0);
+        let light = Light::sphere_area(center, 1.0, Color::WHITE, 1.0);
+        let p = V3::new(0.5, 0.0, 0.0);
+
+        for i in 0..8 {
+            for j in 0..8 {
+                let s = light.sample(p, i as f64 / 8.0, j as f64 / 8.0);
+                let mut on_light = s.direction;
+                on_light.scale(s.distance);
+                on_light.add(p);
+                assert_approx_eq(on_light.d(center), 1.0, 1e-9);
+                // only the side facing p is sampled
+                let mut out = on_light;
+                out.subtract(center);
+                let mut to_p = p;
+                to_p.subtract(on_light);
+                assert!(out.dt(to_p) >= -1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_small_area_lights_match_point_light() {
+        let p = V3::new(0.3, 0.0, -0.2);
+        let at = |position: V3, intensity: f64| {
+            let mut light = Light::point(position, Color::WHITE, intensity);
+            light.set_attenuation(Attenuation::inverse_square());
+            light.sample(p, 0.5, 0.5).radiance.g
+        };
+
+        // 0.1 x 0.1 light facing p from 4 units above
+        let rect = Light::rect_area(V3::new(0.3, -4.0, -0.2), V3::new(0.1, 0.0, 0.0), V3::new(0.0, 0.0, -0.1), Color::WHITE, 3.0);
+        let expected = at(rect.position, 3.0 * 0.01);
+        assert_approx_eq(mean_sample(&rect, p, 8).g / expected, 1.0, 1e-3);
+
+        // farther away and seen at an angle the rectangle gets dimmer like a lambertian emitter
+        let mut tilted = rect.clone();
+        tilted.position = V3::new(6.3, -8.0, -0.2);
+        let cos = 8.0 / 10.0;
+        assert_approx_eq(mean_sample(&tilted, p, 8).g / (at(tilted.position, 3.0 * 0.01) * cos), 1.0, 1e-3);
+
+        let sphere = Light::sphere_area(V3::new(2.0, -3.0, 1.0), 0.05, Color::WHITE, 3.0);
+        let expected = at(sphere.position, 3.0 * PI * 0.05 * 0.05);
+        assert_approx_eq(mean_sample(&sphere, p, 8).g / expected, 1.0, 1e-3);
+    }
+
+    #[test]
+    fn test_area_light_matches_emissive_quad() {
+        // a floor lit by a square light, once as a light and once as glowing geometry
+        let floor = |scene: &mut RayTracingScene| {
+            scene.set_integrator(Integrator::PathTracing);
+            scene.set_ambient_light(Color::RGB(0, 0, 0));
+            scene.set_max_depth(2);
+            scene.add(Quad::new(V3::new(0.0, 1.0, 0.0), V3::new(10.0, 0.1, 10.0), Color::WHITE));
+        };
+        let mut lit = RayTracingScene::new();
+        floor(&mut lit);
+        lit.add_light(Light::rect_area(V3::new(2.0, -1.0, 0.0), V3::new(1.0, 0.0, 0.0), V3::new(0.0, 0.0, -1.0), Color::WHITE, 1.0));
+
+        let mut glowing = RayTracingScene::new();
+        floor(&mut glowing);
+        let mut lamp = Quad::new(V3::new(2.0, -1.0, 0.0), V3::new(1.0, 0.01, 1.0), Color::BLACK);
+        lamp.set_material(Material::emissive(Color::WHITE, 1.0));
+        glowing.add(lamp);
+
+        let mut dir = V3::new(2.0, 0.95, 0.0);
+        dir.normalize();
+        let mean = |scene: &RayTracingScene| {
+            let mut rng = Rng::new(5);
+            (0..20000).map(|_| scene.trace_path(V3::new(0.0, 0.0, 0.0), dir, &mut rng).g).sum::<f64>() / 20000.0
+        };
+        let (a, b) = (mean(&lit), mean(&glowing));
+        assert!(b > 0.01);
+        assert_approx_eq(a / b, 1.0, 0.1);
+    }
+
+    #[test]
+    fn test_area_light_soft_shadow() {
+        // a small blocker between a floor and a wide light: the umbra is black, the penumbra
+        // gets part of the light
+        let mut scene = RayTracingScene::new();
+        scene.set_ambient_light(Color::RGB(0, 0, 0));
+        scene.set_shading_samples(256);
+        scene.add(Quad::new(V3::new(0.0, 1.0, 0.0), V3::new(20.0, 0.1, 20.0), Color::WHITE));
+        scene.add(Sphere::new(V3::new(0.0, -1.0, 0.0), 0.7, Material::new(Color::WHITE, 1.0)));
+        scene.add_light(Light::rect_area(V3::new(0.0, -4.0, 0.0), V3::new(2.0, 0.0, 0.0), V3::new(0.0, 0.0, -2.0), Color::WHITE, 19.0));
+
+        let floor_at = |x: f64| {
+            // looking from the side, below the blocker
+            let mut dir = V3::new(x + 6.0, 0.95, 0.0);
+            dir.normalize();
+            scene.get_collision(V3::new(-6.0, 0.0, 0.0), dir, 100.0).c.g
+        };
+        let umbra = floor_at(0.0);
+        let penumbra = floor_at(0.9);
+        // fully lit, but not so far out that the light falls off
+        let lit = floor_at(2.0);
+        assert_eq!(umbra, 0);
+        assert!(penumbra > 20 && penumbra < lit - 20, "{} {}", penumbra, lit);
+    }
+
+    #[test]
+    fn test_environment_lookup() {
+        // top row red, bottom row blue
+        let env = EnvironmentMap::new(1, 2, vec![Radiance::new(1.0, 0.0, 0.0), Radiance::new(0.0, 0.0, 1.0)]);
+        assert_eq!(env.lookup(V3::new(0.0, -1.0, 0.0)), Radiance::new(1.0, 0.0, 0.0));
+        assert_eq!(env.lookup(V3::new(0.0, 1.0, 0.0)), Radiance::new(0.0, 0.0, 1.0));
+        assert_approx_eq(env.average().r, 0.5, 1e-12);
+
+        // four columns around the horizon, +x looks at the center of the image
+        let cols: Vec<Radiance> = (0..4).map(|i| Radiance::gray(i as f64)).collect();
+        let mut env = EnvironmentMap::new(4, 1, cols);
+        assert_eq!(env.lookup(V3::new(1.0, 0.0, 0.0)).g, 2.0);
+        assert_eq!(env.lookup(V3::new(-1.0, 0.0, 0.1)).g, 0.0);
+        env.set_rotation(PI / 2.0);
+        env.set_intensity(2.0);
+        assert_eq!(env.lookup(V3::new(1.0, 0.0, 0.0)).g, 6.0);
+    }
+
+    #[test]
+    fn test_environment_load_and_render() {
+        let dir = std::env::temp_dir().join("rust3d_env_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("sky.png");
+        let img = image::RgbImage::from_fn(4, 2, |x, y| match (x, y) {
+            (_, 1) => image::Rgb([0, 0, 0]),
+            (0, 0) => image::Rgb([128, 128, 128]),
+            _ => image::Rgb([255, 255, 255]),
+        });
+        img.save(&path).unwrap();
+
+        let env = EnvironmentMap::load(&path).unwrap();
+        assert_eq!((env.width, env.height), (4, 2));
+        assert_approx_eq(env.lookup(V3::new(0.0, -1.0, 0.0)).g, 1.0, 1e-6);
+        assert_approx_eq(env.lookup(V3::new(0.0, 1.0, 0.0)).g, 0.0, 1e-6);
+        // 8 bit pixels are read like the other 8 bit colors, not decoded from sRGB
+        assert_approx_eq(env.lookup(V3::new(-1.0, -0.3, 0.0)).g, 128.0 / 255.0, 1e-6);
+
+        // both scenes show it as background
+        let mut rt = RayTracingScene::new();
+        rt.set_environment(env.clone());
+        let c = rt.get_collision(V3::new(0.0, 0.0, 0.0), V3::new(0.3, -1.0, 0.0), 100.0);
+        assert_eq!(c.c, Color::RGB(255, 255, 255));
+
+        let mut rm = RayMarchingScene::new(0.01);
+        rm.add(Sphere::new(V3::new(50.0, 50.0, 50.0), 1.0, Material::new(Color::WHITE, 1.0)));
+        rm.set_environment(env);
+        let c = rm.get_collision(V3::new(0.0, 0.0, 0.0), V3::new(0.3, 1.0, 0.0), 10.0);
+        assert!(!c.hit);
+        assert_eq!(c.c, Color::RGB(0, 0, 0));
+    }
+
+    #[test]
+    fn test_ray_marching_light_kinds() {
+        let mut scene = RayMarchingScene::new(0.001);
+        scene.add(Sphere::new(V3::new(3.0, 0.0, 0.0), 1.0, Material::new(Color::WHITE, 1.0)));
+        scene.add_light(Light::directional(V3::new(1.0, 0.0, 0.0), Color::WHITE, 0.5));
+        let c = scene.get_collision(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 10.0);
+        assert!(c.hit);
+        assert!(c.c.g > 120 && c.c.g < 130, "{}", c.c.g);
+
+        // area lights facing away contribute nothing
+        let mut scene = RayMarchingScene::new(0.001);
+        scene.add(Sphere::new(V3::new(3.0, 0.0, 0.0), 1.0, Material::new(Color::WHITE, 1.0)));
+        scene.add_light(Light::rect_area(V3::new(0.0, 0.0, 0.0), V3::new(0.0, 0.0, 1.0), V3::new(0.0, 1.0, 0.0), Color::WHITE, 1.0));
+        let c = scene.get_collision(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 10.0);
+        assert!(c.hit);
+        assert_eq!(c.c, Color::RGB(0, 0, 0));
+    }
+}
@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use sdl2::pixels::Color;
+    use rust3d::engine::lighting::Material;
+    use rust3d::engine::pathtracing::{Integrator, RayTracingScene};
+    use rust3d::engine::utils::random::Rng;
+    use rust3d::engine::utils::texture::Texture;
+    use rust3d::geometry::sphere::Sphere;
+    use rust3d::geometry::vector3::Vector3 as V3;
+
+    fn assert_approx_eq(a: f64, b: f64, eps: f64) {
+        assert!((a - b).abs() < eps, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_fresnel() {
+        let glass = Material::new(Color::WHITE, 0.0);
+        // ((1.5 - 1) / (1.5 + 1))^2
+        assert_approx_eq(glass.f0().r, 0.04, 1e-12);
+        assert_approx_eq(glass.fresnel(1.0).g, 0.04, 1e-12);
+        assert_approx_eq(glass.fresnel(0.0).b, 1.0, 1e-12);
+        assert!(glass.fresnel(0.3).r > glass.fresnel(0.7).r);
+
+        let gold = Material::metal(Color::RGB(255, 204, 0), 0.2);
+        assert_approx_eq(gold.f0().r, 1.0, 1e-12);
+        assert_approx_eq(gold.f0().g, 0.8, 1e-12);
+        assert_approx_eq(gold.f0().b, 0.0, 1e-12);
+    }
+
+    #[test]
+    fn test_metal_and_dielectric() {
+        let plastic = Material::new(Color::RGB(255, 0, 0), 0.5);
+        let metal = Material::metal(Color::RGB(255, 0, 0), 0.5);
+        assert_eq!(plastic.diffuse_weight(), 1.0);
+        assert_eq!(metal.diffuse_weight(), 0.0);
+        assert!(metal.diffuse_color(1.0).is_black());
+        assert!(plastic.diffuse_color(1.0).r > 0.9);
+
+        // a smooth surface has a sharper highlight than a rough one
+        let n = V3::new(0.0, -1.0, 0.0);
+        let smooth = Material::new(Color::WHITE, 0.1);
+        let rough = Material::new(Color::WHITE, 0.9);
+        assert!(smooth.shininess() > rough.shininess());
+        assert!(smooth.specular(n, n, n).r > rough.specular(n, n, n).r);
+
+        let mut off = V3::new(0.5, -1.0, 0.0);
+        off.normalize();
+        assert!(smooth.specular(n, n, off).r < rough.specular(n, n, off).r);
+    }
+
+    #[test]
+    fn test_texture_sampling() {
+        let albedo = Texture::new(2, 1, vec![255, 0, 0, 0, 0, 255]);
+        let roughness = Texture::new(2, 1, vec![0, 0, 0, 255, 0, 0]);
+
+        let mut m = Material::new(Color::WHITE, 0.5);
+        m.set_albedo_texture(Arc::new(albedo));
+        m.set_roughness_texture(Arc::new(roughness));
+
+        let left = m.sample(0.25, 0.5);
+        let right = m.sample(0.75, 0.5);
+        assert_eq!(left.albedo, Color::RED);
+        assert_eq!(right.albedo, Color::BLUE);
+        assert_eq!(left.roughness, 0.0);
+        assert_eq!(right.roughness, 1.0);
+        assert!(left.albedo_texture.is_none());
+    }
+
+    #[test]
+    fn test_emissive_sphere() {
+        let mut scene = RayTracingScene::new();
+        scene.set_integrator(Integrator::PathTracing);
+        scene.set_ambient_light(Color::RGB(0, 0, 0));
+        scene.add(Sphere::new(V3::new(3.0, 0.0, 0.0), 1.0, Material::emissive(Color::RGB(255, 128, 0), 2.0)));
+
+        let mut rng = Rng::new(1);
+        let l = scene.trace_path(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), &mut rng);
+        assert_approx_eq(l.r, 2.0, 1e-9);
+        assert_approx_eq(l.g, 2.0 * 128.0 / 255.0, 1e-9);
+        assert_approx_eq(l.b, 0.0, 1e-9);
+    }
+}
@@ -0,0 +1,150 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use sdl2::pixels::Color;
+    use rust3d::engine::camera::RayCamera;
+    use rust3d::engine::lighting::{Light, Material};
+    use rust3d::engine::pathtracing::{Integrator, RayTracingScene};
+    use rust3d::engine::utils::accumulator::Accumulator;
+    use rust3d::engine::utils::radiance::Radiance;
+    use rust3d::engine::utils::random::Rng;
+    use rust3d::geometry::quad::Quad;
+    use rust3d::geometry::sphere::Sphere;
+    use rust3d::geometry::vector3::Vector3 as V3;
+
+    fn assert_approx_eq(a: f64, b: f64, eps: f64) {
+        assert!((a - b).abs() < eps, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_rng_is_reproducible() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        let mut c = Rng::with_stream(42, 1);
+
+        let mut mean = 0.0;
+        let mut differs = false;
+        for _ in 0..10000 {
+            let x = a.next_f64();
+            assert_eq!(x, b.next_f64());
+            assert!((0.0..1.0).contains(&x));
+            differs |= x != c.next_f64();
+            mean += x;
+        }
+        assert!(differs);
+        assert_approx_eq(mean / 10000.0, 0.5, 0.02);
+    }
+
+    #[test]
+    fn test_white_furnace() {
+        // a convex object under a uniform sky reflects exactly albedo * sky
+        let mut scene = RayTracingScene::new();
+        scene.set_integrator(Integrator::PathTracing);
+        scene.set_ambient_light(Color::RGB(102, 102, 102));
+        scene.add(Sphere::new(V3::new(4.0, 0.0, 0.0), 1.0, Material::new(Color::RGB(255, 255, 255), 1.0)));
+
+        // the specular lobe loses a little energy at grazing angles
+        let mut rng = Rng::new(1);
+        for k in 0..10 {
+            let dir = V3::new(1.0, (k as f64 - 5.0) / 25.0, 0.1);
+            let l = (0..20000).fold(Radiance::black(), |sum, _| sum + scene.trace_path(V3::new(0.0, 0.0, 0.0), dir, &mut rng)) / 20000.0;
+            assert_approx_eq(l.r, 0.4, 0.02);
+            assert_approx_eq(l.b, 0.4, 0.02);
+        }
+    }
+
+    #[test]
+    fn test_glossy_bounce_matches_light_sampling() {
+        // a rough metal floor reflecting a square light, once as a light and once as glowing
+        // geometry that only the sampled specular lobe can find
+        let floor = |scene: &mut RayTracingScene| {
+            scene.set_integrator(Integrator::PathTracing);
+            scene.set_ambient_light(Color::RGB(0, 0, 0));
+            scene.set_max_depth(2);
+            let mut quad = Quad::new(V3::new(0.0, 1.0, 0.0), V3::new(10.0, 0.1, 10.0), Color::WHITE);
+            quad.set_material(Material::metal(Color::WHITE, 0.4));
+            scene.add(quad);
+        };
+        let mut lit = RayTracingScene::new();
+        floor(&mut lit);
+        lit.add_light(Light::rect_area(V3::new(2.0, -1.0, 0.0), V3::new(1.0, 0.0, 0.0), V3::new(0.0, 0.0, -1.0), Color::WHITE, 1.0));
+
+        let mut glowing = RayTracingScene::new();
+        floor(&mut glowing);
+        let mut lamp = Quad::new(V3::new(2.0, -1.0, 0.0), V3::new(1.0, 0.01, 1.0), Color::BLACK);
+        lamp.set_material(Material::emissive(Color::WHITE, 1.0));
+        glowing.add(lamp);
+
+        // looking at the mirror image of the light
+        let dir = V3::new(0.655, 0.95, 0.0).normalized();
+        let mean = |scene: &RayTracingScene| {
+            let mut rng = Rng::new(7);
+            (0..40000).map(|_| scene.trace_path(V3::new(0.0, 0.0, 0.0), dir, &mut rng).g).sum::<f64>() / 40000.0
+        };
+        let (a, b) = (mean(&lit), mean(&glowing));
+        assert!(a > 0.05, "{}", a);
+        assert_approx_eq(b / a, 1.0, 0.1);
+    }
+
+    #[test]
+    fn test_direct_light_and_shadow() {
+        let mut scene = RayTracingScene::new();
+        scene.set_integrator(Integrator::PathTracing);
+        scene.set_ambient_light(Color::RGB(0, 0, 0));
+        scene.set_max_depth(1);
+        // floor plane below the camera (+y is down), light straight above the hit point
+        scene.add(Quad::new(V3::new(0.0, 1.0, 0.0), V3::new(10.0, 0.1, 10.0), Color::RGB(255, 255, 255)));
+        scene.add_light(Light::point(V3::new(2.0, -3.0, 0.0), Color::RGB(255, 255, 255), 0.5));
+
+        let mut rng = Rng::new(3);
+        let mut dir = V3::new(2.0, 0.95, 0.0);
+        dir.normalize();
+        let lit = scene.trace_path(V3::new(0.0, 0.0, 0.0), dir, &mut rng);
+
+        // the light is straight above the hit point, the plain dielectric loses the part
+        // that fresnel reflects into the specular lobe
+        let normal = V3::new(0.0, -1.0, 0.0);
+        let mut view = dir;
+        view.scale(-1.0);
+        let m = Material::new(Color::RGB(255, 255, 255), 1.0);
+        let brdf = m.diffuse_color(normal.dt(view)) + m.specular(normal, view, normal);
+        assert_approx_eq(lit.g, 0.5 * brdf.g, 1e-6);
+        assert!(lit.g < 0.5);
+
+        scene.add(Sphere::new(V3::new(2.0, -1.0, 0.0), 0.5, Material::new(Color::WHITE, 1.0)));
+        let shadowed = scene.trace_path(V3::new(0.0, 0.0, 0.0), dir, &mut rng);
+        assert_eq!(shadowed, Radiance::black());
+    }
+
+    #[test]
+    fn test_progressive_accumulation() {
+        let mut scene = RayTracingScene::new();
+        scene.set_integrator(Integrator::PathTracing);
+        scene.set_seed(9);
+        scene.add(Quad::new(V3::new(3.0, 0.0, 0.0), V3::new(0.1, 6.0, 6.0), Color::RGB(200, 120, 60)));
+        scene.add(Sphere::new(V3::new(2.0, 0.3, -0.3), 0.5, Material::new(Color::RGB(60, 200, 90), 1.0)));
+        scene.add_light(Light::point(V3::new(0.5, -1.0, 0.5), Color::WHITE, 0.8));
+
+        let camera = RayCamera::new(V3::new(-1.0, 0.0, 0.0), 0.0, 0.0, 0.0);
+        let scene = Arc::new(scene);
+
+        let mut single = Accumulator::new(24, 16);
+        let mut multi = Accumulator::new(24, 16);
+        for _ in 0..3 {
+            camera.render_progressive(&scene, &mut single);
+            camera.render_progressive_multi(Arc::clone(&scene), &mut multi, 4);
+        }
+        assert_eq!(single.frames, 3);
+        assert_eq!(single.sum, multi.sum);
+
+        // another frame changes the estimate, a reset starts over
+        let before = single.get(12, 8);
+        camera.render_progressive(&scene, &mut single);
+        assert_ne!(before, single.get(12, 8));
+
+        single.reset();
+        assert_eq!(single.frames, 0);
+        assert_eq!(single.get(12, 8), Radiance::black());
+    }
+}
@@ -0,0 +1,142 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use sdl2::pixels::Color;
+    use rust3d::engine::camera::RayCamera;
+    use rust3d::engine::lighting::{Light, Material};
+    use rust3d::engine::pathtracing::RayTracingScene;
+    use rust3d::engine::progressive::ProgressiveRenderer;
+    use rust3d::engine::utils::rendering::RayRenderable;
+    use rust3d::engine::utils::transformation::{Transformable, Watched};
+    use rust3d::geometry::sphere::Sphere;
+    use rust3d::geometry::vector3::Vector3 as V3;
+
+    const W: usize = 24;
+    const H: usize = 16;
+
+    fn sphere_scene() -> RayTracingScene {
+        let mut scene = RayTracingScene::new();
+        scene.add(Sphere::new(V3::new(3.0, 0.0, 0.0), 1.0, Material::new(Color::RGB(200, 40, 40), 1.0)));
+        scene
+    }
+
+    fn camera() -> RayCamera {
+        RayCamera::new(V3::new(0.0, 0.0, 0.0), 0.0, 0.0, 0.0)
+    }
+
+    /// Steps until `samples` passes are in, the passes finish in the background.
+    fn run<R: RayRenderable + 'static>(renderer: &mut ProgressiveRenderer, camera: &RayCamera, obj: &Arc<R>, version: u64, samples: u32) {
+        let start = Instant::now();
+        while renderer.samples() < samples {
+            renderer.step(camera, Arc::clone(obj), version);
+            assert!(start.elapsed() < Duration::from_secs(20), "no progress");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_coarse_to_fine() {
+        let mut renderer = ProgressiveRenderer::new(W, H, 2);
+        let scene = Arc::new(sphere_scene());
+        assert_eq!(renderer.block_size(), 0);
+
+        for block in [16, 8, 4, 2] {
+            assert!(renderer.step(&camera(), Arc::clone(&scene), 0));
+            assert_eq!(renderer.block_size(), block);
+            assert_eq!(renderer.samples(), 0);
+        }
+
+        run(&mut renderer, &camera(), &scene, 0, 3);
+        assert_eq!(renderer.block_size(), 1);
+        // the sphere is in the middle of the image
+        assert_eq!(renderer.frame().get_pixel(W / 2, H / 2).r, 200);
+        assert!((renderer.radiance(W / 2, H / 2).r - 200.0 / 255.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_accumulation_smooths_edges() {
+        let mut renderer = ProgressiveRenderer::new(W, H, 2);
+        renderer.set_start_block(1);
+        renderer.set_max_samples(32);
+        let scene = Arc::new(sphere_scene());
+        run(&mut renderer, &camera(), &scene, 0, 32);
+
+        // pixels on the outline of the sphere get partial coverage
+        let partial = (0..W)
+            .flat_map(|x| (0..H).map(move |y| (x, y)))
+            .filter(|&(x, y)| {
+                let r = renderer.radiance(x, y).r;
+                r > 0.05 && r < 0.75
+            })
+            .count();
+        assert!(partial > 4, "{}", partial);
+
+        // no more passes once the limit is reached
+        assert!(renderer.is_converged());
+        for _ in 0..5 {
+            assert!(!renderer.step(&camera(), Arc::clone(&scene), 0));
+        }
+        assert_eq!(renderer.samples(), 32);
+    }
+
+    #[test]
+    fn test_resets_on_change() {
+        let mut renderer = ProgressiveRenderer::new(W, H, 2);
+        renderer.set_start_block(4);
+        let mut scene = Watched::new(sphere_scene());
+        let snapshot = Arc::new(RayTracingScene::wrapup(scene.get()));
+        run(&mut renderer, &camera(), &snapshot, scene.version(), 2);
+
+        // the camera moved
+        let mut moved = camera();
+        moved.rot(V3::new(0.0, 0.1, 0.0));
+        renderer.step(&moved, Arc::clone(&snapshot), scene.version());
+        assert_eq!(renderer.samples(), 0);
+        assert_eq!(renderer.block_size(), 4);
+        run(&mut renderer, &moved, &snapshot, scene.version(), 1);
+
+        // the scene changed
+        scene.get_mut().get(0).translate(V3::new(0.0, 0.5, 0.0));
+        let snapshot = Arc::new(RayTracingScene::wrapup(scene.get()));
+        renderer.step(&moved, Arc::clone(&snapshot), scene.version());
+        assert_eq!(renderer.samples(), 0);
+        assert_eq!(renderer.block_size(), 4);
+    }
+
+    #[test]
+    fn test_passes_keep_high_dynamic_range() {
+        let mut scene = RayTracingScene::new();
+        scene.add(Sphere::new(V3::new(3.0, 0.0, 0.0), 1.0, Material::new(Color::WHITE, 1.0)));
+        scene.add_light(Light::point(V3::new(0.0, 0.0, 0.5), Color::WHITE, 4.0));
+        scene.add_light(Light::point(V3::new(0.0, 0.0, -0.5), Color::WHITE, 4.0));
+        let scene = Arc::new(scene);
+
+        let mut renderer = ProgressiveRenderer::new(W, H, 2);
+        renderer.set_start_block(1);
+        run(&mut renderer, &camera(), &scene, 0, 2);
+        // the passes are not clamped to 8 bit colors, only the preview is
+        let expected = scene.get_radiance(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), f64::INFINITY);
+        assert!(expected.r > 1.0, "{:?}", expected);
+        assert!((renderer.radiance(W / 2, H / 2).r - expected.r).abs() < 0.05 * expected.r);
+        assert_eq!(renderer.frame().get_pixel(W / 2, H / 2).r, 255);
+    }
+
+    #[test]
+    fn test_watched_version() {
+        let mut sphere = Watched::new(Sphere::new(V3::new(3.0, 0.0, 0.0), 1.0, Material::new(Color::RED, 1.0)));
+        assert_eq!(sphere.version(), 0);
+        let _ = sphere.get().d_(V3::new(0.0, 0.0, 0.0));
+        assert_eq!(sphere.version(), 0);
+
+        sphere.rot(V3::new(0.1, 0.0, 0.0));
+        sphere.rot_by(V3::new(1.0, 0.0, 0.0), V3::new(0.0, 0.1, 0.0));
+        sphere.scale(V3::new(2.0, 2.0, 2.0));
+        sphere.transform().translate(V3::new(1.0, 0.0, 0.0));
+        assert_eq!(sphere.version(), 4);
+
+        sphere.get_mut().set_material(Material::new(Color::BLUE, 1.0));
+        assert_eq!(sphere.version(), 5);
+    }
+}
@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod tests {
+    use sdl2::pixels::Color;
+    use rust3d::engine::camera::RayCamera;
+    use rust3d::engine::lighting::Material;
+    use rust3d::engine::pathtracing::RayTracingScene;
+    use rust3d::engine::projection::rasterizer::{Rasterizer, Triangle};
+    use rust3d::engine::utils::aov::AovSelection;
+    use rust3d::engine::utils::virtual_canvas::VirtualCanvas;
+    use rust3d::geometry::quad::Quad;
+    use rust3d::geometry::sphere::Sphere;
+    use rust3d::geometry::vector3::Vector3 as V3;
+
+    const W: usize = 41;
+    const H: usize = 31;
+    const BACKGROUND: Color = Color::RGB(10, 20, 30);
+
+    fn camera() -> RayCamera {
+        RayCamera::new(V3::new(0.0, 0.0, 0.0), 0.0, 0.0, 0.0)
+    }
+
+    fn render(rasterizer: &Rasterizer) -> VirtualCanvas {
+        let mut canvas = VirtualCanvas::new(W, H);
+        rasterizer.render(&camera(), &mut canvas);
+        canvas
+    }
+
+    fn is_background(canvas: &VirtualCanvas, x: usize, y: usize) -> bool {
+        canvas.get_sdl2_color(x, y) == BACKGROUND
+    }
+
+    #[test]
+    fn test_depth_order() {
+        let wall = Quad::new(V3::new(3.0, 0.0, 0.0), V3::new(0.1, 0.5, 0.5), Color::RED);
+        let mut front_first = Rasterizer::new();
+        front_first.set_background(BACKGROUND);
+        front_first.add_quad(&wall, Color::RED);
+        front_first.add_sphere(V3::new(6.0, 0.0, 0.0), 1.5, Color::BLUE);
+        let mut back_first = Rasterizer::new();
+        back_first.set_background(BACKGROUND);
+        back_first.add_sphere(V3::new(6.0, 0.0, 0.0), 1.5, Color::BLUE);
+        back_first.add_quad(&wall, Color::RED);
+
+        let a = render(&front_first);
+        assert_eq!(a.pixels, render(&back_first).pixels);
+        // the wall faces the camera and is drawn at full brightness, the ball shows around it
+        assert_eq!(a.get_sdl2_color(W / 2, H / 2), Color::RED);
+        let ring = a.get_sdl2_color(W / 2 + 5, H / 2);
+        assert!(ring.b > 0 && ring.r == 0, "{:?}", ring);
+        assert!(is_background(&a, 0, 0));
+    }
+
+    #[test]
+    fn test_clipping() {
+        // a floor below the camera that reaches behind it fills the lower half of the image
+        let mut rasterizer = Rasterizer::new();
+        rasterizer.set_background(BACKGROUND);
+        rasterizer.add_triangle(Triangle::new(V3::new(-20.0, 1.0, -20.0), V3::new(20.0, 1.0, -20.0), V3::new(20.0, 1.0, 20.0), Color::WHITE));
+        rasterizer.add_triangle(Triangle::new(V3::new(-20.0, 1.0, -20.0), V3::new(20.0, 1.0, 20.0), V3::new(-20.0, 1.0, 20.0), Color::WHITE));
+        let canvas = render(&rasterizer);
+        for x in 0..W {
+            assert!(is_background(&canvas, x, 0));
+            assert!(!is_background(&canvas, x, H - 1));
+        }
+        // seen at a flat angle the floor is darker close to the horizon
+        assert!(canvas.get_sdl2_color(W / 2, H / 2 + 2).r < canvas.get_sdl2_color(W / 2, H - 1).r);
+
+        // nothing behind the camera shows
+        let mut behind = Rasterizer::new();
+        behind.set_background(BACKGROUND);
+        behind.add_sphere(V3::new(-3.0, 0.0, 0.0), 1.0, Color::WHITE);
+        assert!(render(&behind).pixels.chunks(4).all(|p| p[..3] == [10, 20, 30]));
+    }
+
+    #[test]
+    fn test_matches_ray_tracer() {
+        // the outline of a ball covers the same pixels as in the ray traced object ids
+        let mut rasterizer = Rasterizer::new();
+        rasterizer.set_background(BACKGROUND);
+        rasterizer.add_sphere(V3::new(5.0, 0.5, -1.0), 1.5, Color::WHITE);
+        let canvas = render(&rasterizer);
+
+        let mut scene = RayTracingScene::new();
+        scene.add(Sphere::new(V3::new(5.0, 0.5, -1.0), 1.5, Material::new(Color::WHITE, 1.0)));
+        let ids = camera().render_aovs(&scene, W, H, AovSelection { object_id: true, ..AovSelection::none() }).object_id.unwrap();
+
+        let mut differences = 0;
+        let mut covered = 0;
+        for y in 0..H {
+            for x in 0..W {
+                let traced = ids[y * W + x].is_some();
+                covered += traced as usize;
+                differences += (traced == is_background(&canvas, x, y)) as usize;
+            }
+        }
+        assert!(covered > 100);
+        assert!(differences * 20 < covered, "{} of {}", differences, covered);
+    }
+}
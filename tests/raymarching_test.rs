@@ -0,0 +1,191 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use sdl2::pixels::Color;
+    use rust3d::engine::lighting::{Light, Material};
+    use rust3d::engine::raymarching::{RayMarchingObject, RayMarchingScene};
+    use rust3d::engine::sdf::primitives::{Plane, RoundedBox};
+    use rust3d::engine::utils::rendering::RayRenderable;
+    use rust3d::engine::utils::transformation::Transformable;
+    use rust3d::geometry::sphere::Sphere;
+    use rust3d::geometry::vector3::Vector3 as V3;
+
+    fn up() -> V3 {
+        V3::new(0.0, -1.0, 0.0)
+    }
+
+    /// Floor at y = 1 with a ball floating above it and a light straight above the ball.
+    fn shadow_scene() -> RayMarchingScene {
+        let mut scene = RayMarchingScene::new(0.001);
+        scene.add(Plane::new(V3::new(0.0, 1.0, 0.0), up(), Material::new(Color::WHITE, 1.0)));
+        scene.add(Sphere::new(V3::new(0.0, -1.0, 0.0), 0.5, Material::new(Color::WHITE, 1.0)));
+        scene.add_light(Light::point(V3::new(0.0, -5.0, 0.0), Color::WHITE, 1.0));
+        scene
+    }
+
+    /// Sphere that counts how often its distance is asked for.
+    struct Counted {
+        sphere: Sphere,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl Transformable for Counted {
+        fn rot(&mut self, r: V3) {
+            self.sphere.rot(r);
+        }
+
+        fn rot_by(&mut self, p: V3, r: V3) {
+            self.sphere.rot_by(p, r);
+        }
+
+        fn translate(&mut self, p: V3) {
+            self.sphere.translate(p);
+        }
+
+        fn scale(&mut self, p: V3) {
+            self.sphere.scale(p);
+        }
+
+        fn transform(&mut self) -> Box<&mut dyn Transformable> {
+            Box::new(self)
+        }
+    }
+
+    impl RayMarchingObject for Counted {
+        fn sdf(&self, p: V3) -> f64 {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            self.sphere.sdf(p)
+        }
+
+        fn clone(&self) -> Box<dyn RayMarchingObject + Send + Sync> {
+            Box::new(Counted { sphere: Clone::clone(&self.sphere), calls: Arc::clone(&self.calls) })
+        }
+
+        fn get_material(&self) -> &Material {
+            self.sphere.get_material()
+        }
+    }
+
+    /// Ray from the side that hits the floor at `x`.
+    fn floor_color(scene: &RayMarchingScene, x: f64) -> Color {
+        let p0 = V3::new(x - 3.0, -2.0, 0.0);
+        let mut dir = V3::new(3.0, 3.0, 0.0);
+        dir.normalize();
+        let c = scene.get_collision(p0, dir, 50.0);
+        assert!(c.hit);
+        assert!((c.p.y - 1.0).abs() < 0.01);
+        c.c
+    }
+
+    #[test]
+    fn test_soft_shadow() {
+        let scene = shadow_scene();
+        let at = |x: f64| {
+            let p = V3::new(x, 1.0, 0.0);
+            let mut to_light = V3::new(0.0, -5.0, 0.0);
+            to_light.subtract(p);
+            let distance = to_light.norm();
+            to_light.normalize();
+            scene.soft_shadow(p, up(), to_light, distance)
+        };
+
+        assert_eq!(at(0.0), 0.0);
+        assert_eq!(at(4.0), 1.0);
+        // the geometric shadow ends at x = 0.5 * 6 / 4; the penumbra reaches beyond it
+        let edge = at(0.85);
+        assert!(edge > 0.0 && edge < 1.0, "{}", edge);
+        assert!(at(0.9) > at(0.8));
+
+        // harder shadows fade in faster outside of the umbra
+        let mut hard = shadow_scene();
+        hard.set_shadow_softness(64.0);
+        let mut to_light = V3::new(-0.85, -6.0, 0.0);
+        let distance = to_light.norm();
+        to_light.normalize();
+        assert!(hard.soft_shadow(V3::new(0.85, 1.0, 0.0), up(), to_light, distance) > edge);
+    }
+
+    #[test]
+    fn test_shadows_in_rendering() {
+        let mut scene = shadow_scene();
+        let lit = floor_color(&scene, 0.0);
+        assert!(lit.g > 200);
+
+        scene.set_shadows(true);
+        let scene = RayMarchingScene::wrapup(&scene);
+        assert_eq!(floor_color(&scene, 0.0), Color::RGB(0, 0, 0));
+        assert_eq!(floor_color(&scene, 4.0).g, {
+            let mut open = shadow_scene();
+            open.set_shadows(false);
+            floor_color(&open, 4.0).g
+        });
+    }
+
+    #[test]
+    fn test_ambient_occlusion() {
+        let mut scene = RayMarchingScene::new(0.001);
+        scene.add(Plane::new(V3::new(0.0, 1.0, 0.0), up(), Material::new(Color::WHITE, 1.0)));
+        scene.add(RoundedBox::new(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 1.0, 1.0), 0.0, Material::new(Color::WHITE, 1.0)));
+
+        let open = scene.ambient_occlusion(V3::new(5.0, 1.0, 0.0), up());
+        let crease = scene.ambient_occlusion(V3::new(1.02, 1.0, 0.0), up());
+        assert!((open - 1.0).abs() < 1e-9, "{}", open);
+        assert!(crease < 0.8, "{}", crease);
+
+        // only the ambient light is occluded
+        scene.add_light(Light::directional(up(), Color::WHITE, 0.0));
+        scene.set_ambient_light(Color::RGB(128, 128, 128));
+        let mut dir = V3::new(-1.0, 3.0, 0.0);
+        dir.normalize();
+        let before = scene.get_collision(V3::new(2.05, -2.0, 0.0), dir, 50.0);
+        scene.set_ambient_occlusion(true);
+        let after = scene.get_collision(V3::new(2.05, -2.0, 0.0), dir, 50.0);
+        assert!(before.hit && after.hit);
+        assert!((after.p.x - 1.05).abs() < 0.01);
+        assert_eq!(before.c.g, 128);
+        assert!(after.c.g < before.c.g, "{}", after.c.g);
+    }
+
+    #[test]
+    fn test_specular_highlight() {
+        let mut scene = RayMarchingScene::new(0.001);
+        scene.add(Sphere::new(V3::new(3.0, 0.0, 0.0), 1.0, Material::new(Color::RGB(100, 100, 100), 0.2)));
+        scene.add_light(Light::point(V3::new(0.0, 0.0, 0.0), Color::WHITE, 0.5));
+
+        let plain = scene.get_collision(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 10.0);
+        scene.set_specular(true);
+        let shiny = scene.get_collision(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 10.0);
+        assert!(shiny.c.g > plain.c.g + 50, "{} {}", shiny.c.g, plain.c.g);
+
+        // away from the reflection of the light there is hardly any highlight
+        let mut dir = V3::new(3.0, 0.9, 0.0);
+        dir.normalize();
+        let shiny = scene.get_collision(V3::new(0.0, 0.0, 0.0), dir, 10.0);
+        scene.set_specular(false);
+        let plain = scene.get_collision(V3::new(0.0, 0.0, 0.0), dir, 10.0);
+        assert!(shiny.c.g - plain.c.g < 5, "{} {}", shiny.c.g, plain.c.g);
+    }
+
+    #[test]
+    fn test_surface_without_shading() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut scene = shadow_scene();
+        scene.set_shadows(true);
+        scene.set_ambient_occlusion(true);
+        scene.add(Counted { sphere: Sphere::new(V3::new(3.0, 0.0, 0.0), 1.0, Material::new(Color::WHITE, 1.0)), calls: Arc::clone(&calls) });
+
+        let (p0, dir) = (V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0));
+        let c = scene.get_collision(p0, dir, 10.0);
+        let shaded = calls.swap(0, Ordering::Relaxed);
+        let hit = scene.get_surface(p0, dir, 10.0).unwrap();
+        let unshaded = calls.swap(0, Ordering::Relaxed);
+        assert_eq!((hit.p, hit.d, hit.object), (c.p, c.d, 2));
+        // marching, picking the object and the normal, but no lights, shadows or occlusion
+        assert!(unshaded < shaded, "{} {}", unshaded, shaded);
+
+        // misses stop after marching
+        assert!(scene.get_surface(p0, V3::new(0.0, 0.0, 1.0), 10.0).is_none());
+    }
+}
@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod tests {
+    use sdl2::pixels::Color;
+    use rust3d::engine::lighting::{Light, Material};
+    use rust3d::engine::pathtracing::{Integrator, PathtracingObject, RayTracingScene};
+    use rust3d::engine::simplex3d_bvh::bvh::Bvh;
+    use rust3d::engine::simplex3d_sphere_tree::poly_tree::PolyTree;
+    use rust3d::engine::utils::radiance::Radiance;
+    use rust3d::engine::utils::random::Rng;
+    use rust3d::engine::utils::rendering::RayRenderable;
+    use rust3d::geometry::face::Face;
+    use rust3d::geometry::quad::Quad;
+    use rust3d::geometry::simplex3d::{MeshMaterial, Simplex3D};
+    use rust3d::geometry::sphere::Sphere;
+    use rust3d::geometry::vector3::Vector3 as V3;
+
+    fn assert_approx_eq(a: f64, b: f64, eps: f64) {
+        assert!((a - b).abs() < eps, "{} != {}", a, b);
+    }
+
+    /// Closed cube mesh around `m` with outward facing normals.
+    fn cube_mesh(m: V3, a: f64, material: Material) -> Simplex3D {
+        let h = a / 2.0;
+        let c = |x: f64, y: f64, z: f64| V3::new(m.x + x * h, m.y + y * h, m.z + z * h);
+        let quads = [
+            [c(-1., -1., -1.), c(1., -1., -1.), c(1., 1., -1.), c(-1., 1., -1.)],
+            [c(-1., -1., 1.), c(1., -1., 1.), c(1., 1., 1.), c(-1., 1., 1.)],
+            [c(-1., -1., -1.), c(-1., 1., -1.), c(-1., 1., 1.), c(-1., -1., 1.)],
+            [c(1., -1., -1.), c(1., 1., -1.), c(1., 1., 1.), c(1., -1., 1.)],
+            [c(-1., -1., -1.), c(1., -1., -1.), c(1., -1., 1.), c(-1., -1., 1.)],
+            [c(-1., 1., -1.), c(1., 1., -1.), c(1., 1., 1.), c(-1., 1., 1.)],
+        ];
+
+        let mut faces = Vec::new();
+        for q in quads.iter() {
+            for (r, a, b) in [(q[0], q[1], q[2]), (q[0], q[2], q[3])] {
+                let mut f = Face::new(r, a, b);
+                let mut out = r;
+                out.subtract(m);
+                if f.n.dt(out) < 0.0 {
+                    f = Face::new(r, b, a);
+                }
+                f.set_material(0);
+                faces.push(f);
+            }
+        }
+
+        let mut mesh = Simplex3D::new(m, faces);
+        mesh.materials.push(MeshMaterial::new(String::from("glass"), material));
+        mesh
+    }
+
+    /// Glass object in front of a glowing wall, everything else black.
+    fn scene_with(obj: impl PathtracingObject + 'static + Send + Sync) -> RayTracingScene {
+        let mut scene = RayTracingScene::new();
+        scene.set_ambient_light(Color::RGB(0, 0, 0));
+        let mut wall = Quad::new(V3::new(10.0, 0.0, 0.0), V3::new(0.1, 20.0, 20.0), Color::WHITE);
+        wall.set_material(Material::emissive(Color::WHITE, 0.5));
+        scene.add(wall);
+        scene.add(obj);
+        scene
+    }
+
+    fn mean_path(scene: &RayTracingScene, p0: V3, p: V3, n: usize) -> Radiance {
+        let mut rng = Rng::new(5);
+        let mut sum = Radiance::black();
+        for _ in 0..n {
+            sum += scene.trace_path(p0, p, &mut rng);
+        }
+        sum / n as f64
+    }
+
+    // straight through at normal incidence, 4% are reflected at each of the two surfaces
+    const THROUGH_GLASS: f64 = 0.5 * 0.96 * 0.96;
+
+    #[test]
+    fn test_glass_is_transparent_for_all_shapes() {
+        let glass = Material::glass(1.5);
+        let scenes = vec![
+            scene_with(Sphere::new(V3::new(5.0, 0.0, 0.0), 1.0, glass.clone())),
+            scene_with({
+                let mut q = Quad::cube(V3::new(5.0, 0.0, 0.0), 2.0, Color::WHITE);
+                q.set_material(glass.clone());
+                q
+            }),
+            scene_with(*PolyTree::new(cube_mesh(V3::new(5.0, 0.1, 0.1), 2.0, glass.clone()))),
+            scene_with(*Bvh::new(cube_mesh(V3::new(5.0, 0.1, 0.1), 2.0, glass.clone()))),
+        ];
+
+        // slightly off the axis, the face test of the meshes does not like axis-parallel rays
+        let mut dir = V3::new(1.0, 0.03, 0.02);
+        dir.normalize();
+        for mut scene in scenes {
+            scene.set_integrator(Integrator::PathTracing);
+            let l = mean_path(&scene, V3::new(0.0, 0.0, 0.0), dir, 8000);
+            assert_approx_eq(l.g, THROUGH_GLASS, 0.015);
+        }
+    }
+
+    #[test]
+    fn test_whitted_refraction() {
+        let mut scene = scene_with(Sphere::new(V3::new(5.0, 0.0, 0.0), 1.0, Material::glass(1.5)));
+        scene.add_light(Light::point(V3::new(0.0, -5.0, 0.0), Color::WHITE, 0.0));
+
+        let c = scene.get_collision(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 0.0);
+        assert!(c.hit);
+        assert_eq!(c.c, Radiance::gray(THROUGH_GLASS).to_color());
+
+        // opaque spheres hide the wall
+        let mut scene = scene_with(Sphere::new(V3::new(5.0, 0.0, 0.0), 1.0, Material::new(Color::WHITE, 1.0)));
+        scene.add_light(Light::point(V3::new(0.0, -5.0, 0.0), Color::WHITE, 0.0));
+        let c = scene.get_collision(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 0.0);
+        assert_eq!(c.c, Color::RGB(0, 0, 0));
+    }
+
+    #[test]
+    fn test_total_internal_reflection() {
+        // a chord at 0.9 r hits the surface at asin(0.9) = 64°, beyond the critical angle of
+        // glass (42°): the light stays trapped inside the sphere
+        let mut scene = RayTracingScene::new();
+        scene.set_integrator(Integrator::PathTracing);
+        scene.set_ambient_light(Color::WHITE);
+        scene.add(Sphere::new(V3::new(5.0, 0.0, 0.0), 1.0, Material::glass(1.5)));
+
+        let l = mean_path(&scene, V3::new(5.0, 0.9, 0.0), V3::new(1.0, 0.0, 0.0), 200);
+        assert_eq!(l, Radiance::black());
+
+        // without a jump in the index of refraction it escapes
+        let mut scene = RayTracingScene::new();
+        scene.set_integrator(Integrator::PathTracing);
+        scene.set_ambient_light(Color::WHITE);
+        scene.add(Sphere::new(V3::new(5.0, 0.0, 0.0), 1.0, Material::glass(1.0)));
+
+        let l = mean_path(&scene, V3::new(5.0, 0.9, 0.0), V3::new(1.0, 0.0, 0.0), 200);
+        assert!(l.g > 0.9);
+    }
+
+    #[test]
+    fn test_beer_lambert_absorption() {
+        let mut tinted = Material::glass(1.5);
+        // half of the red light is left after 2 units, i.e. the diameter of the sphere
+        tinted.set_absorption(Color::RGB(128, 255, 255), 2.0);
+        assert_approx_eq(tinted.transmittance(2.0).r, 128.0 / 255.0, 1e-9);
+        assert_approx_eq(tinted.transmittance(1.0).r, f64::sqrt(128.0 / 255.0), 1e-9);
+        assert_approx_eq(tinted.transmittance(2.0).g, 1.0, 1e-9);
+
+        let mut scene = scene_with(Sphere::new(V3::new(5.0, 0.0, 0.0), 1.0, tinted));
+        scene.set_integrator(Integrator::PathTracing);
+        let l = mean_path(&scene, V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 4000);
+        assert_approx_eq(l.g, THROUGH_GLASS, 0.015);
+        // a bit less than the diameter because of the surface offsets
+        assert_approx_eq(l.r / l.g, 128.0 / 255.0, 1e-3);
+    }
+
+    #[test]
+    fn test_quad_normal_points_outward_from_inside() {
+        let q = Quad::cube(V3::new(0.0, 0.0, 0.0), 2.0, Color::WHITE);
+        let (c, n) = q.get_collision_with_normal(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0));
+        assert!(c.hit);
+        assert_approx_eq(c.d, 1.0, 1e-9);
+        assert!(n.unwrap().x > 0.99);
+
+        let (_, n) = q.get_collision_with_normal(V3::new(-3.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0));
+        assert!(n.unwrap().x < -0.99);
+    }
+}
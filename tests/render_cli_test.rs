@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+    use std::process::{Command, Output};
+
+    const SCENE: &str = "
+render width 24 height 16
+camera position -4 -1 0 look_at 0 0 0
+material red albedo 200 40 40
+sphere center 0 0 0 radius 1 material red
+quad center 0 1 0 size 10 0.1 10
+light point position -2 -4 1
+";
+
+    fn dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("rust3d_render_cli_test").join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn run(args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_rust3d-render")).args(args).output().unwrap()
+    }
+
+    fn size(path: &Path) -> (u32, u32) {
+        image::open(path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e)).to_rgb8().dimensions()
+    }
+
+    #[test]
+    fn test_renderers() {
+        let dir = dir("renderers");
+        let scene = dir.join("ball.scene");
+        std::fs::write(&scene, SCENE).unwrap();
+
+        for renderer in ["raytracing", "raymarching", "projection"] {
+            let output = dir.join(format!("{}.png", renderer));
+            let result = run(&[scene.to_str().unwrap(), "-q", "-r", renderer, "-t", "2", "-o", output.to_str().unwrap()]);
+            assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+            assert_eq!(size(&output), (24, 16));
+        }
+
+        // size, samples and camera from the command line, with AOVs next to the image
+        let output = dir.join("big.png");
+        let result = run(&[scene.to_str().unwrap(), "-q", "-W", "30", "-H", "20", "-s", "2", "--position", "-5,-2,1", "--look-at", "0,0,0", "--aov", "depth,object_id", "-o", output.to_str().unwrap()]);
+        assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+        assert_eq!(size(&output), (30, 20));
+        for pass in ["big_depth.png", "big_depth.exr", "big_object_id.png"] {
+            assert!(dir.join(pass).exists(), "{}", pass);
+        }
+        assert!(!dir.join("big_normal.png").exists());
+    }
+
+    #[test]
+    fn test_model_turntable() {
+        let dir = dir("turntable");
+        let output = dir.join("cube.png");
+        let result = run(&["demo_assets/models/default_cube.obj", "-q", "-r", "projection", "-W", "16", "-H", "16", "--turntable", "3", "-o", output.to_str().unwrap()]);
+        assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+        for i in 0..3 {
+            assert_eq!(size(&dir.join(format!("cube_{:04}.png", i))), (16, 16));
+        }
+        assert!(!output.exists());
+    }
+
+    #[test]
+    fn test_errors() {
+        let result = run(&["--renderer", "magic", "scene.scene"]);
+        assert_eq!(result.status.code(), Some(2));
+        assert!(String::from_utf8_lossy(&result.stderr).contains("unknown renderer 'magic'"));
+        assert_eq!(run(&["--width", "0", "scene.scene"]).status.code(), Some(2));
+        assert_eq!(run(&["--position", "1,2", "scene.scene"]).status.code(), Some(2));
+        assert_eq!(run(&[]).status.code(), Some(2));
+        assert!(run(&["--help"]).status.success());
+
+        // problems with the scene name the file and line
+        let dir = dir("errors");
+        let scene = dir.join("broken.scene");
+        std::fs::write(&scene, "render width 8 height 8\n\nsphere center 0 0 0 radius 1 material gold\n").unwrap();
+        let result = run(&[scene.to_str().unwrap(), "-o", dir.join("x.png").to_str().unwrap()]);
+        assert_eq!(result.status.code(), Some(1));
+        assert!(String::from_utf8_lossy(&result.stderr).contains("broken.scene:3: unknown material 'gold'"));
+
+        let scene = dir.join("sdf.scene");
+        std::fs::write(&scene, "render width 8 height 8\ntorus center 0 0 0 major 1 minor 0.2\n").unwrap();
+        let result = run(&[scene.to_str().unwrap(), "-r", "projection", "-o", dir.join("x.png").to_str().unwrap()]);
+        assert_eq!(result.status.code(), Some(1));
+        let result = run(&[scene.to_str().unwrap(), "-q", "-r", "raymarching", "--aov", "all", "-o", dir.join("x.png").to_str().unwrap()]);
+        assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+        assert!(dir.join("x_position.exr").exists());
+    }
+}
@@ -0,0 +1,192 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::path::Path;
+
+    use sdl2::pixels::Color;
+    use rust3d::engine::lighting::LightKind;
+    use rust3d::engine::pathtracing::Integrator;
+    use rust3d::engine::scene_file::{SceneDescription, SceneError, Shape};
+    use rust3d::engine::utils::rendering::RayRenderable;
+    use rust3d::geometry::vector3::Vector3 as V3;
+
+    const SCENE: &str = "
+# a ball on a floor
+render width 320 height 200 samples 4 output out.png
+tracer integrator path max_depth 3 ambient 10 20 30
+camera position -5 -1 0 rotation 0 0.1 0.2 fov 0.8 supersampling 2 filter tent
+
+material red albedo 200 40 40 roughness 0.5   # trailing comment
+material light albedo 255 255 255 emission 4 4 4
+
+sphere center 0 0 0 radius 1 material red
+quad center 0 1 0 size 20 0.1 20
+line from 0 0 0 to 1 0 0 thickness 0.1 material light
+light point position 0 -3 0 color 255 200 100 intensity 0.5 attenuation 1 0.1 0.01
+light rect position 0 -2 0 u 1 0 0 v 0 0 1
+";
+
+    fn parse(text: &str) -> Result<SceneDescription, SceneError> {
+        SceneDescription::parse(Cursor::new(text), Path::new("test.scene"))
+    }
+
+    fn error_line(text: &str) -> (usize, String) {
+        match parse(text) {
+            Err(SceneError::Parse { line, message, .. }) => (line, message),
+            other => panic!("expected a parse error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let scene = parse(SCENE).unwrap();
+        assert_eq!((scene.render.width, scene.render.height, scene.render.samples), (320, 200, 4));
+        assert_eq!(scene.render.output.as_deref(), Some("out.png"));
+        assert_eq!(scene.tracer.integrator, Integrator::PathTracing);
+        assert_eq!(scene.tracer.max_depth, 3);
+        assert_eq!(scene.tracer.ambient, Color::RGB(10, 20, 30));
+        assert_eq!(scene.camera.position, V3::new(-5.0, -1.0, 0.0));
+        assert_eq!(scene.camera.fov, 0.8);
+        assert_eq!(scene.camera.supersampling.grid, 2);
+
+        assert_eq!(scene.materials.len(), 2);
+        assert_eq!(scene.material("red").unwrap().material.albedo, Color::RGB(200, 40, 40));
+        assert_eq!(scene.material("light").unwrap().material.emission.r, 4.0);
+
+        assert_eq!(scene.objects.len(), 3);
+        assert_eq!(scene.objects[0].shape, Shape::Sphere { center: V3::new(0.0, 0.0, 0.0), radius: 1.0 });
+        assert_eq!(scene.objects[0].material.as_deref(), Some("red"));
+        assert_eq!(scene.objects[0].line, 10);
+        assert_eq!(scene.objects[1].material, None);
+
+        assert_eq!(scene.lights.len(), 2);
+        assert_eq!(scene.lights[0].color, Color::RGB(255, 200, 100));
+        assert_eq!(scene.lights[0].attenuation.linear, 0.1);
+        assert!(matches!(scene.lights[1].kind, LightKind::RectArea { .. }));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let scene = parse(SCENE).unwrap();
+        let text = scene.to_string();
+        let again = parse(&text).unwrap();
+        assert_eq!(again.to_string(), text);
+        assert_eq!(again.camera, scene.camera);
+        assert_eq!(again.objects.len(), scene.objects.len());
+        for (a, b) in again.objects.iter().zip(scene.objects.iter()) {
+            assert_eq!(a.shape, b.shape);
+            assert_eq!(a.material, b.material);
+        }
+
+        // through a file, with a look_at camera that is written as a rotation
+        let scene = SceneDescription::load("demo_assets/scenes/sdf_shapes.scene").unwrap();
+        let path = std::env::temp_dir().join("rust3d_scene_file_test.scene");
+        scene.save(&path).unwrap();
+        let again = SceneDescription::load(&path).unwrap();
+        assert_eq!(again.camera, scene.camera);
+        assert_eq!(again.to_string(), scene.to_string());
+    }
+
+    #[test]
+    fn test_quoted_paths() {
+        let scene = parse("render output \"my renders/out #1.png\" # comment\nmesh path \"models/a horse.obj\" material red\nmaterial red albedo 1 2 3 albedo_texture \"wood grain.png\"").unwrap();
+        assert_eq!(scene.render.output.as_deref(), Some("my renders/out #1.png"));
+        assert!(matches!(&scene.objects[0].shape, Shape::Mesh { path, .. } if path == "models/a horse.obj"));
+        assert_eq!(scene.materials[0].albedo_texture.as_deref(), Some("wood grain.png"));
+        let again = parse(&scene.to_string()).unwrap();
+        assert_eq!(again.to_string(), scene.to_string());
+        assert_eq!(again.objects[0].shape, scene.objects[0].shape);
+        assert_eq!(again.render.output, scene.render.output);
+
+        assert!(error_line("mesh path \"models/horse.obj").1.contains("closing quote"));
+        assert!(error_line("mesh path models/\"horse\".obj").1.contains("whole value"));
+
+        // a quote cannot be written back
+        let mut scene = parse("mesh path horse.obj").unwrap();
+        scene.render.output = Some(String::from("say \"cheese\".png"));
+        let path = std::env::temp_dir().join("rust3d_scene_file_quote_test.scene");
+        assert_eq!(scene.save(&path).unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(error_line("sphere center 0 0 0 radius 1\n\nsphear center 0 0 0").0, 3);
+        assert_eq!(error_line("\n\nsphere center 0 0 0 radius 1 material red\n").1, "unknown material 'red'");
+        let (line, message) = error_line("render width 10\nsphere center 0 0 radius 1");
+        assert_eq!(line, 2);
+        assert!(message.contains("'center' needs 3 values"), "{}", message);
+        assert!(error_line("sphere center 0 0 x radius 1").1.contains("not a valid number"));
+        assert!(error_line("sphere center 0 0 0").1.contains("missing 'radius'"));
+        assert!(error_line("sphere center 0 0 0 radius -1").1.contains("positive"));
+        assert!(error_line("sphere center 0 0 0 radius 1 radius 2").1.contains("twice"));
+        assert!(error_line("sphere center 0 0 0 radius 1 colour 1 2 3").1.contains("unknown key 'colour'"));
+        assert!(error_line("material red albedo 300 0 0").1.contains("0 to 255"));
+        assert!(error_line("material red\nmaterial red").1.contains("already defined"));
+        assert!(error_line("light laser position 0 0 0").1.contains("unknown light"));
+        assert!(error_line("camera rotation 0 0 0 look_at 1 0 0").1.contains("either"));
+        assert!(error_line("sphere center 0 0 0 radius").1.contains("needs 1 value"));
+
+        let err = parse("\nsphere radius 1").unwrap_err();
+        assert_eq!(err.to_string(), "test.scene:2: missing 'center'");
+
+        // missing files name the statement that needs them
+        let scene = parse("sphere center 0 0 0 radius 1\nmesh path missing.obj").unwrap();
+        match scene.to_raytracing() {
+            Err(SceneError::Mesh { line, .. }) => assert_eq!(line, 2),
+            other => panic!("expected a mesh error, got {:?}", other.map(|_| ())),
+        }
+        let scene = parse("material wood albedo_texture missing.png").unwrap();
+        assert!(matches!(scene.to_raytracing(), Err(SceneError::Image { line: 1, .. })));
+        assert!(matches!(SceneDescription::load("missing.scene"), Err(SceneError::Io { .. })));
+    }
+
+    #[test]
+    fn test_build_scenes() {
+        let ray = V3::new(-10.0, 0.0, 0.0);
+        let towards = V3::new(10.0, 0.0, 0.0);
+
+        let scene = parse(SCENE).unwrap();
+        let traced = scene.to_raytracing().unwrap();
+        assert_eq!(traced.object_count(), 3);
+        assert_eq!(traced.max_depth, 3);
+        let hit = traced.get_surface(ray, towards, 0.0).unwrap();
+        assert_eq!(hit.albedo, Color::RGB(200, 40, 40));
+        assert!((hit.p.x + 1.0).abs() < 1e-6, "{:?}", hit.p);
+
+        // SDF shapes only work with the marcher and meshes only with the tracer
+        let sdf = parse("torus center 0 0 0 major 1 minor 0.2\nplane point 0 1 0 normal 0 -1 0").unwrap();
+        match sdf.to_raytracing() {
+            Err(SceneError::Parse { line, .. }) => assert_eq!(line, 1),
+            other => panic!("expected an error, got {:?}", other.map(|_| ())),
+        }
+        assert_eq!(sdf.to_raymarching().unwrap().object_count(), 2);
+
+        let horse = SceneDescription::load("demo_assets/scenes/horse_room.scene").unwrap();
+        assert!(horse.to_raytracing().is_ok());
+        match horse.to_raymarching() {
+            Err(SceneError::Parse { line, .. }) => assert_eq!(line, 12),
+            other => panic!("expected an error, got {:?}", other.map(|_| ())),
+        }
+        assert!(SceneDescription::load("demo_assets/scenes/sdf_shapes.scene").unwrap().to_raymarching().is_ok());
+    }
+
+    #[test]
+    fn test_mesh_placement() {
+        // the projected cube has to sit exactly where the ray tracer places it
+        let center = V3::new(1.0, 2.0, 3.0);
+        let scene = parse("mesh path demo_assets/models/default_cube.obj position 1 2 3 rotation 0.3 0.7 -0.4 scale 2 1 0.5").unwrap();
+        let traced = scene.to_raytracing().unwrap();
+        let projected = scene.to_projection().unwrap();
+        assert_eq!(projected.triangles.len(), 12);
+        for t in projected.triangles.iter() {
+            let c = (t.a + t.b + t.c) / 3.0;
+            let mut n = (t.b - t.a).crossed(t.c - t.a).normalized();
+            if n.dt(c - center) < 0.0 {
+                n = n * -1.0;
+            }
+            let hit = traced.get_surface(c + n * 10.0, n * -20.0, 0.0).unwrap();
+            assert!(hit.p.d(c) < 1e-6, "{:?} {:?}", hit.p, c);
+        }
+    }
+}
@@ -0,0 +1,154 @@
+#[cfg(test)]
+mod tests {
+    use sdl2::pixels::Color;
+    use rust3d::engine::lighting::{Light, LightKind, Material};
+    use rust3d::engine::scene_graph::{Node, Payload, SceneGraph};
+    use rust3d::engine::sdf::primitives::RoundedBox;
+    use rust3d::engine::utils::anker_label::AnkerLabel;
+    use rust3d::engine::utils::rendering::RaySphereable;
+    use rust3d::engine::utils::transformation::{Transform, Transformable};
+    use rust3d::geometry::quaternion::Quaternion;
+    use rust3d::geometry::sphere::Sphere;
+    use rust3d::geometry::vector3::Vector3 as V3;
+
+    fn assert_close(a: V3, b: V3) {
+        assert!((a - b).norm() < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    fn moved(p: V3) -> Transform {
+        Transform::new(p, Quaternion::identity(), V3::new(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn test_hierarchy() {
+        let mut graph = SceneGraph::new();
+        let robot = graph.add(graph.root(), Node::new("robot", Payload::Empty).with_transform(moved(V3::new(10.0, 0.0, 0.0))));
+        let arm = graph.add(robot, Node::new("arm", Payload::Empty).with_transform(moved(V3::new(0.0, 0.0, 2.0))));
+        let hand = graph.add(arm, Node::new("hand", Payload::Empty));
+        let other = graph.add(graph.root(), Node::new("other", Payload::Empty));
+        graph.add(other, Node::new("hand", Payload::Empty));
+
+        assert_eq!(graph.find("arm"), Some(arm));
+        assert_eq!(graph.find("hand"), Some(hand));
+        assert_eq!(graph.find_path("robot/arm/hand"), Some(hand));
+        assert_eq!(graph.find_path("other/arm"), None);
+        assert_eq!(graph.get(hand).unwrap().parent(), Some(arm));
+        assert_eq!(graph.len(), 6);
+
+        // children follow their parents, rotations of a parent turn around its origin
+        let origin = V3::new(0.0, 0.0, 0.0);
+        assert_close(graph.world_matrix(hand).transform_point(origin), V3::new(10.0, 0.0, 2.0));
+        graph.get_mut(robot).unwrap().transform.rot(V3::new(0.0, std::f64::consts::FRAC_PI_2, 0.0));
+        assert_close(graph.world_matrix(hand).transform_point(origin), V3::new(12.0, 0.0, 0.0));
+        graph.translate(V3::new(0.0, -1.0, 0.0));
+        assert_close(graph.world_matrix(hand).transform_point(origin), V3::new(12.0, -1.0, 0.0));
+
+        // reparenting keeps the local transform and refuses cycles
+        assert!(!graph.set_parent(robot, hand));
+        assert!(graph.set_parent(hand, other));
+        assert_close(graph.world_matrix(hand).transform_point(origin), V3::new(0.0, -1.0, 0.0));
+        assert!(graph.get(arm).unwrap().children().is_empty());
+
+        // removing a node removes what hangs below it
+        assert!(graph.remove(graph.root()).is_none());
+        let removed = graph.remove(other).unwrap();
+        assert_eq!(removed.name, "other");
+        assert!(graph.get(hand).is_none());
+        assert_eq!(graph.find("hand"), None);
+        assert_eq!(graph.len(), 3);
+    }
+
+    #[test]
+    fn test_visibility_and_payloads() {
+        let material = Material::new(Color::WHITE, 1.0);
+        let mut graph = SceneGraph::new();
+        let group = graph.add(graph.root(), Node::new("group", Payload::Empty).with_transform(moved(V3::new(5.0, 0.0, 0.0))));
+        graph.add(group, Node::new("ball", Payload::ray_traced(Sphere::new(V3::new(0.0, 0.0, 0.0), 1.0, material.clone()))));
+        graph.add(group, Node::new("box", Payload::ray_marched(RoundedBox::new(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 1.0, 1.0), 0.0, material))));
+        let spot = Light { kind: LightKind::Spot { direction: V3::new(1.0, 0.0, 0.0), inner_angle: 0.2, outer_angle: 0.4 }, ..Light::point(V3::new(0.0, 0.0, 0.0), Color::WHITE, 1.0) };
+        let lamp = graph.add(group, Node::new("lamp", Payload::Light(spot)).with_transform(moved(V3::new(0.0, -2.0, 0.0))));
+        graph.get_mut(lamp).unwrap().transform.rot(V3::new(0.0, 0.0, std::f64::consts::FRAC_PI_2));
+
+        let scene = graph.to_raytracing();
+        assert_eq!(scene.objects().len(), 1);
+        let c = scene.objects()[0].get_collision(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0));
+        assert!(c.hit && (c.d - 4.0).abs() < 1e-9);
+        assert_eq!(scene.lights.len(), 1);
+        assert_close(scene.lights[0].position, V3::new(5.0, -2.0, 0.0));
+        match scene.lights[0].kind {
+            LightKind::Spot { direction, .. } => assert_close(direction, V3::new(0.0, 1.0, 0.0)),
+            _ => panic!("not a spot light"),
+        }
+
+        let marched = graph.to_raymarching(0.01);
+        assert_eq!(marched.objects.len(), 1);
+        assert!((marched.nearest_distance(V3::new(8.0, 0.0, 0.0)) - 2.0).abs() < 1e-9);
+
+        // hiding a group hides everything in it
+        graph.set_visible(group, false);
+        assert!(!graph.is_visible(lamp));
+        assert!(graph.to_raytracing().objects().is_empty());
+        assert!(graph.lights().is_empty());
+        graph.set_visible(group, true);
+        graph.set_visible(lamp, false);
+        assert_eq!(graph.to_raytracing().objects().len(), 1);
+        assert!(graph.lights().is_empty());
+    }
+
+    #[test]
+    fn test_scaled_parent() {
+        // a child turned inside a stretched parent is sheared, the ray tracer still finds it
+        let mut graph = SceneGraph::new();
+        let parent = graph.add(graph.root(), Node::new("stretched", Payload::Empty).with_transform(Transform::new(V3::new(0.0, 0.0, 0.0), Quaternion::identity(), V3::new(2.0, 1.0, 1.0))));
+        let turned = Transform::new(V3::new(5.0, 0.0, 0.0), Quaternion::from_axis_angle(V3::new(0.0, 0.0, 1.0), 0.7), V3::new(1.0, 1.0, 1.0));
+        graph.add(parent, Node::new("ball", Payload::ray_traced(Sphere::new(V3::new(0.0, 0.0, 0.0), 1.0, Material::new(Color::WHITE, 1.0)))).with_transform(turned));
+
+        let scene = graph.to_raytracing();
+        let (c, n) = scene.objects()[0].get_collision_with_normal(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0));
+        assert!(c.hit);
+        assert!((c.d - 8.0).abs() < 1e-9, "{}", c.d);
+        assert_close(c.p, V3::new(8.0, 0.0, 0.0));
+        assert_close(n.unwrap(), V3::new(-1.0, 0.0, 0.0));
+        // distances never overestimate
+        let d = scene.objects()[0].d(V3::new(10.0, 3.0, 0.0));
+        assert!(d > 0.0 && d < 3.0);
+    }
+
+    #[test]
+    fn test_flat_parent() {
+        // a group scaled by 0 along one axis hides its payloads instead of showing them unscaled
+        let mut graph = SceneGraph::new();
+        let flat = graph.add(graph.root(), Node::new("flat", Payload::Empty).with_transform(Transform::new(V3::new(0.0, 0.0, 0.0), Quaternion::identity(), V3::new(1.0, 0.0, 1.0))));
+        graph.add(flat, Node::new("traced", Payload::ray_traced(Sphere::new(V3::new(5.0, 0.0, 0.0), 1.0, Material::new(Color::WHITE, 1.0)))));
+        graph.add(flat, Node::new("marched", Payload::ray_marched(Sphere::new(V3::new(5.0, 0.0, 0.0), 1.0, Material::new(Color::WHITE, 1.0)))));
+        graph.add(graph.root(), Node::new("ball", Payload::ray_traced(Sphere::new(V3::new(0.0, 0.0, 5.0), 1.0, Material::new(Color::WHITE, 1.0)))));
+
+        assert_eq!(graph.to_raytracing().objects().len(), 1);
+        assert!(graph.to_raymarching(0.01).objects.is_empty());
+    }
+
+    #[test]
+    fn test_labels() {
+        let font = include_bytes!("../demo_assets/fonts/NotoSansMath-Regular.ttf") as &[u8];
+        let font = fontdue::Font::from_bytes(font, fontdue::FontSettings::default()).unwrap();
+        let label = AnkerLabel::new(1.0, 0.0, 0.0, String::from("x"), &font, 12.0, Color::BLACK, Color::WHITE);
+        assert_close(label.get_middle(), V3::new(1.0, 0.0, 0.0));
+
+        // the anchor follows the parents, turning the group turns it around the group's origin
+        let mut graph = SceneGraph::new();
+        let group = graph.add(graph.root(), Node::new("group", Payload::Empty).with_transform(moved(V3::new(5.0, 0.0, 0.0))));
+        graph.get_mut(group).unwrap().transform.rot(V3::new(0.0, std::f64::consts::FRAC_PI_2, 0.0));
+        let arm = graph.add(group, Node::new("arm", Payload::Empty).with_transform(moved(V3::new(0.0, -2.0, 0.0))));
+        graph.add(arm, Node::new("label", Payload::Label(label)));
+
+        let labels = graph.labels();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].text, "x");
+        let expected = graph.world_matrix(arm).transform_point(V3::new(1.0, 0.0, 0.0));
+        assert_close(labels[0].get_middle(), expected);
+        assert_close(expected, V3::new(5.0, -2.0, -1.0));
+
+        graph.set_visible(arm, false);
+        assert!(graph.labels().is_empty());
+    }
+}
@@ -0,0 +1,199 @@
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use sdl2::pixels::Color;
+    use rust3d::engine::lighting::Material;
+    use rust3d::engine::raymarching::{RayMarchingObject, RayMarchingScene};
+    use rust3d::engine::sdf::domain::{Bend, Mirror, Repeat, Twist};
+    use rust3d::engine::sdf::operators::{smooth_min, Difference, Intersection, Union};
+    use rust3d::engine::sdf::primitives::{Capsule, Cone, Cylinder, Ellipsoid, Plane, RoundedBox, Torus};
+    use rust3d::engine::utils::rendering::RayRenderable;
+    use rust3d::engine::utils::transformation::Transformable;
+    use rust3d::geometry::sphere::Sphere;
+    use rust3d::geometry::vector3::Vector3 as V3;
+
+    fn assert_approx_eq(a: f64, b: f64, eps: f64) {
+        assert!((a - b).abs() < eps, "{} != {}", a, b);
+    }
+
+    fn white() -> Material {
+        Material::new(Color::WHITE, 1.0)
+    }
+
+    fn origin() -> V3 {
+        V3::new(0.0, 0.0, 0.0)
+    }
+
+    #[test]
+    fn test_primitive_distances() {
+        let plane = Plane::new(V3::new(0.0, 1.0, 0.0), V3::new(0.0, -2.0, 0.0), white());
+        assert_approx_eq(plane.sdf(V3::new(3.0, -1.0, 2.0)), 2.0, 1e-12);
+        assert_approx_eq(plane.sdf(V3::new(0.0, 1.5, 0.0)), -0.5, 1e-12);
+
+        let torus = Torus::new(origin(), 2.0, 0.5, white());
+        assert_approx_eq(torus.sdf(V3::new(2.0, 0.0, 0.0)), -0.5, 1e-12);
+        assert_approx_eq(torus.sdf(V3::new(0.0, 0.0, 0.0)), 1.5, 1e-12);
+        assert_approx_eq(torus.sdf(V3::new(0.0, -1.0, 2.0)), 0.5, 1e-12);
+
+        let capsule = Capsule::new(V3::new(-1.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 0.5, white());
+        assert_approx_eq(capsule.sdf(V3::new(0.0, 2.0, 0.0)), 1.5, 1e-12);
+        assert_approx_eq(capsule.sdf(V3::new(3.0, 0.0, 0.0)), 1.5, 1e-12);
+
+        let cylinder = Cylinder::new(origin(), 1.0, 2.0, white());
+        assert_approx_eq(cylinder.sdf(V3::new(3.0, 0.0, 0.0)), 2.0, 1e-12);
+        assert_approx_eq(cylinder.sdf(V3::new(0.0, 3.0, 0.0)), 1.0, 1e-12);
+        assert_approx_eq(cylinder.sdf(V3::new(0.0, 0.0, 0.5)), -0.5, 1e-12);
+        assert_approx_eq(cylinder.sdf(V3::new(4.0, 6.0, 0.0)), 5.0, 1e-12);
+
+        // tip at the origin, base of radius 1 at y = 1
+        let cone = Cone::new(origin(), 1.0, 1.0, white());
+        assert_approx_eq(cone.sdf(V3::new(0.0, 1.5, 0.0)), 0.5, 1e-12);
+        assert_approx_eq(cone.sdf(V3::new(0.0, -1.0, 0.0)), 1.0, 1e-12);
+        assert_approx_eq(cone.sdf(V3::new(0.0, 0.5, 0.0)), -0.5 / f64::sqrt(2.0), 1e-12);
+        assert_approx_eq(cone.sdf(V3::new(1.0, 0.0, 0.0)), 1.0 / f64::sqrt(2.0), 1e-12);
+
+        let rounded = RoundedBox::new(origin(), V3::new(1.0, 2.0, 3.0), 0.25, white());
+        assert_approx_eq(rounded.sdf(V3::new(2.0, 0.0, 0.0)), 1.0, 1e-12);
+        assert_approx_eq(rounded.sdf(V3::new(0.0, 0.0, -3.5)), 0.5, 1e-12);
+        assert_approx_eq(rounded.sdf(origin()), -1.0, 1e-12);
+        // the corner is cut off by the rounding
+        assert_approx_eq(rounded.sdf(V3::new(1.0, 2.0, 3.0)), 0.25 * f64::sqrt(3.0) - 0.25, 1e-12);
+
+        let ellipsoid = Ellipsoid::new(origin(), V3::new(2.0, 1.0, 1.0), white());
+        assert_approx_eq(ellipsoid.sdf(V3::new(2.0, 0.0, 0.0)), 0.0, 1e-12);
+        assert_approx_eq(ellipsoid.sdf(V3::new(0.0, -1.0, 0.0)), 0.0, 1e-12);
+        assert!(ellipsoid.sdf(V3::new(1.9, 0.0, 0.0)) < 0.0);
+        let d = ellipsoid.sdf(V3::new(0.0, 0.0, 3.0));
+        assert!(d > 0.0 && d <= 2.0);
+    }
+
+    #[test]
+    fn test_primitive_transformations() {
+        // lying on its side the cylinder reaches 2 along x instead of y
+        let mut cylinder = Cylinder::new(origin(), 1.0, 2.0, white());
+        cylinder.rot(V3::new(0.0, 0.0, PI / 2.0));
+        assert_approx_eq(cylinder.sdf(V3::new(3.0, 0.0, 0.0)), 1.0, 1e-12);
+        assert_approx_eq(cylinder.sdf(V3::new(0.0, 3.0, 0.0)), 2.0, 1e-12);
+
+        cylinder.translate(V3::new(0.0, 0.0, 5.0));
+        assert_approx_eq(cylinder.sdf(V3::new(3.0, 0.0, 5.0)), 1.0, 1e-12);
+
+        let mut torus = Torus::new(V3::new(1.0, 0.0, 0.0), 2.0, 0.5, white());
+        torus.rot_by(origin(), V3::new(0.0, PI, 0.0));
+        assert_approx_eq(torus.sdf(V3::new(-1.0, 0.0, 2.0)), -0.5, 1e-12);
+        torus.scale(V3::new(2.0, 2.0, 2.0));
+        assert_approx_eq(torus.sdf(V3::new(-1.0, 0.0, 4.0)), -1.0, 1e-12);
+
+        let mut capsule = Capsule::new(V3::new(-1.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 0.5, white());
+        capsule.rot(V3::new(0.0, 0.0, PI / 2.0));
+        assert_approx_eq(capsule.sdf(V3::new(0.0, 2.0, 0.0)), 0.5, 1e-12);
+    }
+
+    #[test]
+    fn test_boolean_operators() {
+        let a = || Sphere::new(V3::new(-0.5, 0.0, 0.0), 1.0, white());
+        let b = || Sphere::new(V3::new(0.5, 0.0, 0.0), 1.0, Material::new(Color::RED, 1.0));
+
+        let union = Union::new(a(), b());
+        assert_approx_eq(union.sdf(V3::new(-2.0, 0.0, 0.0)), 0.5, 1e-12);
+        assert_approx_eq(union.sdf(V3::new(2.0, 0.0, 0.0)), 0.5, 1e-12);
+        assert_eq!(union.material_at(V3::new(-2.0, 0.0, 0.0)).albedo, Color::WHITE);
+        assert_eq!(union.material_at(V3::new(2.0, 0.0, 0.0)).albedo, Color::RED);
+
+        let intersection = Intersection::new(a(), b());
+        assert_approx_eq(intersection.sdf(origin()), -0.5, 1e-12);
+        assert_approx_eq(intersection.sdf(V3::new(-1.0, 0.0, 0.0)), 0.5, 1e-12);
+
+        let difference = Difference::new(a(), b());
+        assert_approx_eq(difference.sdf(V3::new(-1.0, 0.0, 0.0)), -0.5, 1e-12);
+        assert_approx_eq(difference.sdf(origin()), 0.5, 1e-12);
+        // the inner wall of the cut belongs to the sphere that was subtracted
+        assert_eq!(difference.material_at(V3::new(-0.5, 0.0, 0.0)).albedo, Color::RED);
+
+        // smoothing only changes the region where both distances are close
+        let smooth = Union::smooth(a(), b(), 0.5);
+        assert!(smooth.sdf(V3::new(0.0, 1.0, 0.0)) < union.sdf(V3::new(0.0, 1.0, 0.0)));
+        assert_approx_eq(smooth.sdf(V3::new(-3.0, 0.0, 0.0)), union.sdf(V3::new(-3.0, 0.0, 0.0)), 1e-12);
+        assert_approx_eq(smooth_min(1.0, 1.0, 0.4), 0.9, 1e-12);
+        assert_approx_eq(smooth_min(1.0, 2.0, 0.4), 1.0, 1e-12);
+
+        let smooth = Difference::smooth(a(), b(), 0.5);
+        assert!(smooth.sdf(V3::new(-1.0, 0.0, 0.0)) > difference.sdf(V3::new(-1.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_domain_operators() {
+        let ball = || Sphere::new(origin(), 0.5, white());
+
+        let repeat = Repeat::new(ball(), origin(), V3::new(2.0, 0.0, 2.0));
+        assert_approx_eq(repeat.sdf(V3::new(10.0, 0.0, -4.0)), -0.5, 1e-12);
+        assert_approx_eq(repeat.sdf(V3::new(11.0, 0.0, 0.0)), 0.5, 1e-12);
+        // no repetition along y
+        assert_approx_eq(repeat.sdf(V3::new(2.0, 3.0, 0.0)), 2.5, 1e-12);
+
+        let limited = Repeat::limited(ball(), origin(), V3::new(2.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0));
+        assert_approx_eq(limited.sdf(V3::new(2.0, 0.0, 0.0)), -0.5, 1e-12);
+        assert_approx_eq(limited.sdf(V3::new(6.0, 0.0, 0.0)), 3.5, 1e-12);
+
+        let mirror = Mirror::new(Sphere::new(V3::new(2.0, 0.0, 0.0), 0.5, white()), origin(), [true, false, false]);
+        assert_approx_eq(mirror.sdf(V3::new(-2.0, 0.0, 0.0)), -0.5, 1e-12);
+        assert_approx_eq(mirror.sdf(V3::new(2.0, 0.0, 0.0)), -0.5, 1e-12);
+
+        // a quarter turn per unit: a bar along x is turned to z one unit below the center
+        let bar = || RoundedBox::new(origin(), V3::new(2.0, 4.0, 0.2), 0.0, white());
+        let twist = Twist::new(bar(), origin(), PI / 2.0);
+        assert!(twist.sdf(V3::new(1.5, 0.0, 0.0)) < 0.0);
+        assert!(twist.sdf(V3::new(0.0, 1.0, 1.5)) < 0.0);
+        assert!(twist.sdf(V3::new(1.5, 1.0, 0.0)) > 0.0);
+
+        let bend = Bend::new(bar(), origin(), 0.0);
+        assert_approx_eq(bend.sdf(V3::new(3.0, 0.0, 0.0)), bar().sdf(V3::new(3.0, 0.0, 0.0)), 1e-12);
+        let bend = Bend::new(Capsule::new(V3::new(-2.0, 0.0, 0.0), V3::new(2.0, 0.0, 0.0), 0.1, white()), origin(), 0.5);
+        // the ends curl away from the straight line
+        assert!(bend.sdf(V3::new(0.0, 0.0, 0.0)) < 0.0);
+        assert!(bend.sdf(V3::new(1.9, 0.0, 0.0)) > 0.0);
+    }
+
+    #[test]
+    fn test_domain_operators_move_with_their_frame() {
+        let mut repeat = Repeat::limited(Sphere::new(origin(), 0.5, white()), origin(), V3::new(2.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0));
+        repeat.rot(V3::new(0.0, 0.0, PI / 2.0));
+        assert_approx_eq(repeat.sdf(V3::new(0.0, 2.0, 0.0)), -0.5, 1e-12);
+        assert_approx_eq(repeat.sdf(V3::new(2.0, 0.0, 0.0)), 1.5, 1e-12);
+
+        repeat.translate(V3::new(0.0, 0.0, 3.0));
+        assert_approx_eq(repeat.sdf(V3::new(0.0, -2.0, 3.0)), -0.5, 1e-12);
+    }
+
+    #[test]
+    fn test_nested_nodes_render() {
+        // a box with a cylinder shaped hole, in front of a plane
+        let block = Difference::new(
+            RoundedBox::new(V3::new(5.0, 0.0, 0.0), V3::new(1.0, 1.0, 1.0), 0.1, white()),
+            Cylinder::new(V3::new(5.0, 0.0, 0.0), 0.4, 2.0, Material::new(Color::RED, 1.0)),
+        );
+        let mut scene = RayMarchingScene::new(0.001);
+        scene.add(Union::smooth(block, Plane::new(V3::new(8.0, 0.0, 0.0), V3::new(-1.0, 0.0, 0.0), white()), 0.2));
+        let scene = RayMarchingScene::wrapup(&scene);
+
+        let mut dir = V3::new(1.0, 0.0, 0.1);
+        dir.normalize();
+        let c = scene.get_collision(origin(), dir, 50.0);
+        assert!(c.hit);
+        assert_approx_eq(c.p.x, 4.0, 0.01);
+
+        // looking down the hole shows its wall
+        let mut dir = V3::new(0.0, 1.0, 0.5);
+        dir.normalize();
+        let c = scene.get_collision(V3::new(5.0, -1.5, -0.3), dir, 50.0);
+        assert!(c.hit);
+        assert_approx_eq(c.p.y, -0.1, 0.01);
+        assert_eq!(c.c, Color::RED);
+
+        // next to the block the plane is hit
+        let c = scene.get_collision(V3::new(0.0, 0.0, 3.0), V3::new(1.0, 0.0, 0.0), 50.0);
+        assert!(c.hit);
+        assert_approx_eq(c.p.x, 8.0, 0.01);
+    }
+}
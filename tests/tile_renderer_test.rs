@@ -0,0 +1,182 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use sdl2::pixels::Color;
+    use rust3d::engine::camera::RayCamera;
+    use rust3d::engine::lighting::Material;
+    use rust3d::engine::pathtracing::RayTracingScene;
+    use rust3d::engine::tile_renderer::TileRenderer;
+    use rust3d::engine::utils::antialiasing::Supersampling;
+    use rust3d::engine::utils::rendering::{Collision, RayRenderable};
+    use rust3d::engine::utils::virtual_canvas::VirtualCanvas;
+    use rust3d::geometry::sphere::Sphere;
+    use rust3d::geometry::vector3::Vector3 as V3;
+
+    fn sphere_scene() -> RayTracingScene {
+        let mut scene = RayTracingScene::new();
+        scene.add(Sphere::new(V3::new(3.0, 0.0, 0.0), 1.0, Material::new(Color::RGB(200, 40, 40), 1.0)));
+        scene
+    }
+
+    fn camera() -> RayCamera {
+        RayCamera::new(V3::new(0.0, 0.0, 0.0), 0.0, 0.0, 0.0)
+    }
+
+    /// Counts its rays and takes its time with each of them.
+    struct Slow {
+        rays: AtomicUsize,
+    }
+
+    impl RayRenderable for Slow {
+        fn get_collision(&self, _p0: V3, _p: V3, _radius: f64) -> Collision {
+            self.rays.fetch_add(1, Ordering::Relaxed);
+            std::thread::sleep(Duration::from_micros(200));
+            Collision::empty()
+        }
+    }
+
+    #[test]
+    fn test_tiles_cover_the_image() {
+        let mut renderer = TileRenderer::new(2);
+        renderer.set_tile_size(16);
+        let (w, h) = (50, 33);
+        let tiles = renderer.tiles(w, h);
+        assert_eq!(tiles.len(), 4 * 3);
+
+        let mut covered = vec![0; w * h];
+        for t in &tiles {
+            for x in t.x..t.x + t.width {
+                for y in t.y..t.y + t.height {
+                    covered[y * w + x] += 1;
+                }
+            }
+        }
+        assert!(covered.iter().all(|&c| c == 1));
+
+        // the tile at the center of the image comes first
+        assert_eq!((tiles[0].x, tiles[0].y), (16, 16));
+    }
+
+    #[test]
+    fn test_matches_single_threaded() {
+        let scene = Arc::new(sphere_scene());
+        let mut camera = camera();
+        let mut adaptive = Supersampling::jittered(3);
+        adaptive.set_adaptive(0.05);
+
+        for supersampling in [Supersampling::none(), adaptive] {
+            camera.set_supersampling(supersampling);
+            let mut single = VirtualCanvas::new(45, 30);
+            camera.render(&*scene, &mut single);
+
+            for tile_size in [1, 7, 16, 64] {
+                let mut renderer = TileRenderer::new(3);
+                renderer.set_tile_size(tile_size);
+                let frame = renderer.render(&camera, Arc::clone(&scene), 45, 30).wait().unwrap();
+                assert_eq!(frame.pixels, single.pixels, "tile size {}", tile_size);
+            }
+        }
+    }
+
+    #[test]
+    fn test_progress() {
+        let mut renderer = TileRenderer::new(4);
+        renderer.set_tile_size(8);
+        let scene = Arc::new(sphere_scene());
+
+        // the pool is reused for several frames
+        for _ in 0..2 {
+            let mut job = renderer.render(&camera(), Arc::clone(&scene), 32, 16);
+            assert_eq!(job.total, 8);
+            assert_eq!(job.progress(), 0.0);
+
+            let mut seen = 0;
+            while let Some(rect) = job.next_tile() {
+                seen += 1;
+                assert_eq!(job.finished_tiles(), seen);
+                // the tile is in the frame, the frame is opaque there
+                assert_eq!(job.frame().get_pixel(rect.x, rect.y).a, 255);
+            }
+            assert_eq!(seen, 8);
+            assert!(job.is_finished());
+            assert_eq!(job.progress(), 1.0);
+            assert!(job.poll().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_cancel() {
+        let mut renderer = TileRenderer::new(2);
+        renderer.set_tile_size(4);
+        let slow = Arc::new(Slow { rays: AtomicUsize::new(0) });
+        let (w, h) = (64, 64);
+
+        let mut job = renderer.render(&camera(), Arc::clone(&slow), w, h);
+        job.next_tile().unwrap();
+        job.cancel();
+        assert!(job.is_cancelled());
+        assert!(job.wait().is_none());
+        assert!(slow.rays.load(Ordering::Relaxed) < w * h / 2);
+
+        // dropping a job cancels it as well, the next one gets the workers
+        let before = slow.rays.load(Ordering::Relaxed);
+        drop(renderer.render(&camera(), Arc::clone(&slow), w, h));
+        let frame = renderer.render(&camera(), Arc::new(sphere_scene()), 8, 8).wait();
+        assert!(frame.is_some());
+        assert!(slow.rays.load(Ordering::Relaxed) - before < w * h / 2);
+    }
+
+    #[test]
+    fn test_panicking_tile() {
+        let renderer = TileRenderer::new(2);
+        let job = renderer.render_pixels(64, 64, |x, y| {
+            assert!(x != 40 || y != 40, "bad pixel");
+            x + y
+        });
+        // the job fails instead of waiting for the tile forever
+        assert!(job.wait().is_none());
+
+        // and the workers are still there
+        let values = renderer.render_pixels(8, 8, |x, y| x * y).wait().unwrap();
+        assert_eq!(values[3 * 8 + 5], 15);
+        let mut job = renderer.render_pixels(8, 8, |_, _| -> usize { panic!("bad tile") });
+        assert!(job.next().is_none());
+        assert!(job.is_failed());
+    }
+
+    #[test]
+    fn test_per_pixel_values() {
+        let mut renderer = TileRenderer::new(3);
+        renderer.set_tile_size(7);
+        let (w, h) = (20, 9);
+
+        let mut job = renderer.render_pixels(w, h, move |x, y| (x, y));
+        assert_eq!(job.total, 6);
+        let tile = job.next().unwrap();
+        assert_eq!(job.progress(), 1.0 / 6.0);
+        // column-major within the tile
+        let r = tile.rect;
+        assert_eq!(tile.pixels.len(), r.width * r.height);
+        assert_eq!(tile.pixels[1], (r.x, r.y + 1));
+        assert_eq!(tile.pixels[r.height], (r.x + 1, r.y));
+        assert_eq!(job.count(), 5);
+
+        // put together row-major
+        let values = renderer.render_pixels(w, h, move |x, y| (x, y)).wait().unwrap();
+        for y in 0..h {
+            for x in 0..w {
+                assert_eq!(values[y * w + x], (x, y));
+            }
+        }
+
+        let job = renderer.render_tiles(w, h, |r| {
+            std::thread::sleep(Duration::from_millis(20));
+            vec![0; r.width * r.height]
+        });
+        job.cancel();
+        assert!(job.wait().is_none());
+    }
+}
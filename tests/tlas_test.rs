@@ -0,0 +1,231 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use sdl2::pixels::Color;
+    use rust3d::engine::lighting::Material;
+    use rust3d::engine::pathtracing::{RayTracingScene, RaytracingObject};
+    use rust3d::engine::simplex3d_bvh::tlas::Tlas;
+    use rust3d::engine::utils::random::Rng;
+    use rust3d::engine::utils::rendering::{Collision, RayRenderScene, RayRenderable, SurfaceHit};
+    use rust3d::engine::utils::transformation::Transformable;
+    use rust3d::geometry::aabb::Aabb;
+    use rust3d::geometry::quad::Quad;
+    use rust3d::geometry::sphere::Sphere;
+    use rust3d::geometry::vector3::Vector3 as V3;
+
+    fn random_point(rng: &mut Rng, size: f64) -> V3 {
+        V3::new(rng.range(-size, size), rng.range(-size, size), rng.range(-size, size))
+    }
+
+    fn random_scene(rng: &mut Rng) -> RayTracingScene {
+        let mut scene = RayTracingScene::new();
+        for i in 0..200 {
+            let p = random_point(rng, 20.0);
+            if i % 2 == 0 {
+                scene.add(Sphere::new(p, rng.range(0.2, 1.5), Material::new(Color::WHITE, 1.0)));
+            } else {
+                let sides = V3::new(rng.range(0.2, 2.0), rng.range(0.2, 2.0), rng.range(0.2, 2.0));
+                scene.add(Quad::new(p, sides, Color::RED));
+            }
+        }
+        scene
+    }
+
+    /// Closest hit by testing every object.
+    fn brute_force(scene: &RayTracingScene, p0: V3, p: V3) -> Option<(usize, f64)> {
+        let mut best: Option<(usize, f64)> = None;
+        for (i, o) in scene.objects().iter().enumerate() {
+            let c = o.get_collision(p0, p);
+            if c.hit && best.is_none_or(|(_, d)| c.d < d) {
+                best = Some((i, c.d));
+            }
+        }
+        best
+    }
+
+    fn assert_same_hits(scene: &RayTracingScene, rng: &mut Rng) {
+        let mut hits = 0;
+        for _ in 0..500 {
+            let p0 = random_point(rng, 25.0);
+            let p = (random_point(rng, 10.0) - p0).normalized();
+            let expected = brute_force(scene, p0, p);
+            let found = scene.get_surface(p0, p, 0.0);
+            match (expected, found) {
+                (Some((i, d)), Some(hit)) => {
+                    hits += 1;
+                    assert!((hit.d - d).abs() < 1e-9, "{} != {}", hit.d, d);
+                    assert_eq!(hit.object, i);
+                }
+                (None, None) => {}
+                (e, f) => panic!("{:?} != {:?}", e, f.map(|h| (h.object, h.d))),
+            }
+        }
+        assert!(hits > 50, "{} hits", hits);
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let mut rng = Rng::new(7);
+        let scene = random_scene(&mut rng);
+        assert_same_hits(&scene, &mut rng);
+
+        // shadow rays only need to know whether anything is in the way
+        let bounds: Vec<Aabb> = scene.objects().iter().map(|o| o.bounds()).collect();
+        let tlas = Tlas::build(&bounds);
+        for _ in 0..500 {
+            let p0 = random_point(&mut rng, 25.0);
+            let p = (random_point(&mut rng, 10.0) - p0).normalized();
+            let max_d = rng.range(1.0, 40.0);
+            let blocked = |o: &Box<dyn RaytracingObject + Send + Sync>| {
+                let c = o.get_collision(p0, p);
+                c.hit && c.d < max_d
+            };
+            let expected = scene.objects().iter().any(blocked);
+            assert_eq!(tlas.any(p0, p, max_d, |i| blocked(&scene.objects()[i])), expected);
+        }
+    }
+
+    #[test]
+    fn test_moved_objects() {
+        let mut rng = Rng::new(11);
+        let mut scene = random_scene(&mut rng);
+        assert_same_hits(&scene, &mut rng);
+
+        // moving the scene keeps the hierarchy and refits its boxes
+        scene.translate(V3::new(3.0, -2.0, 0.0));
+        scene.rot(V3::new(0.0, 0.4, 0.2));
+        assert_same_hits(&scene, &mut rng);
+
+        for i in 0..20 {
+            let p = random_point(&mut rng, 15.0);
+            scene.get(i * 3).translate(p);
+        }
+        assert_same_hits(&scene, &mut rng);
+
+        scene.get(5).translate(V3::new(0.0, 40.0, 0.0));
+        assert_same_hits(&scene, &mut rng);
+
+        scene.remove(0);
+        scene.add(Sphere::new(V3::new(0.0, 0.0, 0.0), 3.0, Material::new(Color::WHITE, 1.0)));
+        assert_same_hits(&scene, &mut rng);
+    }
+
+    #[test]
+    fn test_unbounded_and_empty() {
+        let bounds = [
+            Aabb::new(V3::new(4.0, -1.0, -1.0), V3::new(6.0, 1.0, 1.0)),
+            Aabb::infinite(),
+            Aabb::empty(),
+            Aabb::new(V3::new(-6.0, -1.0, -1.0), V3::new(-4.0, 1.0, 1.0)),
+        ];
+        let tlas = Tlas::build(&bounds);
+        assert!(!tlas.bounds().is_finite());
+
+        let mut visited = Vec::new();
+        tlas.closest(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), |i| {
+            visited.push(i);
+            None
+        });
+        // objects without bounds are always tested first, empty ones never
+        assert_eq!(visited[0], 1);
+        assert!(visited.contains(&0) && !visited.contains(&2));
+
+        let far = Aabb::new(V3::new(14.0, -1.0, -1.0), V3::new(16.0, 1.0, 1.0));
+        let finite = Tlas::build(&[bounds[0], bounds[3], far]);
+        assert!(finite.bounds().min.x <= -6.0 && finite.bounds().max.x >= 16.0);
+        assert!(!finite.any(V3::new(0.0, 5.0, 0.0), V3::new(1.0, 0.0, 0.0), 100.0, |_| true));
+        // boxes behind the end of a shadow ray are skipped
+        assert!(!finite.any(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 3.0, |_| true));
+        assert!(finite.any(V3::new(0.0, 0.0, 0.0), V3::new(-1.0, 0.0, 0.0), 5.0, |_| true));
+
+        // nothing to refit without bounded objects
+        let mut unbounded = Tlas::build(&bounds[1..3]);
+        assert!(unbounded.refit(&bounds[1..3]));
+        let mut scene = RayTracingScene::new();
+        assert!(scene.get_surface(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 0.0).is_none());
+        scene.translate(V3::new(1.0, 0.0, 0.0));
+        assert!(scene.get_surface(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 0.0).is_none());
+        assert!(scene.bounds().is_empty());
+    }
+
+    /// Scene that counts the rays it is asked about.
+    struct Counted {
+        scene: RayTracingScene,
+        rays: Arc<AtomicUsize>,
+    }
+
+    impl RayRenderable for Counted {
+        fn get_collision(&self, p0: V3, p: V3, radius: f64) -> Collision {
+            self.rays.fetch_add(1, Ordering::Relaxed);
+            self.scene.get_collision(p0, p, radius)
+        }
+
+        fn get_surface(&self, p0: V3, p: V3, radius: f64) -> Option<SurfaceHit> {
+            self.rays.fetch_add(1, Ordering::Relaxed);
+            self.scene.get_surface(p0, p, radius)
+        }
+
+        fn object_count(&self) -> usize {
+            self.scene.object_count()
+        }
+
+        fn bounds(&self) -> Aabb {
+            self.scene.bounds()
+        }
+    }
+
+    #[test]
+    fn test_render_scene() {
+        // every ball in a scene of its own, the last scene holds two
+        let mut rng = Rng::new(5);
+        let rays = Arc::new(AtomicUsize::new(0));
+        let mut scene = RayRenderScene::new();
+        for i in 0..100 {
+            let mut inner = RayTracingScene::new();
+            inner.add(Sphere::new(random_point(&mut rng, 20.0), rng.range(0.2, 1.5), Material::new(Color::WHITE, 1.0)));
+            if i == 99 {
+                inner.add(Sphere::new(V3::new(30.0, 0.0, 0.0), 1.0, Material::new(Color::RED, 1.0)));
+            }
+            scene.wrap(Box::new(Counted { scene: inner, rays: Arc::clone(&rays) }));
+        }
+        assert_eq!(scene.object_count(), 101);
+        assert!(scene.bounds().is_finite());
+
+        let mut hits = 0;
+        for _ in 0..300 {
+            let p0 = random_point(&mut rng, 25.0);
+            let p = (random_point(&mut rng, 10.0) - p0).normalized();
+            let mut expected: Option<(usize, f64)> = None;
+            for (i, o) in scene.objects().iter().enumerate() {
+                let c = o.get_collision(p0, p, 100.0);
+                if c.hit && expected.is_none_or(|(_, d)| c.d < d) {
+                    expected = Some((i, c.d));
+                }
+            }
+            let c = scene.get_collision(p0, p, 100.0);
+            let surface = scene.get_surface(p0, p, 100.0);
+            match expected {
+                Some((i, d)) => {
+                    hits += 1;
+                    assert!(c.hit && (c.d - d).abs() < 1e-9, "{} {}", c.d, d);
+                    assert!((surface.unwrap().d - d).abs() < 1e-9);
+                }
+                None => assert!(!c.hit && surface.is_none()),
+            }
+        }
+        assert!(hits > 30, "{} hits", hits);
+
+        // the second ball of the last scene comes after all others
+        let surface = scene.get_surface(V3::new(40.0, 0.0, 0.0), V3::new(-1.0, 0.0, 0.0), 100.0).unwrap();
+        assert_eq!(surface.object, 100);
+
+        // rays only ask the scenes they pass
+        rays.store(0, Ordering::Relaxed);
+        assert!(!scene.get_collision(V3::new(0.0, 50.0, 0.0), V3::new(0.0, 1.0, 0.0), 100.0).hit);
+        assert_eq!(rays.load(Ordering::Relaxed), 0);
+        scene.get_surface(V3::new(-30.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 100.0);
+        assert!(rays.load(Ordering::Relaxed) < 20, "{}", rays.load(Ordering::Relaxed));
+    }
+}
@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rust3d::engine::instance::Instance;
+    use rust3d::engine::lighting::Material;
+    use rust3d::engine::pathtracing::RaytracingObject;
+    use rust3d::engine::raymarching::RayMarchingObject;
+    use rust3d::engine::sdf::primitives::RoundedBox;
+    use rust3d::engine::utils::transformation::{Matrix4, Transform, Transformable};
+    use rust3d::geometry::quaternion::Quaternion;
+    use rust3d::geometry::quad::Quad;
+    use rust3d::geometry::sphere::Sphere;
+    use rust3d::geometry::vector3::Vector3 as V3;
+    use sdl2::pixels::Color;
+
+    fn assert_close(a: V3, b: V3) {
+        assert!((a - b).norm() < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_quaternion_matches_euler() {
+        let r = V3::new(0.7, -1.1, 2.3);
+        let q = Quaternion::from_euler(r);
+        let v = V3::new(1.0, -2.0, 0.5);
+        let mut rotated = v;
+        rotated.rotate(r);
+        assert_close(q.rotate(v), rotated);
+        assert_close(q.conjugate().rotate(rotated), v);
+        assert_close(Quaternion::from_euler(q.to_euler()).rotate(v), rotated);
+
+        // composition and interpolation
+        let a = Quaternion::from_axis_angle(V3::new(0.0, 0.0, 1.0), 0.4);
+        let b = Quaternion::from_axis_angle(V3::new(1.0, 0.0, 0.0), 1.2);
+        assert_close((a * b).rotate(v), a.rotate(b.rotate(v)));
+        let half = Quaternion::identity().slerp(Quaternion::from_axis_angle(V3::new(0.0, 1.0, 0.0), 1.0), 0.5);
+        assert_close(half.rotate(v), Quaternion::from_axis_angle(V3::new(0.0, 1.0, 0.0), 0.5).rotate(v));
+    }
+
+    #[test]
+    fn test_rot_reverse() {
+        // rotating about x, y and z at once is not undone by negating the angles one by one
+        let r = V3::new(0.9, 0.6, -1.3);
+        let mut t = Transform::new(V3::new(1.0, 2.0, 3.0), Quaternion::from_euler(V3::new(0.1, 0.2, 0.3)), V3::new(1.0, 1.0, 1.0));
+        let before = t;
+        t.rot(r);
+        t.rot_reverse(r);
+        assert_close(t.apply_point(V3::new(1.0, -1.0, 2.0)), before.apply_point(V3::new(1.0, -1.0, 2.0)));
+
+        // the default of the trait, used by the sdf primitives
+        let mut shape = RoundedBox::new(V3::new(0.0, 0.0, 0.0), V3::new(2.0, 0.5, 1.0), 0.0, Material::new(Color::WHITE, 1.0));
+        let p = V3::new(1.5, 0.7, -0.4);
+        let d = shape.sdf(p);
+        shape.rot(r);
+        assert!((shape.sdf(p) - d).abs() > 1e-3);
+        shape.rot_reverse(r);
+        assert!((shape.sdf(p) - d).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_matrix() {
+        let t = Transform::new(V3::new(1.0, -2.0, 0.5), Quaternion::from_euler(V3::new(0.3, 1.0, -0.4)), V3::new(2.0, 0.5, 3.0));
+        let p = V3::new(0.3, 0.7, -1.1);
+        let m = t.matrix();
+        assert_close(m.transform_point(p), t.apply_point(p));
+        assert_close(t.inverse_matrix().transform_point(m.transform_point(p)), p);
+        assert_close(t.inverse_point(t.apply_point(p)), p);
+
+        let inverse = m.inverse().unwrap();
+        for (a, b) in (m * inverse).m.iter().flatten().zip(Matrix4::identity().m.iter().flatten()) {
+            assert!((a - b).abs() < 1e-9);
+        }
+        for (a, b) in inverse.m.iter().flatten().zip(t.inverse_matrix().m.iter().flatten()) {
+            assert!((a - b).abs() < 1e-9);
+        }
+        assert!(Matrix4::scaling(V3::new(1.0, 0.0, 1.0)).inverse().is_none());
+
+        // composition applies the right hand side first
+        let u = Transform::new(V3::new(0.0, 4.0, 0.0), Quaternion::from_euler(V3::new(0.0, 0.0, 0.5)), V3::new(1.0, 1.0, 1.0));
+        assert_close((u * t).transform_point(p), u.apply_point(t.apply_point(p)));
+    }
+
+    #[test]
+    fn test_instanced_sphere() {
+        // a moved unit sphere scaled by 2 is hit like a sphere of radius 2
+        let material = Material::new(Color::WHITE, 1.0);
+        let mut moved = Instance::new(Arc::new(Sphere::new(V3::new(0.0, 0.0, 0.0), 1.0, material.clone())), Transform::identity());
+        moved.scale(V3::new(2.0, 2.0, 2.0));
+        moved.translate(V3::new(5.0, 1.0, 0.0));
+        moved.rot_by(V3::new(0.0, 0.0, 0.0), V3::new(0.0, 0.0, 0.0));
+        let baked = Sphere::new(V3::new(5.0, 1.0, 0.0), 2.0, material);
+
+        let p0 = V3::new(0.0, 0.0, 0.0);
+        let dir = V3::new(1.0, 0.3, 0.1).normalized();
+        let (a, na) = moved.get_collision_with_normal(p0, dir);
+        let (b, nb) = baked.get_collision_with_normal(p0, dir);
+        assert!(a.hit && b.hit);
+        assert!((a.d - b.d).abs() < 1e-9);
+        assert_close(a.p, b.p);
+        assert_close(na.unwrap(), nb.unwrap());
+        assert!((moved.d(V3::new(9.0, 1.0, 0.0)) - 2.0).abs() < 1e-9);
+        assert!(!moved.get_collision(p0, V3::new(-1.0, 0.0, 0.0)).hit);
+    }
+
+    #[test]
+    fn test_instanced_quad() {
+        // a unit cube stretched along x and turned by 90° around y reaches out along z
+        let cube = Quad::new(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 1.0, 1.0), Color::WHITE);
+        let rotation = Quaternion::from_axis_angle(V3::new(0.0, 1.0, 0.0), std::f64::consts::FRAC_PI_2);
+        let box_ = Instance::new(Arc::new(cube), Transform::new(V3::new(0.0, 0.0, 10.0), rotation, V3::new(3.0, 1.0, 1.0)));
+
+        let c = box_.get_collision(V3::new(0.0, 0.0, 0.0), V3::new(0.0, 0.0, 1.0));
+        assert!(c.hit);
+        assert!((c.d - 8.5).abs() < 1e-9, "{}", c.d);
+        let (c, n) = box_.get_collision_with_normal(V3::new(-5.0, 0.0, 10.0), V3::new(1.0, 0.0, 0.0));
+        assert!((c.d - 4.5).abs() < 1e-9, "{}", c.d);
+        assert_close(n.unwrap(), V3::new(-1.0, 0.0, 0.0));
+    }
+}
@@ -71,4 +71,74 @@ mod tests {
         assert_approx_eq(orthogonalized[0].dot(&orthogonalized[1]), 0., 0.0001);
         assert_approx_eq(orthogonalized[2].dot(&orthogonalized[1]), 0., 0.0001);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_vector3_operators() {
+        let a = Vector3::new(1.0, 2.0, 3.0);
+        let b = Vector3::from([4.0, -5.0, 6.0]);
+        assert_eq!(a + b, Vector3::new(5.0, -3.0, 9.0));
+        assert_eq!(a - b, Vector3::new(-3.0, 7.0, -3.0));
+        assert_eq!(-a, Vector3::new(-1.0, -2.0, -3.0));
+        assert_eq!(a * 2.0, Vector3::new(2.0, 4.0, 6.0));
+        assert_eq!(2.0 * a, a * 2.0);
+        assert_eq!(a * b, Vector3::new(4.0, -10.0, 18.0));
+        assert_eq!(b / 2.0, Vector3::new(2.0, -2.5, 3.0));
+        assert_eq!(b / a, Vector3::new(4.0, -2.5, 2.0));
+        assert_eq!(<[f64; 3]>::from(a), [1.0, 2.0, 3.0]);
+
+        let mut c = a;
+        c += b;
+        c -= a;
+        assert_eq!(c, b);
+        c *= 2.0;
+        c /= 4.0;
+        assert_eq!(c, b / 2.0);
+        c *= a;
+        c /= a;
+        assert_eq!(c, b / 2.0);
+
+        // the operators give the same results as the methods that change the vector in place
+        let mut d = a;
+        d.subtract(b);
+        d.scale(0.5);
+        d.add(a);
+        assert_eq!(d, (a - b) * 0.5 + a);
+    }
+
+    #[test]
+    fn test_vector3_methods() {
+        let x = Vector3::new(1.0, 0.0, 0.0);
+        let y = Vector3::new(0.0, 1.0, 0.0);
+        assert_eq!(x.crossed(y), Vector3::new(0.0, 0.0, 1.0));
+        let mut z = x;
+        z.cross(y);
+        assert_eq!(z, x.crossed(y));
+
+        let v = Vector3::new(3.0, 0.0, -4.0);
+        assert_approx_eq(v.normalized().d(Vector3::new(0.6, 0.0, -0.8)), 0.0, 1e-12);
+        assert_eq!(v, Vector3::new(3.0, 0.0, -4.0));
+        assert_eq!(x.lerp(v, 0.0), x);
+        assert_eq!(x.lerp(v, 1.0), v);
+        assert_eq!(x.lerp(v, 0.5), Vector3::new(2.0, 0.0, -2.0));
+
+        assert_eq!(v.abs(), Vector3::new(3.0, 0.0, 4.0));
+        assert_eq!(v.min(x), Vector3::new(1.0, 0.0, -4.0));
+        assert_eq!(v.max(x), Vector3::new(3.0, 0.0, 0.0));
+
+        // a ray going down and forward bounces off a floor facing up (-y)
+        let down = Vector3::new(1.0, 1.0, 0.0).normalized();
+        let up = Vector3::new(0.0, -1.0, 0.0);
+        let reflected = down.reflect(up);
+        assert_approx_eq(reflected.x, down.x, 1e-12);
+        assert_approx_eq(reflected.y, -down.y, 1e-12);
+
+        // straight through without a change of medium, bent towards the normal going into glass
+        let through = down.refract(up, 1.0).unwrap();
+        assert_approx_eq(through.d(down), 0.0, 1e-12);
+        let bent = down.refract(up, 1.0 / 1.5).unwrap();
+        assert_approx_eq(bent.norm(), 1.0, 1e-12);
+        assert!(bent.x < down.x && bent.y > down.y);
+        // total internal reflection going out of glass at a flat angle
+        assert_eq!(Vector3::new(1.0, 0.2, 0.0).normalized().refract(up, 1.5), None);
+    }
+}
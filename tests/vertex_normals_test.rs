@@ -0,0 +1,150 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::path::Path;
+
+    use rust3d::engine::pathtracing::RaytracingObject;
+    use rust3d::engine::simplex3d_bvh::bvh::Bvh;
+    use rust3d::engine::simplex3d_sphere_tree::poly_tree::PolyTree;
+    use rust3d::engine::utils::transformation::Transformable;
+    use rust3d::geometry::face::Face;
+    use rust3d::geometry::simplex3d::Simplex3D;
+    use rust3d::geometry::vector3::Vector3 as V3;
+    use rust3d::geometry::wavefront::parse_obj;
+
+    fn assert_close(a: V3, b: V3) {
+        assert!((a - b).norm() < 1e-9, "{:?} != {:?}", a, b);
+    }
+
+    /// Octahedron with its corners on the axes, faces wound outwards.
+    fn octahedron() -> Simplex3D {
+        let mut faces = Vec::new();
+        for sx in [-1.0, 1.0] {
+            for sy in [-1.0, 1.0] {
+                for sz in [-1.0, 1.0] {
+                    let (r, a, b) = (V3::new(sx, 0.0, 0.0), V3::new(0.0, sy, 0.0), V3::new(0.0, 0.0, sz));
+                    faces.push(if sx * sy * sz > 0.0 { Face::new(r, a, b) } else { Face::new(r, b, a) });
+                }
+            }
+        }
+        Simplex3D::new(V3::new(0.0, 0.0, 0.0), faces)
+    }
+
+    #[test]
+    fn test_crease_angle() {
+        // neighbouring faces of the octahedron meet at 70.5°, opposite ones at 109.5°
+        let mut mesh = octahedron();
+        mesh.compute_vertex_normals(2.0);
+        for f in mesh.x.iter() {
+            for (p, n) in [f.r, f.a, f.b].iter().zip(f.vn.unwrap()) {
+                assert_close(n, *p);
+            }
+        }
+
+        mesh.compute_vertex_normals(1.0);
+        for f in mesh.x.iter() {
+            for n in f.vn.unwrap() {
+                assert_close(n, f.n.normalized());
+            }
+        }
+
+        // the cube keeps its edges
+        let mut cube = Simplex3D::load_obj("demo_assets/models/default_cube.obj").unwrap();
+        cube.compute_vertex_normals(0.5);
+        for f in cube.x.iter() {
+            for n in f.vn.unwrap() {
+                assert_close(n, f.n.normalized());
+            }
+        }
+        cube.clear_vertex_normals();
+        assert!(cube.x.iter().all(|f| f.vn.is_none()));
+    }
+
+    #[test]
+    fn test_angle_weights() {
+        // two triangles in one plane, one of them split in two, next to a third at a right angle.
+        // the split must not pull the vertex normal towards its plane.
+        let o = V3::new(0.0, 0.0, 0.0);
+        let (x, y, z) = (V3::new(1.0, 0.0, 0.0), V3::new(0.0, 1.0, 0.0), V3::new(0.0, 0.0, 1.0));
+        let faces = vec![
+            Face::new(o, x * 0.5 + y * 0.5, y),
+            Face::new(o, x, x * 0.5 + y * 0.5),
+            Face::new(o, z, x),
+        ];
+        let mut mesh = Simplex3D::new(o, faces);
+        mesh.compute_vertex_normals(std::f64::consts::PI);
+        let expected = (mesh.x[0].n.normalized() + mesh.x[2].n.normalized()).normalized();
+        assert_close(mesh.x[0].vn.unwrap()[0], expected);
+        assert_close(mesh.x[2].vn.unwrap()[0], expected);
+    }
+
+    #[test]
+    fn test_interpolated_hits() {
+        let mut mesh = octahedron();
+        mesh.compute_vertex_normals(2.0);
+        let bvh = Bvh::new(Clone::clone(&mesh));
+        let tree = PolyTree::new(Clone::clone(&mesh));
+
+        // a point on the face of the positive octant near the corner on the x axis
+        let target = V3::new(0.8, 0.1, 0.1);
+        let p0 = V3::new(3.0, 2.0, 1.0);
+        let dir = (target - p0).normalized();
+        let expected = target.normalized();
+        let objects: [&dyn RaytracingObject; 3] = [&mesh, &*bvh, &*tree];
+        for o in objects {
+            let (c, n) = o.get_collision_with_normal(p0, dir);
+            assert!(c.hit);
+            assert_close(c.p, target);
+            assert_close(n.unwrap(), expected);
+            let (_, n, _) = o.get_collision_with_material(p0, dir);
+            assert_close(n.unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_scaled_normals() {
+        let mut mesh = octahedron();
+        mesh.compute_vertex_normals(2.0);
+        let mut stretched = Clone::clone(&mesh);
+        stretched.scale(V3::new(2.0, 1.0, 1.0));
+        let f = &stretched.x[0];
+        for (p, n) in [f.r, f.a, f.b].iter().zip(f.vn.unwrap()) {
+            // the corners (±2, 0, 0) of the ellipsoid-like shape keep their normal along x
+            let expected = V3::new(p.x / 4.0, p.y, p.z).normalized();
+            assert_close(n, expected);
+        }
+
+        // flattened onto the xz plane all normals point along y, none of them is infinite
+        let mut flat = Clone::clone(&mesh);
+        flat.scale(V3::new(1.0, 0.0, 1.0));
+        for f in flat.x.iter() {
+            for n in f.vn.unwrap() {
+                assert!((n.y.abs() - 1.0).abs() < 1e-9, "{:?}", n);
+            }
+        }
+
+        // normals lying in the flattened plane fall back to the face normal
+        let mut face = Face::new(V3::new(0.0, 0.0, 0.0), V3::new(0.0, 1.0, 0.0), V3::new(1.0, 0.0, 1.0));
+        face.set_vertex_normals([V3::new(0.0, 1.0, 0.0); 3]);
+        face.scale_by(V3::new(1.0, 1.0, 0.0), V3::new(0.0, 0.0, 0.0));
+        for n in face.vn.unwrap() {
+            assert_close(n, face.n.normalized());
+        }
+    }
+
+    #[test]
+    fn test_obj_normals() {
+        // normals from the file are interpolated the same way
+        let src = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 -1\nvn 1 0 -1\nvn 0 1 -1\nf 1//1 2//2 3//3\n";
+        let mesh = parse_obj(Cursor::new(src), Path::new("test.obj"), Path::new(".")).unwrap();
+        assert_close(mesh.x[0].normal_at((0.0, 0.0)), V3::new(0.0, 0.0, -1.0));
+        assert_close(mesh.x[0].normal_at((0.5, 0.0)), V3::new(0.5, 0.0, -1.0).normalized());
+
+        let tree = PolyTree::new(mesh);
+        // the face solver of the tree can't handle rays along an axis
+        let dir = V3::new(0.1, 0.2, 1.0);
+        let (c, n) = tree.get_collision_with_normal(V3::new(0.25, 0.25, 0.0) - dir, dir.normalized());
+        assert!(c.hit);
+        assert_close(n.unwrap(), V3::new(0.25, 0.25, -1.0).normalized());
+    }
+}
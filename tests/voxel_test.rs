@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod tests {
+    use sdl2::pixels::Color;
+    use rust3d::engine::lighting::Material;
+    use rust3d::engine::pathtracing::PathtracingObject;
+    use rust3d::engine::voxels::chunk::AIR;
+    use rust3d::engine::voxels::terrain::TerrainGenerator;
+    use rust3d::engine::voxels::voxel_world::VoxelWorld;
+    use rust3d::geometry::vector3::Vector3 as V3;
+
+    fn assert_approx_eq(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_set_and_get_across_chunks() {
+        let mut world = VoxelWorld::new(1.0);
+        let stone = world.add_material(Material::new(Color::GRAY, 1.0));
+        world.set_voxel(-1, 0, 0, stone);
+        world.set_voxel(16, 31, -17, stone);
+
+        assert_eq!(world.get_voxel(-1, 0, 0), stone);
+        assert_eq!(world.get_voxel(16, 31, -17), stone);
+        assert_eq!(world.get_voxel(0, 0, 0), AIR);
+        assert_eq!(world.chunks.len(), 2);
+
+        world.set_voxel(-1, 0, 0, AIR);
+        assert_eq!(world.chunks.len(), 1);
+    }
+
+    #[test]
+    fn test_trace_hits_first_voxel() {
+        let mut world = VoxelWorld::new(0.5);
+        let stone = world.add_material(Material::new(Color::GRAY, 1.0));
+        world.fill((4, 0, 0), (6, 0, 0), stone);
+
+        let hit = world.trace(V3::new(0.0, 0.25, 0.25), V3::new(1.0, 0.0, 0.0), 100.0).unwrap();
+        assert_eq!(hit.voxel, (4, 0, 0));
+        assert_approx_eq(hit.d, 2.0);
+        assert_approx_eq(hit.normal.x, -1.0);
+
+        // pointing away from the filled voxels
+        let hit = world.trace(V3::new(1.9, 0.25, 0.25), V3::new(-1.0, 0.0, 0.0), 100.0);
+        assert!(hit.is_none());
+    }
+
+    #[test]
+    fn test_trace_diagonal_and_negative_coordinates() {
+        let mut world = VoxelWorld::new(1.0);
+        let stone = world.add_material(Material::new(Color::GRAY, 1.0));
+        world.fill((-20, -20, -20), (-20, 20, 20), stone);
+
+        let mut dir = V3::new(-1.0, 0.3, -0.2);
+        dir.normalize();
+        let hit = world.trace(V3::new(0.5, 0.5, 0.5), dir, 100.0).unwrap();
+        assert_eq!(hit.voxel.0, -20);
+        assert_approx_eq(hit.p.x, -19.0);
+        assert_approx_eq(hit.normal.x, 1.0);
+
+        assert!(world.trace(V3::new(0.5, 0.5, 0.5), dir, 5.0).is_none());
+    }
+
+    #[test]
+    fn test_terrain_is_reproducible_and_renderable() {
+        let mut a = VoxelWorld::new(1.0);
+        let mut b = VoxelWorld::new(1.0);
+        TerrainGenerator::new(3).generate(&mut a, 20, 20);
+        TerrainGenerator::new(3).generate(&mut b, 20, 20);
+
+        for x in 0..20 {
+            for z in 0..20 {
+                for y in 0..30 {
+                    assert_eq!(a.get_voxel(x, y, z), b.get_voxel(x, y, z));
+                }
+            }
+        }
+
+        let (c, n) = a.get_collision_with_normal(V3::new(10.5, 100.0, 10.5), V3::new(0.0, -1.0, 0.0));
+        assert!(c.hit);
+        assert_approx_eq(n.unwrap().y, 1.0);
+        assert!(a.is_surface(10, (c.p.y - 0.5).floor() as i64, 10));
+    }
+
+    #[test]
+    fn test_trace_starting_inside() {
+        let mut world = VoxelWorld::new(1.0);
+        let stone = world.add_material(Material::new(Color::GRAY, 1.0));
+        world.fill((0, 0, 0), (3, 3, 3), stone);
+
+        // the z boundary of the start cell comes first, the x slab is only the one behind
+        let hit = world.trace(V3::new(1.5, 1.5, 1.05), V3::new(1.0, 0.0, -0.2), 100.0).unwrap();
+        assert_eq!(hit.voxel, (1, 1, 1));
+        assert_approx_eq(hit.d, 0.0);
+        assert_approx_eq(hit.normal.x, 0.0);
+        assert_approx_eq(hit.normal.z, 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot rotate")]
+    fn test_rotation_is_refused() {
+        use rust3d::engine::utils::transformation::Transformable;
+        let mut world = VoxelWorld::new(1.0);
+        world.rot(V3::new(0.0, 0.5, 0.0));
+    }
+}
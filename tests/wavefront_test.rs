@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::path::Path;
+
+    use rust3d::geometry::simplex3d::Simplex3D;
+    use rust3d::geometry::wavefront::{parse_mtl, parse_obj, ObjError};
+
+    fn parse(src: &str) -> Result<Simplex3D, ObjError> {
+        parse_obj(Cursor::new(src), Path::new("test.obj"), Path::new("."))
+    }
+
+    #[test]
+    fn test_quad_with_negative_indices_and_normals() {
+        let mesh = parse(
+            "# unit quad\n\
+             v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+             vt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\n\
+             vn 0 0 1\n\
+             g quad\ns off\n\
+             f -4/-4/-1 -3/-3/-1 -2/-2/-1 -1/-1/-1\n",
+        )
+        .unwrap();
+
+        assert_eq!(mesh.x.len(), 2);
+        assert_eq!(mesh.tm.len(), 2);
+        assert_eq!(mesh.x[1].a.x, 1.0);
+        assert_eq!(mesh.x[1].b.y, 1.0);
+        assert_eq!(mesh.tm[1].b, (0.0, 1.0));
+        assert_eq!(mesh.x[0].vn.unwrap()[2].z, 1.0);
+        assert!(mesh.x[0].material.is_none());
+        assert_eq!(mesh.m.x, 0.5);
+    }
+
+    #[test]
+    fn test_faces_without_uvs_or_normals() {
+        let mesh = parse("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\nf 1//1 2 3\n");
+        assert!(matches!(mesh, Err(ObjError::Parse { line: 5, .. })));
+
+        let mesh = parse("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n").unwrap();
+        assert!(mesh.x[0].vn.is_none());
+        assert_eq!(mesh.tm[0].a, (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_errors_report_line_numbers() {
+        let e = parse("v 0 0 0\nv 1 0 0\n\nv 0 x 0\n").unwrap_err();
+        assert!(matches!(e, ObjError::Parse { line: 4, .. }));
+        assert_eq!(e.to_string(), "test.obj:4: invalid number 'x'");
+
+        let e = parse("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n").unwrap_err();
+        assert!(matches!(e, ObjError::Parse { line: 4, .. }));
+
+        let e = parse("v 0 0 0\nv 1 0 0\nf 1 2\n").unwrap_err();
+        assert!(matches!(e, ObjError::Parse { line: 3, .. }));
+
+        assert!(matches!(parse("v 0 0 0\n"), Err(ObjError::NoFaces { .. })));
+        assert!(matches!(Simplex3D::load_obj("does/not/exist.obj"), Err(ObjError::Io { .. })));
+    }
+
+    #[test]
+    fn test_mtl_colors() {
+        let materials = parse_mtl(
+            Cursor::new("newmtl red\nKd 1 0 0\nnewmtl textured\nmap_Kd -s 1 1 1 -clamp on horse_tex.png\n"),
+            Path::new("test.mtl"),
+            Path::new("demo_assets/models"),
+        )
+        .unwrap();
+
+        assert_eq!(materials.len(), 2);
+        assert_eq!(materials[0].name, "red");
+        assert_eq!(materials[0].material.albedo.r, 255);
+        assert_eq!(materials[0].material.albedo.g, 0);
+        assert!(materials[1].material.albedo_texture.is_some());
+
+        // a texture that cannot be loaded fails like a missing .mtl
+        let e = parse_mtl(Cursor::new("newmtl textured\n\nmap_Kd missing.png\n"), Path::new("test.mtl"), Path::new(".")).unwrap_err();
+        assert!(matches!(e, ObjError::Texture { line: 3, .. }), "{}", e);
+
+        let e = parse_mtl(Cursor::new("Kd 1 0 0\n"), Path::new("test.mtl"), Path::new(".")).unwrap_err();
+        assert!(matches!(e, ObjError::Parse { line: 1, .. }));
+    }
+
+    #[test]
+    fn test_load_scene_with_multiple_materials() {
+        let mesh = Simplex3D::load_obj("demo_assets/models/horse_scene.obj").unwrap();
+
+        assert_eq!(mesh.materials.len(), 2);
+        assert_eq!(mesh.materials[0].name, "Material.001");
+        assert!(mesh.materials[0].material.albedo_texture.is_some());
+        assert!(mesh.materials[1].material.albedo_texture.is_some());
+
+        let horse = mesh.x.iter().filter(|f| f.material == Some(0)).count();
+        let cube = mesh.x.iter().filter(|f| f.material == Some(1)).count();
+        assert!(horse > 0 && cube > 0);
+        assert_eq!(horse + cube, mesh.x.len());
+    }
+
+    #[test]
+    fn test_texture_file_name_with_spaces() {
+        let dir = std::env::temp_dir().join("rust3d_wavefront_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::copy("demo_assets/models/horse_tex.png", dir.join("horse tex.png")).unwrap();
+
+        let materials = parse_mtl(Cursor::new("newmtl a\nmap_Kd -o 0.5 0.5 horse tex.png \nmap_Pr horse tex.png"), Path::new("test.mtl"), &dir).unwrap();
+        assert!(materials[0].material.albedo_texture.is_some());
+        assert!(materials[0].material.roughness_texture.is_some());
+    }
+}